@@ -0,0 +1,23 @@
+-- Persistent recrawl schedule
+-- Backs the in-memory CrawlScheduler so scheduled recrawls survive restarts
+
+CREATE TABLE IF NOT EXISTS scheduled_crawls (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    url VARCHAR(2000) NOT NULL UNIQUE,
+    priority SMALLINT NOT NULL DEFAULT 50 CHECK (priority BETWEEN 0 AND 100),
+    frequency VARCHAR(20) NOT NULL DEFAULT 'daily',
+    next_crawl_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    last_crawled_at TIMESTAMP WITH TIME ZONE,
+    freshness_score DOUBLE PRECISION NOT NULL DEFAULT 0,
+    -- Most recent crawl job enqueued for this URL
+    last_job_id UUID,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+-- The recrawl loop polls for due rows ordered by priority
+CREATE INDEX IF NOT EXISTS idx_scheduled_crawls_next_crawl_at ON scheduled_crawls(next_crawl_at);
+CREATE INDEX IF NOT EXISTS idx_scheduled_crawls_priority ON scheduled_crawls(priority DESC);
+
+COMMENT ON COLUMN scheduled_crawls.frequency IS 'hourly, daily, weekly, monthly or never';
+COMMENT ON COLUMN scheduled_crawls.next_crawl_at IS 'Pushed forward by a lease while a recrawl job is in flight';
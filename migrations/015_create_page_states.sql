@@ -0,0 +1,19 @@
+-- Per-URL change detection state
+-- HTTP validators for conditional recrawls and a content hash to skip re-indexing unchanged pages
+
+CREATE TABLE IF NOT EXISTS page_states (
+    url VARCHAR(2000) PRIMARY KEY,
+    etag TEXT,
+    last_modified TEXT,
+    -- SHA-256 of the extracted title and content
+    content_hash VARCHAR(64),
+    first_seen_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    last_checked_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    last_changed_at TIMESTAMP WITH TIME ZONE,
+    check_count INTEGER NOT NULL DEFAULT 1,
+    change_count INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE INDEX IF NOT EXISTS idx_page_states_last_checked_at ON page_states(last_checked_at);
+
+COMMENT ON COLUMN page_states.change_count IS 'Number of checks where the content hash differed from the previous one';
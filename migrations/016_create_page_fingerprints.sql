@@ -0,0 +1,15 @@
+-- SimHash fingerprints for near-duplicate detection
+-- Loaded into the crawler's fingerprint store at startup
+
+CREATE TABLE IF NOT EXISTS page_fingerprints (
+    document_id UUID PRIMARY KEY,
+    url VARCHAR(2000) NOT NULL,
+    simhash BIGINT NOT NULL,
+    -- Representative document this page is a near-duplicate of (NULL for representatives)
+    duplicate_of UUID,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_page_fingerprints_duplicate_of ON page_fingerprints(duplicate_of)
+WHERE duplicate_of IS NOT NULL;
@@ -0,0 +1,39 @@
+-- Link graph and link-based authority scores
+-- page_links is written by the crawler worker; the authority tables are rebuilt by the nightly PageRank job
+
+CREATE TABLE IF NOT EXISTS page_links (
+    source_url VARCHAR(2000) NOT NULL,
+    target_url VARCHAR(2000) NOT NULL,
+    source_domain VARCHAR(255) NOT NULL,
+    target_domain VARCHAR(255) NOT NULL,
+    anchor_text TEXT,
+    -- rel="nofollow"/"ugc"/"sponsored" or a page-level robots nofollow
+    nofollow BOOLEAN NOT NULL DEFAULT FALSE,
+    first_seen_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    last_seen_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (source_url, target_url)
+);
+
+CREATE INDEX IF NOT EXISTS idx_page_links_target_url ON page_links(target_url);
+CREATE INDEX IF NOT EXISTS idx_page_links_target_domain ON page_links(target_domain);
+
+CREATE TABLE IF NOT EXISTS page_authority (
+    url VARCHAR(2000) PRIMARY KEY,
+    -- Raw PageRank over the page graph
+    score DOUBLE PRECISION NOT NULL,
+    -- Combined page and domain authority bucket, 0-10
+    authority SMALLINT NOT NULL,
+    inbound_links INTEGER NOT NULL DEFAULT 0,
+    computed_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS domain_authority (
+    domain VARCHAR(255) PRIMARY KEY,
+    -- Raw PageRank over the domain graph (cross-domain links only)
+    score DOUBLE PRECISION NOT NULL,
+    authority SMALLINT NOT NULL,
+    inbound_domains INTEGER NOT NULL DEFAULT 0,
+    computed_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_page_authority_authority ON page_authority(authority);
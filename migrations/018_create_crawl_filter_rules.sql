@@ -0,0 +1,14 @@
+-- Admin-managed crawl filter rules
+-- Loaded by every crawler worker and reloaded on NOTIFY crawl_filter_rules
+
+CREATE TABLE IF NOT EXISTS crawl_filter_rules (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    kind VARCHAR(20) NOT NULL CHECK (kind IN ('url_include', 'url_exclude', 'domain_allow', 'domain_block')),
+    -- Regex for URL rules, lowercase domain for domain rules
+    pattern TEXT NOT NULL,
+    description TEXT,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (kind, pattern)
+);
@@ -0,0 +1,10 @@
+-- Per-URL crawl failures: domain and HTTP status for filtering and aggregation
+-- error_type is one of robots_blocked, filtered, circuit_open, http_status,
+-- timeout, network, parse_error, empty_content
+
+ALTER TABLE crawl_errors ADD COLUMN IF NOT EXISTS domain VARCHAR(255);
+ALTER TABLE crawl_errors ADD COLUMN IF NOT EXISTS status_code SMALLINT;
+
+CREATE INDEX IF NOT EXISTS idx_crawl_errors_domain ON crawl_errors(domain);
+CREATE INDEX IF NOT EXISTS idx_crawl_errors_error_type ON crawl_errors(error_type);
+CREATE INDEX IF NOT EXISTS idx_crawl_errors_occurred_at ON crawl_errors(occurred_at DESC);
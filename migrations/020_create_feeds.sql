@@ -0,0 +1,37 @@
+-- RSS/Atom feeds discovered while crawling
+-- Polled on an adaptive schedule; new entries are enqueued for crawling
+
+CREATE TABLE IF NOT EXISTS feeds (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    url VARCHAR(2000) NOT NULL UNIQUE,
+    -- Host of the site the feed was found on
+    domain VARCHAR(255) NOT NULL,
+    title TEXT,
+    poll_interval_secs INTEGER NOT NULL DEFAULT 3600,
+    next_poll_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    last_polled_at TIMESTAMP WITH TIME ZONE,
+    last_entry_at TIMESTAMP WITH TIME ZONE,
+    -- HTTP validators for conditional polls
+    etag TEXT,
+    last_modified TEXT,
+    consecutive_failures INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_feeds_domain ON feeds(domain);
+CREATE INDEX IF NOT EXISTS idx_feeds_next_poll_at ON feeds(next_poll_at);
+
+-- Entries seen in each feed, so only new posts are enqueued
+CREATE TABLE IF NOT EXISTS feed_entries (
+    feed_id UUID NOT NULL REFERENCES feeds(id) ON DELETE CASCADE,
+    url VARCHAR(2000) NOT NULL,
+    published_at TIMESTAMP WITH TIME ZONE,
+    first_seen_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    -- Crawl job enqueued for the entry
+    job_id UUID,
+    PRIMARY KEY (feed_id, url)
+);
+
+COMMENT ON COLUMN feeds.poll_interval_secs IS 'Shrinks while the feed publishes new entries, grows while it does not';
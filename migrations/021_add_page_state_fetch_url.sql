@@ -0,0 +1,7 @@
+-- URL a page was fetched from when it differs from its canonical URL
+-- page_states rows are keyed by canonical URL; recrawls fetch the scheduled
+-- URL and look its state up through this column
+
+ALTER TABLE page_states ADD COLUMN IF NOT EXISTS fetch_url VARCHAR(2000);
+
+CREATE INDEX IF NOT EXISTS idx_page_states_fetch_url ON page_states(fetch_url);
@@ -23,10 +23,14 @@ use crate::{
         self, Backend, Credentials, RegisterRequest, AuthResponse, UserRole, UserResponse,
         CreateInvitationRequest, InvitationRepository, InvitationStatus, AuthSession, // Phase 8.3
     }, // Phase 8
-    search::crawler::{Crawler, ImageData}, // Phase 10.5: ImageData for hybrid image search
+    search::crawler::{
+        CrawlErrorQuery, CrawlErrorRepository, Crawler, CreateFilterRuleRequest, FilterRuleKind, FilterRuleRepository, FilterRuleSet,
+        ImageData, UpdateFilterRuleRequest, UrlFrontier, UrlProcessor,
+    }, // Phase 10.5: ImageData for hybrid image search
     ory, // Phase 8.6: Ory Kratos integration
     search::qdrant::{QdrantService, ScoredImage}, // Phase 10: Semantic search, Phase 10.5: Image search
-    search::redis::{CacheManager, JobQueue},
+    search::redis::{CacheManager, CrawlJob, JobControl, JobQueue, JobStatus},
+    search::scheduler::{CreateScheduledCrawlRequest, FeedRepository, ScheduleRepository, UpdateScheduledCrawlRequest},
     search::search::SearchClient,
     types::{ApiResponse, CrawlRequest, SearchQuery},
 };
@@ -68,8 +72,9 @@ pub async fn serve(
     search_client.initialize_index().await?;
 
     // Note: Crawler in API state is not actively used for crawling
-    // (workers handle actual crawling). This is kept for compatibility.
-    let crawler = Crawler::new(3, 10);
+    // (workers handle actual crawling). It shares the workers' circuit
+    // breakers and domain delays through Redis so admins can inspect them.
+    let crawler = Crawler::new(3, 10).with_redis(cache.get_connection().await?);
 
     // Phase 7.6-7.7: Initialize analytics manager
     let analytics_redis = cache.get_connection().await?;
@@ -243,6 +248,14 @@ pub async fn serve(
         .route("/api/admin/users/:id", get(get_user))
         .route("/api/admin/users/:id", post(update_user))
         .route("/api/admin/users/:id", delete(delete_user))
+        .route("/api/admin/crawler/filters", get(list_filter_rules))
+        .route("/api/admin/crawler/filters", post(create_filter_rule))
+        .route("/api/admin/crawler/filters/test", post(test_filter_rules))
+        .route("/api/admin/crawler/filters/:id", post(update_filter_rule))
+        .route("/api/admin/crawler/filters/:id", delete(delete_filter_rule))
+        .route("/api/admin/crawler/circuits", get(list_open_circuits))
+        .route("/api/admin/crawler/circuits/:domain/open", post(open_circuit))
+        .route("/api/admin/crawler/circuits/:domain/reset", post(reset_circuit))
         .route_layer(middleware::from_fn(auth::middleware::require_admin));
 
     let app = Router::new()
@@ -251,6 +264,10 @@ pub async fn serve(
         .route("/api/crawl", post(crawl))
         .route("/api/crawl/history", get(crawl_history))
         .route("/api/jobs/:job_id", get(get_job_status))
+        .route("/api/jobs/:job_id", delete(cancel_job))
+        .route("/api/jobs/:job_id/pause", post(pause_job))
+        .route("/api/jobs/:job_id/resume", post(resume_job))
+        .route("/api/jobs/:job_id/errors", get(job_errors))
         .route("/api/search", get(search))
         .route("/api/search/hybrid", get(hybrid_search)) // Phase 10: Hybrid semantic search
         .route("/api/search/autocomplete", get(autocomplete)) // Phase 7.1
@@ -262,7 +279,13 @@ pub async fn serve(
         // Crawler metrics endpoints (Phase 6.10)
         .route("/api/crawler/metrics", get(crawler_metrics))
         .route("/api/crawler/domains", get(crawler_domains))
+        .route("/api/crawler/errors", get(crawler_errors))
         .route("/api/crawler/scheduler", get(crawler_scheduler))
+        .route("/api/crawler/scheduler", post(create_scheduled_crawl))
+        .route("/api/crawler/scheduler/:id", get(get_scheduled_crawl))
+        .route("/api/crawler/scheduler/:id", post(update_scheduled_crawl))
+        .route("/api/crawler/scheduler/:id", delete(delete_scheduled_crawl))
+        .route("/api/crawler/feeds", get(crawler_feeds))
         // Analytics endpoints (Phase 7.6-7.8)
         .route("/api/analytics/summary", get(analytics_summary))
         .route("/api/analytics/click", post(track_click))
@@ -336,12 +359,27 @@ async fn crawl(
 ) -> impl IntoResponse {
     info!("Received crawl request for {} URLs", payload.urls.len());
 
+    let budgets = std::iter::once(&payload.budget).chain(payload.domain_budget.as_ref());
+    for budget in budgets {
+        if let Err(e) = budget.validate() {
+            let response = ApiResponse::error(format!("Invalid crawl budget: {}", e));
+            return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+        }
+    }
+    if let Err(e) = payload.options.validate() {
+        let response = ApiResponse::error(format!("Invalid crawl options: {:#}", e));
+        return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+    }
+
     // Create a new crawl job
-    let job = crate::search::redis::CrawlJob::new(
+    let mut job = crate::search::redis::CrawlJob::new(
         payload.urls.clone(),
         payload.max_depth,
         None, // collection_id can be added later
     );
+    job.budget = payload.budget;
+    job.domain_budget = payload.domain_budget;
+    job.options = payload.options;
 
     // Enqueue the job for background processing
     let mut queue = state.job_queue.clone();
@@ -416,6 +454,63 @@ async fn crawl_history(
     }
 }
 
+/// Failed URLs of one crawl job
+async fn job_errors(
+    State(state): State<Arc<AppState>>,
+    Path(job_id): Path<Uuid>,
+    Query(params): Query<CrawlErrorQuery>,
+) -> impl IntoResponse {
+    let params = CrawlErrorQuery {
+        crawl_id: Some(job_id),
+        ..params
+    };
+    list_crawl_errors(&state, params).await
+}
+
+/// Failed URLs across all crawl jobs
+async fn crawler_errors(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<CrawlErrorQuery>,
+) -> impl IntoResponse {
+    list_crawl_errors(&state, params).await
+}
+
+/// Page of matching crawl errors with counts by error type and domain
+async fn list_crawl_errors(state: &AppState, params: CrawlErrorQuery) -> impl IntoResponse {
+    let params = params.clamped();
+    info!(
+        "Fetching crawl errors: crawl_id={:?}, domain={:?}, error_type={:?}",
+        params.crawl_id, params.domain, params.error_type
+    );
+
+    let repo = CrawlErrorRepository::new(state.db_pool.clone());
+    let result = async {
+        let (errors, total) = repo.list(&params).await?;
+        let summary = repo.summary(&params).await?;
+        anyhow::Ok((errors, total, summary))
+    }
+    .await;
+
+    match result {
+        Ok((errors, total, summary)) => {
+            let response = ApiResponse::success(serde_json::json!({
+                "errors": errors,
+                "total": total,
+                "by_type": summary.by_type,
+                "by_domain": summary.by_domain,
+                "limit": params.limit,
+                "offset": params.offset
+            }));
+            (StatusCode::OK, Json(response)).into_response()
+        }
+        Err(e) => {
+            error!("Failed to fetch crawl errors: {}", e);
+            let response = ApiResponse::error(format!("Failed to fetch crawl errors: {}", e));
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response()
+        }
+    }
+}
+
 async fn search(
     State(state): State<Arc<AppState>>,
     Query(params): Query<SearchQuery>,
@@ -539,6 +634,23 @@ async fn hybrid_search(
         }
     };
 
+    // Semantic hits must pass the same filters as the keyword leg
+    let semantic_ids: Vec<&str> = semantic_results.iter().map(|page| page.id.as_str()).collect();
+    let semantic_results = match state
+        .search_client
+        .filter_document_ids(&semantic_ids, &params)
+        .await
+    {
+        Ok(allowed) => semantic_results
+            .into_iter()
+            .filter(|page| allowed.contains(&page.id))
+            .collect(),
+        Err(e) => {
+            tracing::warn!("Failed to filter semantic results: {}", e);
+            Vec::new()
+        }
+    };
+
     // 3. Merge results (deduplicate by URL, combine scores)
     let semantic_count = semantic_results.len();
     let merged = merge_search_results(keyword_results.hits, semantic_results);
@@ -590,7 +702,7 @@ fn merge_search_results(
                 url: result.url,
                 title: result.title,
                 description: result.description,
-                content: Some(result.content),
+                content: (!result.nosnippet).then_some(result.content),
                 keyword_score: Some(keyword_score),
                 semantic_score: None,
                 combined_score: keyword_score * 0.5, // 50% weight for keyword
@@ -946,6 +1058,169 @@ async fn get_job_status(
     }
 }
 
+/// Look up a job for the cancel, pause and resume handlers
+async fn find_job(queue: &mut JobQueue, job_id: Uuid) -> Result<CrawlJob, axum::response::Response> {
+    match queue.get_job(job_id).await {
+        Ok(Some(job)) => Ok(job),
+        Ok(None) => {
+            let response = ApiResponse::error("Job not found".to_string());
+            Err((StatusCode::NOT_FOUND, Json(response)).into_response())
+        }
+        Err(e) => {
+            let response = ApiResponse::error(format!("Failed to get job status: {}", e));
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response())
+        }
+    }
+}
+
+/// Cancel a pending, running or paused job
+///
+/// Paused jobs are cancelled right away. Pending and running ones are
+/// cancelled by the worker that dequeues or runs them, between pages.
+async fn cancel_job(
+    State(state): State<Arc<AppState>>,
+    Path(job_id): Path<Uuid>,
+) -> impl IntoResponse {
+    info!("Cancelling job {}", job_id);
+
+    let mut queue = state.job_queue.clone();
+    let job = match find_job(&mut queue, job_id).await {
+        Ok(job) => job,
+        Err(response) => return response,
+    };
+
+    if job.status.is_finished() {
+        let response = ApiResponse::error(format!("Job is already finished ({:?})", job.status));
+        return (StatusCode::CONFLICT, Json(response)).into_response();
+    }
+
+    let result = async {
+        // No worker holds a paused job; one resumed meanwhile gets a request instead
+        if job.status == JobStatus::Paused && queue.cancel_paused_job(job_id).await? {
+            // Frontier URLs set aside while it was paused are never resumed now
+            let mut conn = state.cache.get_connection().await?;
+            UrlFrontier::drop_parked_with(&mut conn, job_id).await?;
+            sqlx::query("UPDATE crawl_history SET status = 'cancelled', completed_at = NOW() WHERE id = $1")
+                .bind(job_id)
+                .execute(&state.db_pool)
+                .await?;
+            Ok::<_, anyhow::Error>((StatusCode::OK, "Job cancelled", JobStatus::Cancelled))
+        } else {
+            queue.request_control(job_id, JobControl::Cancel).await?;
+            let status = match job.status {
+                JobStatus::Paused => JobStatus::Pending,
+                ref status => status.clone(),
+            };
+            Ok((StatusCode::ACCEPTED, "Cancellation requested", status))
+        }
+    }
+    .await;
+
+    match result {
+        Ok((status_code, message, status)) => {
+            let response = ApiResponse::success(serde_json::json!({
+                "message": message,
+                "job_id": job_id,
+                "status": format!("{:?}", status)
+            }));
+            (status_code, Json(response)).into_response()
+        }
+        Err(e) => {
+            error!("Failed to cancel job {}: {}", job_id, e);
+            let response = ApiResponse::error(format!("Failed to cancel job: {}", e));
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response()
+        }
+    }
+}
+
+/// Pause a pending or running job, keeping its remaining URLs for `resume_job`
+async fn pause_job(
+    State(state): State<Arc<AppState>>,
+    Path(job_id): Path<Uuid>,
+) -> impl IntoResponse {
+    info!("Pausing job {}", job_id);
+
+    let mut queue = state.job_queue.clone();
+    let job = match find_job(&mut queue, job_id).await {
+        Ok(job) => job,
+        Err(response) => return response,
+    };
+
+    if !matches!(job.status, JobStatus::Pending | JobStatus::Processing) {
+        let response = ApiResponse::error(format!("Only pending or running jobs can be paused (job is {:?})", job.status));
+        return (StatusCode::CONFLICT, Json(response)).into_response();
+    }
+
+    let result = async {
+        if queue.control(job_id).await? == Some(JobControl::Cancel) {
+            return Ok(false);
+        }
+        queue.request_control(job_id, JobControl::Pause).await?;
+        Ok::<_, anyhow::Error>(true)
+    }
+    .await;
+
+    match result {
+        Ok(true) => {
+            let response = ApiResponse::success(serde_json::json!({
+                "message": "Pause requested",
+                "job_id": job_id,
+                "status": format!("{:?}", job.status)
+            }));
+            (StatusCode::ACCEPTED, Json(response)).into_response()
+        }
+        Ok(false) => {
+            let response = ApiResponse::error("Job is being cancelled".to_string());
+            (StatusCode::CONFLICT, Json(response)).into_response()
+        }
+        Err(e) => {
+            error!("Failed to pause job {}: {}", job_id, e);
+            let response = ApiResponse::error(format!("Failed to pause job: {}", e));
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response()
+        }
+    }
+}
+
+/// Put a paused job back on the queue to crawl the URLs it has left
+async fn resume_job(
+    State(state): State<Arc<AppState>>,
+    Path(job_id): Path<Uuid>,
+) -> impl IntoResponse {
+    info!("Resuming job {}", job_id);
+
+    let mut queue = state.job_queue.clone();
+    let job = match find_job(&mut queue, job_id).await {
+        Ok(job) => job,
+        Err(response) => return response,
+    };
+
+    if job.status != JobStatus::Paused {
+        let response = ApiResponse::error(format!("Only paused jobs can be resumed (job is {:?})", job.status));
+        return (StatusCode::CONFLICT, Json(response)).into_response();
+    }
+
+    match queue.resume_job(job_id).await {
+        Ok(true) => {
+            let response = ApiResponse::success(serde_json::json!({
+                "message": "Job resumed",
+                "job_id": job_id,
+                "status": format!("{:?}", JobStatus::Pending),
+                "urls": job.urls
+            }));
+            (StatusCode::OK, Json(response)).into_response()
+        }
+        Ok(false) => {
+            let response = ApiResponse::error("Job is no longer paused".to_string());
+            (StatusCode::CONFLICT, Json(response)).into_response()
+        }
+        Err(e) => {
+            error!("Failed to resume job {}: {}", job_id, e);
+            let response = ApiResponse::error(format!("Failed to resume job: {}", e));
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response()
+        }
+    }
+}
+
 async fn clear_index(State(state): State<Arc<AppState>>) -> impl IntoResponse {
     info!("Clearing search index");
 
@@ -971,10 +1246,12 @@ async fn crawler_metrics(State(state): State<Arc<AppState>>) -> impl IntoRespons
     let metrics = serde_json::json!({
         "rate_limiter": state.crawler.rate_limiter_stats(),
         "politeness": state.crawler.politeness_stats(),
-        "circuit_breaker": state.crawler.circuit_breaker_stats(),
+        "circuit_breaker": state.crawler.circuit_breaker_stats().await,
         "scheduler": state.crawler.scheduler_stats(),
         "filters": state.crawler.filter_stats(),
         "robots": state.crawler.robots_stats(),
+        "sitemaps": state.crawler.sitemap_stats(),
+        "fingerprints": state.crawler.fingerprint_stats(),
     });
 
     let response = ApiResponse::success(metrics);
@@ -984,27 +1261,489 @@ async fn crawler_metrics(State(state): State<Arc<AppState>>) -> impl IntoRespons
 async fn crawler_domains(State(state): State<Arc<AppState>>) -> impl IntoResponse {
     info!("Getting per-domain crawler stats");
 
-    let domains = state.crawler.circuit_breaker().get_all_domains();
+    let domains = state.crawler.circuit_breaker().get_all_domains().await;
+    let delays = state.crawler.politeness().domain_delays().await;
 
     let response = ApiResponse::success(serde_json::json!({
-        "domains": domains
+        "domains": domains,
+        "delays": delays
     }));
     (StatusCode::OK, Json(response)).into_response()
 }
 
-async fn crawler_scheduler(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+#[derive(Deserialize)]
+struct SchedulerListQuery {
+    #[serde(default = "default_scheduler_limit")]
+    limit: i64,
+    #[serde(default)]
+    offset: i64,
+}
+
+fn default_scheduler_limit() -> i64 {
+    100
+}
+
+async fn crawler_scheduler(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<SchedulerListQuery>,
+) -> impl IntoResponse {
     info!("Getting crawler scheduler info");
 
-    let stats = state.crawler.scheduler_stats();
-    let tasks = state.crawler.scheduler().get_all();
+    let repo = ScheduleRepository::new(state.db_pool.clone());
+
+    let stats = match repo.stats().await {
+        Ok(stats) => stats,
+        Err(e) => {
+            error!("Failed to get scheduler stats: {}", e);
+            let response = ApiResponse::error(format!("Failed to get scheduler stats: {}", e));
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response();
+        }
+    };
+
+    match repo.list(params.limit.clamp(1, 1000), params.offset.max(0)).await {
+        Ok((tasks, total)) => {
+            let response = ApiResponse::success(serde_json::json!({
+                "stats": stats,
+                "tasks": tasks,
+                "total": total,
+                "limit": params.limit,
+                "offset": params.offset
+            }));
+            (StatusCode::OK, Json(response)).into_response()
+        }
+        Err(e) => {
+            error!("Failed to list scheduled crawls: {}", e);
+            let response = ApiResponse::error(format!("Failed to list scheduled crawls: {}", e));
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response()
+        }
+    }
+}
+
+async fn create_scheduled_crawl(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<CreateScheduledCrawlRequest>,
+) -> impl IntoResponse {
+    let url = match UrlProcessor::new().normalize(&payload.url) {
+        Ok(url) => url,
+        Err(e) => {
+            let response = ApiResponse::error(format!("Invalid URL: {}", e));
+            return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+        }
+    };
+
+    if payload.priority > 100 {
+        let response = ApiResponse::error("Priority must be between 0 and 100".to_string());
+        return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+    }
+
+    info!("Scheduling {} ({:?}, priority {})", url, payload.frequency, payload.priority);
+
+    let repo = ScheduleRepository::new(state.db_pool.clone());
+
+    match repo
+        .upsert(&url, payload.frequency, payload.priority, payload.next_crawl_at)
+        .await
+    {
+        Ok(record) => {
+            let response = ApiResponse::success(record);
+            (StatusCode::CREATED, Json(response)).into_response()
+        }
+        Err(e) => {
+            error!("Failed to create scheduled crawl: {}", e);
+            let response = ApiResponse::error(format!("Failed to create scheduled crawl: {}", e));
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response()
+        }
+    }
+}
+
+async fn get_scheduled_crawl(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    let repo = ScheduleRepository::new(state.db_pool.clone());
+
+    match repo.get(id).await {
+        Ok(Some(record)) => {
+            let response = ApiResponse::success(record);
+            (StatusCode::OK, Json(response)).into_response()
+        }
+        Ok(None) => {
+            let response = ApiResponse::error("Scheduled crawl not found".to_string());
+            (StatusCode::NOT_FOUND, Json(response)).into_response()
+        }
+        Err(e) => {
+            error!("Failed to get scheduled crawl: {}", e);
+            let response = ApiResponse::error(format!("Failed to get scheduled crawl: {}", e));
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response()
+        }
+    }
+}
+
+async fn update_scheduled_crawl(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<UpdateScheduledCrawlRequest>,
+) -> impl IntoResponse {
+    if payload.priority.is_some_and(|p| p > 100) {
+        let response = ApiResponse::error("Priority must be between 0 and 100".to_string());
+        return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+    }
+
+    info!("Updating scheduled crawl {}", id);
+
+    let repo = ScheduleRepository::new(state.db_pool.clone());
+
+    match repo
+        .update(id, payload.frequency, payload.priority, payload.next_crawl_at)
+        .await
+    {
+        Ok(Some(record)) => {
+            let response = ApiResponse::success(record);
+            (StatusCode::OK, Json(response)).into_response()
+        }
+        Ok(None) => {
+            let response = ApiResponse::error("Scheduled crawl not found".to_string());
+            (StatusCode::NOT_FOUND, Json(response)).into_response()
+        }
+        Err(e) => {
+            error!("Failed to update scheduled crawl: {}", e);
+            let response = ApiResponse::error(format!("Failed to update scheduled crawl: {}", e));
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response()
+        }
+    }
+}
+
+async fn delete_scheduled_crawl(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    info!("Deleting scheduled crawl {}", id);
+
+    let repo = ScheduleRepository::new(state.db_pool.clone());
+
+    match repo.delete(id).await {
+        Ok(true) => {
+            let response = ApiResponse::success(serde_json::json!({
+                "message": "Scheduled crawl deleted successfully"
+            }));
+            (StatusCode::OK, Json(response)).into_response()
+        }
+        Ok(false) => {
+            let response = ApiResponse::error("Scheduled crawl not found".to_string());
+            (StatusCode::NOT_FOUND, Json(response)).into_response()
+        }
+        Err(e) => {
+            error!("Failed to delete scheduled crawl: {}", e);
+            let response = ApiResponse::error(format!("Failed to delete scheduled crawl: {}", e));
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response()
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct FeedListQuery {
+    domain: Option<String>,
+    #[serde(default = "default_scheduler_limit")]
+    limit: i64,
+    #[serde(default)]
+    offset: i64,
+}
+
+async fn crawler_feeds(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<FeedListQuery>,
+) -> impl IntoResponse {
+    info!("Listing discovered feeds");
+
+    let repo = FeedRepository::new(state.db_pool.clone());
+    let domain = params.domain.as_deref().map(str::to_lowercase);
+
+    match repo.list(domain.as_deref(), params.limit.clamp(1, 1000), params.offset.max(0)).await {
+        Ok((feeds, total)) => {
+            let response = ApiResponse::success(serde_json::json!({
+                "feeds": feeds,
+                "total": total,
+                "limit": params.limit,
+                "offset": params.offset
+            }));
+            (StatusCode::OK, Json(response)).into_response()
+        }
+        Err(e) => {
+            error!("Failed to list feeds: {}", e);
+            let response = ApiResponse::error(format!("Failed to list feeds: {}", e));
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response()
+        }
+    }
+}
+
+// Crawl filter rule admin handlers
+
+async fn list_filter_rules(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let repo = FilterRuleRepository::new(state.db_pool.clone());
+
+    match repo.list().await {
+        Ok(rules) => {
+            let response = ApiResponse::success(serde_json::json!({
+                "total": rules.len(),
+                "rules": rules,
+            }));
+            (StatusCode::OK, Json(response)).into_response()
+        }
+        Err(e) => {
+            error!("Failed to list filter rules: {}", e);
+            let response = ApiResponse::error(format!("Failed to list filter rules: {}", e));
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response()
+        }
+    }
+}
+
+async fn create_filter_rule(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<CreateFilterRuleRequest>,
+) -> impl IntoResponse {
+    let pattern = match payload.kind.normalize_pattern(&payload.pattern) {
+        Ok(pattern) => pattern,
+        Err(e) => {
+            let response = ApiResponse::error(format!("{:#}", e));
+            return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+        }
+    };
+
+    info!("Admin creating {} filter rule {}", payload.kind.as_str(), pattern);
+
+    let repo = FilterRuleRepository::new(state.db_pool.clone());
+
+    match repo
+        .create(payload.kind, &pattern, payload.description.as_deref(), payload.enabled)
+        .await
+    {
+        Ok(rule) => {
+            let response = ApiResponse::success(rule);
+            (StatusCode::CREATED, Json(response)).into_response()
+        }
+        Err(e) => {
+            error!("Failed to create filter rule: {}", e);
+            let response = ApiResponse::error(format!("Failed to create filter rule: {}", e));
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response()
+        }
+    }
+}
+
+async fn update_filter_rule(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<UpdateFilterRuleRequest>,
+) -> impl IntoResponse {
+    let repo = FilterRuleRepository::new(state.db_pool.clone());
+
+    // A new pattern or kind is validated against the rule's resulting kind
+    let pattern = if payload.pattern.is_some() || payload.kind.is_some() {
+        let existing = match repo.get(id).await {
+            Ok(Some(rule)) => rule,
+            Ok(None) => {
+                let response = ApiResponse::error("Filter rule not found".to_string());
+                return (StatusCode::NOT_FOUND, Json(response)).into_response();
+            }
+            Err(e) => {
+                error!("Failed to get filter rule: {}", e);
+                let response = ApiResponse::error(format!("Failed to get filter rule: {}", e));
+                return (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response();
+            }
+        };
+
+        let kind = payload.kind.or_else(|| FilterRuleKind::parse(&existing.kind));
+        let pattern = payload.pattern.as_deref().unwrap_or(&existing.pattern);
+        match kind.map(|kind| kind.normalize_pattern(pattern)) {
+            Some(Ok(pattern)) => Some(pattern),
+            Some(Err(e)) => {
+                let response = ApiResponse::error(format!("{:#}", e));
+                return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+            }
+            None => {
+                let response = ApiResponse::error(format!("Unknown rule kind {}", existing.kind));
+                return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+            }
+        }
+    } else {
+        None
+    };
+
+    info!("Admin updating filter rule {}", id);
+
+    match repo
+        .update(
+            id,
+            payload.kind,
+            pattern.as_deref(),
+            payload.description.as_deref(),
+            payload.enabled,
+        )
+        .await
+    {
+        Ok(Some(rule)) => {
+            let response = ApiResponse::success(rule);
+            (StatusCode::OK, Json(response)).into_response()
+        }
+        Ok(None) => {
+            let response = ApiResponse::error("Filter rule not found".to_string());
+            (StatusCode::NOT_FOUND, Json(response)).into_response()
+        }
+        Err(e) => {
+            error!("Failed to update filter rule: {}", e);
+            let response = ApiResponse::error(format!("Failed to update filter rule: {}", e));
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response()
+        }
+    }
+}
+
+async fn delete_filter_rule(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    info!("Admin deleting filter rule {}", id);
+
+    let repo = FilterRuleRepository::new(state.db_pool.clone());
+
+    match repo.delete(id).await {
+        Ok(true) => {
+            let response = ApiResponse::success(serde_json::json!({
+                "message": "Filter rule deleted successfully"
+            }));
+            (StatusCode::OK, Json(response)).into_response()
+        }
+        Ok(false) => {
+            let response = ApiResponse::error("Filter rule not found".to_string());
+            (StatusCode::NOT_FOUND, Json(response)).into_response()
+        }
+        Err(e) => {
+            error!("Failed to delete filter rule: {}", e);
+            let response = ApiResponse::error(format!("Failed to delete filter rule: {}", e));
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response()
+        }
+    }
+}
+
+async fn list_open_circuits(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let circuits: Vec<_> = state
+        .crawler
+        .circuit_breaker()
+        .open_circuits()
+        .await
+        .into_iter()
+        .map(|(domain, stats)| serde_json::json!({ "domain": domain, "circuit": stats }))
+        .collect();
 
     let response = ApiResponse::success(serde_json::json!({
-        "stats": stats,
-        "tasks": tasks.into_iter().take(100).collect::<Vec<_>>(), // Limit to 100 tasks
+        "total": circuits.len(),
+        "circuits": circuits
     }));
     (StatusCode::OK, Json(response)).into_response()
 }
 
+#[derive(Debug, Deserialize)]
+struct OpenCircuitQuery {
+    #[serde(default = "default_circuit_open_secs")]
+    duration_secs: u64,
+}
+
+fn default_circuit_open_secs() -> u64 {
+    3600
+}
+
+/// Longest an admin can force a circuit open for (7 days)
+const MAX_CIRCUIT_OPEN_SECS: u64 = 7 * 24 * 60 * 60;
+
+/// Stop all workers from crawling a domain for a while
+async fn open_circuit(
+    State(state): State<Arc<AppState>>,
+    Path(domain): Path<String>,
+    Query(params): Query<OpenCircuitQuery>,
+) -> impl IntoResponse {
+    let duration_secs = params.duration_secs;
+    if duration_secs == 0 || duration_secs > MAX_CIRCUIT_OPEN_SECS {
+        let response = ApiResponse::error(format!(
+            "duration_secs must be between 1 and {}",
+            MAX_CIRCUIT_OPEN_SECS
+        ));
+        return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+    }
+
+    let domain = domain.trim().to_lowercase();
+    info!("Admin opening circuit for {} for {}s", domain, duration_secs);
+
+    let circuit_breaker = state.crawler.circuit_breaker();
+    circuit_breaker
+        .force_open(&domain, std::time::Duration::from_secs(duration_secs))
+        .await;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "domain": domain,
+        "circuit": circuit_breaker.get_domain_stats(&domain).await
+    }));
+    (StatusCode::OK, Json(response)).into_response()
+}
+
+/// Close a domain's circuit so workers crawl it again
+async fn reset_circuit(
+    State(state): State<Arc<AppState>>,
+    Path(domain): Path<String>,
+) -> impl IntoResponse {
+    let domain = domain.trim().to_lowercase();
+    info!("Admin resetting circuit for {}", domain);
+
+    state.crawler.reset_circuit_breaker(&domain).await;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "domain": domain,
+        "circuit": state.crawler.circuit_breaker().get_domain_stats(&domain).await
+    }));
+    (StatusCode::OK, Json(response)).into_response()
+}
+
+#[derive(Debug, Deserialize)]
+struct TestFilterRulesRequest {
+    url: String,
+}
+
+/// Dry run: would the crawler fetch this URL under the stored rules?
+///
+/// Runs the same admin rule and content filter checks crawlers run before
+/// fetching a URL; robots.txt and per-job patterns are not consulted.
+async fn test_filter_rules(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<TestFilterRulesRequest>,
+) -> impl IntoResponse {
+    // Crawlers check the normalized URL
+    let url = match UrlProcessor::new().normalize(&payload.url) {
+        Ok(url) => url,
+        Err(e) => {
+            let response = ApiResponse::error(format!("Invalid URL: {}", e));
+            return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+        }
+    };
+
+    let repo = FilterRuleRepository::new(state.db_pool.clone());
+
+    match repo.list().await {
+        Ok(rules) => {
+            let decision = state.crawler.check_url_with(&FilterRuleSet::compile(rules), &url);
+            let response = ApiResponse::success(serde_json::json!({
+                "url": payload.url,
+                "normalized_url": url,
+                "allowed": decision.allowed,
+                "rule": decision.rule,
+                "reason": decision.reason,
+            }));
+            (StatusCode::OK, Json(response)).into_response()
+        }
+        Err(e) => {
+            error!("Failed to load filter rules: {}", e);
+            let response = ApiResponse::error(format!("Failed to load filter rules: {}", e));
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response()
+        }
+    }
+}
+
 // Phase 7.6-7.8: Analytics endpoint handlers
 
 #[derive(Debug, Deserialize)]
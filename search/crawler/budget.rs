@@ -0,0 +1,516 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+
+use super::errors::CrawlFailure;
+use super::warc::WarcWriter;
+
+/// Limits on how much a crawl may fetch
+///
+/// Every limit is optional; an empty budget never runs out. Path limits cap
+/// the pages fetched under a URL path prefix (e.g. `"/blog": 100`).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CrawlBudget {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_pages: Option<usize>,
+    /// Response bytes downloaded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_bytes: Option<u64>,
+    /// Wall-clock time from the start of the crawl
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_duration_secs: Option<u64>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub max_pages_per_path: BTreeMap<String, usize>,
+}
+
+impl CrawlBudget {
+    pub fn is_unlimited(&self) -> bool {
+        self.max_pages.is_none()
+            && self.max_bytes.is_none()
+            && self.max_duration_secs.is_none()
+            && self.max_pages_per_path.is_empty()
+    }
+
+    /// Reject limits that could never let a page through
+    pub fn validate(&self) -> Result<()> {
+        if self.max_pages == Some(0) {
+            anyhow::bail!("max_pages must be at least 1");
+        }
+        if self.max_bytes == Some(0) {
+            anyhow::bail!("max_bytes must be at least 1");
+        }
+        if self.max_duration_secs == Some(0) {
+            anyhow::bail!("max_duration_secs must be at least 1");
+        }
+        for (prefix, limit) in &self.max_pages_per_path {
+            if !prefix.starts_with('/') {
+                anyhow::bail!("Path prefix {:?} must start with '/'", prefix);
+            }
+            if *limit == 0 {
+                anyhow::bail!("Page limit for {} must be at least 1", prefix);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The limit that stopped a crawl
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "limit", content = "value", rename_all = "snake_case")]
+pub enum BudgetExhausted {
+    MaxPages(usize),
+    MaxBytes(u64),
+    MaxDurationSecs(u64),
+}
+
+impl fmt::Display for BudgetExhausted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MaxPages(limit) => write!(f, "page budget of {} reached", limit),
+            Self::MaxBytes(limit) => write!(f, "download budget of {} bytes reached", limit),
+            Self::MaxDurationSecs(limit) => write!(f, "time budget of {}s reached", limit),
+        }
+    }
+}
+
+/// What a crawl used of its budget
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BudgetUsage {
+    pub pages: usize,
+    pub bytes: u64,
+    pub elapsed_secs: u64,
+    /// Pages fetched under each limited path prefix
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub path_pages: BTreeMap<String, usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exhausted: Option<BudgetExhausted>,
+}
+
+/// Usage of a job and each domain as recorded by `CrawlSession::summary`
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SessionUsage {
+    #[serde(default)]
+    pub job: BudgetUsage,
+    #[serde(default)]
+    pub domains: HashMap<String, BudgetUsage>,
+    #[serde(default)]
+    pub skipped_pages: usize,
+}
+
+/// Running totals against one budget
+#[derive(Debug, Clone)]
+pub struct BudgetTracker {
+    budget: CrawlBudget,
+    started: Instant,
+    pages: usize,
+    bytes: u64,
+    path_pages: HashMap<String, usize>,
+}
+
+impl BudgetTracker {
+    pub fn new(budget: CrawlBudget) -> Self {
+        Self {
+            budget,
+            started: Instant::now(),
+            pages: 0,
+            bytes: 0,
+            path_pages: HashMap::new(),
+        }
+    }
+
+    /// A tracker carrying on from earlier usage (a resumed job)
+    ///
+    /// Only the time the earlier run took counts against the time budget,
+    /// not the time the job was paused.
+    pub fn resume(budget: CrawlBudget, usage: &BudgetUsage) -> Self {
+        let elapsed = Duration::from_secs(usage.elapsed_secs);
+        Self {
+            budget,
+            started: Instant::now().checked_sub(elapsed).unwrap_or_else(Instant::now),
+            pages: usage.pages,
+            bytes: usage.bytes,
+            path_pages: usage.path_pages.clone().into_iter().collect(),
+        }
+    }
+
+    /// Whether a page under `path` still fits its path limits
+    pub fn admits(&self, path: &str) -> bool {
+        self.budget
+            .max_pages_per_path
+            .iter()
+            .filter(|(prefix, _)| path.starts_with(prefix.as_str()))
+            .all(|(prefix, limit)| self.path_pages.get(prefix).copied().unwrap_or(0) < *limit)
+    }
+
+    /// Count a fetched page
+    pub fn record(&mut self, path: &str, bytes: u64) {
+        self.pages += 1;
+        self.bytes += bytes;
+        for prefix in self.budget.max_pages_per_path.keys() {
+            if path.starts_with(prefix.as_str()) {
+                *self.path_pages.entry(prefix.clone()).or_insert(0) += 1;
+            }
+        }
+    }
+
+    /// The first limit that has run out, if any
+    pub fn exhausted(&self) -> Option<BudgetExhausted> {
+        if let Some(limit) = self.budget.max_pages {
+            if self.pages >= limit {
+                return Some(BudgetExhausted::MaxPages(limit));
+            }
+        }
+        if let Some(limit) = self.budget.max_bytes {
+            if self.bytes >= limit {
+                return Some(BudgetExhausted::MaxBytes(limit));
+            }
+        }
+        if let Some(limit) = self.budget.max_duration_secs {
+            if self.started.elapsed() >= Duration::from_secs(limit) {
+                return Some(BudgetExhausted::MaxDurationSecs(limit));
+            }
+        }
+        None
+    }
+
+    pub fn remaining_pages(&self) -> Option<usize> {
+        self.budget.max_pages.map(|limit| limit.saturating_sub(self.pages))
+    }
+
+    pub fn remaining_time(&self) -> Option<Duration> {
+        self.budget
+            .max_duration_secs
+            .map(|limit| Duration::from_secs(limit).saturating_sub(self.started.elapsed()))
+    }
+
+    pub fn usage(&self) -> BudgetUsage {
+        BudgetUsage {
+            pages: self.pages,
+            bytes: self.bytes,
+            elapsed_secs: self.started.elapsed().as_secs(),
+            path_pages: self.path_pages.iter().map(|(prefix, pages)| (prefix.clone(), *pages)).collect(),
+            exhausted: self.exhausted(),
+        }
+    }
+}
+
+/// Flag that stops a crawl between pages, shared with whoever may stop it
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    inner: Arc<CancellationState>,
+}
+
+#[derive(Debug, Default)]
+struct CancellationState {
+    cancelled: AtomicBool,
+    notify: Notify,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::SeqCst);
+        self.inner.notify.notify_waiters();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Wait until the token is cancelled
+    pub async fn cancelled(&self) {
+        loop {
+            let notified = self.inner.notify.notified();
+            if self.is_cancelled() {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+/// State shared by every fetch of one crawl job
+///
+/// Tracks the job budget, a budget per domain, the job's WARC archive, the
+/// job's cancellation token and the URLs that failed since the caller last
+/// took them.
+pub struct CrawlSession {
+    archive: Option<WarcWriter>,
+    cancellation: CancellationToken,
+    job: BudgetTracker,
+    domain_budget: CrawlBudget,
+    domains: HashMap<String, BudgetTracker>,
+    /// Pages dropped because their path prefix was used up
+    skipped_pages: usize,
+    failures: Vec<CrawlFailure>,
+}
+
+impl CrawlSession {
+    pub fn new(job_budget: CrawlBudget, domain_budget: CrawlBudget) -> Self {
+        Self {
+            archive: None,
+            cancellation: CancellationToken::new(),
+            job: BudgetTracker::new(job_budget),
+            domain_budget,
+            domains: HashMap::new(),
+            skipped_pages: 0,
+            failures: Vec::new(),
+        }
+    }
+
+    /// Carry on from the usage of an earlier run of the job (resumed jobs)
+    pub fn with_usage(mut self, usage: &SessionUsage) -> Self {
+        self.job = BudgetTracker::resume(self.job.budget.clone(), &usage.job);
+        self.domains = usage
+            .domains
+            .iter()
+            .map(|(domain, usage)| (domain.clone(), BudgetTracker::resume(self.domain_budget.clone(), usage)))
+            .collect();
+        self.skipped_pages = usage.skipped_pages;
+        self
+    }
+
+    /// A session with no limits (single fetches, tools)
+    pub fn unlimited() -> Self {
+        Self::new(CrawlBudget::default(), CrawlBudget::default())
+    }
+
+    pub fn with_archive(mut self, archive: Option<WarcWriter>) -> Self {
+        self.archive = archive;
+        self
+    }
+
+    pub fn archive(&self) -> Option<&WarcWriter> {
+        self.archive.as_ref()
+    }
+
+    pub fn with_cancellation(mut self, cancellation: CancellationToken) -> Self {
+        self.cancellation = cancellation;
+        self
+    }
+
+    pub fn cancellation(&self) -> &CancellationToken {
+        &self.cancellation
+    }
+
+    /// Whether the job was stopped (cancelled or paused) from outside
+    pub fn is_cancelled(&self) -> bool {
+        self.cancellation.is_cancelled()
+    }
+
+    pub fn record_failure(&mut self, failure: CrawlFailure) {
+        self.failures.push(failure);
+    }
+
+    /// Failures recorded since the last call, for `crawl_errors`
+    pub fn take_failures(&mut self) -> Vec<CrawlFailure> {
+        std::mem::take(&mut self.failures)
+    }
+
+    /// Why the whole job has to stop, if it does
+    pub fn exhausted(&self) -> Option<BudgetExhausted> {
+        self.job.exhausted()
+    }
+
+    /// Why no more pages may be fetched from `domain`, if so
+    pub fn domain_exhausted(&self, domain: &str) -> Option<BudgetExhausted> {
+        self.exhausted()
+            .or_else(|| self.domains.get(domain).and_then(|tracker| tracker.exhausted()))
+    }
+
+    /// Whether a page still fits the path limits of the job and its domain
+    pub fn admits(&mut self, domain: &str, path: &str) -> bool {
+        let admitted = self.job.admits(path)
+            && self.domains.get(domain).is_none_or(|tracker| tracker.admits(path));
+        if !admitted {
+            self.skipped_pages += 1;
+        }
+        admitted
+    }
+
+    /// Count a fetched page against the job and its domain
+    pub fn record(&mut self, domain: &str, path: &str, bytes: u64) {
+        self.job.record(path, bytes);
+        self.domain_tracker(domain).record(path, bytes);
+    }
+
+    /// Time left before the job or the domain runs out, if either is limited
+    pub fn remaining_time(&mut self, domain: &str) -> Option<Duration> {
+        let domain_remaining = self.domain_tracker(domain).remaining_time();
+        match (self.job.remaining_time(), domain_remaining) {
+            (Some(job), Some(domain)) => Some(job.min(domain)),
+            (job, domain) => job.or(domain),
+        }
+    }
+
+    /// Usage of the job and each domain, for `crawl_history.metadata`
+    pub fn summary(&self) -> serde_json::Value {
+        let domains: BTreeMap<&str, BudgetUsage> = self
+            .domains
+            .iter()
+            .map(|(domain, tracker)| (domain.as_str(), tracker.usage()))
+            .collect();
+
+        serde_json::json!({
+            "job": self.job.usage(),
+            "domains": domains,
+            "skipped_pages": self.skipped_pages,
+        })
+    }
+
+    fn domain_tracker(&mut self, domain: &str) -> &mut BudgetTracker {
+        let budget = &self.domain_budget;
+        self.domains
+            .entry(domain.to_string())
+            .or_insert_with(|| BudgetTracker::new(budget.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn budget() -> CrawlBudget {
+        CrawlBudget {
+            max_pages: Some(3),
+            max_bytes: Some(1_000),
+            max_duration_secs: None,
+            max_pages_per_path: BTreeMap::from([("/blog".to_string(), 1)]),
+        }
+    }
+
+    #[test]
+    fn test_tracker_limits() {
+        let mut tracker = BudgetTracker::new(budget());
+        assert_eq!(tracker.exhausted(), None);
+
+        tracker.record("/", 100);
+        assert!(tracker.admits("/blog/first"));
+        tracker.record("/blog/first", 100);
+        assert!(!tracker.admits("/blog/second"));
+        assert!(tracker.admits("/docs"));
+        assert_eq!(tracker.remaining_pages(), Some(1));
+
+        tracker.record("/docs", 100);
+        assert_eq!(tracker.exhausted(), Some(BudgetExhausted::MaxPages(3)));
+
+        let mut tracker = BudgetTracker::new(budget());
+        tracker.record("/", 1_500);
+        assert_eq!(tracker.exhausted(), Some(BudgetExhausted::MaxBytes(1_000)));
+    }
+
+    #[test]
+    fn test_time_limit() {
+        let tracker = BudgetTracker::new(CrawlBudget {
+            max_duration_secs: Some(0),
+            ..Default::default()
+        });
+        assert_eq!(tracker.exhausted(), Some(BudgetExhausted::MaxDurationSecs(0)));
+        assert_eq!(tracker.remaining_time(), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn test_unlimited_budget() {
+        let mut tracker = BudgetTracker::new(CrawlBudget::default());
+        for _ in 0..1_000 {
+            tracker.record("/", 1_000_000);
+        }
+        assert!(CrawlBudget::default().is_unlimited());
+        assert_eq!(tracker.exhausted(), None);
+        assert_eq!(tracker.remaining_pages(), None);
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(budget().validate().is_ok());
+        assert!(CrawlBudget { max_pages: Some(0), ..Default::default() }.validate().is_err());
+        assert!(CrawlBudget {
+            max_pages_per_path: BTreeMap::from([("blog".to_string(), 5)]),
+            ..Default::default()
+        }
+        .validate()
+        .is_err());
+    }
+
+    #[test]
+    fn test_session_domain_budgets() {
+        let mut session = CrawlSession::new(
+            CrawlBudget { max_pages: Some(10), ..Default::default() },
+            CrawlBudget { max_pages: Some(2), ..Default::default() },
+        );
+
+        session.record("a.com", "/", 10);
+        session.record("a.com", "/about", 10);
+        assert_eq!(session.domain_exhausted("a.com"), Some(BudgetExhausted::MaxPages(2)));
+        assert_eq!(session.domain_exhausted("b.com"), None);
+        assert_eq!(session.exhausted(), None);
+
+        let summary = session.summary();
+        assert_eq!(summary["job"]["pages"], 2);
+        assert_eq!(summary["domains"]["a.com"]["exhausted"]["limit"], "max_pages");
+    }
+
+    #[test]
+    fn test_session_path_limits() {
+        let mut session = CrawlSession::new(budget(), CrawlBudget::default());
+
+        assert!(session.admits("a.com", "/blog/one"));
+        session.record("a.com", "/blog/one", 10);
+        assert!(!session.admits("a.com", "/blog/two"));
+        assert!(session.admits("a.com", "/docs/one"));
+        assert_eq!(session.summary()["skipped_pages"], 1);
+    }
+
+    #[test]
+    fn test_session_resumes_usage() {
+        let mut session = CrawlSession::new(budget(), CrawlBudget { max_pages: Some(2), ..Default::default() });
+        session.record("a.com", "/blog/one", 400);
+        session.record("a.com", "/", 400);
+
+        let usage: SessionUsage = serde_json::from_value(session.summary()).unwrap();
+        let mut resumed = CrawlSession::new(budget(), CrawlBudget { max_pages: Some(2), ..Default::default() })
+            .with_usage(&usage);
+        assert_eq!(resumed.domain_exhausted("a.com"), Some(BudgetExhausted::MaxPages(2)));
+        assert!(!resumed.admits("b.com", "/blog/two"));
+
+        resumed.record("b.com", "/", 300);
+        assert_eq!(resumed.exhausted(), Some(BudgetExhausted::MaxPages(3)));
+        let summary = resumed.summary();
+        assert_eq!(summary["job"]["bytes"], 1_100);
+        assert_eq!(summary["domains"]["a.com"]["pages"], 2);
+        assert_eq!(summary["skipped_pages"], 1);
+    }
+
+    #[test]
+    fn test_budget_deserialize() {
+        let budget: CrawlBudget =
+            serde_json::from_str(r#"{"max_pages": 50, "max_pages_per_path": {"/docs": 10}}"#).unwrap();
+        assert_eq!(budget.max_pages, Some(50));
+        assert_eq!(budget.max_pages_per_path.get("/docs"), Some(&10));
+        assert!(budget.max_bytes.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_session_cancellation() {
+        let token = CancellationToken::new();
+        let session = CrawlSession::unlimited().with_cancellation(token.clone());
+        assert!(!session.is_cancelled());
+
+        let waiter = tokio::spawn({
+            let token = session.cancellation().clone();
+            async move { token.cancelled().await }
+        });
+        token.cancel();
+
+        assert!(session.is_cancelled());
+        tokio::time::timeout(Duration::from_secs(1), waiter).await.unwrap().unwrap();
+    }
+}
@@ -1,10 +1,105 @@
 use anyhow::Result;
+use chrono::{DateTime, TimeZone, Utc};
 use dashmap::DashMap;
+use redis::aio::ConnectionManager;
+use redis::{AsyncCommands, Script};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tracing::{debug, info, warn};
 
+/// Redis set indexing every domain with a shared circuit
+const CIRCUITS_KEY: &str = "crawler:circuits";
+
+/// Shared circuits untouched for this long are dropped
+const CIRCUIT_TTL_MS: u64 = 7 * 24 * 60 * 60 * 1000;
+
+/// The circuit state machine, run atomically on a domain's Redis hash
+///
+/// ARGV: operation (`check`, `success`, `failure`, `open`, `reset`), failure
+/// threshold, open timeout ms, success threshold, half-open request limit,
+/// entry TTL ms and the domain. `open` uses the open timeout as its duration.
+/// Half-open probes hold their slot for one open timeout, so probes that
+/// never report back can't keep the circuit half-open forever.
+/// Returns 1 when a checked request may proceed, otherwise 0.
+const CIRCUIT_SCRIPT: &str = r#"
+local time = redis.call('TIME')
+local now = tonumber(time[1]) * 1000 + math.floor(tonumber(time[2]) / 1000)
+local op = ARGV[1]
+local fields = redis.call('HMGET', KEYS[1], 'state', 'failure_count', 'success_count', 'open_until',
+    'half_open_requests', 'probe_until')
+local state = fields[1] or 'closed'
+local failures = tonumber(fields[2]) or 0
+local successes = tonumber(fields[3]) or 0
+local open_until = tonumber(fields[4]) or 0
+local half_open = tonumber(fields[5]) or 0
+local probe_until = tonumber(fields[6]) or 0
+local allowed = 1
+
+local function open()
+    state = 'open'
+    open_until = now + tonumber(ARGV[3])
+    failures = 0
+    successes = 0
+end
+
+local function close()
+    state = 'closed'
+    open_until = 0
+    failures = 0
+    successes = 0
+    half_open = 0
+end
+
+if op == 'check' then
+    if state == 'open' and now >= open_until then
+        state = 'half_open'
+        half_open = 0
+        successes = 0
+    end
+    if state == 'half_open' and now >= probe_until then
+        half_open = 0
+    end
+    if state == 'open' then
+        allowed = 0
+    elseif state == 'half_open' then
+        if half_open < tonumber(ARGV[5]) then
+            half_open = half_open + 1
+            probe_until = now + tonumber(ARGV[3])
+        else
+            allowed = 0
+        end
+    end
+elseif op == 'success' then
+    redis.call('HINCRBY', KEYS[1], 'total_successes', 1)
+    if state == 'closed' then
+        failures = 0
+    elseif state == 'half_open' then
+        successes = successes + 1
+        if successes >= tonumber(ARGV[4]) then close() end
+    end
+elseif op == 'failure' then
+    redis.call('HINCRBY', KEYS[1], 'total_failures', 1)
+    if state == 'closed' then
+        failures = failures + 1
+        if failures >= tonumber(ARGV[2]) then open() end
+    elseif state == 'half_open' then
+        open()
+    end
+elseif op == 'open' then
+    open()
+elseif op == 'reset' then
+    close()
+end
+
+redis.call('HSET', KEYS[1], 'state', state, 'failure_count', failures, 'success_count', successes,
+    'open_until', open_until, 'half_open_requests', half_open, 'probe_until', probe_until)
+redis.call('PEXPIRE', KEYS[1], ARGV[6])
+redis.call('SADD', KEYS[2], ARGV[7])
+return allowed
+"#;
+
 /// Circuit breaker states
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum CircuitState {
@@ -16,6 +111,17 @@ pub enum CircuitState {
     HalfOpen,
 }
 
+impl CircuitState {
+    /// Parse the state name stored in Redis
+    fn parse(value: &str) -> Self {
+        match value {
+            "open" => CircuitState::Open,
+            "half_open" => CircuitState::HalfOpen,
+            _ => CircuitState::Closed,
+        }
+    }
+}
+
 /// Circuit breaker configuration
 #[derive(Debug, Clone)]
 pub struct CircuitBreakerConfig {
@@ -49,10 +155,12 @@ struct CircuitBreakerState {
     failure_count: u32,
     /// Number of consecutive successes in half-open state
     success_count: u32,
-    /// Time when the circuit was opened
-    opened_at: Option<Instant>,
+    /// Time until which the circuit stays open
+    open_until: Option<Instant>,
     /// Number of requests in half-open state
     half_open_requests: u32,
+    /// Until when the latest half-open probe holds its slot
+    probe_until: Option<Instant>,
     /// Total failures tracked
     total_failures: u64,
     /// Total successes tracked
@@ -65,8 +173,9 @@ impl Default for CircuitBreakerState {
             state: CircuitState::Closed,
             failure_count: 0,
             success_count: 0,
-            opened_at: None,
+            open_until: None,
             half_open_requests: 0,
+            probe_until: None,
             total_failures: 0,
             total_successes: 0,
         }
@@ -75,16 +184,12 @@ impl Default for CircuitBreakerState {
 
 impl CircuitBreakerState {
     /// Check if circuit should transition to half-open
-    fn should_attempt_reset(&self, config: &CircuitBreakerConfig) -> bool {
+    fn should_attempt_reset(&self) -> bool {
         if self.state != CircuitState::Open {
             return false;
         }
 
-        if let Some(opened_at) = self.opened_at {
-            Instant::now().duration_since(opened_at) >= config.open_timeout
-        } else {
-            false
-        }
+        self.open_until.is_some_and(|until| Instant::now() >= until)
     }
 
     /// Transition to half-open state
@@ -95,10 +200,10 @@ impl CircuitBreakerState {
         debug!("Circuit transitioned to HalfOpen state");
     }
 
-    /// Transition to open state
-    fn transition_to_open(&mut self) {
+    /// Transition to open state for `duration`
+    fn transition_to_open(&mut self, duration: Duration) {
         self.state = CircuitState::Open;
-        self.opened_at = Some(Instant::now());
+        self.open_until = Some(Instant::now() + duration);
         self.failure_count = 0;
         self.success_count = 0;
         warn!("Circuit opened due to repeated failures");
@@ -107,10 +212,11 @@ impl CircuitBreakerState {
     /// Transition to closed state
     fn transition_to_closed(&mut self) {
         self.state = CircuitState::Closed;
-        self.opened_at = None;
+        self.open_until = None;
         self.failure_count = 0;
         self.success_count = 0;
         self.half_open_requests = 0;
+        self.probe_until = None;
         info!("Circuit closed - normal operation resumed");
     }
 
@@ -144,12 +250,12 @@ impl CircuitBreakerState {
             CircuitState::Closed => {
                 self.failure_count += 1;
                 if self.failure_count >= config.failure_threshold {
-                    self.transition_to_open();
+                    self.transition_to_open(config.open_timeout);
                 }
             }
             CircuitState::HalfOpen => {
                 // Any failure in half-open immediately reopens circuit
-                self.transition_to_open();
+                self.transition_to_open(config.open_timeout);
             }
             CircuitState::Open => {
                 // Already open, just track the failure
@@ -160,16 +266,22 @@ impl CircuitBreakerState {
     /// Check if a request can proceed
     fn can_proceed(&mut self, config: &CircuitBreakerConfig) -> bool {
         // Check if we should attempt reset
-        if self.should_attempt_reset(config) {
+        if self.should_attempt_reset() {
             self.transition_to_half_open();
         }
 
+        // Probes that never reported back give up their slots
+        if self.state == CircuitState::HalfOpen && self.probe_until.is_none_or(|until| Instant::now() >= until) {
+            self.half_open_requests = 0;
+        }
+
         match self.state {
             CircuitState::Closed => true,
             CircuitState::Open => false,
             CircuitState::HalfOpen => {
                 if self.half_open_requests < config.half_open_max_requests {
                     self.half_open_requests += 1;
+                    self.probe_until = Some(Instant::now() + config.open_timeout);
                     true
                 } else {
                     false
@@ -177,13 +289,56 @@ impl CircuitBreakerState {
             }
         }
     }
+
+    fn stats(&self) -> DomainCircuitStats {
+        DomainCircuitStats {
+            state: self.state,
+            failure_count: self.failure_count,
+            success_count: self.success_count,
+            total_failures: self.total_failures,
+            total_successes: self.total_successes,
+            open_until: self.open_until.filter(|_| self.state == CircuitState::Open).and_then(|until| {
+                let remaining = until.saturating_duration_since(Instant::now());
+                Some(Utc::now() + chrono::Duration::from_std(remaining).ok()?)
+            }),
+        }
+    }
+}
+
+/// Operations of the shared state machine
+#[derive(Debug, Clone, Copy)]
+enum CircuitOp {
+    Check,
+    Success,
+    Failure,
+    Open(Duration),
+    Reset,
+}
+
+impl CircuitOp {
+    fn as_str(&self) -> &'static str {
+        match self {
+            CircuitOp::Check => "check",
+            CircuitOp::Success => "success",
+            CircuitOp::Failure => "failure",
+            CircuitOp::Open(_) => "open",
+            CircuitOp::Reset => "reset",
+        }
+    }
 }
 
 /// Circuit breaker manager for multiple domains
+///
+/// With Redis configured, circuits are shared: a domain tripped by one
+/// worker is skipped by all of them, and the API sees and changes the same
+/// state. Without Redis, or while it is unreachable, each process keeps its
+/// own circuits.
 #[derive(Clone)]
 pub struct CircuitBreakerManager {
     /// Circuit breaker state per domain
     circuits: Arc<DashMap<String, CircuitBreakerState>>,
+    /// Shared circuit store
+    redis: Option<ConnectionManager>,
     /// Configuration
     config: CircuitBreakerConfig,
 }
@@ -193,6 +348,7 @@ impl CircuitBreakerManager {
     pub fn new(config: CircuitBreakerConfig) -> Self {
         Self {
             circuits: Arc::new(DashMap::new()),
+            redis: None,
             config,
         }
     }
@@ -202,10 +358,22 @@ impl CircuitBreakerManager {
         Self::new(CircuitBreakerConfig::default())
     }
 
+    /// Share circuits with other workers and the API through Redis
+    pub fn with_redis(mut self, conn: ConnectionManager) -> Self {
+        info!("Circuit breakers shared through Redis");
+        self.redis = Some(conn);
+        self
+    }
+
     /// Check if a request to a domain can proceed
-    pub fn can_proceed(&self, domain: &str) -> bool {
-        let mut entry = self.circuits.entry(domain.to_string()).or_default();
-        let can_proceed = entry.can_proceed(&self.config);
+    pub async fn can_proceed(&self, domain: &str) -> bool {
+        let can_proceed = match self.run_shared(domain, CircuitOp::Check).await {
+            Some(allowed) => allowed,
+            None => {
+                let mut entry = self.circuits.entry(domain.to_string()).or_default();
+                entry.can_proceed(&self.config)
+            }
+        };
 
         if !can_proceed {
             debug!("Circuit breaker blocked request to {}", domain);
@@ -215,52 +383,70 @@ impl CircuitBreakerManager {
     }
 
     /// Record a successful request for a domain
-    pub fn record_success(&self, domain: &str) {
-        let mut entry = self.circuits.entry(domain.to_string()).or_default();
-        entry.record_success(&self.config);
+    pub async fn record_success(&self, domain: &str) {
+        if self.run_shared(domain, CircuitOp::Success).await.is_none() {
+            let mut entry = self.circuits.entry(domain.to_string()).or_default();
+            entry.record_success(&self.config);
+        }
     }
 
     /// Record a failed request for a domain
-    pub fn record_failure(&self, domain: &str) {
-        let mut entry = self.circuits.entry(domain.to_string()).or_default();
-        entry.record_failure(&self.config);
+    pub async fn record_failure(&self, domain: &str) {
+        if self.run_shared(domain, CircuitOp::Failure).await.is_none() {
+            let mut entry = self.circuits.entry(domain.to_string()).or_default();
+            entry.record_failure(&self.config);
+        }
     }
 
     /// Get the current state of a domain's circuit
-    pub fn get_state(&self, domain: &str) -> CircuitState {
-        self.circuits
-            .get(domain)
-            .map(|entry| entry.state)
+    pub async fn get_state(&self, domain: &str) -> CircuitState {
+        self.get_domain_stats(domain)
+            .await
+            .map(|stats| stats.state)
             .unwrap_or(CircuitState::Closed)
     }
 
     /// Reset a circuit breaker for a domain
-    pub fn reset(&self, domain: &str) {
-        if let Some(mut entry) = self.circuits.get_mut(domain) {
-            entry.transition_to_closed();
-            info!("Manually reset circuit breaker for {}", domain);
+    pub async fn reset(&self, domain: &str) {
+        if self.run_shared(domain, CircuitOp::Reset).await.is_none() {
+            if let Some(mut entry) = self.circuits.get_mut(domain) {
+                entry.transition_to_closed();
+            }
+        }
+        info!("Manually reset circuit breaker for {}", domain);
+    }
+
+    /// Open a domain's circuit for `duration`, whatever its health
+    pub async fn force_open(&self, domain: &str, duration: Duration) {
+        if self.run_shared(domain, CircuitOp::Open(duration)).await.is_none() {
+            let mut entry = self.circuits.entry(domain.to_string()).or_default();
+            entry.transition_to_open(duration);
         }
+        info!("Manually opened circuit breaker for {} for {:?}", domain, duration);
     }
 
     /// Get statistics for a domain
-    pub fn get_domain_stats(&self, domain: &str) -> Option<DomainCircuitStats> {
-        self.circuits.get(domain).map(|entry| DomainCircuitStats {
-            state: entry.state,
-            failure_count: entry.failure_count,
-            success_count: entry.success_count,
-            total_failures: entry.total_failures,
-            total_successes: entry.total_successes,
-        })
+    pub async fn get_domain_stats(&self, domain: &str) -> Option<DomainCircuitStats> {
+        if let Some(conn) = &self.redis {
+            match Self::load_shared(conn.clone(), domain).await {
+                Ok(stats) => return stats,
+                Err(e) => warn!("Shared circuit state unavailable for {}: {}", domain, e),
+            }
+        }
+
+        self.circuits.get(domain).map(|entry| entry.stats())
     }
 
     /// Get overall statistics
-    pub fn stats(&self) -> CircuitBreakerStats {
+    pub async fn stats(&self) -> CircuitBreakerStats {
+        let domains = self.get_all_domains().await;
+
         let mut open_count = 0;
         let mut half_open_count = 0;
         let mut closed_count = 0;
 
-        for entry in self.circuits.iter() {
-            match entry.state {
+        for (_, stats) in &domains {
+            match stats.state {
                 CircuitState::Open => open_count += 1,
                 CircuitState::HalfOpen => half_open_count += 1,
                 CircuitState::Closed => closed_count += 1,
@@ -268,7 +454,7 @@ impl CircuitBreakerManager {
         }
 
         CircuitBreakerStats {
-            total_circuits: self.circuits.len(),
+            total_circuits: domains.len(),
             open_circuits: open_count,
             half_open_circuits: half_open_count,
             closed_circuits: closed_count,
@@ -276,29 +462,99 @@ impl CircuitBreakerManager {
     }
 
     /// Clear all circuit breakers
+    ///
+    /// Only clears this process's circuits.
     pub fn clear_all(&self) {
         self.circuits.clear();
         info!("Cleared all circuit breakers");
     }
 
     /// Get all domains and their circuit states (Phase 6.10)
-    pub fn get_all_domains(&self) -> Vec<(String, DomainCircuitStats)> {
+    pub async fn get_all_domains(&self) -> Vec<(String, DomainCircuitStats)> {
+        if let Some(conn) = &self.redis {
+            match Self::load_all_shared(conn.clone()).await {
+                Ok(domains) => return domains,
+                Err(e) => warn!("Shared circuit state unavailable: {}", e),
+            }
+        }
+
         self.circuits
             .iter()
-            .map(|entry| {
-                let domain = entry.key().clone();
-                let state = entry.value();
-                let stats = DomainCircuitStats {
-                    state: state.state,
-                    failure_count: state.failure_count,
-                    success_count: state.success_count,
-                    total_failures: state.total_failures,
-                    total_successes: state.total_successes,
-                };
-                (domain, stats)
-            })
+            .map(|entry| (entry.key().clone(), entry.value().stats()))
             .collect()
     }
+
+    /// Domains whose circuit is open or half-open
+    pub async fn open_circuits(&self) -> Vec<(String, DomainCircuitStats)> {
+        let mut domains: Vec<_> = self
+            .get_all_domains()
+            .await
+            .into_iter()
+            .filter(|(_, stats)| stats.state != CircuitState::Closed)
+            .collect();
+        domains.sort_by(|a, b| a.0.cmp(&b.0));
+        domains
+    }
+
+    /// Run `op` on the domain's shared circuit
+    ///
+    /// Returns whether a checked request may proceed, or `None` when there is
+    /// no shared store to use.
+    async fn run_shared(&self, domain: &str, op: CircuitOp) -> Option<bool> {
+        let mut conn = self.redis.clone()?;
+
+        let open_timeout = match op {
+            CircuitOp::Open(duration) => duration,
+            _ => self.config.open_timeout,
+        };
+
+        let result: redis::RedisResult<i64> = Script::new(CIRCUIT_SCRIPT)
+            .key(Self::circuit_key(domain))
+            .key(CIRCUITS_KEY)
+            .arg(op.as_str())
+            .arg(self.config.failure_threshold)
+            .arg(open_timeout.as_millis() as u64)
+            .arg(self.config.success_threshold)
+            .arg(self.config.half_open_max_requests)
+            .arg(CIRCUIT_TTL_MS)
+            .arg(domain)
+            .invoke_async(&mut conn)
+            .await;
+
+        match result {
+            Ok(allowed) => Some(allowed == 1),
+            Err(e) => {
+                warn!("Shared circuit state unavailable for {}, using local state: {}", domain, e);
+                None
+            }
+        }
+    }
+
+    async fn load_shared(mut conn: ConnectionManager, domain: &str) -> Result<Option<DomainCircuitStats>> {
+        let fields: HashMap<String, String> = conn.hgetall(Self::circuit_key(domain)).await?;
+        Ok(DomainCircuitStats::from_fields(&fields))
+    }
+
+    async fn load_all_shared(mut conn: ConnectionManager) -> Result<Vec<(String, DomainCircuitStats)>> {
+        let domains: Vec<String> = conn.smembers(CIRCUITS_KEY).await?;
+
+        let mut circuits = Vec::with_capacity(domains.len());
+        for domain in domains {
+            match Self::load_shared(conn.clone(), &domain).await? {
+                Some(stats) => circuits.push((domain, stats)),
+                // The circuit expired; drop it from the index
+                None => {
+                    let _: () = conn.srem(CIRCUITS_KEY, &domain).await?;
+                }
+            }
+        }
+
+        Ok(circuits)
+    }
+
+    fn circuit_key(domain: &str) -> String {
+        format!("crawler:circuit:{}", domain)
+    }
 }
 
 /// Circuit breaker statistics
@@ -318,21 +574,50 @@ pub struct DomainCircuitStats {
     pub success_count: u32,
     pub total_failures: u64,
     pub total_successes: u64,
+    /// When an open circuit lets requests through again
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub open_until: Option<DateTime<Utc>>,
+}
+
+impl DomainCircuitStats {
+    /// Read a circuit's Redis hash; `None` when it doesn't exist
+    fn from_fields(fields: &HashMap<String, String>) -> Option<Self> {
+        if fields.is_empty() {
+            return None;
+        }
+
+        let number = |name: &str| fields.get(name).and_then(|value| value.parse::<u64>().ok()).unwrap_or(0);
+
+        let state = CircuitState::parse(fields.get("state").map(String::as_str).unwrap_or_default());
+        let open_until = match state {
+            CircuitState::Open => Utc.timestamp_millis_opt(number("open_until") as i64).single(),
+            _ => None,
+        };
+
+        Some(Self {
+            state,
+            failure_count: number("failure_count") as u32,
+            success_count: number("success_count") as u32,
+            total_failures: number("total_failures"),
+            total_successes: number("total_successes"),
+            open_until,
+        })
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_circuit_breaker_initial_state() {
+    #[tokio::test]
+    async fn test_circuit_breaker_initial_state() {
         let manager = CircuitBreakerManager::default();
-        assert_eq!(manager.get_state("example.com"), CircuitState::Closed);
-        assert!(manager.can_proceed("example.com"));
+        assert_eq!(manager.get_state("example.com").await, CircuitState::Closed);
+        assert!(manager.can_proceed("example.com").await);
     }
 
-    #[test]
-    fn test_circuit_opens_after_failures() {
+    #[tokio::test]
+    async fn test_circuit_opens_after_failures() {
         let config = CircuitBreakerConfig {
             failure_threshold: 3,
             ..Default::default()
@@ -342,19 +627,19 @@ mod tests {
         let domain = "example.com";
 
         // First 2 failures - should stay closed
-        manager.record_failure(domain);
-        manager.record_failure(domain);
-        assert_eq!(manager.get_state(domain), CircuitState::Closed);
-        assert!(manager.can_proceed(domain));
+        manager.record_failure(domain).await;
+        manager.record_failure(domain).await;
+        assert_eq!(manager.get_state(domain).await, CircuitState::Closed);
+        assert!(manager.can_proceed(domain).await);
 
         // Third failure - should open
-        manager.record_failure(domain);
-        assert_eq!(manager.get_state(domain), CircuitState::Open);
-        assert!(!manager.can_proceed(domain));
+        manager.record_failure(domain).await;
+        assert_eq!(manager.get_state(domain).await, CircuitState::Open);
+        assert!(!manager.can_proceed(domain).await);
     }
 
-    #[test]
-    fn test_circuit_resets_on_success() {
+    #[tokio::test]
+    async fn test_circuit_resets_on_success() {
         let config = CircuitBreakerConfig {
             failure_threshold: 3,
             ..Default::default()
@@ -364,20 +649,20 @@ mod tests {
         let domain = "example.com";
 
         // Record 2 failures
-        manager.record_failure(domain);
-        manager.record_failure(domain);
+        manager.record_failure(domain).await;
+        manager.record_failure(domain).await;
 
         // Success should reset failure count
-        manager.record_success(domain);
+        manager.record_success(domain).await;
 
         // Now 2 more failures shouldn't open circuit
-        manager.record_failure(domain);
-        manager.record_failure(domain);
-        assert_eq!(manager.get_state(domain), CircuitState::Closed);
+        manager.record_failure(domain).await;
+        manager.record_failure(domain).await;
+        assert_eq!(manager.get_state(domain).await, CircuitState::Closed);
     }
 
-    #[test]
-    fn test_half_open_state_transitions() {
+    #[tokio::test]
+    async fn test_half_open_state_transitions() {
         let config = CircuitBreakerConfig {
             failure_threshold: 2,
             open_timeout: Duration::from_millis(100),
@@ -389,28 +674,28 @@ mod tests {
         let domain = "example.com";
 
         // Open the circuit
-        manager.record_failure(domain);
-        manager.record_failure(domain);
-        assert_eq!(manager.get_state(domain), CircuitState::Open);
+        manager.record_failure(domain).await;
+        manager.record_failure(domain).await;
+        assert_eq!(manager.get_state(domain).await, CircuitState::Open);
 
         // Should not allow requests
-        assert!(!manager.can_proceed(domain));
+        assert!(!manager.can_proceed(domain).await);
 
         // Wait for timeout
-        std::thread::sleep(Duration::from_millis(150));
+        tokio::time::sleep(Duration::from_millis(150)).await;
 
         // Should transition to half-open on next check
-        assert!(manager.can_proceed(domain));
-        assert_eq!(manager.get_state(domain), CircuitState::HalfOpen);
+        assert!(manager.can_proceed(domain).await);
+        assert_eq!(manager.get_state(domain).await, CircuitState::HalfOpen);
 
         // Record successes to close circuit
-        manager.record_success(domain);
-        manager.record_success(domain);
-        assert_eq!(manager.get_state(domain), CircuitState::Closed);
+        manager.record_success(domain).await;
+        manager.record_success(domain).await;
+        assert_eq!(manager.get_state(domain).await, CircuitState::Closed);
     }
 
-    #[test]
-    fn test_half_open_reopens_on_failure() {
+    #[tokio::test]
+    async fn test_half_open_reopens_on_failure() {
         let config = CircuitBreakerConfig {
             failure_threshold: 2,
             open_timeout: Duration::from_millis(100),
@@ -422,25 +707,25 @@ mod tests {
         let domain = "example.com";
 
         // Open the circuit
-        manager.record_failure(domain);
-        manager.record_failure(domain);
-        assert_eq!(manager.get_state(domain), CircuitState::Open);
+        manager.record_failure(domain).await;
+        manager.record_failure(domain).await;
+        assert_eq!(manager.get_state(domain).await, CircuitState::Open);
 
         // Wait for timeout
-        std::thread::sleep(Duration::from_millis(150));
+        tokio::time::sleep(Duration::from_millis(150)).await;
 
         // Transition to half-open
-        assert!(manager.can_proceed(domain));
-        assert_eq!(manager.get_state(domain), CircuitState::HalfOpen);
+        assert!(manager.can_proceed(domain).await);
+        assert_eq!(manager.get_state(domain).await, CircuitState::HalfOpen);
 
         // Any failure in half-open should reopen circuit
-        manager.record_failure(domain);
-        assert_eq!(manager.get_state(domain), CircuitState::Open);
-        assert!(!manager.can_proceed(domain));
+        manager.record_failure(domain).await;
+        assert_eq!(manager.get_state(domain).await, CircuitState::Open);
+        assert!(!manager.can_proceed(domain).await);
     }
 
-    #[test]
-    fn test_half_open_request_limit() {
+    #[tokio::test]
+    async fn test_half_open_request_limit() {
         let config = CircuitBreakerConfig {
             failure_threshold: 2,
             open_timeout: Duration::from_millis(100),
@@ -452,81 +737,147 @@ mod tests {
         let domain = "example.com";
 
         // Open the circuit
-        manager.record_failure(domain);
-        manager.record_failure(domain);
+        manager.record_failure(domain).await;
+        manager.record_failure(domain).await;
 
         // Wait for timeout
-        std::thread::sleep(Duration::from_millis(150));
+        tokio::time::sleep(Duration::from_millis(150)).await;
 
         // Should allow up to half_open_max_requests
-        assert!(manager.can_proceed(domain)); // Request 1
-        assert!(manager.can_proceed(domain)); // Request 2
-        assert!(!manager.can_proceed(domain)); // Request 3 - blocked
+        assert!(manager.can_proceed(domain).await); // Request 1
+        assert!(manager.can_proceed(domain).await); // Request 2
+        assert!(!manager.can_proceed(domain).await); // Request 3 - blocked
     }
 
-    #[test]
-    fn test_manual_reset() {
+    #[tokio::test]
+    async fn test_half_open_probe_lease_expires() {
+        let config = CircuitBreakerConfig {
+            failure_threshold: 1,
+            open_timeout: Duration::from_millis(100),
+            success_threshold: 1,
+            half_open_max_requests: 1,
+        };
+        let manager = CircuitBreakerManager::new(config);
+
+        let domain = "example.com";
+        manager.record_failure(domain).await;
+        tokio::time::sleep(Duration::from_millis(150)).await;
+
+        // The probe never reports back
+        assert!(manager.can_proceed(domain).await);
+        assert!(!manager.can_proceed(domain).await);
+
+        // Once its lease ends another probe may go
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        assert!(manager.can_proceed(domain).await);
+        assert_eq!(manager.get_state(domain).await, CircuitState::HalfOpen);
+    }
+
+    #[tokio::test]
+    async fn test_manual_reset() {
         let manager = CircuitBreakerManager::default();
         let domain = "example.com";
 
         // Open the circuit
         for _ in 0..5 {
-            manager.record_failure(domain);
+            manager.record_failure(domain).await;
         }
-        assert_eq!(manager.get_state(domain), CircuitState::Open);
+        assert_eq!(manager.get_state(domain).await, CircuitState::Open);
 
         // Manually reset
-        manager.reset(domain);
-        assert_eq!(manager.get_state(domain), CircuitState::Closed);
-        assert!(manager.can_proceed(domain));
+        manager.reset(domain).await;
+        assert_eq!(manager.get_state(domain).await, CircuitState::Closed);
+        assert!(manager.can_proceed(domain).await);
     }
 
-    #[test]
-    fn test_stats() {
+    #[tokio::test]
+    async fn test_stats() {
         let manager = CircuitBreakerManager::default();
 
         // Open circuit for domain1
         for _ in 0..5 {
-            manager.record_failure("domain1.com");
+            manager.record_failure("domain1.com").await;
         }
 
         // Keep domain2 closed
-        manager.record_success("domain2.com");
+        manager.record_success("domain2.com").await;
 
-        let stats = manager.stats();
+        let stats = manager.stats().await;
         assert_eq!(stats.total_circuits, 2);
         assert_eq!(stats.open_circuits, 1);
         assert_eq!(stats.closed_circuits, 1);
     }
 
-    #[test]
-    fn test_domain_stats() {
+    #[tokio::test]
+    async fn test_domain_stats() {
         let manager = CircuitBreakerManager::default();
         let domain = "example.com";
 
-        manager.record_failure(domain);
-        manager.record_failure(domain);
-        manager.record_success(domain);
+        manager.record_failure(domain).await;
+        manager.record_failure(domain).await;
+        manager.record_success(domain).await;
 
-        let stats = manager.get_domain_stats(domain).unwrap();
+        let stats = manager.get_domain_stats(domain).await.unwrap();
         assert_eq!(stats.state, CircuitState::Closed);
         assert_eq!(stats.total_failures, 2);
         assert_eq!(stats.total_successes, 1);
     }
 
-    #[test]
-    fn test_multiple_domains_independent() {
+    #[tokio::test]
+    async fn test_multiple_domains_independent() {
         let manager = CircuitBreakerManager::default();
 
         // Open circuit for domain1
         for _ in 0..5 {
-            manager.record_failure("domain1.com");
+            manager.record_failure("domain1.com").await;
         }
 
         // domain2 should still work
-        assert_eq!(manager.get_state("domain1.com"), CircuitState::Open);
-        assert_eq!(manager.get_state("domain2.com"), CircuitState::Closed);
-        assert!(!manager.can_proceed("domain1.com"));
-        assert!(manager.can_proceed("domain2.com"));
+        assert_eq!(manager.get_state("domain1.com").await, CircuitState::Open);
+        assert_eq!(manager.get_state("domain2.com").await, CircuitState::Closed);
+        assert!(!manager.can_proceed("domain1.com").await);
+        assert!(manager.can_proceed("domain2.com").await);
+    }
+
+    #[tokio::test]
+    async fn test_force_open() {
+        let manager = CircuitBreakerManager::default();
+        let domain = "example.com";
+
+        manager.force_open(domain, Duration::from_secs(600)).await;
+        assert_eq!(manager.get_state(domain).await, CircuitState::Open);
+        assert!(!manager.can_proceed(domain).await);
+
+        let stats = manager.get_domain_stats(domain).await.unwrap();
+        let remaining = stats.open_until.unwrap() - Utc::now();
+        assert!(remaining > chrono::Duration::seconds(590));
+
+        let open = manager.open_circuits().await;
+        assert_eq!(open.len(), 1);
+        assert_eq!(open[0].0, domain);
+
+        manager.reset(domain).await;
+        assert!(manager.open_circuits().await.is_empty());
+    }
+
+    #[test]
+    fn test_stats_from_redis_fields() {
+        assert!(DomainCircuitStats::from_fields(&HashMap::new()).is_none());
+
+        let fields: HashMap<String, String> = [
+            ("state", "open"),
+            ("failure_count", "0"),
+            ("open_until", "1700000000000"),
+            ("total_failures", "7"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+
+        let stats = DomainCircuitStats::from_fields(&fields).unwrap();
+        assert_eq!(stats.state, CircuitState::Open);
+        assert_eq!(stats.total_failures, 7);
+        assert_eq!(stats.total_successes, 0);
+        assert_eq!(stats.open_until.unwrap().timestamp(), 1_700_000_000);
     }
 }
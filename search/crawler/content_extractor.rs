@@ -0,0 +1,547 @@
+use ego_tree::NodeId;
+use regex::Regex;
+use scraper::{ElementRef, Html, Node, Selector};
+use std::collections::{HashMap, HashSet};
+use std::sync::OnceLock;
+
+use super::util::collapse_whitespace;
+
+/// Subtrees that never contain main content
+const BOILERPLATE_TAGS: &[&str] = &[
+    "script", "style", "noscript", "template", "nav", "footer", "aside", "form", "iframe", "svg",
+    "button", "select", "textarea", "dialog", "menu",
+];
+
+/// ARIA landmarks that mark page chrome
+const BOILERPLATE_ROLES: &[&str] = &[
+    "navigation", "banner", "contentinfo", "complementary", "dialog", "alertdialog", "menu", "menubar",
+];
+
+/// Elements rendered as their own block
+const BLOCK_TAGS: &[&str] = &[
+    "address", "article", "aside", "blockquote", "body", "caption", "dd", "details", "div", "dl",
+    "dt", "fieldset", "figcaption", "figure", "footer", "form", "h1", "h2", "h3", "h4", "h5", "h6",
+    "header", "hr", "li", "main", "nav", "ol", "p", "pre", "section", "summary", "table", "tbody",
+    "td", "tfoot", "th", "thead", "tr", "ul",
+];
+
+/// Blocks whose text is taken as a whole rather than walked further
+const LEAF_BLOCK_TAGS: &[&str] = &[
+    "p", "pre", "li", "td", "th", "dt", "dd", "figcaption", "caption", "summary", "h1", "h2", "h3",
+    "h4", "h5", "h6",
+];
+
+const HEADING_TAGS: &[&str] = &["h1", "h2", "h3", "h4", "h5", "h6"];
+
+/// Paragraphs shorter than this don't vote for a container
+const MIN_PARAGRAPH_CHARS: usize = 25;
+
+/// Blocks that are mostly link text are navigation, not content
+const MAX_BLOCK_LINK_DENSITY: f64 = 0.5;
+
+/// Siblings scoring at least this fraction of the top candidate are kept
+const SIBLING_SCORE_RATIO: f64 = 0.2;
+
+fn negative_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(
+            r"(?i)cookie|consent|gdpr|banner|navbar|\bnav\b|menu|footer|sidebar|share|social|comment|promo|advert|\bads?\b|sponsor|popup|modal|newsletter|subscribe|breadcrumb|related|masthead|skip-link|widget",
+        )
+        .expect("valid boilerplate pattern")
+    })
+}
+
+fn positive_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(r"(?i)article|content|main|post|entry|story|text|body|blog")
+            .expect("valid content pattern")
+    })
+}
+
+/// Scoring main-content extractor in the spirit of Readability/boilerpipe
+///
+/// Paragraph-like elements vote for their ancestors based on how much prose
+/// they contain; the best-scoring container, discounted by its link density,
+/// is taken as the article. Page chrome (navigation, cookie banners, footers,
+/// sidebars) is discarded by tag, ARIA role and class/id hints. The result
+/// keeps one block per heading or paragraph, separated by blank lines.
+pub struct ContentExtractor<'a> {
+    document: &'a Html,
+    removed: HashSet<NodeId>,
+}
+
+impl<'a> ContentExtractor<'a> {
+    pub fn new(document: &'a Html) -> Self {
+        let mut extractor = Self {
+            document,
+            removed: HashSet::new(),
+        };
+        extractor.mark_boilerplate();
+        extractor
+    }
+
+    /// Extract the main content of a page as blank-line separated blocks
+    pub fn extract(document: &Html) -> String {
+        ContentExtractor::new(document).main_content()
+    }
+
+    /// Main content, or the whole cleaned body if no container stands out
+    pub fn main_content(&self) -> String {
+        let mut blocks = Vec::new();
+
+        match self.top_candidate().map(|candidate| self.enclosing_article(candidate)) {
+            Some(candidate) => {
+                for element in self.with_related_siblings(candidate) {
+                    self.render(element, &mut blocks);
+                }
+            }
+            None => {
+                if let Some(body) = self.body() {
+                    self.render(body, &mut blocks);
+                }
+            }
+        }
+
+        blocks.dedup();
+        blocks.join("\n\n")
+    }
+
+    fn body(&self) -> Option<ElementRef<'a>> {
+        let selector = Selector::parse("body").ok()?;
+        self.document.select(&selector).next()
+    }
+
+    /// Mark boilerplate subtrees, top-down so children inherit removal
+    fn mark_boilerplate(&mut self) {
+        for node in self.document.root_element().descendants() {
+            let Some(element) = ElementRef::wrap(node) else {
+                continue;
+            };
+
+            let parent_removed = node.parent().is_some_and(|parent| self.removed.contains(&parent.id()));
+            if parent_removed || is_boilerplate(element) {
+                self.removed.insert(node.id());
+            }
+        }
+    }
+
+    fn is_removed(&self, element: ElementRef) -> bool {
+        self.removed.contains(&element.id())
+    }
+
+    /// Score containers by the paragraphs they hold and pick the best one
+    fn top_candidate(&self) -> Option<ElementRef<'a>> {
+        let selector = Selector::parse("p, pre, td, blockquote, div").ok()?;
+        let mut scores: HashMap<NodeId, f64> = HashMap::new();
+
+        for paragraph in self.document.select(&selector) {
+            if self.is_removed(paragraph) {
+                continue;
+            }
+            // Divs only count when used as paragraphs (no block children)
+            if paragraph.value().name() == "div" && has_block_children(paragraph) {
+                continue;
+            }
+
+            let text = collapse_whitespace(&self.text(paragraph));
+            let length = text.chars().count();
+            if length < MIN_PARAGRAPH_CHARS {
+                continue;
+            }
+
+            let commas = text.chars().filter(|c| matches!(c, ',' | '،' | '、' | '，')).count();
+            let score = 1.0 + commas as f64 + (length as f64 / 100.0).min(3.0);
+
+            // Parent gets the full score, grandparent half, great-grandparent a third
+            for (level, ancestor) in paragraph.ancestors().filter_map(ElementRef::wrap).take(3).enumerate() {
+                let entry = scores
+                    .entry(ancestor.id())
+                    .or_insert_with(|| initial_score(ancestor));
+                *entry += score / (level as f64 + 1.0);
+            }
+        }
+
+        scores
+            .into_iter()
+            .filter_map(|(id, score)| {
+                let element = ElementRef::wrap(self.document.tree.get(id)?)?;
+                Some((element, score * (1.0 - self.link_density(element))))
+            })
+            .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+            .map(|(element, _)| element)
+    }
+
+    /// Widen a candidate to its enclosing `<article>`, which also holds the
+    /// headline and byline that sit outside the body container
+    fn enclosing_article(&self, candidate: ElementRef<'a>) -> ElementRef<'a> {
+        candidate
+            .ancestors()
+            .filter_map(ElementRef::wrap)
+            .find(|ancestor| ancestor.value().name() == "article" && !self.is_removed(*ancestor))
+            .filter(|article| self.link_density(*article) < MAX_BLOCK_LINK_DENSITY)
+            .unwrap_or(candidate)
+    }
+
+    /// The top candidate plus siblings that look like part of the same article
+    ///
+    /// Articles are often split into several sibling containers (lead,
+    /// body, a second column), so siblings with enough prose are kept.
+    fn with_related_siblings(&self, candidate: ElementRef<'a>) -> Vec<ElementRef<'a>> {
+        let Some(parent) = candidate.parent().and_then(ElementRef::wrap) else {
+            return vec![candidate];
+        };
+
+        let candidate_score = self.content_score(candidate);
+        let threshold = (candidate_score * SIBLING_SCORE_RATIO).max(10.0);
+
+        parent
+            .children()
+            .filter_map(ElementRef::wrap)
+            .filter(|sibling| {
+                if *sibling == candidate {
+                    return true;
+                }
+                if self.is_removed(*sibling) {
+                    return false;
+                }
+
+                let text = collapse_whitespace(&self.text(*sibling));
+                let length = text.chars().count();
+                let link_density = self.link_density(*sibling);
+
+                if self.content_score(*sibling) >= threshold {
+                    return true;
+                }
+                sibling.value().name() == "p"
+                    && ((length > 80 && link_density < 0.25)
+                        || (link_density == 0.0 && text.contains(". ")))
+            })
+            .collect()
+    }
+
+    /// Text-based score used to compare siblings
+    fn content_score(&self, element: ElementRef) -> f64 {
+        let text = collapse_whitespace(&self.text(element));
+        let commas = text.chars().filter(|c| matches!(c, ',' | '،' | '、' | '，')).count();
+        let score = initial_score(element) + commas as f64 + text.chars().count() as f64 / 100.0;
+        score * (1.0 - self.link_density(element))
+    }
+
+    /// Share of the element's text that sits inside links
+    fn link_density(&self, element: ElementRef) -> f64 {
+        let length = collapse_whitespace(&self.text(element)).chars().count();
+        if length == 0 {
+            return 0.0;
+        }
+
+        let Ok(selector) = Selector::parse("a") else {
+            return 0.0;
+        };
+        let link_length: usize = element
+            .select(&selector)
+            .filter(|link| !self.is_removed(*link))
+            .map(|link| collapse_whitespace(&self.text(link)).chars().count())
+            .sum();
+
+        (link_length as f64 / length as f64).min(1.0)
+    }
+
+    /// Text of an element, skipping boilerplate subtrees
+    fn text(&self, element: ElementRef) -> String {
+        let mut text = String::new();
+        self.collect_text(element, &mut text);
+        text
+    }
+
+    fn collect_text(&self, element: ElementRef, out: &mut String) {
+        for child in element.children() {
+            match child.value() {
+                Node::Text(text) => out.push_str(text),
+                Node::Element(el) => {
+                    if let Some(child) = ElementRef::wrap(child) {
+                        if self.is_removed(child) {
+                            continue;
+                        }
+                        // Keep words in adjacent blocks and line breaks apart
+                        let separate = el.name() == "br" || BLOCK_TAGS.contains(&el.name());
+                        if separate {
+                            out.push(' ');
+                        }
+                        self.collect_text(child, out);
+                        if separate {
+                            out.push(' ');
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Render an element as blocks: one per heading, paragraph or list item
+    fn render(&self, element: ElementRef, blocks: &mut Vec<String>) {
+        if self.is_removed(element) {
+            return;
+        }
+
+        let name = element.value().name();
+        if LEAF_BLOCK_TAGS.contains(&name) {
+            let text = collapse_whitespace(&self.text(element));
+            if text.is_empty() {
+                return;
+            }
+            // Headings are short and may be links; everything else must be prose
+            if !HEADING_TAGS.contains(&name) && self.link_density(element) > MAX_BLOCK_LINK_DENSITY {
+                return;
+            }
+            blocks.push(if name == "li" { format!("- {}", text) } else { text });
+            return;
+        }
+
+        // Containers: inline runs between child blocks become blocks of their own
+        let mut inline = String::new();
+        let mut inline_links = 0;
+        for child in element.children() {
+            match child.value() {
+                Node::Text(text) => inline.push_str(text),
+                Node::Element(el) => {
+                    let Some(child) = ElementRef::wrap(child) else {
+                        continue;
+                    };
+                    if self.is_removed(child) {
+                        continue;
+                    }
+
+                    if BLOCK_TAGS.contains(&el.name()) {
+                        flush_inline(&mut inline, &mut inline_links, blocks);
+                        self.render(child, blocks);
+                    } else {
+                        let text = self.text(child);
+                        if el.name() == "a" {
+                            inline_links += collapse_whitespace(&text).chars().count();
+                        }
+                        inline.push_str(&text);
+                        if el.name() == "br" {
+                            inline.push(' ');
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        flush_inline(&mut inline, &mut inline_links, blocks);
+    }
+}
+
+/// Push accumulated inline text as a block unless it is mostly links
+fn flush_inline(inline: &mut String, inline_links: &mut usize, blocks: &mut Vec<String>) {
+    let text = collapse_whitespace(inline);
+    let length = text.chars().count();
+    if length > 0 && (*inline_links as f64 / length as f64) <= MAX_BLOCK_LINK_DENSITY {
+        blocks.push(text);
+    }
+    inline.clear();
+    *inline_links = 0;
+}
+
+/// Check if an element is page chrome by tag, ARIA role, visibility or class/id
+fn is_boilerplate(element: ElementRef) -> bool {
+    let el = element.value();
+    let name = el.name();
+
+    if BOILERPLATE_TAGS.contains(&name) {
+        return true;
+    }
+
+    // Site headers are chrome; article headers hold the headline
+    if name == "header" {
+        let in_article = element
+            .ancestors()
+            .filter_map(ElementRef::wrap)
+            .any(|ancestor| matches!(ancestor.value().name(), "article" | "main"));
+        if !in_article {
+            return true;
+        }
+    }
+
+    if el
+        .attr("role")
+        .is_some_and(|role| BOILERPLATE_ROLES.contains(&role.trim().to_ascii_lowercase().as_str()))
+    {
+        return true;
+    }
+
+    if el.attr("hidden").is_some() || el.attr("aria-hidden") == Some("true") {
+        return true;
+    }
+    if let Some(style) = el.attr("style") {
+        let style: String = style.chars().filter(|c| !c.is_whitespace()).collect::<String>().to_ascii_lowercase();
+        if style.contains("display:none") || style.contains("visibility:hidden") {
+            return true;
+        }
+    }
+
+    // body/html/article/main are never dropped on class hints alone
+    if matches!(name, "html" | "body" | "article" | "main") {
+        return false;
+    }
+
+    let hints = class_and_id(element);
+    negative_pattern().is_match(&hints) && !positive_pattern().is_match(&hints)
+}
+
+/// Starting score of a candidate from its tag and class/id
+fn initial_score(element: ElementRef) -> f64 {
+    let tag_score = match element.value().name() {
+        "article" | "main" => 8.0,
+        "div" => 5.0,
+        "section" | "pre" | "td" | "blockquote" => 3.0,
+        "address" | "ol" | "ul" | "dl" | "dd" | "dt" | "li" | "form" => -3.0,
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "th" => -5.0,
+        _ => 0.0,
+    };
+
+    let hints = class_and_id(element);
+    let mut class_score = 0.0;
+    if negative_pattern().is_match(&hints) {
+        class_score -= 25.0;
+    }
+    if positive_pattern().is_match(&hints) {
+        class_score += 25.0;
+    }
+
+    tag_score + class_score
+}
+
+fn class_and_id(element: ElementRef) -> String {
+    let el = element.value();
+    format!("{} {}", el.attr("class").unwrap_or(""), el.attr("id").unwrap_or(""))
+}
+
+fn has_block_children(element: ElementRef) -> bool {
+    element
+        .children()
+        .filter_map(ElementRef::wrap)
+        .any(|child| BLOCK_TAGS.contains(&child.value().name()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn extract_fixture(html: &str) -> String {
+        ContentExtractor::extract(&Html::parse_document(html))
+    }
+
+    fn assert_contains_all(content: &str, phrases: &[&str]) {
+        for phrase in phrases {
+            assert!(content.contains(phrase), "missing {:?} in:\n{}", phrase, content);
+        }
+    }
+
+    fn assert_contains_none(content: &str, phrases: &[&str]) {
+        for phrase in phrases {
+            assert!(!content.contains(phrase), "boilerplate {:?} kept in:\n{}", phrase, content);
+        }
+    }
+
+    #[test]
+    fn test_news_article_fixture() {
+        let content = extract_fixture(include_str!("fixtures/news_article.html"));
+
+        assert_contains_all(
+            &content,
+            &[
+                "City council approves new bike lanes",
+                "The council voted seven to two on Tuesday",
+                "Construction is expected to begin in the spring",
+                "What happens next",
+            ],
+        );
+        assert_contains_none(
+            &content,
+            &[
+                "We use cookies",
+                "Subscribe to our newsletter",
+                "All rights reserved",
+                "Most read",
+                "Sports",
+                "Share on",
+            ],
+        );
+
+        // Headings and paragraphs stay separate blocks
+        assert!(content.contains("\n\nWhat happens next\n\n"));
+    }
+
+    #[test]
+    fn test_blog_post_fixture() {
+        let content = extract_fixture(include_str!("fixtures/blog_post.html"));
+
+        assert_contains_all(
+            &content,
+            &[
+                "Understanding ownership in Rust",
+                "Every value in Rust has a single owner",
+                "- Each value has an owner",
+                "borrow checker",
+            ],
+        );
+        assert_contains_none(
+            &content,
+            &["About me", "Archives", "Leave a comment", "Tags:", "Powered by"],
+        );
+    }
+
+    #[test]
+    fn test_documentation_fixture() {
+        let content = extract_fixture(include_str!("fixtures/docs_page.html"));
+
+        assert_contains_all(
+            &content,
+            &[
+                "Configuration",
+                "The service reads its configuration from environment variables",
+                "SEARCH_PORT",
+                "Restart the service after changing",
+            ],
+        );
+        assert_contains_none(&content, &["Getting started", "Edit this page", "Previous", "Search docs"]);
+    }
+
+    #[test]
+    fn test_div_based_layout_fixture() {
+        let content = extract_fixture(include_str!("fixtures/div_layout.html"));
+
+        assert_contains_all(
+            &content,
+            &[
+                "Les marchés européens ont terminé la séance en hausse",
+                "la banque centrale a maintenu ses taux",
+            ],
+        );
+        assert_contains_none(&content, &["Accepter les cookies", "Mentions légales", "Accueil"]);
+    }
+
+    #[test]
+    fn test_hidden_and_inline_scripts_are_dropped() {
+        let content = extract_fixture(
+            r#"<html><body><article>
+                <p>This paragraph is long enough to be considered real article content, honestly.</p>
+                <p style="display: none">Hidden tracking text that should never be indexed.</p>
+                <p>Another paragraph<script>var tracking = "secret";</script> with an inline script.</p>
+            </article></body></html>"#,
+        );
+
+        assert_contains_all(&content, &["real article content", "Another paragraph with an inline script."]);
+        assert_contains_none(&content, &["Hidden tracking", "secret"]);
+    }
+
+    #[test]
+    fn test_falls_back_to_body() {
+        let content = extract_fixture("<html><body>Just some short text</body></html>");
+        assert_eq!(content, "Just some short text");
+    }
+}
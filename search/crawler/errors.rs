@@ -0,0 +1,366 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+use std::collections::BTreeMap;
+use std::fmt;
+use url::Url;
+use uuid::Uuid;
+
+use crate::db::CrawlError;
+
+/// Why a URL could not be crawled (`crawl_errors.error_type`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CrawlErrorKind {
+    RobotsBlocked,
+    /// Rejected by filter rules, content filters or content type
+    Filtered,
+    CircuitOpen,
+    HttpStatus,
+    Timeout,
+    /// Connection, DNS or TLS failure
+    Network,
+    /// Invalid URL or content that could not be processed
+    ParseError,
+    EmptyContent,
+}
+
+impl CrawlErrorKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::RobotsBlocked => "robots_blocked",
+            Self::Filtered => "filtered",
+            Self::CircuitOpen => "circuit_open",
+            Self::HttpStatus => "http_status",
+            Self::Timeout => "timeout",
+            Self::Network => "network",
+            Self::ParseError => "parse_error",
+            Self::EmptyContent => "empty_content",
+        }
+    }
+}
+
+/// A URL that failed to crawl
+#[derive(Debug, Clone, PartialEq)]
+pub struct CrawlFailure {
+    pub url: String,
+    pub kind: CrawlErrorKind,
+    pub status_code: Option<u16>,
+    pub message: String,
+}
+
+impl CrawlFailure {
+    pub fn new(url: &str, kind: CrawlErrorKind, message: impl Into<String>) -> Self {
+        Self {
+            url: url.to_string(),
+            kind,
+            status_code: None,
+            message: message.into(),
+        }
+    }
+
+    /// A non-success response; gateway and request timeouts count as timeouts
+    pub fn http_status(url: &str, status: u16) -> Self {
+        let kind = match status {
+            408 | 504 | 522 | 524 => CrawlErrorKind::Timeout,
+            _ => CrawlErrorKind::HttpStatus,
+        };
+        Self {
+            url: url.to_string(),
+            kind,
+            status_code: Some(status),
+            message: format!("HTTP {}", status),
+        }
+    }
+
+    /// Classify an error from a fetch
+    ///
+    /// Failures raised as `CrawlFailure` keep their category; request errors
+    /// are timeouts or network failures.
+    pub fn from_error(url: &str, error: &anyhow::Error) -> Self {
+        if let Some(failure) = error.downcast_ref::<CrawlFailure>() {
+            return failure.clone();
+        }
+
+        let timed_out = error
+            .chain()
+            .filter_map(|cause| cause.downcast_ref::<reqwest::Error>())
+            .any(|e| e.is_timeout());
+        let kind = if timed_out {
+            CrawlErrorKind::Timeout
+        } else {
+            CrawlErrorKind::Network
+        };
+        Self::new(url, kind, format!("{:#}", error))
+    }
+
+    pub fn domain(&self) -> Option<String> {
+        Url::parse(&self.url)
+            .ok()
+            .and_then(|url| url.host_str().map(|host| host.to_ascii_lowercase()))
+    }
+}
+
+impl fmt::Display for CrawlFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({}): {}", self.url, self.kind.as_str(), self.message)
+    }
+}
+
+impl std::error::Error for CrawlFailure {}
+
+/// Filters for listing crawl errors; unset fields match everything
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CrawlErrorQuery {
+    pub crawl_id: Option<Uuid>,
+    pub domain: Option<String>,
+    pub error_type: Option<CrawlErrorKind>,
+    pub since: Option<DateTime<Utc>>,
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+    #[serde(default)]
+    pub offset: i64,
+}
+
+fn default_limit() -> i64 {
+    50
+}
+
+/// Largest page of errors a listing returns
+const MAX_LIMIT: i64 = 500;
+
+/// Domains listed in a summary, most errors first
+const MAX_SUMMARY_DOMAINS: usize = 100;
+
+impl CrawlErrorQuery {
+    /// Keep paging within bounds
+    pub fn clamped(mut self) -> Self {
+        self.limit = self.limit.clamp(1, MAX_LIMIT);
+        self.offset = self.offset.max(0);
+        self
+    }
+}
+
+/// Error count for one domain and error type
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct CrawlErrorCount {
+    pub domain: Option<String>,
+    pub error_type: String,
+    pub count: i64,
+    pub last_occurred_at: Option<DateTime<Utc>>,
+}
+
+/// Errors of one domain, by error type
+#[derive(Debug, Clone, Serialize)]
+pub struct DomainErrors {
+    pub domain: Option<String>,
+    pub count: i64,
+    pub by_type: BTreeMap<String, i64>,
+    pub last_occurred_at: Option<DateTime<Utc>>,
+}
+
+/// Crawl errors aggregated by error type and by domain
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CrawlErrorSummary {
+    pub by_type: BTreeMap<String, i64>,
+    /// Domains with the most errors first
+    pub by_domain: Vec<DomainErrors>,
+}
+
+impl CrawlErrorSummary {
+    pub fn from_counts(counts: Vec<CrawlErrorCount>) -> Self {
+        let mut by_type: BTreeMap<String, i64> = BTreeMap::new();
+        let mut domains: BTreeMap<Option<String>, DomainErrors> = BTreeMap::new();
+
+        for count in counts {
+            *by_type.entry(count.error_type.clone()).or_default() += count.count;
+
+            let entry = domains.entry(count.domain.clone()).or_insert_with(|| DomainErrors {
+                domain: count.domain.clone(),
+                count: 0,
+                by_type: BTreeMap::new(),
+                last_occurred_at: None,
+            });
+            entry.count += count.count;
+            *entry.by_type.entry(count.error_type).or_default() += count.count;
+            entry.last_occurred_at = entry.last_occurred_at.max(count.last_occurred_at);
+        }
+
+        let mut by_domain: Vec<DomainErrors> = domains.into_values().collect();
+        by_domain.sort_by_key(|domain| std::cmp::Reverse(domain.count));
+        by_domain.truncate(MAX_SUMMARY_DOMAINS);
+
+        Self { by_type, by_domain }
+    }
+}
+
+/// Repository for per-URL crawl failures
+#[derive(Clone)]
+pub struct CrawlErrorRepository {
+    pool: PgPool,
+}
+
+impl CrawlErrorRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Record failures of a crawl job
+    pub async fn record(&self, crawl_id: Uuid, failures: &[CrawlFailure]) -> Result<u64> {
+        if failures.is_empty() {
+            return Ok(0);
+        }
+
+        let urls: Vec<String> = failures.iter().map(|f| f.url.clone()).collect();
+        let domains: Vec<Option<String>> = failures.iter().map(|f| f.domain()).collect();
+        let types: Vec<String> = failures.iter().map(|f| f.kind.as_str().to_string()).collect();
+        let statuses: Vec<Option<i16>> = failures
+            .iter()
+            .map(|f| f.status_code.map(|status| status as i16))
+            .collect();
+        let messages: Vec<String> = failures.iter().map(|f| f.message.clone()).collect();
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO crawl_errors (crawl_id, url, domain, error_type, status_code, error_message)
+            SELECT $1, LEFT(url, 2000), LEFT(domain, 255), error_type, status_code, error_message
+            FROM UNNEST($2::text[], $3::text[], $4::text[], $5::smallint[], $6::text[])
+                AS t(url, domain, error_type, status_code, error_message)
+            "#,
+        )
+        .bind(crawl_id)
+        .bind(&urls)
+        .bind(&domains)
+        .bind(&types)
+        .bind(&statuses)
+        .bind(&messages)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Matching errors, newest first, and the total number of matches
+    pub async fn list(&self, query: &CrawlErrorQuery) -> Result<(Vec<CrawlError>, i64)> {
+        let errors = sqlx::query_as::<_, CrawlError>(
+            r#"
+            SELECT * FROM crawl_errors
+            WHERE ($1::uuid IS NULL OR crawl_id = $1)
+              AND ($2::text IS NULL OR domain = $2)
+              AND ($3::text IS NULL OR error_type = $3)
+              AND ($4::timestamptz IS NULL OR occurred_at >= $4)
+            ORDER BY occurred_at DESC
+            LIMIT $5 OFFSET $6
+            "#,
+        )
+        .bind(query.crawl_id)
+        .bind(query.domain.as_deref())
+        .bind(query.error_type.map(|kind| kind.as_str()))
+        .bind(query.since)
+        .bind(query.limit)
+        .bind(query.offset)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let total = sqlx::query_scalar::<_, i64>(
+            r#"
+            SELECT COUNT(*) FROM crawl_errors
+            WHERE ($1::uuid IS NULL OR crawl_id = $1)
+              AND ($2::text IS NULL OR domain = $2)
+              AND ($3::text IS NULL OR error_type = $3)
+              AND ($4::timestamptz IS NULL OR occurred_at >= $4)
+            "#,
+        )
+        .bind(query.crawl_id)
+        .bind(query.domain.as_deref())
+        .bind(query.error_type.map(|kind| kind.as_str()))
+        .bind(query.since)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok((errors, total))
+    }
+
+    /// Matching errors aggregated by error type and by domain
+    pub async fn summary(&self, query: &CrawlErrorQuery) -> Result<CrawlErrorSummary> {
+        let counts = sqlx::query_as::<_, CrawlErrorCount>(
+            r#"
+            SELECT domain, error_type, COUNT(*) AS count, MAX(occurred_at) AS last_occurred_at
+            FROM crawl_errors
+            WHERE ($1::uuid IS NULL OR crawl_id = $1)
+              AND ($2::text IS NULL OR domain = $2)
+              AND ($3::text IS NULL OR error_type = $3)
+              AND ($4::timestamptz IS NULL OR occurred_at >= $4)
+            GROUP BY domain, error_type
+            "#,
+        )
+        .bind(query.crawl_id)
+        .bind(query.domain.as_deref())
+        .bind(query.error_type.map(|kind| kind.as_str()))
+        .bind(query.since)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(CrawlErrorSummary::from_counts(counts))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_http_status_failures() {
+        let failure = CrawlFailure::http_status("https://Example.com/missing", 404);
+        assert_eq!(failure.kind, CrawlErrorKind::HttpStatus);
+        assert_eq!(failure.status_code, Some(404));
+        assert_eq!(failure.domain().as_deref(), Some("example.com"));
+
+        let failure = CrawlFailure::http_status("https://example.com/slow", 504);
+        assert_eq!(failure.kind, CrawlErrorKind::Timeout);
+    }
+
+    #[test]
+    fn test_from_error_keeps_category() {
+        let failure = CrawlFailure::new("https://example.com/", CrawlErrorKind::RobotsBlocked, "Disallowed");
+        let error = anyhow::Error::new(failure.clone());
+        assert_eq!(CrawlFailure::from_error("https://example.com/", &error), failure);
+
+        let error = anyhow::anyhow!("connection refused");
+        let failure = CrawlFailure::from_error("https://example.com/", &error);
+        assert_eq!(failure.kind, CrawlErrorKind::Network);
+        assert_eq!(failure.message, "connection refused");
+    }
+
+    #[test]
+    fn test_summary_from_counts() {
+        let count = |domain: &str, error_type: CrawlErrorKind, count: i64| CrawlErrorCount {
+            domain: Some(domain.to_string()),
+            error_type: error_type.as_str().to_string(),
+            count,
+            last_occurred_at: None,
+        };
+        let summary = CrawlErrorSummary::from_counts(vec![
+            count("a.com", CrawlErrorKind::Timeout, 2),
+            count("b.com", CrawlErrorKind::HttpStatus, 3),
+            count("b.com", CrawlErrorKind::Timeout, 1),
+        ]);
+
+        assert_eq!(summary.by_type.get("timeout"), Some(&3));
+        assert_eq!(summary.by_type.get("http_status"), Some(&3));
+        assert_eq!(summary.by_domain[0].domain.as_deref(), Some("b.com"));
+        assert_eq!(summary.by_domain[0].count, 4);
+        assert_eq!(summary.by_domain[1].by_type.get("timeout"), Some(&2));
+    }
+
+    #[test]
+    fn test_kind_serialization() {
+        assert_eq!(
+            serde_json::to_string(&CrawlErrorKind::RobotsBlocked).unwrap(),
+            "\"robots_blocked\""
+        );
+        let kind: CrawlErrorKind = serde_json::from_str("\"empty_content\"").unwrap();
+        assert_eq!(kind.as_str(), "empty_content");
+    }
+}
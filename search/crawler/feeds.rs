@@ -0,0 +1,527 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use dashmap::{DashMap, DashSet};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use scraper::{Html, Selector};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use tracing::debug;
+use url::Url;
+
+use super::page_state::PageValidators;
+use super::rate_limiter::RequestGate;
+use super::read_body_limited;
+use super::retry::RetryPolicy;
+
+/// Paths probed on sites whose pages don't advertise a feed
+pub const COMMON_FEED_PATHS: &[&str] = &["/feed", "/rss", "/feed.xml", "/rss.xml", "/atom.xml", "/index.xml"];
+
+/// `<link rel="alternate">` types that announce a feed
+const FEED_TYPES: &[&str] = &["application/rss+xml", "application/atom+xml"];
+
+/// Largest feed document accepted
+const MAX_FEED_BYTES: usize = 10 * 1024 * 1024;
+
+/// Most entries read from one feed document
+const MAX_FEED_ENTRIES: usize = 500;
+
+/// Flavour of a feed document
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FeedFormat {
+    Rss,
+    Atom,
+}
+
+/// A post listed in a feed
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FeedEntry {
+    pub url: String,
+    pub title: Option<String>,
+    /// Publication (or, for Atom without `<published>`, update) time
+    pub published_at: Option<DateTime<Utc>>,
+}
+
+/// A parsed RSS or Atom document
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ParsedFeed {
+    pub format: FeedFormat,
+    pub title: Option<String>,
+    pub entries: Vec<FeedEntry>,
+}
+
+/// Result of a conditional feed fetch
+#[derive(Debug, Clone)]
+pub enum FeedFetch {
+    /// Server answered 304, nothing was published since the last poll
+    NotModified { validators: PageValidators },
+    Fetched { feed: ParsedFeed, validators: PageValidators },
+}
+
+/// A feed found while crawling, not yet persisted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscoveredFeed {
+    pub url: String,
+    /// Host of the site the feed was found on
+    pub domain: String,
+}
+
+/// Feed discovery, fetching and parsing
+///
+/// Feeds found while crawling (through `<link rel="alternate">` tags or by
+/// probing common paths) are collected here until the feed poll loop
+/// persists them (see `FeedPollLoop`).
+#[derive(Clone)]
+pub struct FeedManager {
+    /// User agent string used when fetching feeds
+    user_agent: String,
+    /// Retry policy for fetching feeds
+    retry_policy: RetryPolicy,
+    /// Rate limit and politeness delay of feed requests
+    gate: RequestGate,
+    /// Feeds found since the poll loop last took them
+    pending: Arc<Mutex<Vec<DiscoveredFeed>>>,
+    /// Feed URLs already reported, so each is only queued once
+    known: Arc<DashSet<String>>,
+    /// Domains with at least one known feed
+    domains_with_feeds: Arc<DashSet<String>>,
+    /// Map of domain -> last time common feed paths were probed
+    last_probed: Arc<DashMap<String, DateTime<Utc>>>,
+    /// How long to wait before probing a domain again
+    probe_interval: Duration,
+}
+
+impl FeedManager {
+    pub fn new(user_agent: String) -> Self {
+        Self {
+            user_agent,
+            retry_policy: RetryPolicy::new(),
+            gate: RequestGate::default(),
+            pending: Arc::new(Mutex::new(Vec::new())),
+            known: Arc::new(DashSet::new()),
+            domains_with_feeds: Arc::new(DashSet::new()),
+            last_probed: Arc::new(DashMap::new()),
+            probe_interval: Duration::hours(24),
+        }
+    }
+
+    /// Send feed requests through the crawler's request gate
+    pub fn with_gate(mut self, gate: RequestGate) -> Self {
+        self.gate = gate;
+        self
+    }
+
+    /// Record feeds found on a domain, returns how many were new
+    pub fn add_discovered(&self, domain: &str, urls: Vec<String>) -> usize {
+        let domain = domain.to_lowercase();
+        let mut added = 0;
+        let mut pending = self.pending.lock().unwrap();
+
+        for url in urls {
+            if !self.known.insert(url.clone()) {
+                continue;
+            }
+            debug!("Discovered feed {} on {}", url, domain);
+            pending.push(DiscoveredFeed {
+                url,
+                domain: domain.clone(),
+            });
+            added += 1;
+        }
+
+        if added > 0 {
+            self.domains_with_feeds.insert(domain);
+        }
+        added
+    }
+
+    /// Take the feeds discovered since the last call
+    pub fn take_pending(&self) -> Vec<DiscoveredFeed> {
+        std::mem::take(&mut *self.pending.lock().unwrap())
+    }
+
+    /// Put back feeds that could not be persisted
+    pub fn restore_pending(&self, feeds: Vec<DiscoveredFeed>) {
+        self.pending.lock().unwrap().extend(feeds);
+    }
+
+    /// Whether common feed paths of a domain should be probed
+    ///
+    /// Domains that already advertised a feed are not probed.
+    pub fn should_probe(&self, domain: &str) -> bool {
+        let domain = domain.to_lowercase();
+        if self.domains_with_feeds.contains(&domain) {
+            return false;
+        }
+        match self.last_probed.get(&domain) {
+            Some(last) => Utc::now() - *last >= self.probe_interval,
+            None => true,
+        }
+    }
+
+    /// Record that a domain's common feed paths were probed
+    pub fn mark_probed(&self, domain: &str) {
+        self.last_probed.insert(domain.to_lowercase(), Utc::now());
+    }
+
+    /// Fetch and parse a feed with a conditional request
+    pub async fn fetch(&self, url: &str, validators: &PageValidators) -> Result<FeedFetch> {
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(30))
+            .build()?;
+
+        let response = self
+            .retry_policy
+            .execute_http(url, || {
+                let request = client
+                    .get(url)
+                    .header("User-Agent", self.user_agent.as_str())
+                    .header(
+                        "Accept",
+                        "application/rss+xml, application/atom+xml, application/xml;q=0.9, text/xml;q=0.8",
+                    );
+                self.gate.send(url, validators.apply(request))
+            })
+            .await?;
+
+        let status = response.status();
+        let received = PageValidators::from_headers(response.headers());
+
+        if status == reqwest::StatusCode::NOT_MODIFIED {
+            return Ok(FeedFetch::NotModified {
+                validators: validators.merge(&received),
+            });
+        }
+        if !status.is_success() {
+            return Err(anyhow::anyhow!("feed not available (status: {})", status));
+        }
+
+        let body = read_body_limited(response, MAX_FEED_BYTES).await?;
+
+        Ok(FeedFetch::Fetched {
+            feed: parse_feed(&body, url)?,
+            validators: received,
+        })
+    }
+}
+
+/// Feed URLs a page advertises through `<link rel="alternate">`
+///
+/// WordPress-style comment feeds are skipped; they list comments, not posts.
+pub fn discover_feed_links(document: &Html, page_url: &str) -> Vec<String> {
+    let Ok(page) = Url::parse(page_url) else {
+        return Vec::new();
+    };
+    let Ok(selector) = Selector::parse("link[rel][type][href]") else {
+        return Vec::new();
+    };
+
+    let mut seen = HashSet::new();
+    let mut feeds = Vec::new();
+
+    for link in document.select(&selector) {
+        let element = link.value();
+        let rel = element.attr("rel").unwrap_or_default();
+        if !rel.split_ascii_whitespace().any(|token| token.eq_ignore_ascii_case("alternate")) {
+            continue;
+        }
+
+        let content_type = element.attr("type").unwrap_or_default();
+        let content_type = content_type.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+        if !FEED_TYPES.contains(&content_type.as_str()) {
+            continue;
+        }
+
+        let Some(url) = element.attr("href").and_then(|href| page.join(href.trim()).ok()) else {
+            continue;
+        };
+        if !matches!(url.scheme(), "http" | "https") || url.path().contains("/comments/feed") {
+            continue;
+        }
+
+        let url = url.to_string();
+        if seen.insert(url.clone()) {
+            feeds.push(url);
+        }
+    }
+
+    feeds
+}
+
+/// Parse an RSS 2.0, RSS 1.0 (RDF) or Atom document
+///
+/// Relative entry links are resolved against `feed_url`. Entries without a
+/// usable link are dropped.
+pub fn parse_feed(xml: &[u8], feed_url: &str) -> Result<ParsedFeed> {
+    let base = Url::parse(feed_url).ok();
+    let resolve = |href: &str| -> Option<String> {
+        let href = href.trim();
+        let url = match &base {
+            Some(base) => base.join(href).ok()?,
+            None => Url::parse(href).ok()?,
+        };
+        matches!(url.scheme(), "http" | "https").then(|| url.to_string())
+    };
+
+    let mut reader = Reader::from_reader(xml);
+    reader.trim_text(true);
+
+    let mut buf = Vec::new();
+    let mut format = None;
+    let mut title = None;
+    let mut entries = Vec::new();
+    let mut current: Option<EntryBuilder> = None;
+    let mut field: Option<Vec<u8>> = None;
+    let mut text = String::new();
+
+    loop {
+        let event = reader.read_event_into(&mut buf)?;
+        match &event {
+            Event::Start(e) | Event::Empty(e) => {
+                let name = e.local_name().as_ref().to_ascii_lowercase();
+
+                if format.is_none() {
+                    format = match name.as_slice() {
+                        b"rss" | b"rdf" => Some(FeedFormat::Rss),
+                        b"feed" => Some(FeedFormat::Atom),
+                        _ => anyhow::bail!("not an RSS or Atom feed"),
+                    };
+                }
+
+                match name.as_slice() {
+                    b"item" | b"entry" => current = Some(EntryBuilder::default()),
+                    // Atom links carry the URL in an attribute
+                    b"link" if format == Some(FeedFormat::Atom) => {
+                        if let Some(entry) = current.as_mut() {
+                            if entry.link.is_none() && is_alternate_link(e) {
+                                entry.link = attribute(e, b"href");
+                            }
+                        }
+                    }
+                    b"guid" => {
+                        let permalink = attribute(e, b"isPermaLink").as_deref() != Some("false");
+                        if permalink && matches!(event, Event::Start(_)) {
+                            field = Some(name);
+                            text.clear();
+                        }
+                    }
+                    b"title" | b"link" | b"pubdate" | b"published" | b"updated" | b"date"
+                        if matches!(event, Event::Start(_)) =>
+                    {
+                        field = Some(name);
+                        text.clear();
+                    }
+                    _ => {}
+                }
+            }
+            Event::Text(t) if field.is_some() => {
+                text.push_str(&t.unescape()?);
+            }
+            Event::CData(c) if field.is_some() => {
+                text.push_str(&String::from_utf8_lossy(c));
+            }
+            Event::End(e) => {
+                let name = e.local_name().as_ref().to_ascii_lowercase();
+                match name.as_slice() {
+                    b"item" | b"entry" => {
+                        if let Some(entry) = current.take() {
+                            if let Some(entry) = entry.build(&resolve) {
+                                if entries.len() < MAX_FEED_ENTRIES {
+                                    entries.push(entry);
+                                }
+                            }
+                        }
+                    }
+                    _ if field.as_deref() == Some(name.as_slice()) => {
+                        let value = text.trim().to_string();
+                        match (name.as_slice(), current.as_mut()) {
+                            (b"title", None) if title.is_none() && !value.is_empty() => title = Some(value),
+                            (b"title", Some(entry)) if !value.is_empty() => entry.title = Some(value),
+                            (b"link", Some(entry)) if entry.link.is_none() && !value.is_empty() => {
+                                entry.link = Some(value)
+                            }
+                            (b"guid", Some(entry)) => entry.guid = Some(value),
+                            (b"pubdate", Some(entry)) => {
+                                entry.published = DateTime::parse_from_rfc2822(&value)
+                                    .ok()
+                                    .map(|date| date.with_timezone(&Utc))
+                            }
+                            (b"published" | b"date", Some(entry)) => entry.published = parse_rfc3339(&value),
+                            (b"updated", Some(entry)) => entry.updated = parse_rfc3339(&value),
+                            _ => {}
+                        }
+                        field = None;
+                    }
+                    _ => {}
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    let Some(format) = format else {
+        anyhow::bail!("not an RSS or Atom feed");
+    };
+    debug!("Parsed {:?} feed {} with {} entries", format, feed_url, entries.len());
+
+    Ok(ParsedFeed { format, title, entries })
+}
+
+/// Fields of an `<item>` / `<entry>` collected while parsing
+#[derive(Default)]
+struct EntryBuilder {
+    title: Option<String>,
+    link: Option<String>,
+    guid: Option<String>,
+    published: Option<DateTime<Utc>>,
+    updated: Option<DateTime<Utc>>,
+}
+
+impl EntryBuilder {
+    /// The entry's link, falling back to a permalink `<guid>`
+    fn build(self, resolve: &impl Fn(&str) -> Option<String>) -> Option<FeedEntry> {
+        let url = self
+            .link
+            .as_deref()
+            .and_then(resolve)
+            .or_else(|| self.guid.as_deref().filter(|guid| guid.starts_with("http")).and_then(resolve))?;
+
+        Some(FeedEntry {
+            url,
+            title: self.title,
+            published_at: self.published.or(self.updated),
+        })
+    }
+}
+
+/// Atom `<link>` without `rel` or with `rel="alternate"`
+fn is_alternate_link(element: &BytesStart) -> bool {
+    attribute(element, b"rel").is_none_or(|rel| rel.eq_ignore_ascii_case("alternate"))
+}
+
+fn attribute(element: &BytesStart, name: &[u8]) -> Option<String> {
+    element
+        .attributes()
+        .flatten()
+        .find(|attribute| attribute.key.local_name().as_ref() == name)
+        .and_then(|attribute| attribute.unescape_value().ok())
+        .map(|value| value.trim().to_string())
+}
+
+fn parse_rfc3339(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value).ok().map(|date| date.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rss() {
+        let xml = br#"<?xml version="1.0"?>
+            <rss version="2.0"><channel>
+                <title>Example Blog</title>
+                <link>https://blog.example.com/</link>
+                <item>
+                    <title>First post</title>
+                    <link>https://blog.example.com/first</link>
+                    <pubDate>Tue, 10 Jun 2025 04:00:00 GMT</pubDate>
+                </item>
+                <item>
+                    <title><![CDATA[Second & last]]></title>
+                    <guid isPermaLink="true">https://blog.example.com/second</guid>
+                </item>
+                <item>
+                    <title>No link</title>
+                    <guid isPermaLink="false">post-3</guid>
+                </item>
+            </channel></rss>"#;
+
+        let feed = parse_feed(xml, "https://blog.example.com/feed").unwrap();
+        assert_eq!(feed.format, FeedFormat::Rss);
+        assert_eq!(feed.title.as_deref(), Some("Example Blog"));
+        assert_eq!(feed.entries.len(), 2);
+
+        assert_eq!(feed.entries[0].url, "https://blog.example.com/first");
+        assert_eq!(feed.entries[0].title.as_deref(), Some("First post"));
+        assert_eq!(
+            feed.entries[0].published_at,
+            Some(DateTime::parse_from_rfc3339("2025-06-10T04:00:00Z").unwrap().with_timezone(&Utc))
+        );
+
+        assert_eq!(feed.entries[1].url, "https://blog.example.com/second");
+        assert_eq!(feed.entries[1].title.as_deref(), Some("Second & last"));
+    }
+
+    #[test]
+    fn test_parse_atom() {
+        let xml = br#"<?xml version="1.0" encoding="utf-8"?>
+            <feed xmlns="http://www.w3.org/2005/Atom">
+                <title>Example News</title>
+                <link href="https://news.example.com/" />
+                <entry>
+                    <title>Headline</title>
+                    <link rel="edit" href="/api/posts/1" />
+                    <link rel="alternate" href="/2025/headline" />
+                    <updated>2025-06-11T08:30:00+02:00</updated>
+                </entry>
+            </feed>"#;
+
+        let feed = parse_feed(xml, "https://news.example.com/atom.xml").unwrap();
+        assert_eq!(feed.format, FeedFormat::Atom);
+        assert_eq!(feed.title.as_deref(), Some("Example News"));
+        assert_eq!(feed.entries.len(), 1);
+        assert_eq!(feed.entries[0].url, "https://news.example.com/2025/headline");
+        assert_eq!(
+            feed.entries[0].published_at,
+            Some(DateTime::parse_from_rfc3339("2025-06-11T06:30:00Z").unwrap().with_timezone(&Utc))
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_other_documents() {
+        assert!(parse_feed(b"<html><body>Not a feed</body></html>", "https://example.com/feed").is_err());
+        assert!(parse_feed(b"<urlset></urlset>", "https://example.com/feed").is_err());
+    }
+
+    #[test]
+    fn test_discover_feed_links() {
+        let html = Html::parse_document(
+            r#"<head>
+                <link rel="alternate" type="application/rss+xml" title="Posts" href="/feed/">
+                <link rel="alternate" type="application/rss+xml" title="Comments" href="/comments/feed/">
+                <link rel="Alternate" type="application/atom+xml; charset=utf-8" href="https://example.com/atom.xml">
+                <link rel="alternate" type="text/html" hreflang="fr" href="/fr/">
+                <link rel="stylesheet" type="text/css" href="/style.css">
+            </head>"#,
+        );
+
+        assert_eq!(
+            discover_feed_links(&html, "https://example.com/blog/post"),
+            vec!["https://example.com/feed/".to_string(), "https://example.com/atom.xml".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_discovered_feeds_are_queued_once() {
+        let feeds = FeedManager::new("TestBot/1.0".to_string());
+        assert!(feeds.should_probe("example.com"));
+
+        let urls = vec!["https://example.com/feed".to_string()];
+        assert_eq!(feeds.add_discovered("Example.com", urls.clone()), 1);
+        assert_eq!(feeds.add_discovered("example.com", urls), 0);
+        assert!(!feeds.should_probe("example.com"));
+
+        let pending = feeds.take_pending();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].domain, "example.com");
+        assert!(feeds.take_pending().is_empty());
+
+        feeds.mark_probed("other.com");
+        assert!(!feeds.should_probe("other.com"));
+    }
+}
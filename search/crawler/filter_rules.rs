@@ -0,0 +1,531 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgListener;
+use sqlx::{FromRow, PgPool};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tracing::{info, warn};
+use url::Url;
+use uuid::Uuid;
+
+/// Postgres channel notified whenever filter rules change
+pub const FILTER_RULES_CHANNEL: &str = "crawl_filter_rules";
+
+/// What a filter rule matches and what it does with a match
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterRuleKind {
+    /// URL regex; when any exist, URLs must match one
+    UrlInclude,
+    /// URL regex excluding matches; wins over include rules
+    UrlExclude,
+    /// Domain (and its subdomains); when any exist, hosts must match one
+    DomainAllow,
+    /// Domain (and its subdomains) never crawled; wins over everything
+    DomainBlock,
+}
+
+impl FilterRuleKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::UrlInclude => "url_include",
+            Self::UrlExclude => "url_exclude",
+            Self::DomainAllow => "domain_allow",
+            Self::DomainBlock => "domain_block",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "url_include" => Some(Self::UrlInclude),
+            "url_exclude" => Some(Self::UrlExclude),
+            "domain_allow" => Some(Self::DomainAllow),
+            "domain_block" => Some(Self::DomainBlock),
+            _ => None,
+        }
+    }
+
+    fn is_domain(&self) -> bool {
+        matches!(self, Self::DomainAllow | Self::DomainBlock)
+    }
+
+    /// Check a pattern for this kind and return it in stored form
+    ///
+    /// URL patterns must be valid regexes; domains are lowercased and may be
+    /// written as `example.com`, `.example.com` or `*.example.com`.
+    pub fn normalize_pattern(&self, pattern: &str) -> Result<String> {
+        let pattern = pattern.trim();
+        if pattern.is_empty() {
+            anyhow::bail!("Pattern must not be empty");
+        }
+
+        if self.is_domain() {
+            let domain = pattern
+                .trim_start_matches("*.")
+                .trim_start_matches('.')
+                .trim_end_matches('.')
+                .to_ascii_lowercase();
+            if domain.is_empty() || domain.contains(|c: char| c == '/' || c == ':' || c.is_whitespace()) {
+                anyhow::bail!("Invalid domain {:?}", pattern);
+            }
+            Ok(domain)
+        } else {
+            Regex::new(pattern).with_context(|| format!("Invalid URL pattern {:?}", pattern))?;
+            Ok(pattern.to_string())
+        }
+    }
+}
+
+/// Persisted filter rule (row of `crawl_filter_rules`)
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct FilterRule {
+    pub id: Uuid,
+    pub kind: String,
+    pub pattern: String,
+    pub description: Option<String>,
+    pub enabled: bool,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// Request body for creating a filter rule
+#[derive(Debug, Deserialize)]
+pub struct CreateFilterRuleRequest {
+    pub kind: FilterRuleKind,
+    pub pattern: String,
+    pub description: Option<String>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+/// Request body for updating a filter rule (all fields optional)
+#[derive(Debug, Deserialize)]
+pub struct UpdateFilterRuleRequest {
+    pub kind: Option<FilterRuleKind>,
+    pub pattern: Option<String>,
+    pub description: Option<String>,
+    pub enabled: Option<bool>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// Whether a URL may be crawled, and why
+#[derive(Debug, Clone, Serialize)]
+pub struct FilterDecision {
+    pub allowed: bool,
+    /// Rule that decided; `None` when no rule matched
+    pub rule: Option<FilterRule>,
+    pub reason: String,
+}
+
+impl FilterDecision {
+    pub fn allowed(rule: Option<FilterRule>, reason: &str) -> Self {
+        Self {
+            allowed: true,
+            rule,
+            reason: reason.to_string(),
+        }
+    }
+
+    pub fn denied(rule: Option<FilterRule>, reason: &str) -> Self {
+        Self {
+            allowed: false,
+            rule,
+            reason: reason.to_string(),
+        }
+    }
+}
+
+struct CompiledRule {
+    rule: FilterRule,
+    kind: FilterRuleKind,
+    /// URL rules only
+    regex: Option<Regex>,
+}
+
+impl CompiledRule {
+    fn matches(&self, url: &str, host: &str) -> bool {
+        match &self.regex {
+            Some(regex) => regex.is_match(url),
+            None => host == self.rule.pattern || host.ends_with(&format!(".{}", self.rule.pattern)),
+        }
+    }
+}
+
+/// Enabled filter rules, compiled for matching
+#[derive(Default)]
+pub struct FilterRuleSet {
+    rules: Vec<CompiledRule>,
+}
+
+impl FilterRuleSet {
+    /// Compile enabled rules; rules that no longer compile are skipped
+    pub fn compile(rules: Vec<FilterRule>) -> Self {
+        let rules = rules
+            .into_iter()
+            .filter(|rule| rule.enabled)
+            .filter_map(|rule| {
+                let Some(kind) = FilterRuleKind::parse(&rule.kind) else {
+                    warn!("Skipping filter rule {} with unknown kind {}", rule.id, rule.kind);
+                    return None;
+                };
+                let regex = if kind.is_domain() {
+                    None
+                } else {
+                    match Regex::new(&rule.pattern) {
+                        Ok(regex) => Some(regex),
+                        Err(e) => {
+                            warn!("Skipping filter rule {} with invalid pattern: {}", rule.id, e);
+                            return None;
+                        }
+                    }
+                };
+                Some(CompiledRule { rule, kind, regex })
+            })
+            .collect();
+
+        Self { rules }
+    }
+
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Decide whether `url` may be crawled
+    ///
+    /// Same precedence as `ContentFilter`: blocked domains, then the domain
+    /// allow list, then URL excludes, then URL includes.
+    pub fn evaluate(&self, url: &str) -> FilterDecision {
+        let Ok(parsed) = Url::parse(url) else {
+            return FilterDecision::denied(None, "Invalid URL");
+        };
+        let host = parsed.host_str().unwrap_or_default().to_ascii_lowercase();
+
+        let of_kind = |kind: FilterRuleKind| self.rules.iter().filter(move |rule| rule.kind == kind);
+        let first_match = |kind: FilterRuleKind| of_kind(kind).find(|rule| rule.matches(url, &host));
+
+        if let Some(rule) = first_match(FilterRuleKind::DomainBlock) {
+            return FilterDecision::denied(Some(rule.rule.clone()), "Domain is blocked");
+        }
+
+        let allowed_by = first_match(FilterRuleKind::DomainAllow);
+        if allowed_by.is_none() && of_kind(FilterRuleKind::DomainAllow).next().is_some() {
+            return FilterDecision::denied(None, "Domain is not in the allow list");
+        }
+
+        if let Some(rule) = first_match(FilterRuleKind::UrlExclude) {
+            return FilterDecision::denied(Some(rule.rule.clone()), "URL matches an exclude rule");
+        }
+
+        if of_kind(FilterRuleKind::UrlInclude).next().is_some() {
+            return match first_match(FilterRuleKind::UrlInclude) {
+                Some(rule) => FilterDecision::allowed(Some(rule.rule.clone()), "URL matches an include rule"),
+                None => FilterDecision::denied(None, "URL matches no include rule"),
+            };
+        }
+
+        match allowed_by {
+            Some(rule) => FilterDecision::allowed(Some(rule.rule.clone()), "Domain is in the allow list"),
+            None => FilterDecision::allowed(None, "No rule matched"),
+        }
+    }
+}
+
+/// Filter rules shared by all clones of a crawler and swapped on change
+#[derive(Clone, Default)]
+pub struct FilterRules {
+    rules: Arc<RwLock<FilterRuleSet>>,
+}
+
+impl FilterRules {
+    pub fn replace(&self, rules: FilterRuleSet) {
+        *self.rules.write().unwrap() = rules;
+    }
+
+    pub fn evaluate(&self, url: &str) -> FilterDecision {
+        self.rules.read().unwrap().evaluate(url)
+    }
+
+    pub fn len(&self) -> usize {
+        self.rules.read().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Reload the rules from Postgres
+    pub async fn reload(&self, repo: &FilterRuleRepository) -> Result<()> {
+        let rules = FilterRuleSet::compile(repo.list().await?);
+        info!("Loaded {} crawl filter rules", rules.len());
+        self.replace(rules);
+        Ok(())
+    }
+}
+
+/// Repository for admin-managed crawl filter rules
+///
+/// Every change notifies `FILTER_RULES_CHANNEL` so crawlers reload.
+#[derive(Clone)]
+pub struct FilterRuleRepository {
+    pool: PgPool,
+}
+
+impl FilterRuleRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn list(&self) -> Result<Vec<FilterRule>> {
+        let rules = sqlx::query_as::<_, FilterRule>(
+            "SELECT * FROM crawl_filter_rules ORDER BY kind, pattern",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rules)
+    }
+
+    pub async fn get(&self, id: Uuid) -> Result<Option<FilterRule>> {
+        let rule = sqlx::query_as::<_, FilterRule>("SELECT * FROM crawl_filter_rules WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(rule)
+    }
+
+    /// Create a rule, or update description and state if it already exists
+    ///
+    /// `pattern` must already be normalized for its kind.
+    pub async fn create(
+        &self,
+        kind: FilterRuleKind,
+        pattern: &str,
+        description: Option<&str>,
+        enabled: bool,
+    ) -> Result<FilterRule> {
+        let mut tx = self.pool.begin().await?;
+
+        let rule = sqlx::query_as::<_, FilterRule>(
+            r#"
+            INSERT INTO crawl_filter_rules (kind, pattern, description, enabled)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (kind, pattern) DO UPDATE SET
+                description = COALESCE(EXCLUDED.description, crawl_filter_rules.description),
+                enabled = EXCLUDED.enabled,
+                updated_at = NOW()
+            RETURNING *
+            "#,
+        )
+        .bind(kind.as_str())
+        .bind(pattern)
+        .bind(description)
+        .bind(enabled)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        notify(&mut tx).await?;
+        tx.commit().await?;
+
+        Ok(rule)
+    }
+
+    /// Update a rule; `pattern` must already be normalized for the rule's kind
+    pub async fn update(
+        &self,
+        id: Uuid,
+        kind: Option<FilterRuleKind>,
+        pattern: Option<&str>,
+        description: Option<&str>,
+        enabled: Option<bool>,
+    ) -> Result<Option<FilterRule>> {
+        let mut tx = self.pool.begin().await?;
+
+        let rule = sqlx::query_as::<_, FilterRule>(
+            r#"
+            UPDATE crawl_filter_rules
+            SET kind = COALESCE($2, kind),
+                pattern = COALESCE($3, pattern),
+                description = COALESCE($4, description),
+                enabled = COALESCE($5, enabled),
+                updated_at = NOW()
+            WHERE id = $1
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .bind(kind.map(|kind| kind.as_str()))
+        .bind(pattern)
+        .bind(description)
+        .bind(enabled)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        notify(&mut tx).await?;
+        tx.commit().await?;
+
+        Ok(rule)
+    }
+
+    /// Delete a rule, returns false if it did not exist
+    pub async fn delete(&self, id: Uuid) -> Result<bool> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query("DELETE FROM crawl_filter_rules WHERE id = $1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+
+        notify(&mut tx).await?;
+        tx.commit().await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+/// Queue a change notification, delivered when the transaction commits
+async fn notify(tx: &mut sqlx::Transaction<'_, sqlx::Postgres>) -> Result<()> {
+    sqlx::query("SELECT pg_notify($1, '')")
+        .bind(FILTER_RULES_CHANNEL)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+/// Keep `rules` in sync with Postgres
+///
+/// Loads the rules before returning, so no URL is checked against an empty
+/// set, then reloads them on every change notification. If the listener
+/// connection drops, it reconnects and reloads.
+pub async fn spawn_filter_rule_listener(db_pool: PgPool, rules: FilterRules) -> Result<()> {
+    rules.reload(&FilterRuleRepository::new(db_pool.clone())).await?;
+
+    tokio::spawn(async move {
+        loop {
+            if let Err(e) = listen_for_changes(&db_pool, &rules).await {
+                warn!("Filter rule listener failed: {}. Reconnecting", e);
+            }
+            tokio::time::sleep(Duration::from_secs(5)).await;
+        }
+    });
+
+    Ok(())
+}
+
+async fn listen_for_changes(db_pool: &PgPool, rules: &FilterRules) -> Result<()> {
+    let repo = FilterRuleRepository::new(db_pool.clone());
+    let mut listener = PgListener::connect_with(db_pool).await?;
+    listener.listen(FILTER_RULES_CHANNEL).await?;
+
+    // Catch up on changes made while not listening
+    rules.reload(&repo).await?;
+
+    loop {
+        listener.recv().await?;
+        rules.reload(&repo).await?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(kind: FilterRuleKind, pattern: &str) -> FilterRule {
+        FilterRule {
+            id: Uuid::new_v4(),
+            kind: kind.as_str().to_string(),
+            pattern: pattern.to_string(),
+            description: None,
+            enabled: true,
+            created_at: None,
+            updated_at: None,
+        }
+    }
+
+    #[test]
+    fn test_no_rules_allow_everything() {
+        let decision = FilterRuleSet::default().evaluate("https://example.com/");
+        assert!(decision.allowed);
+        assert!(decision.rule.is_none());
+    }
+
+    #[test]
+    fn test_domain_rules() {
+        let rules = FilterRuleSet::compile(vec![
+            rule(FilterRuleKind::DomainAllow, "example.com"),
+            rule(FilterRuleKind::DomainBlock, "ads.example.com"),
+        ]);
+
+        let decision = rules.evaluate("https://blog.example.com/post");
+        assert!(decision.allowed);
+        assert_eq!(decision.rule.unwrap().pattern, "example.com");
+
+        let decision = rules.evaluate("https://ads.example.com/banner");
+        assert!(!decision.allowed);
+        assert_eq!(decision.rule.unwrap().pattern, "ads.example.com");
+
+        let decision = rules.evaluate("https://notexample.com/");
+        assert!(!decision.allowed);
+        assert!(decision.rule.is_none());
+    }
+
+    #[test]
+    fn test_url_rules() {
+        let rules = FilterRuleSet::compile(vec![
+            rule(FilterRuleKind::UrlInclude, r"/docs/"),
+            rule(FilterRuleKind::UrlExclude, r"\.pdf$"),
+        ]);
+
+        let decision = rules.evaluate("https://example.com/docs/intro");
+        assert!(decision.allowed);
+        assert_eq!(decision.rule.unwrap().kind, "url_include");
+
+        // Exclude wins over include
+        let decision = rules.evaluate("https://example.com/docs/manual.pdf");
+        assert!(!decision.allowed);
+        assert_eq!(decision.rule.unwrap().kind, "url_exclude");
+
+        assert!(!rules.evaluate("https://example.com/blog/").allowed);
+    }
+
+    #[test]
+    fn test_disabled_rules_are_ignored() {
+        let mut blocked = rule(FilterRuleKind::DomainBlock, "example.com");
+        blocked.enabled = false;
+        let rules = FilterRuleSet::compile(vec![blocked]);
+
+        assert!(rules.is_empty());
+        assert!(rules.evaluate("https://example.com/").allowed);
+    }
+
+    #[test]
+    fn test_normalize_pattern() {
+        assert_eq!(
+            FilterRuleKind::DomainBlock.normalize_pattern(" *.Example.COM ").unwrap(),
+            "example.com"
+        );
+        assert!(FilterRuleKind::DomainAllow.normalize_pattern("https://example.com").is_err());
+        assert!(FilterRuleKind::UrlInclude.normalize_pattern("/docs/(").is_err());
+        assert!(FilterRuleKind::UrlExclude.normalize_pattern("  ").is_err());
+    }
+
+    #[test]
+    fn test_shared_rules_swap() {
+        let rules = FilterRules::default();
+        let clone = rules.clone();
+        assert!(clone.evaluate("https://example.com/").allowed);
+
+        rules.replace(FilterRuleSet::compile(vec![rule(FilterRuleKind::DomainBlock, "example.com")]));
+        assert_eq!(clone.len(), 1);
+        assert!(!clone.evaluate("https://example.com/").allowed);
+    }
+}
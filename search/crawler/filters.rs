@@ -29,6 +29,9 @@ impl Default for ContentFilter {
         allowed_types.insert("text/plain".to_string());
         allowed_types.insert("application/xhtml+xml".to_string());
         allowed_types.insert("application/xml".to_string());
+        allowed_types.insert("application/pdf".to_string());
+        allowed_types.insert("text/markdown".to_string());
+        allowed_types.insert("text/x-markdown".to_string());
 
         Self {
             allowed_content_types: allowed_types,
@@ -206,7 +209,8 @@ mod tests {
         assert!(filter.is_content_type_allowed("text/html"));
         assert!(filter.is_content_type_allowed("text/html; charset=utf-8"));
         assert!(filter.is_content_type_allowed("application/xml"));
-        assert!(!filter.is_content_type_allowed("application/pdf"));
+        assert!(filter.is_content_type_allowed("application/pdf"));
+        assert!(filter.is_content_type_allowed("text/markdown"));
         assert!(!filter.is_content_type_allowed("image/jpeg"));
     }
 
@@ -297,7 +301,7 @@ mod tests {
         filter.add_domain_blacklist("spam.com".to_string());
 
         let stats = filter.stats();
-        assert_eq!(stats.allowed_content_types, 4); // Default types
+        assert_eq!(stats.allowed_content_types, 7); // Default types
         assert_eq!(stats.url_include_patterns, 1);
         assert_eq!(stats.url_exclude_patterns, 1);
         assert_eq!(stats.domain_whitelist_count, 1);
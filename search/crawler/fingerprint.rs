@@ -0,0 +1,486 @@
+use anyhow::Result;
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+use std::sync::Arc;
+use tracing::debug;
+use uuid::Uuid;
+
+/// Number of words per shingle
+const SHINGLE_SIZE: usize = 3;
+
+/// Documents with fewer words are not fingerprinted (their SimHash is too noisy)
+const MIN_WORDS: usize = 50;
+
+/// Number of 16-bit bands the 64-bit fingerprint is split into for lookup
+///
+/// Two fingerprints within Hamming distance 3 must agree on at least one of
+/// 4 bands (pigeonhole), so band lookups find every candidate.
+const BANDS: u32 = 4;
+
+/// Default maximum Hamming distance for two documents to count as near-duplicates
+pub const DEFAULT_MAX_DISTANCE: u32 = 3;
+
+/// 64-bit FNV-1a, used for shingle hashes (stable across processes and releases)
+fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+/// Compute the SimHash of a text over lowercased word shingles
+///
+/// Returns `None` if the text is too short to fingerprint reliably.
+pub fn simhash(text: &str) -> Option<u64> {
+    let words: Vec<String> = text
+        .split_whitespace()
+        .map(|w| {
+            w.chars()
+                .filter(|c| c.is_alphanumeric())
+                .flat_map(char::to_lowercase)
+                .collect::<String>()
+        })
+        .filter(|w| !w.is_empty())
+        .collect();
+
+    if words.len() < MIN_WORDS {
+        return None;
+    }
+
+    let mut weights = [0i64; 64];
+    for shingle in words.windows(SHINGLE_SIZE) {
+        let hash = fnv1a(shingle.join(" ").as_bytes());
+        for (bit, weight) in weights.iter_mut().enumerate() {
+            if hash & (1 << bit) != 0 {
+                *weight += 1;
+            } else {
+                *weight -= 1;
+            }
+        }
+    }
+
+    let fingerprint = weights
+        .iter()
+        .enumerate()
+        .filter(|(_, weight)| **weight > 0)
+        .fold(0u64, |acc, (bit, _)| acc | (1 << bit));
+
+    Some(fingerprint)
+}
+
+/// Number of differing bits between two fingerprints
+pub fn hamming_distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+fn band_keys(fingerprint: u64) -> impl Iterator<Item = (u32, u16)> {
+    (0..BANDS).map(move |band| (band, (fingerprint >> (band * 16)) as u16))
+}
+
+/// Fingerprint of an indexed document
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FingerprintEntry {
+    pub document_id: String,
+    pub url: String,
+    pub simhash: u64,
+    /// Representative document this one is a near-duplicate of
+    pub duplicate_of: Option<String>,
+}
+
+/// Fingerprint store statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FingerprintStats {
+    pub documents: usize,
+    pub duplicates: usize,
+    pub max_distance: u32,
+}
+
+/// Near-duplicate detection store, shared by all crawler clones
+///
+/// Near-duplicates are collapsed onto a single representative: the first
+/// document seen with that content. Later documents are recorded as aliases
+/// of it through `duplicate_of`.
+#[derive(Clone)]
+pub struct FingerprintStore {
+    /// Entries by document ID
+    entries: Arc<DashMap<String, FingerprintEntry>>,
+    /// Band index: (band, band value) -> document IDs
+    bands: Arc<DashMap<(u32, u16), Vec<String>>>,
+    max_distance: u32,
+}
+
+impl FingerprintStore {
+    /// Create a new store (`max_distance` is capped at 3, the most the band index can find)
+    pub fn new(max_distance: u32) -> Self {
+        Self {
+            entries: Arc::new(DashMap::new()),
+            bands: Arc::new(DashMap::new()),
+            max_distance: max_distance.min(BANDS - 1),
+        }
+    }
+
+    /// Fingerprint a document without recording it
+    ///
+    /// The entry's `duplicate_of` is the representative document if this one
+    /// is a near-duplicate of another, `None` if it is a representative itself.
+    /// Returns `None` if the content is too short to fingerprint.
+    pub fn check(&self, document_id: &str, url: &str, content: &str) -> Option<FingerprintEntry> {
+        let fingerprint = simhash(content)?;
+
+        // Unchanged content keeps its previous decision
+        let duplicate_of = match self.entries.get(document_id) {
+            Some(existing) if existing.simhash == fingerprint => existing.duplicate_of.clone(),
+            _ => self.find_representative(document_id, fingerprint),
+        };
+        if let Some(representative) = &duplicate_of {
+            debug!("{} is a near-duplicate of document {}", url, representative);
+        }
+
+        Some(FingerprintEntry {
+            document_id: document_id.to_string(),
+            url: url.to_string(),
+            simhash: fingerprint,
+            duplicate_of,
+        })
+    }
+
+    /// Record a checked fingerprint once its document is indexed, so pages
+    /// that never make it into the index can't hide their duplicates
+    pub fn register(&self, entry: FingerprintEntry) {
+        self.remove(&entry.document_id);
+        self.insert(entry);
+    }
+
+    /// Find the closest representative within `max_distance`
+    fn find_representative(&self, document_id: &str, fingerprint: u64) -> Option<String> {
+        let mut candidates: Vec<String> = band_keys(fingerprint)
+            .filter_map(|key| self.bands.get(&key).map(|ids| ids.clone()))
+            .flatten()
+            .filter(|id| id != document_id)
+            .collect();
+        candidates.sort();
+        candidates.dedup();
+
+        candidates
+            .into_iter()
+            .filter_map(|id| {
+                let entry = self.entries.get(&id)?;
+                let distance = hamming_distance(entry.simhash, fingerprint);
+                // Aliases point at their representative, never at another alias
+                let representative = entry.duplicate_of.clone().unwrap_or(id);
+                (distance <= self.max_distance).then_some((distance, representative))
+            })
+            .filter(|(_, representative)| representative != document_id)
+            .min()
+            .map(|(_, representative)| representative)
+    }
+
+    /// Add an entry (e.g. when loading persisted fingerprints)
+    pub fn insert(&self, entry: FingerprintEntry) {
+        for key in band_keys(entry.simhash) {
+            self.bands
+                .entry(key)
+                .or_default()
+                .push(entry.document_id.clone());
+        }
+        self.entries.insert(entry.document_id.clone(), entry);
+    }
+
+    /// Remove a document's fingerprint
+    pub fn remove(&self, document_id: &str) {
+        if let Some((_, entry)) = self.entries.remove(document_id) {
+            for key in band_keys(entry.simhash) {
+                if let Some(mut ids) = self.bands.get_mut(&key) {
+                    ids.retain(|id| id != document_id);
+                }
+            }
+        }
+    }
+
+    /// Remove a document's fingerprint and re-point its near-duplicates
+    ///
+    /// Aliases are re-checked in ID order against the remaining documents, so
+    /// the first one without another representative becomes the new one.
+    /// Returns the aliases with their new `duplicate_of`.
+    pub fn release(&self, document_id: &str) -> Vec<FingerprintEntry> {
+        self.remove(document_id);
+
+        let mut aliases = self.aliases_of(document_id);
+        aliases.sort_by(|a, b| a.document_id.cmp(&b.document_id));
+        for alias in &aliases {
+            self.remove(&alias.document_id);
+        }
+
+        aliases
+            .into_iter()
+            .map(|mut alias| {
+                alias.duplicate_of = self.find_representative(&alias.document_id, alias.simhash);
+                self.insert(alias.clone());
+                alias
+            })
+            .collect()
+    }
+
+    /// Get a document's fingerprint entry
+    pub fn get(&self, document_id: &str) -> Option<FingerprintEntry> {
+        self.entries.get(document_id).map(|entry| entry.clone())
+    }
+
+    /// All documents recorded as near-duplicates of a representative
+    pub fn aliases_of(&self, document_id: &str) -> Vec<FingerprintEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.duplicate_of.as_deref() == Some(document_id))
+            .map(|entry| entry.clone())
+            .collect()
+    }
+
+    /// Number of fingerprinted documents
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if the store is empty
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Get store statistics
+    pub fn stats(&self) -> FingerprintStats {
+        FingerprintStats {
+            documents: self.entries.len(),
+            duplicates: self
+                .entries
+                .iter()
+                .filter(|entry| entry.duplicate_of.is_some())
+                .count(),
+            max_distance: self.max_distance,
+        }
+    }
+}
+
+impl Default for FingerprintStore {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_DISTANCE)
+    }
+}
+
+#[derive(Debug, FromRow)]
+struct FingerprintRow {
+    document_id: Uuid,
+    url: String,
+    simhash: i64,
+    duplicate_of: Option<Uuid>,
+}
+
+/// Persists fingerprints so the store survives restarts
+#[derive(Clone)]
+pub struct FingerprintRepository {
+    pool: PgPool,
+}
+
+impl FingerprintRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Load all persisted fingerprints
+    pub async fn load_all(&self) -> Result<Vec<FingerprintEntry>> {
+        let rows = sqlx::query_as::<_, FingerprintRow>(
+            "SELECT document_id, url, simhash, duplicate_of FROM page_fingerprints",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| FingerprintEntry {
+                document_id: row.document_id.to_string(),
+                url: row.url,
+                simhash: row.simhash as u64,
+                duplicate_of: row.duplicate_of.map(|id| id.to_string()),
+            })
+            .collect())
+    }
+
+    /// Delete a document's fingerprint
+    pub async fn delete(&self, document_id: &str) -> Result<()> {
+        sqlx::query("DELETE FROM page_fingerprints WHERE document_id = $1")
+            .bind(Uuid::parse_str(document_id)?)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Insert or update a fingerprint
+    pub async fn upsert(&self, entry: &FingerprintEntry) -> Result<()> {
+        let document_id = Uuid::parse_str(&entry.document_id)?;
+        let duplicate_of = entry
+            .duplicate_of
+            .as_deref()
+            .map(Uuid::parse_str)
+            .transpose()?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO page_fingerprints (document_id, url, simhash, duplicate_of)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (document_id) DO UPDATE SET
+                url = EXCLUDED.url,
+                simhash = EXCLUDED.simhash,
+                duplicate_of = EXCLUDED.duplicate_of,
+                updated_at = NOW()
+            "#,
+        )
+        .bind(document_id)
+        .bind(&entry.url)
+        .bind(entry.simhash as i64)
+        .bind(duplicate_of)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// ~260 words of distinct sentences
+    fn article(topics: &[&str]) -> String {
+        (0..20)
+            .map(|i| {
+                format!(
+                    "The {} near the {} opened in year {} and drew {} visitors.",
+                    topics[i % topics.len()],
+                    topics[(i * 5 + 3) % topics.len()],
+                    1900 + i,
+                    i * 37 % 1000
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn sample() -> String {
+        article(&["river", "mountain", "city", "market", "school", "garden", "harbor", "station"])
+    }
+
+    #[test]
+    fn test_simhash_short_text() {
+        assert_eq!(simhash("too short to fingerprint"), None);
+    }
+
+    #[test]
+    fn test_simhash_is_stable() {
+        let text = sample();
+        assert_eq!(simhash(&text), simhash(&text));
+        // Case and punctuation do not matter
+        assert_eq!(simhash(&text), simhash(&text.to_uppercase().replace(' ', " , ")));
+    }
+
+    #[test]
+    fn test_simhash_near_duplicates() {
+        let text = sample();
+        let original = simhash(&text).unwrap();
+
+        // Print view with extra boilerplate around the same article
+        let variant = format!("Home | About | Contact {} Print this page. Share on social media.", text);
+        let distance = hamming_distance(original, simhash(&variant).unwrap());
+        assert!(distance <= DEFAULT_MAX_DISTANCE, "distance was {}", distance);
+
+        let unrelated = article(&["protein", "enzyme", "cell", "membrane", "gene", "virus"]);
+        let distance = hamming_distance(original, simhash(&unrelated).unwrap());
+        assert!(distance > DEFAULT_MAX_DISTANCE, "distance was {}", distance);
+    }
+
+    #[test]
+    fn test_store_collapses_duplicates() {
+        let store = FingerprintStore::default();
+        let text = sample();
+        let check_and_register = |id: &str, url: &str| {
+            let entry = store.check(id, url, &text).unwrap();
+            store.register(entry.clone());
+            entry.duplicate_of
+        };
+
+        assert_eq!(check_and_register("a", "https://example.com/a"), None);
+        assert_eq!(
+            check_and_register("b", "https://mirror.example.com/a"),
+            Some("a".to_string())
+        );
+
+        // A third copy points at the representative, not at the alias
+        assert_eq!(
+            check_and_register("c", "https://example.com/a?print=1"),
+            Some("a".to_string())
+        );
+
+        // Re-checking the representative does not make it an alias of its aliases
+        assert_eq!(check_and_register("a", "https://example.com/a"), None);
+
+        assert_eq!(store.aliases_of("a").len(), 2);
+        let stats = store.stats();
+        assert_eq!(stats.documents, 3);
+        assert_eq!(stats.duplicates, 2);
+    }
+
+    #[test]
+    fn test_store_check_does_not_record() {
+        let store = FingerprintStore::default();
+        let text = sample();
+
+        // A page that was checked but never indexed is not a representative
+        let entry = store.check("a", "https://example.com/a", &text).unwrap();
+        assert_eq!(entry.duplicate_of, None);
+        assert!(store.is_empty());
+        assert_eq!(store.check("b", "https://example.com/b", &text).unwrap().duplicate_of, None);
+
+        store.register(entry);
+        assert_eq!(
+            store.check("b", "https://example.com/b", &text).unwrap().duplicate_of,
+            Some("a".to_string())
+        );
+    }
+
+    #[test]
+    fn test_store_release_repoints_aliases() {
+        let store = FingerprintStore::default();
+        let text = sample();
+        for (id, url) in [("a", "https://example.com/a"), ("b", "https://example.com/b"), ("c", "https://example.com/c")] {
+            let entry = store.check(id, url, &text).unwrap();
+            store.register(entry);
+        }
+
+        let released = store.release("a");
+        assert!(store.get("a").is_none());
+        let released: Vec<_> = released
+            .into_iter()
+            .map(|entry| (entry.document_id, entry.duplicate_of))
+            .collect();
+        assert_eq!(
+            released,
+            vec![("b".to_string(), None), ("c".to_string(), Some("b".to_string()))]
+        );
+        assert_eq!(store.get("c").unwrap().duplicate_of, Some("b".to_string()));
+        assert_eq!(store.stats().duplicates, 1);
+    }
+
+    #[test]
+    fn test_store_remove() {
+        let store = FingerprintStore::default();
+        let text = sample();
+
+        store.register(store.check("a", "https://example.com/a", &text).unwrap());
+        store.remove("a");
+        assert!(store.is_empty());
+
+        // With the representative gone, the next copy becomes the representative
+        assert_eq!(store.check("b", "https://example.com/b", &text).unwrap().duplicate_of, None);
+    }
+}
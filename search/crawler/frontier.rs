@@ -0,0 +1,952 @@
+use anyhow::Result;
+use chrono::Utc;
+use redis::aio::ConnectionManager;
+use redis::{AsyncCommands, Script};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+use url::Url;
+use uuid::Uuid;
+
+use super::budget::{BudgetExhausted, BudgetUsage, CrawlBudget};
+use super::util::host_of;
+use super::scheduler::{CrawlFrequency, ScheduledCrawl};
+
+// Every frontier key carries the `{frontier}` hash tag, so the keys one
+// script touches always share a Redis Cluster slot
+
+/// Redis sorted set of hosts with queued URLs, scored by when they may be
+/// fetched from next (milliseconds of Redis server time)
+const HOSTS_KEY: &str = "crawler:{frontier}:hosts";
+
+/// Prefix of the per-host URL queues, sorted by priority
+const HOST_QUEUE_PREFIX: &str = "crawler:{frontier}:host:";
+
+/// Per-job counters are dropped after this long without changes
+const JOB_STATE_TTL_MS: u64 = 2 * 24 * 60 * 60 * 1000;
+
+/// Priority of an unscheduled seed; each link hop away from it costs 10
+const DEFAULT_PRIORITY: u8 = 50;
+
+/// Queue URLs, skipping the ones fetched within the freshness window
+///
+/// Fetched URLs are kept in a Bloom filter per generation of the freshness
+/// window (`KEYS[2]` current, `KEYS[3]` previous). A URL counts as fetched
+/// when all its bit positions are set in either generation. `KEYS[4]` is the
+/// job's counter hash, `KEYS[5..]` the host queue of each entry.
+///
+/// ARGV: force (`1` queues fetched URLs too), job state TTL ms, then host,
+/// member, priority and comma-separated bit positions of every entry.
+/// Returns the number of URLs queued.
+const PUSH_SCRIPT: &str = r#"
+local time = redis.call('TIME')
+local now = tonumber(time[1]) * 1000 + math.floor(tonumber(time[2]) / 1000)
+local force = ARGV[1] == '1'
+local pushed = 0
+
+local function contains(key, positions)
+    for _, position in ipairs(positions) do
+        if redis.call('GETBIT', key, position) == 0 then return false end
+    end
+    return true
+end
+
+for i = 0, (#ARGV - 2) / 4 - 1 do
+    local host = ARGV[3 + i * 4]
+    local member = ARGV[4 + i * 4]
+    local priority = tonumber(ARGV[5 + i * 4])
+    local positions = {}
+    for position in string.gmatch(ARGV[6 + i * 4], '%d+') do
+        positions[#positions + 1] = position
+    end
+
+    if force or not (contains(KEYS[2], positions) or contains(KEYS[3], positions)) then
+        if redis.call('ZADD', KEYS[5 + i], priority, member) == 1 then
+            redis.call('ZADD', KEYS[1], 'NX', now, host)
+            pushed = pushed + 1
+        end
+    end
+end
+
+if pushed > 0 then
+    redis.call('HINCRBY', KEYS[4], 'pending', pushed)
+    redis.call('HSETNX', KEYS[4], 'started', now)
+end
+redis.call('PEXPIRE', KEYS[4], tonumber(ARGV[2]))
+return pushed
+"#;
+
+/// Take the host that has waited longest, if its next fetch time has passed
+///
+/// The host is pushed back by `ARGV[1]` ms right away, so other workers
+/// move on to other hosts while this one takes a URL from its queue.
+const POP_HOST_SCRIPT: &str = r#"
+local time = redis.call('TIME')
+local now = tonumber(time[1]) * 1000 + math.floor(tonumber(time[2]) / 1000)
+
+local ready = redis.call('ZRANGEBYSCORE', KEYS[1], '-inf', now, 'LIMIT', 0, 1)
+if #ready == 0 then return false end
+
+redis.call('ZADD', KEYS[1], now + tonumber(ARGV[1]), ready[1])
+return ready[1]
+"#;
+
+/// Take the highest-priority URL of host `ARGV[1]` (queue `KEYS[2]`)
+///
+/// The host is dropped from `KEYS[1]` once its queue is empty. Returns nil
+/// when the queue already was.
+const POP_URL_SCRIPT: &str = r#"
+local entry = redis.call('ZPOPMAX', KEYS[2])
+if redis.call('ZCARD', KEYS[2]) == 0 then
+    redis.call('ZREM', KEYS[1], ARGV[1])
+end
+if #entry == 0 then return false end
+return entry[1]
+"#;
+
+/// Reserve a fetch of one URL against the job's budgets
+///
+/// `KEYS[1]` is the job's counter hash, `KEYS[2]`/`KEYS[3]` the current and
+/// previous Bloom filter generation. The URL is refused if it was fetched
+/// within the freshness window (unless forced), if a page, byte or time
+/// limit of the job or its host ran out, or if a path limit is used up.
+/// Otherwise its page counters go up and its bits are set in the filter.
+///
+/// ARGV: host, force, Bloom filter TTL ms, comma-separated bit positions,
+/// job max pages, max bytes and max duration ms, host max pages, max bytes
+/// and max duration ms (`''` when unlimited), job state TTL ms, then field
+/// and limit of every path counter the URL falls under. Returns `ok`,
+/// `seen`, `path`, or `job:`/`domain:` followed by the exhausted limit.
+const FETCH_SCRIPT: &str = r#"
+local time = redis.call('TIME')
+local now = tonumber(time[1]) * 1000 + math.floor(tonumber(time[2]) / 1000)
+local host = ARGV[1]
+
+local positions = {}
+for position in string.gmatch(ARGV[4], '%d+') do
+    positions[#positions + 1] = position
+end
+
+local function contains(key)
+    for _, position in ipairs(positions) do
+        if redis.call('GETBIT', key, position) == 0 then return false end
+    end
+    return true
+end
+
+local function count(field)
+    return tonumber(redis.call('HGET', KEYS[1], field) or '0')
+end
+
+local function over(field, limit)
+    return limit ~= '' and count(field) >= tonumber(limit)
+end
+
+local function expired(field, limit)
+    local started = redis.call('HGET', KEYS[1], field)
+    return limit ~= '' and started and now - tonumber(started) >= tonumber(limit)
+end
+
+if ARGV[2] ~= '1' and (contains(KEYS[2]) or contains(KEYS[3])) then
+    return 'seen'
+end
+
+local stop = nil
+if over('fetched', ARGV[5]) then stop = 'max_pages'
+elseif over('bytes', ARGV[6]) then stop = 'max_bytes'
+elseif expired('started', ARGV[7]) then stop = 'max_duration_secs'
+end
+if stop then
+    redis.call('HSETNX', KEYS[1], 'stopped', stop)
+    return 'job:' .. stop
+end
+
+if over('fetched:' .. host, ARGV[8]) then stop = 'max_pages'
+elseif over('bytes:' .. host, ARGV[9]) then stop = 'max_bytes'
+elseif expired('started:' .. host, ARGV[10]) then stop = 'max_duration_secs'
+end
+if stop then
+    redis.call('HSETNX', KEYS[1], 'exhausted:' .. host, stop)
+    return 'domain:' .. stop
+end
+
+for i = 12, #ARGV, 2 do
+    if count(ARGV[i]) >= tonumber(ARGV[i + 1]) then
+        redis.call('HINCRBY', KEYS[1], 'skipped', 1)
+        return 'path'
+    end
+end
+
+for _, position in ipairs(positions) do
+    redis.call('SETBIT', KEYS[2], position, 1)
+end
+redis.call('PEXPIRE', KEYS[2], tonumber(ARGV[3]))
+
+redis.call('HINCRBY', KEYS[1], 'fetched', 1)
+redis.call('HINCRBY', KEYS[1], 'fetched:' .. host, 1)
+for i = 12, #ARGV, 2 do
+    redis.call('HINCRBY', KEYS[1], ARGV[i], 1)
+end
+redis.call('HSETNX', KEYS[1], 'started:' .. host, now)
+redis.call('HSET', KEYS[1], 'last', now, 'last:' .. host, now)
+redis.call('PEXPIRE', KEYS[1], tonumber(ARGV[11]))
+return 'ok'
+"#;
+
+/// URL frontier settings
+#[derive(Debug, Clone)]
+pub struct FrontierConfig {
+    /// A fetched URL is not queued or fetched again for at least this long
+    pub freshness_window: Duration,
+    /// Size of each Bloom filter generation, in bits (at most 2^32)
+    pub bloom_bits: u64,
+    /// Bit positions per URL
+    pub bloom_hashes: u32,
+    /// Minimum time between two URLs of the same host leaving the frontier
+    pub host_interval: Duration,
+}
+
+impl Default for FrontierConfig {
+    fn default() -> Self {
+        // 2^27 bits (16 MiB) keep false positives around 1% up to ~14M URLs
+        Self {
+            freshness_window: Duration::from_secs(24 * 60 * 60),
+            bloom_bits: 1 << 27,
+            bloom_hashes: 7,
+            host_interval: Duration::from_secs(1),
+        }
+    }
+}
+
+/// A URL waiting in the frontier
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FrontierEntry {
+    pub url: String,
+    pub job_id: Uuid,
+    /// Link hops from the job's seed URL
+    pub depth: usize,
+    /// Host of the seed URL, which the job's crawl scope is relative to
+    pub seed_host: String,
+    pub priority: i64,
+}
+
+impl FrontierEntry {
+    /// A seed URL of a job, at depth 0
+    pub fn seed(url: String, job_id: Uuid, priority: i64) -> Option<Self> {
+        let seed_host = host_of(&url)?;
+        Some(Self {
+            url,
+            job_id,
+            depth: 0,
+            seed_host,
+            priority,
+        })
+    }
+
+    /// A URL linked from this entry's page, one hop deeper
+    pub fn child(&self, url: String, priority: i64) -> Self {
+        Self {
+            url,
+            job_id: self.job_id,
+            depth: self.depth + 1,
+            seed_host: self.seed_host.clone(),
+            priority,
+        }
+    }
+
+    pub fn host(&self) -> Option<String> {
+        host_of(&self.url)
+    }
+
+    pub fn path(&self) -> String {
+        Url::parse(&self.url).map(|url| url.path().to_string()).unwrap_or_default()
+    }
+}
+
+/// Whether a popped URL may be fetched
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FetchPermit {
+    Granted,
+    /// Fetched (by any job) within the freshness window
+    Seen,
+    /// A path limit of the job or the host is used up
+    PathLimit,
+    /// The job ran out of budget
+    JobExhausted(BudgetExhausted),
+    /// The job's budget for the URL's host ran out
+    DomainExhausted(BudgetExhausted),
+}
+
+/// What a frontier job used of its budgets, shaped like `CrawlSession::summary`
+#[derive(Debug, Clone, Serialize)]
+pub struct FrontierBudgetUsage {
+    pub job: BudgetUsage,
+    pub domains: BTreeMap<String, BudgetUsage>,
+    pub skipped_pages: usize,
+}
+
+/// Frontier priority of a URL `depth` hops from its seed
+///
+/// URLs on the recrawl schedule keep their `scheduling_score`; others are
+/// scored like a weekly task whose priority drops with every hop.
+pub fn url_priority(url: &str, depth: usize, scheduled: Option<&ScheduledCrawl>) -> i64 {
+    match scheduled {
+        Some(task) => task.scheduling_score(),
+        None => {
+            let priority = DEFAULT_PRIORITY.saturating_sub(depth.min(5) as u8 * 10);
+            ScheduledCrawl::new(url.to_string(), CrawlFrequency::Weekly, priority).scheduling_score()
+        }
+    }
+}
+
+/// Redis-backed URL frontier shared by all workers
+///
+/// URLs wait in per-host queues ordered by priority. Workers pop one URL at
+/// a time from the host that has waited longest, so load spreads across
+/// hosts instead of one worker crawling a whole site. A Bloom filter of
+/// fetched URLs keeps discovered links from being fetched twice within the
+/// freshness window, across jobs. Each job counts its queued and in-flight
+/// URLs, so the worker finishing the last one can complete the job, and what
+/// it fetched, so its budgets hold across workers.
+#[derive(Clone)]
+pub struct UrlFrontier {
+    conn: ConnectionManager,
+    config: FrontierConfig,
+}
+
+impl UrlFrontier {
+    pub fn new(conn: ConnectionManager, config: FrontierConfig) -> Self {
+        Self { conn, config }
+    }
+
+    /// Queue URLs of one job, returning how many were queued
+    ///
+    /// URLs fetched within the freshness window are skipped unless `force`
+    /// is set (used for seeds, which the caller asked for explicitly).
+    pub async fn push(&self, job_id: Uuid, entries: &[FrontierEntry], force: bool) -> Result<usize> {
+        let generation = generation(Utc::now().timestamp(), self.config.freshness_window);
+
+        let script = Script::new(PUSH_SCRIPT);
+        let mut invocation = script.prepare_invoke();
+        invocation
+            .key(HOSTS_KEY)
+            .key(Self::seen_key(generation))
+            .key(Self::seen_key(generation - 1))
+            .key(Self::job_key(job_id))
+            .arg(if force { 1 } else { 0 })
+            .arg(JOB_STATE_TTL_MS);
+
+        let mut queued = 0;
+        for entry in entries {
+            anyhow::ensure!(entry.job_id == job_id, "Frontier entry of job {} pushed for job {}", entry.job_id, job_id);
+            let Some(host) = entry.host() else {
+                continue;
+            };
+
+            invocation
+                .key(Self::host_queue_key(&host))
+                .arg(host)
+                .arg(serde_json::to_string(entry)?)
+                .arg(entry.priority)
+                .arg(self.positions(&entry.url));
+            queued += 1;
+        }
+
+        if queued == 0 {
+            return Ok(0);
+        }
+
+        let mut conn = self.conn.clone();
+        let pushed: usize = invocation.invoke_async(&mut conn).await?;
+
+        Ok(pushed)
+    }
+
+    /// Take the next URL to crawl, if any host is ready
+    pub async fn pop(&self) -> Result<Option<FrontierEntry>> {
+        let mut conn = self.conn.clone();
+        loop {
+            let host: Option<String> = Script::new(POP_HOST_SCRIPT)
+                .key(HOSTS_KEY)
+                .arg(self.config.host_interval.as_millis() as u64)
+                .invoke_async(&mut conn)
+                .await?;
+            let Some(host) = host else {
+                return Ok(None);
+            };
+
+            // The host was dropped if another worker emptied its queue meanwhile
+            let member: Option<String> = Script::new(POP_URL_SCRIPT)
+                .key(HOSTS_KEY)
+                .key(Self::host_queue_key(&host))
+                .arg(&host)
+                .invoke_async(&mut conn)
+                .await?;
+            if let Some(member) = member {
+                return Ok(Some(serde_json::from_str(&member)?));
+            }
+        }
+    }
+
+    /// Mark a popped URL of a job as done, returning how many are left
+    pub async fn complete(&self, job_id: Uuid) -> Result<i64> {
+        let mut conn = self.conn.clone();
+        let remaining: i64 = conn.hincr(Self::job_key(job_id), "pending", -1).await?;
+        Ok(remaining)
+    }
+
+    /// Reserve a fetch of a popped URL against its job's budgets
+    ///
+    /// A granted URL counts as fetched from then on: it is added to the
+    /// seen-URL filter and to the job's and its host's page counts. Seeds
+    /// are fetched even if another job fetched them recently.
+    pub async fn start_fetch(
+        &self,
+        entry: &FrontierEntry,
+        job_budget: &CrawlBudget,
+        domain_budget: &CrawlBudget,
+    ) -> Result<FetchPermit> {
+        let host = entry.host().unwrap_or_default();
+        let generation = generation(Utc::now().timestamp(), self.config.freshness_window);
+        let limit = |value: Option<u64>| value.map(|value| value.to_string()).unwrap_or_default();
+
+        let script = Script::new(FETCH_SCRIPT);
+        let mut invocation = script.prepare_invoke();
+        invocation
+            .key(Self::job_key(entry.job_id))
+            .key(Self::seen_key(generation))
+            .key(Self::seen_key(generation - 1))
+            .arg(&host)
+            .arg(if entry.depth == 0 { 1 } else { 0 })
+            .arg(self.config.freshness_window.as_millis() as u64 * 2)
+            .arg(self.positions(&entry.url))
+            .arg(limit(job_budget.max_pages.map(|pages| pages as u64)))
+            .arg(limit(job_budget.max_bytes))
+            .arg(limit(job_budget.max_duration_secs.map(|secs| secs * 1000)))
+            .arg(limit(domain_budget.max_pages.map(|pages| pages as u64)))
+            .arg(limit(domain_budget.max_bytes))
+            .arg(limit(domain_budget.max_duration_secs.map(|secs| secs * 1000)))
+            .arg(JOB_STATE_TTL_MS);
+        for (field, max) in path_counters(&host, &entry.path(), job_budget, domain_budget) {
+            invocation.arg(field).arg(max);
+        }
+
+        let mut conn = self.conn.clone();
+        let permit: String = invocation.invoke_async(&mut conn).await?;
+        fetch_permit(&permit, job_budget, domain_budget)
+    }
+
+    /// Count the downloaded bytes of a granted fetch
+    pub async fn record_bytes(&self, job_id: Uuid, host: &str, bytes: u64) -> Result<()> {
+        let mut conn = self.conn.clone();
+        let _: () = redis::pipe()
+            .atomic()
+            .hincr(Self::job_key(job_id), "bytes", bytes)
+            .ignore()
+            .hincr(Self::job_key(job_id), format!("bytes:{}", host), bytes)
+            .ignore()
+            .query_async(&mut conn)
+            .await?;
+
+        Ok(())
+    }
+
+    /// What a job used of its budgets, and which limits ran out
+    pub async fn budget_usage(
+        &self,
+        job_id: Uuid,
+        job_budget: &CrawlBudget,
+        domain_budget: &CrawlBudget,
+    ) -> Result<FrontierBudgetUsage> {
+        let mut conn = self.conn.clone();
+        let fields: HashMap<String, String> = conn.hgetall(Self::job_key(job_id)).await?;
+        Ok(budget_usage(&fields, job_budget, domain_budget))
+    }
+
+    /// Add crawled and indexed pages to a job, returning its totals
+    pub async fn record_progress(&self, job_id: Uuid, crawled: usize, indexed: usize) -> Result<(usize, usize)> {
+        let mut conn = self.conn.clone();
+        let totals: (usize, usize) = redis::pipe()
+            .atomic()
+            .hincr(Self::job_key(job_id), "crawled", crawled)
+            .hincr(Self::job_key(job_id), "indexed", indexed)
+            .query_async(&mut conn)
+            .await?;
+
+        Ok(totals)
+    }
+
+    /// Set aside a popped URL of a paused job until it is resumed
+    pub async fn park(&self, entry: &FrontierEntry) -> Result<()> {
+        let key = Self::parked_key(entry.job_id);
+        let mut conn = self.conn.clone();
+        let _: () = redis::pipe()
+            .atomic()
+            .rpush(&key, serde_json::to_string(entry)?)
+            .pexpire(&key, JOB_STATE_TTL_MS as i64)
+            .query_async(&mut conn)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Queue the URLs set aside while a job was paused, returns how many
+    pub async fn unpark(&self, job_id: Uuid) -> Result<usize> {
+        let key = Self::parked_key(job_id);
+        let mut conn = self.conn.clone();
+        let (members, _): (Vec<String>, ()) = redis::pipe()
+            .atomic()
+            .lrange(&key, 0, -1)
+            .del(&key)
+            .query_async(&mut conn)
+            .await?;
+
+        let entries = members
+            .iter()
+            .map(|member| serde_json::from_str(member))
+            .collect::<Result<Vec<FrontierEntry>, _>>()?;
+
+        // They were crawlable when parked, so skip the seen-URL filter
+        self.push(job_id, &entries, true).await
+    }
+
+    /// Drop the URLs set aside for a paused job that will not be resumed
+    pub async fn drop_parked(&self, job_id: Uuid) -> Result<()> {
+        let mut conn = self.conn.clone();
+        Self::drop_parked_with(&mut conn, job_id).await
+    }
+
+    /// `drop_parked` on a plain connection, for the API, which cancels
+    /// paused jobs without running a frontier
+    pub async fn drop_parked_with(conn: &mut ConnectionManager, job_id: Uuid) -> Result<()> {
+        let _: () = conn.del(Self::parked_key(job_id)).await?;
+        Ok(())
+    }
+
+    /// Whether URLs of a job were ever queued (and its counters still exist)
+    pub async fn is_started(&self, job_id: Uuid) -> Result<bool> {
+        let mut conn = self.conn.clone();
+        let exists: bool = conn.exists(Self::job_key(job_id)).await?;
+        Ok(exists)
+    }
+
+    /// URLs of a job queued or being crawled
+    pub async fn pending(&self, job_id: Uuid) -> Result<i64> {
+        let mut conn = self.conn.clone();
+        let pending: Option<i64> = conn.hget(Self::job_key(job_id), "pending").await?;
+        Ok(pending.unwrap_or(0))
+    }
+
+    /// Crawled and indexed pages of a job
+    pub async fn progress(&self, job_id: Uuid) -> Result<(usize, usize)> {
+        let mut conn = self.conn.clone();
+        let (crawled, indexed): (Option<usize>, Option<usize>) = redis::cmd("HMGET")
+            .arg(Self::job_key(job_id))
+            .arg("crawled")
+            .arg("indexed")
+            .query_async(&mut conn)
+            .await?;
+
+        Ok((crawled.unwrap_or(0), indexed.unwrap_or(0)))
+    }
+
+    /// Comma-separated Bloom filter bit positions of a URL
+    fn positions(&self, url: &str) -> String {
+        bloom_positions(url, self.config.bloom_bits, self.config.bloom_hashes)
+            .iter()
+            .map(u64::to_string)
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    fn host_queue_key(host: &str) -> String {
+        format!("{}{}", HOST_QUEUE_PREFIX, host)
+    }
+
+    fn job_key(job_id: Uuid) -> String {
+        format!("crawler:{{frontier}}:job:{}", job_id)
+    }
+
+    fn parked_key(job_id: Uuid) -> String {
+        format!("crawler:{{frontier}}:parked:{}", job_id)
+    }
+
+    fn seen_key(generation: i64) -> String {
+        format!("crawler:{{frontier}}:seen:{}", generation)
+    }
+}
+
+/// Job hash fields and limits of the path counters a page falls under
+fn path_counters(host: &str, path: &str, job_budget: &CrawlBudget, domain_budget: &CrawlBudget) -> Vec<(String, usize)> {
+    let matching = |budget: &CrawlBudget| {
+        budget
+            .max_pages_per_path
+            .iter()
+            .filter(|(prefix, _)| path.starts_with(prefix.as_str()))
+            .map(|(prefix, limit)| (prefix.clone(), *limit))
+            .collect::<Vec<_>>()
+    };
+
+    let job = matching(job_budget)
+        .into_iter()
+        .map(|(prefix, limit)| (format!("path:{}", prefix), limit));
+    let domain = matching(domain_budget)
+        .into_iter()
+        .map(|(prefix, limit)| (format!("path:{}:{}", host, prefix), limit));
+    job.chain(domain).collect()
+}
+
+/// The limit of a budget named by `FETCH_SCRIPT`
+fn exhausted_limit(name: &str, budget: &CrawlBudget) -> Option<BudgetExhausted> {
+    match name {
+        "max_pages" => budget.max_pages.map(BudgetExhausted::MaxPages),
+        "max_bytes" => budget.max_bytes.map(BudgetExhausted::MaxBytes),
+        "max_duration_secs" => budget.max_duration_secs.map(BudgetExhausted::MaxDurationSecs),
+        _ => None,
+    }
+}
+
+fn fetch_permit(reply: &str, job_budget: &CrawlBudget, domain_budget: &CrawlBudget) -> Result<FetchPermit> {
+    let permit = match reply.split_once(':') {
+        None if reply == "ok" => Some(FetchPermit::Granted),
+        None if reply == "seen" => Some(FetchPermit::Seen),
+        None if reply == "path" => Some(FetchPermit::PathLimit),
+        Some(("job", limit)) => exhausted_limit(limit, job_budget).map(FetchPermit::JobExhausted),
+        Some(("domain", limit)) => exhausted_limit(limit, domain_budget).map(FetchPermit::DomainExhausted),
+        _ => None,
+    };
+    permit.ok_or_else(|| anyhow::anyhow!("Unexpected frontier fetch reply {:?}", reply))
+}
+
+/// Budget usage from the fields of a job's counter hash
+fn budget_usage(fields: &HashMap<String, String>, job_budget: &CrawlBudget, domain_budget: &CrawlBudget) -> FrontierBudgetUsage {
+    let number = |field: &str| fields.get(field).and_then(|value| value.parse::<u64>().ok()).unwrap_or(0);
+    let elapsed_secs = |started: &str, last: &str| number(last).saturating_sub(number(started)) / 1000;
+
+    // Job path counters are `path:/prefix`, a host's `path:host:/prefix`
+    let path_pages = |prefix: &str| -> BTreeMap<String, usize> {
+        fields
+            .iter()
+            .filter_map(|(field, value)| {
+                let path = field.strip_prefix(prefix)?;
+                path.starts_with('/').then(|| (path.to_string(), value.parse().unwrap_or(0)))
+            })
+            .collect()
+    };
+
+    let job = BudgetUsage {
+        pages: number("fetched") as usize,
+        bytes: number("bytes"),
+        elapsed_secs: elapsed_secs("started", "last"),
+        path_pages: path_pages("path:"),
+        exhausted: fields.get("stopped").and_then(|name| exhausted_limit(name, job_budget)),
+    };
+
+    let domains = fields
+        .keys()
+        .filter_map(|field| field.strip_prefix("fetched:"))
+        .map(|host| {
+            let usage = BudgetUsage {
+                pages: number(&format!("fetched:{}", host)) as usize,
+                bytes: number(&format!("bytes:{}", host)),
+                elapsed_secs: elapsed_secs(&format!("started:{}", host), &format!("last:{}", host)),
+                path_pages: path_pages(&format!("path:{}:", host)),
+                exhausted: fields
+                    .get(&format!("exhausted:{}", host))
+                    .and_then(|name| exhausted_limit(name, domain_budget)),
+            };
+            (host.to_string(), usage)
+        })
+        .collect();
+
+    FrontierBudgetUsage {
+        job,
+        domains,
+        skipped_pages: number("skipped") as usize,
+    }
+}
+
+/// Bloom filter generation a time falls into
+fn generation(unix_secs: i64, window: Duration) -> i64 {
+    unix_secs.div_euclid(window.as_secs().max(1) as i64)
+}
+
+/// Bit positions of a URL, by double hashing its SHA-256 digest
+fn bloom_positions(url: &str, bits: u64, hashes: u32) -> Vec<u64> {
+    let digest = Sha256::digest(url.as_bytes());
+    let h1 = u64::from_be_bytes(digest[..8].try_into().expect("SHA-256 digest has 32 bytes"));
+    // An odd step visits distinct positions for power-of-two sizes
+    let h2 = u64::from_be_bytes(digest[8..16].try_into().expect("SHA-256 digest has 32 bytes")) | 1;
+    let bits = bits.max(1);
+
+    (0..u64::from(hashes))
+        .map(|i| h1.wrapping_add(i.wrapping_mul(h2)) % bits)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bloom_positions() {
+        let positions = bloom_positions("https://example.com/a", 1 << 20, 7);
+        assert_eq!(positions.len(), 7);
+        assert!(positions.iter().all(|&position| position < 1 << 20));
+
+        let unique: std::collections::HashSet<_> = positions.iter().collect();
+        assert_eq!(unique.len(), 7);
+
+        // Stable across calls (and workers), different for other URLs
+        assert_eq!(positions, bloom_positions("https://example.com/a", 1 << 20, 7));
+        assert_ne!(positions, bloom_positions("https://example.com/b", 1 << 20, 7));
+    }
+
+    #[test]
+    fn test_generation() {
+        let day = Duration::from_secs(86400);
+        assert_eq!(generation(0, day), 0);
+        assert_eq!(generation(86399, day), 0);
+        assert_eq!(generation(86400, day), 1);
+        assert_eq!(generation(-1, day), -1);
+    }
+
+    #[test]
+    fn test_url_priority() {
+        let url = "https://example.com/page";
+        assert!(url_priority(url, 0, None) > url_priority(url, 1, None));
+        assert_eq!(url_priority(url, 5, None), url_priority(url, 9, None));
+
+        let scheduled = ScheduledCrawl::new(url.to_string(), CrawlFrequency::Daily, 90);
+        assert_eq!(url_priority(url, 3, Some(&scheduled)), scheduled.scheduling_score());
+    }
+
+    #[test]
+    fn test_entries() {
+        let job_id = Uuid::new_v4();
+        let seed = FrontierEntry::seed("https://Example.com/".to_string(), job_id, 100).unwrap();
+        assert_eq!(seed.seed_host, "example.com");
+        assert_eq!(seed.depth, 0);
+
+        let child = seed.child("https://blog.example.com/post".to_string(), 10);
+        assert_eq!(child.depth, 1);
+        assert_eq!(child.seed_host, "example.com");
+        assert_eq!(child.host().as_deref(), Some("blog.example.com"));
+
+        let json = serde_json::to_string(&child).unwrap();
+        assert_eq!(serde_json::from_str::<FrontierEntry>(&json).unwrap(), child);
+
+        assert!(FrontierEntry::seed("not a url".to_string(), job_id, 0).is_none());
+        assert_eq!(child.path(), "/post");
+    }
+
+    #[test]
+    fn test_keys_share_cluster_slot() {
+        let keys = [
+            HOSTS_KEY.to_string(),
+            UrlFrontier::host_queue_key("example.com"),
+            UrlFrontier::job_key(Uuid::new_v4()),
+            UrlFrontier::parked_key(Uuid::new_v4()),
+            UrlFrontier::seen_key(1),
+        ];
+        for key in keys {
+            let tag = key.split_once('{').and_then(|(_, rest)| rest.split_once('}')).map(|(tag, _)| tag);
+            assert_eq!(tag, Some("frontier"), "{}", key);
+        }
+    }
+
+    #[test]
+    fn test_path_counters() {
+        let job_budget = CrawlBudget {
+            max_pages_per_path: BTreeMap::from([("/blog".to_string(), 5), ("/docs".to_string(), 2)]),
+            ..Default::default()
+        };
+        let domain_budget = CrawlBudget {
+            max_pages_per_path: BTreeMap::from([("/blog/2024".to_string(), 1)]),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            path_counters("a.com", "/blog/2024/post", &job_budget, &domain_budget),
+            vec![("path:/blog".to_string(), 5), ("path:a.com:/blog/2024".to_string(), 1)]
+        );
+        assert!(path_counters("a.com", "/about", &job_budget, &domain_budget).is_empty());
+    }
+
+    #[test]
+    fn test_fetch_permit() {
+        let budget = CrawlBudget {
+            max_pages: Some(10),
+            max_bytes: Some(1_000),
+            ..Default::default()
+        };
+        let unlimited = CrawlBudget::default();
+
+        assert_eq!(fetch_permit("ok", &budget, &unlimited).unwrap(), FetchPermit::Granted);
+        assert_eq!(fetch_permit("seen", &budget, &unlimited).unwrap(), FetchPermit::Seen);
+        assert_eq!(fetch_permit("path", &budget, &unlimited).unwrap(), FetchPermit::PathLimit);
+        assert_eq!(
+            fetch_permit("job:max_bytes", &budget, &unlimited).unwrap(),
+            FetchPermit::JobExhausted(BudgetExhausted::MaxBytes(1_000))
+        );
+        assert_eq!(
+            fetch_permit("domain:max_pages", &unlimited, &budget).unwrap(),
+            FetchPermit::DomainExhausted(BudgetExhausted::MaxPages(10))
+        );
+        assert!(fetch_permit("domain:max_pages", &budget, &unlimited).is_err());
+        assert!(fetch_permit("nope", &budget, &unlimited).is_err());
+    }
+
+    #[test]
+    fn test_budget_usage() {
+        let budget = CrawlBudget {
+            max_pages: Some(2),
+            max_duration_secs: Some(60),
+            ..Default::default()
+        };
+        let fields: HashMap<String, String> = [
+            ("pending", "3"),
+            ("started", "1000"),
+            ("last", "9000"),
+            ("fetched", "2"),
+            ("bytes", "300"),
+            ("skipped", "1"),
+            ("stopped", "max_pages"),
+            ("fetched:a.com", "2"),
+            ("bytes:a.com", "300"),
+            ("started:a.com", "1000"),
+            ("last:a.com", "4000"),
+            ("exhausted:a.com", "max_duration_secs"),
+            ("path:/blog", "1"),
+            ("path:b.com:/docs", "1"),
+        ]
+        .into_iter()
+        .map(|(field, value)| (field.to_string(), value.to_string()))
+        .collect();
+
+        let usage = budget_usage(&fields, &budget, &budget);
+        assert_eq!(usage.job.pages, 2);
+        assert_eq!(usage.job.bytes, 300);
+        assert_eq!(usage.job.elapsed_secs, 8);
+        assert_eq!(usage.job.exhausted, Some(BudgetExhausted::MaxPages(2)));
+        assert_eq!(usage.skipped_pages, 1);
+        assert_eq!(usage.domains.len(), 1);
+        assert_eq!(usage.domains["a.com"].elapsed_secs, 3);
+        assert_eq!(usage.job.path_pages, BTreeMap::from([("/blog".to_string(), 1)]));
+        assert!(usage.domains["a.com"].path_pages.is_empty());
+        assert_eq!(usage.domains["a.com"].exhausted, Some(BudgetExhausted::MaxDurationSecs(60)));
+    }
+
+    /// A frontier on the Redis server at `REDIS_URL`; the script tests use
+    /// their own hosts and jobs but share the frontier's global keys
+    async fn redis_frontier() -> UrlFrontier {
+        let url = std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1/".to_string());
+        let client = redis::Client::open(url).unwrap();
+        let conn = ConnectionManager::new(client).await.unwrap();
+        UrlFrontier::new(
+            conn,
+            FrontierConfig {
+                bloom_bits: 1 << 16,
+                host_interval: Duration::from_secs(60),
+                ..Default::default()
+            },
+        )
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a Redis server at REDIS_URL"]
+    async fn test_push_pop_scripts() {
+        let frontier = redis_frontier().await;
+        let job_id = Uuid::new_v4();
+        let host = format!("{}.test", job_id);
+        let seed = FrontierEntry::seed(format!("https://{}/", host), job_id, 10).unwrap();
+        let low = seed.child(format!("https://{}/low", host), 1);
+        let high = seed.child(format!("https://{}/high", host), 5);
+
+        let entries = [seed.clone(), low.clone(), high.clone()];
+        assert_eq!(frontier.push(job_id, &entries, false).await.unwrap(), 3);
+        // An entry already waiting is not queued twice
+        assert_eq!(frontier.push(job_id, std::slice::from_ref(&low), false).await.unwrap(), 0);
+        assert_eq!(frontier.pending(job_id).await.unwrap(), 3);
+
+        // Hosts of other tests may be ready too; skip past them
+        let mut popped = None;
+        while let Some(entry) = frontier.pop().await.unwrap() {
+            if entry.job_id == job_id {
+                popped = Some(entry);
+                break;
+            }
+        }
+        assert_eq!(popped, Some(seed));
+
+        // The host waits `host_interval` before its next URL leaves
+        let mut conn = frontier.conn.clone();
+        let score: Option<f64> = conn.zscore(HOSTS_KEY, &host).await.unwrap();
+        assert!(score.is_some());
+        let _: () = conn.zadd(HOSTS_KEY, &host, 0).await.unwrap();
+        assert_eq!(frontier.pop().await.unwrap(), Some(high));
+        let _: () = conn.zadd(HOSTS_KEY, &host, 0).await.unwrap();
+        assert_eq!(frontier.pop().await.unwrap(), Some(low.clone()));
+
+        // An emptied host leaves the host set
+        let score: Option<f64> = conn.zscore(HOSTS_KEY, &host).await.unwrap();
+        assert_eq!(score, None);
+
+        // Once fetched, a URL is neither fetched nor queued again unless forced
+        let unlimited = CrawlBudget::default();
+        assert_eq!(frontier.start_fetch(&low, &unlimited, &unlimited).await.unwrap(), FetchPermit::Granted);
+        assert_eq!(frontier.start_fetch(&low, &unlimited, &unlimited).await.unwrap(), FetchPermit::Seen);
+        assert_eq!(frontier.push(job_id, std::slice::from_ref(&low), false).await.unwrap(), 0);
+        assert_eq!(frontier.push(job_id, &[low], true).await.unwrap(), 1);
+
+        let _: () = conn.del(UrlFrontier::host_queue_key(&host)).await.unwrap();
+        let _: () = conn.zrem(HOSTS_KEY, &host).await.unwrap();
+        let _: () = conn.del(UrlFrontier::job_key(job_id)).await.unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a Redis server at REDIS_URL"]
+    async fn test_fetch_script_budgets() {
+        let frontier = redis_frontier().await;
+        let job_id = Uuid::new_v4();
+        let seed = FrontierEntry::seed(format!("https://{}.test/", job_id), job_id, 10).unwrap();
+        let page = |path: &str| seed.child(format!("https://{}.test{}", job_id, path), 1);
+        let other = seed.child(format!("https://other-{}.test/", job_id), 1);
+        let host = seed.host().unwrap();
+
+        let job_budget = CrawlBudget {
+            max_pages: Some(4),
+            max_bytes: Some(1_000),
+            max_pages_per_path: BTreeMap::from([("/blog".to_string(), 1)]),
+            ..Default::default()
+        };
+        let domain_budget = CrawlBudget {
+            max_pages: Some(2),
+            ..Default::default()
+        };
+        let start = |entry: FrontierEntry| {
+            let frontier = frontier.clone();
+            let (job_budget, domain_budget) = (job_budget.clone(), domain_budget.clone());
+            async move { frontier.start_fetch(&entry, &job_budget, &domain_budget).await.unwrap() }
+        };
+
+        assert_eq!(start(seed.clone()).await, FetchPermit::Granted);
+        assert_eq!(start(page("/blog/a")).await, FetchPermit::Granted);
+        assert_eq!(start(page("/blog/b")).await, FetchPermit::PathLimit);
+        assert_eq!(start(page("/about")).await, FetchPermit::DomainExhausted(BudgetExhausted::MaxPages(2)));
+
+        frontier.record_bytes(job_id, &host, 1_200).await.unwrap();
+        assert_eq!(start(other).await, FetchPermit::JobExhausted(BudgetExhausted::MaxBytes(1_000)));
+
+        let usage = frontier.budget_usage(job_id, &job_budget, &domain_budget).await.unwrap();
+        assert_eq!(usage.job.pages, 2);
+        assert_eq!(usage.job.bytes, 1_200);
+        assert_eq!(usage.job.exhausted, Some(BudgetExhausted::MaxBytes(1_000)));
+        assert_eq!(usage.domains[&host].exhausted, Some(BudgetExhausted::MaxPages(2)));
+        assert_eq!(usage.skipped_pages, 1);
+
+        let mut conn = frontier.conn.clone();
+        let _: () = conn.del(UrlFrontier::job_key(job_id)).await.unwrap();
+    }
+}
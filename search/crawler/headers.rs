@@ -135,6 +135,11 @@ impl HeaderManager {
     pub fn set_accept_language(&mut self, language: String) {
         self.accept_language = language;
     }
+
+    /// Replace the User-Agent product (contact info is still appended)
+    pub fn set_user_agent(&mut self, user_agent: String) {
+        self.user_agent = user_agent;
+    }
 }
 
 impl Default for HeaderManager {
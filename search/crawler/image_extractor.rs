@@ -30,6 +30,15 @@ pub struct ImageData {
 pub struct ImageExtractor;
 
 impl ImageExtractor {
+    /// Deterministic image ID, keyed by the page it appears on and the image URL
+    ///
+    /// Like document IDs this is a UUID v5, so re-extracting an image from the
+    /// same page overwrites the existing entry.
+    pub fn image_id(source_url: &str, image_url: &str) -> String {
+        let key = format!("{}\n{}", source_url, image_url);
+        Uuid::new_v5(&Uuid::NAMESPACE_URL, key.as_bytes()).to_string()
+    }
+
     /// Extract all valid images from HTML
     pub fn extract_images(
         html: &str,
@@ -122,7 +131,7 @@ impl ImageExtractor {
                 .and_then(|srcset| Self::parse_srcset(srcset, &base_url));
 
             let image_data = ImageData {
-                id: Uuid::new_v4().to_string(),
+                id: Self::image_id(source_url, &image_url),
                 image_url: image_url.clone(),
                 source_url: source_url.to_string(),
                 alt_text,
@@ -217,7 +226,7 @@ impl ImageExtractor {
             .and_then(|h| h.parse::<u32>().ok());
 
         Some(ImageData {
-            id: Uuid::new_v4().to_string(),
+            id: Self::image_id(source_url, &image_url),
             image_url,
             source_url: source_url.to_string(),
             alt_text,
@@ -336,6 +345,23 @@ mod tests {
         assert_eq!(images[0].height, Some(600));
     }
 
+    #[test]
+    fn test_image_ids_are_stable() {
+        let html = r#"<html><body><img src="/photo.jpg" alt="A photo"></body></html>"#;
+
+        let first = ImageExtractor::extract_images(html, "https://example.com/page", "Page", "Content").unwrap();
+        let second = ImageExtractor::extract_images(html, "https://example.com/page", "Page", "Content").unwrap();
+        assert_eq!(first[0].id, second[0].id);
+        assert_eq!(
+            first[0].id,
+            ImageExtractor::image_id("https://example.com/page", "https://example.com/photo.jpg")
+        );
+
+        // The same image on another page is a separate entry
+        let other = ImageExtractor::extract_images(html, "https://example.com/other", "Page", "Content").unwrap();
+        assert_ne!(first[0].id, other[0].id);
+    }
+
     #[test]
     fn test_filter_small_images() {
         let html = r#"
@@ -0,0 +1,182 @@
+use scraper::{Html, Selector};
+
+/// Maximum number of characters fed to the statistical detector
+const MAX_DETECTION_CHARS: usize = 2000;
+
+/// Minimum detector confidence for overriding a declared language
+const MIN_OVERRIDE_CONFIDENCE: f64 = 0.9;
+
+/// Language signals gathered for a page
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LanguageHints {
+    /// `<html lang="...">`
+    pub html_lang: Option<String>,
+    /// `Content-Language` response header or its `<meta http-equiv>` equivalent
+    pub content_language: Option<String>,
+}
+
+impl LanguageHints {
+    /// Collect hints from the parsed page and the response header, if any
+    pub fn from_document(document: &Html, content_language: Option<&str>) -> Self {
+        let html_lang = Selector::parse("html[lang]")
+            .ok()
+            .and_then(|selector| {
+                document
+                    .select(&selector)
+                    .next()
+                    .and_then(|html| html.value().attr("lang"))
+                    .map(|lang| lang.to_string())
+            });
+
+        let meta_language = Selector::parse("meta[http-equiv]").ok().and_then(|selector| {
+            document
+                .select(&selector)
+                .find(|meta| {
+                    meta.value()
+                        .attr("http-equiv")
+                        .is_some_and(|name| name.eq_ignore_ascii_case("content-language"))
+                })
+                .and_then(|meta| meta.value().attr("content"))
+                .map(|lang| lang.to_string())
+        });
+
+        Self {
+            html_lang,
+            content_language: content_language.map(|lang| lang.to_string()).or(meta_language),
+        }
+    }
+
+    /// The declared language, preferring `<html lang>` over `Content-Language`
+    pub fn declared(&self) -> Option<String> {
+        self.html_lang
+            .as_deref()
+            .and_then(normalize_tag)
+            .or_else(|| self.content_language.as_deref().and_then(normalize_tag))
+    }
+}
+
+/// Reduce a BCP 47 tag such as `fr-CA` to its ISO 639-1 primary subtag
+///
+/// `Content-Language` may list several languages; a list is ambiguous and
+/// yields `None`.
+pub fn normalize_tag(tag: &str) -> Option<String> {
+    let tag = tag.trim();
+    if tag.is_empty() || tag.contains(',') {
+        return None;
+    }
+
+    let primary = tag.split(['-', '_']).next()?.to_ascii_lowercase();
+    match primary.len() {
+        2 if primary.chars().all(|c| c.is_ascii_alphabetic()) => Some(primary),
+        // Some sites declare three-letter codes
+        3 => isolang::Language::from_639_3(&primary)
+            .and_then(|language| language.to_639_1())
+            .map(|code| code.to_string()),
+        _ => None,
+    }
+}
+
+/// Detect the language of a text, as an ISO 639-1 code and a confidence
+pub fn detect_text(text: &str) -> Option<(String, f64)> {
+    let sample: String = text.chars().take(MAX_DETECTION_CHARS).collect();
+    let info = whatlang::detect(&sample)?;
+
+    let code = isolang::Language::from_639_3(info.lang().code())?.to_639_1()?;
+    Some((code.to_string(), info.confidence()))
+}
+
+/// Decide the language of a page
+///
+/// Declared languages are trusted unless the text detector is confident it is
+/// something else: many sites ship a template-wide `lang="en"` regardless of
+/// the language of the content.
+pub fn detect_language(hints: &LanguageHints, text: &str) -> Option<String> {
+    let declared = hints.declared();
+    let detected = detect_text(text);
+
+    match (declared, detected) {
+        (Some(declared), Some((detected, confidence))) => {
+            if detected != declared && confidence >= MIN_OVERRIDE_CONFIDENCE {
+                Some(detected)
+            } else {
+                Some(declared)
+            }
+        }
+        (Some(declared), None) => Some(declared),
+        (None, Some((detected, _))) => Some(detected),
+        (None, None) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENGLISH: &str = "The quick brown fox jumps over the lazy dog while the farmer \
+        watches from the porch and wonders whether the weather will hold until the harvest \
+        is finished later this week.";
+    const FRENCH: &str = "Le gouvernement a annoncé hier une nouvelle série de mesures \
+        destinées à soutenir les petites entreprises qui ont souffert de la hausse des prix \
+        de l'énergie pendant tout l'hiver dernier.";
+    const ARABIC: &str = "أعلنت الحكومة أمس عن سلسلة جديدة من الإجراءات لدعم الشركات \
+        الصغيرة التي عانت من ارتفاع أسعار الطاقة طوال فصل الشتاء الماضي في جميع أنحاء البلاد.";
+
+    fn hints(html: &str, header: Option<&str>) -> LanguageHints {
+        LanguageHints::from_document(&Html::parse_document(html), header)
+    }
+
+    #[test]
+    fn test_normalize_tag() {
+        assert_eq!(normalize_tag("fr-CA"), Some("fr".to_string()));
+        assert_eq!(normalize_tag("EN_us"), Some("en".to_string()));
+        assert_eq!(normalize_tag(" ar "), Some("ar".to_string()));
+        assert_eq!(normalize_tag("fra"), Some("fr".to_string()));
+        assert_eq!(normalize_tag("en, fr"), None);
+        assert_eq!(normalize_tag(""), None);
+        assert_eq!(normalize_tag("x"), None);
+    }
+
+    #[test]
+    fn test_hints_from_document() {
+        let h = hints(r#"<html lang="fr-FR"><head></head><body></body></html>"#, Some("en"));
+        assert_eq!(h.html_lang.as_deref(), Some("fr-FR"));
+        assert_eq!(h.declared(), Some("fr".to_string()));
+
+        let h = hints(
+            r#"<html><head><meta http-equiv="Content-Language" content="ar"></head></html>"#,
+            None,
+        );
+        assert_eq!(h.declared(), Some("ar".to_string()));
+
+        // The response header wins over the meta equivalent
+        let h = hints(
+            r#"<html><head><meta http-equiv="content-language" content="ar"></head></html>"#,
+            Some("fr"),
+        );
+        assert_eq!(h.declared(), Some("fr".to_string()));
+    }
+
+    #[test]
+    fn test_detect_text() {
+        assert_eq!(detect_text(ENGLISH).map(|(code, _)| code), Some("en".to_string()));
+        assert_eq!(detect_text(FRENCH).map(|(code, _)| code), Some("fr".to_string()));
+        assert_eq!(detect_text(ARABIC).map(|(code, _)| code), Some("ar".to_string()));
+    }
+
+    #[test]
+    fn test_detect_language() {
+        // Declared and detected agree
+        let h = hints(r#"<html lang="fr"></html>"#, None);
+        assert_eq!(detect_language(&h, FRENCH), Some("fr".to_string()));
+
+        // Template-wide lang="en" on an Arabic article
+        let h = hints(r#"<html lang="en"></html>"#, None);
+        assert_eq!(detect_language(&h, ARABIC), Some("ar".to_string()));
+
+        // Nothing declared: fall back to the detector
+        assert_eq!(detect_language(&LanguageHints::default(), FRENCH), Some("fr".to_string()));
+
+        // Nothing to go on
+        assert_eq!(detect_language(&LanguageHints::default(), ""), None);
+    }
+}
@@ -0,0 +1,447 @@
+use anyhow::Result;
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::collections::{HashMap, HashSet};
+use url::Url;
+
+use super::util::{collapse_whitespace, host_of};
+
+/// Longest anchor text kept per link
+const MAX_ANCHOR_CHARS: usize = 200;
+
+/// Most distinct inbound anchors indexed per page
+pub const MAX_INBOUND_ANCHORS: usize = 20;
+
+/// Total length budget of the inbound anchors indexed per page
+const MAX_INBOUND_ANCHOR_CHARS: usize = 2000;
+
+/// Anchors that say nothing about the target
+const GENERIC_ANCHORS: &[&str] = &[
+    "click here", "here", "link", "this link", "more", "read more", "learn more", "continue reading",
+    "see more", "details", "website", "home", "next", "previous", "source", "via",
+];
+
+/// `rel` values that mean the link does not vouch for its target
+const NOFOLLOW_RELS: &[&str] = &["nofollow", "ugc", "sponsored"];
+
+/// An outgoing link found on a page
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PageLink {
+    pub target_url: String,
+    pub anchor_text: Option<String>,
+    pub nofollow: bool,
+}
+
+/// Extract outgoing HTTP(S) links from a page
+///
+/// Relative links are resolved against `<base href>` or the page URL, and
+/// fragments are dropped. Several links to the same target collapse into
+/// one: the first non-empty anchor text wins, and the link only counts as
+/// nofollow if every occurrence is.
+pub fn extract_links(document: &Html, page_url: &str) -> Vec<PageLink> {
+    let Ok(page) = Url::parse(page_url) else {
+        return Vec::new();
+    };
+
+    let base = Selector::parse("base[href]")
+        .ok()
+        .and_then(|selector| document.select(&selector).next())
+        .and_then(|base| base.value().attr("href"))
+        .and_then(|href| page.join(href).ok())
+        .unwrap_or_else(|| page.clone());
+
+    // <meta name="robots" content="nofollow"> applies to every link on the page
+    let page_nofollow = Selector::parse(r#"meta[name="robots" i]"#)
+        .ok()
+        .map(|selector| {
+            document.select(&selector).any(|meta| {
+                meta.value()
+                    .attr("content")
+                    .is_some_and(|content| robots_directives(content).any(|d| d == "nofollow" || d == "none"))
+            })
+        })
+        .unwrap_or(false);
+
+    let Ok(selector) = Selector::parse("a[href], area[href]") else {
+        return Vec::new();
+    };
+    let Ok(img_selector) = Selector::parse("img[alt]") else {
+        return Vec::new();
+    };
+
+    let mut links: Vec<PageLink> = Vec::new();
+
+    for anchor in document.select(&selector) {
+        let Some(href) = anchor.value().attr("href").map(str::trim) else {
+            continue;
+        };
+        if href.is_empty() || href.starts_with('#') {
+            continue;
+        }
+
+        let Ok(mut target) = base.join(href) else {
+            continue;
+        };
+        if !matches!(target.scheme(), "http" | "https") {
+            continue;
+        }
+        target.set_fragment(None);
+
+        let mut page_without_fragment = page.clone();
+        page_without_fragment.set_fragment(None);
+        if target == page_without_fragment {
+            continue;
+        }
+
+        let nofollow = page_nofollow
+            || anchor.value().attr("rel").is_some_and(|rel| {
+                rel.split_whitespace()
+                    .any(|value| NOFOLLOW_RELS.contains(&value.to_ascii_lowercase().as_str()))
+            });
+
+        // Image links are described by their alt text, then the title attribute
+        let text = collapse_whitespace(&anchor.text().collect::<String>());
+        let anchor_text = Some(text)
+            .filter(|text| !text.is_empty())
+            .or_else(|| {
+                anchor
+                    .select(&img_selector)
+                    .filter_map(|img| img.value().attr("alt"))
+                    .map(collapse_whitespace)
+                    .find(|alt| !alt.is_empty())
+            })
+            .or_else(|| anchor.value().attr("title").map(collapse_whitespace).filter(|t| !t.is_empty()))
+            .map(|text| truncate_chars(&text, MAX_ANCHOR_CHARS));
+
+        let target_url = target.to_string();
+        match links.iter_mut().find(|link| link.target_url == target_url) {
+            Some(existing) => {
+                existing.nofollow &= nofollow;
+                if existing.anchor_text.is_none() {
+                    existing.anchor_text = anchor_text;
+                }
+            }
+            None => links.push(PageLink {
+                target_url,
+                anchor_text,
+                nofollow,
+            }),
+        }
+    }
+
+    links
+}
+
+/// Split a robots meta `content` value into lowercase directives
+pub(crate) fn robots_directives(content: &str) -> impl Iterator<Item = String> + '_ {
+    content
+        .split(',')
+        .map(|directive| directive.trim().to_ascii_lowercase())
+        .filter(|directive| !directive.is_empty())
+}
+
+/// Aggregate the inbound anchor texts of one page
+///
+/// `anchors` must be ordered by preference (most linking sources first).
+/// Anchors are deduplicated case-insensitively, generic ones ("click here",
+/// bare URLs) are dropped, and the result is capped both in count and in
+/// total length.
+pub fn aggregate_anchors<'a>(anchors: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    let mut aggregated = Vec::new();
+    let mut total_chars = 0;
+
+    for anchor in anchors {
+        let anchor = collapse_whitespace(anchor);
+        let key = anchor.to_lowercase();
+        if anchor.is_empty() || is_generic_anchor(&key) || !seen.insert(key) {
+            continue;
+        }
+
+        let length = anchor.chars().count();
+        if total_chars + length > MAX_INBOUND_ANCHOR_CHARS {
+            break;
+        }
+        total_chars += length;
+        aggregated.push(anchor);
+
+        if aggregated.len() >= MAX_INBOUND_ANCHORS {
+            break;
+        }
+    }
+
+    aggregated
+}
+
+fn is_generic_anchor(lowercase: &str) -> bool {
+    let trimmed = lowercase.trim_matches(|c: char| !c.is_alphanumeric());
+    trimmed.is_empty()
+        || GENERIC_ANCHORS.contains(&trimmed)
+        || lowercase.starts_with("http://")
+        || lowercase.starts_with("https://")
+        || lowercase.starts_with("www.")
+}
+
+fn truncate_chars(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => text[..end].trim_end().to_string(),
+        None => text.to_string(),
+    }
+}
+
+/// Repository for the crawled link graph
+#[derive(Clone)]
+pub struct LinkRepository {
+    pool: PgPool,
+}
+
+impl LinkRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Replace the outgoing links of a page with the ones just crawled
+    ///
+    /// Links that disappeared from the page are removed; links still present
+    /// keep their `first_seen_at`.
+    pub async fn replace_outlinks(&self, source_url: &str, links: &[PageLink]) -> Result<()> {
+        let targets: Vec<String> = links.iter().map(|link| link.target_url.clone()).collect();
+        let target_domains: Vec<String> = targets.iter().map(|url| host_of(url).unwrap_or_default()).collect();
+        let anchors: Vec<Option<String>> = links.iter().map(|link| link.anchor_text.clone()).collect();
+        let nofollows: Vec<bool> = links.iter().map(|link| link.nofollow).collect();
+
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM page_links WHERE source_url = $1 AND NOT (target_url = ANY($2))")
+            .bind(source_url)
+            .bind(&targets)
+            .execute(&mut *tx)
+            .await?;
+
+        if !links.is_empty() {
+            sqlx::query(
+                r#"
+                INSERT INTO page_links (source_url, target_url, source_domain, target_domain, anchor_text, nofollow)
+                SELECT $1, t.target_url, $2, t.target_domain, t.anchor_text, t.nofollow
+                FROM UNNEST($3::text[], $4::text[], $5::text[], $6::bool[])
+                    AS t(target_url, target_domain, anchor_text, nofollow)
+                ON CONFLICT (source_url, target_url) DO UPDATE SET
+                    anchor_text = EXCLUDED.anchor_text,
+                    nofollow = EXCLUDED.nofollow,
+                    last_seen_at = NOW()
+                "#,
+            )
+            .bind(source_url)
+            .bind(host_of(source_url).unwrap_or_default())
+            .bind(&targets)
+            .bind(&target_domains)
+            .bind(&anchors)
+            .bind(&nofollows)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Aggregated inbound anchor texts for the given target URLs
+    ///
+    /// Only followed links count, and each anchor is weighted by the number
+    /// of distinct domains using it, so one site repeating a link in its
+    /// navigation can't dominate a page's description.
+    pub async fn inbound_anchors(&self, target_urls: &[String]) -> Result<HashMap<String, Vec<String>>> {
+        let rows = sqlx::query_as::<_, (String, String)>(
+            r#"
+            SELECT target_url, MIN(anchor_text)
+            FROM page_links
+            WHERE target_url = ANY($1) AND NOT nofollow AND anchor_text IS NOT NULL
+            GROUP BY target_url, LOWER(anchor_text)
+            ORDER BY target_url, COUNT(DISTINCT source_domain) DESC, COUNT(*) DESC
+            "#,
+        )
+        .bind(target_urls)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(group_anchors(rows))
+    }
+
+    /// Aggregated inbound anchor texts for every page crawled with content that has any
+    pub async fn indexed_inbound_anchors(&self) -> Result<HashMap<String, Vec<String>>> {
+        let rows = sqlx::query_as::<_, (String, String)>(
+            r#"
+            SELECT l.target_url, MIN(l.anchor_text)
+            FROM page_links l
+            JOIN page_states s ON s.url = l.target_url
+            WHERE s.content_hash IS NOT NULL AND NOT l.nofollow AND l.anchor_text IS NOT NULL
+            GROUP BY l.target_url, LOWER(l.anchor_text)
+            ORDER BY l.target_url, COUNT(DISTINCT l.source_domain) DESC, COUNT(*) DESC
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(group_anchors(rows))
+    }
+
+    /// All followed (not nofollow) page-to-page edges
+    pub async fn followed_edges(&self) -> Result<Vec<(String, String)>> {
+        let edges = sqlx::query_as::<_, (String, String)>(
+            "SELECT source_url, target_url FROM page_links WHERE NOT nofollow",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(edges)
+    }
+
+    /// Distinct followed edges between different domains
+    pub async fn followed_domain_edges(&self) -> Result<Vec<(String, String)>> {
+        let edges = sqlx::query_as::<_, (String, String)>(
+            r#"
+            SELECT DISTINCT source_domain, target_domain
+            FROM page_links
+            WHERE NOT nofollow AND source_domain <> target_domain
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(edges)
+    }
+}
+
+/// Group `(target_url, anchor)` rows, already in preference order, per target
+fn group_anchors(rows: Vec<(String, String)>) -> HashMap<String, Vec<String>> {
+    let mut grouped: HashMap<String, Vec<String>> = HashMap::new();
+    for (target_url, anchor) in rows {
+        grouped.entry(target_url).or_default().push(anchor);
+    }
+
+    grouped
+        .into_iter()
+        .map(|(target_url, anchors)| (target_url, aggregate_anchors(anchors.iter().map(String::as_str))))
+        .filter(|(_, anchors)| !anchors.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn links(html: &str) -> Vec<PageLink> {
+        extract_links(&Html::parse_document(html), "https://example.com/blog/post")
+    }
+
+    #[test]
+    fn test_extract_links() {
+        let found = links(
+            r##"<body>
+                <a href="/about">About us</a>
+                <a href="other#section">Other   post</a>
+                <a href="https://partner.org/" rel="sponsored noopener">Partner</a>
+                <a href="mailto:me@example.com">Mail</a>
+                <a href="#top">Top</a>
+                <a href="post">Self</a>
+                <a href="https://cdn.example.com/"><img src="logo.png" alt="CDN logo"></a>
+            </body>"##,
+        );
+
+        assert_eq!(
+            found,
+            vec![
+                PageLink {
+                    target_url: "https://example.com/about".to_string(),
+                    anchor_text: Some("About us".to_string()),
+                    nofollow: false,
+                },
+                PageLink {
+                    target_url: "https://example.com/blog/other".to_string(),
+                    anchor_text: Some("Other post".to_string()),
+                    nofollow: false,
+                },
+                PageLink {
+                    target_url: "https://partner.org/".to_string(),
+                    anchor_text: Some("Partner".to_string()),
+                    nofollow: true,
+                },
+                PageLink {
+                    target_url: "https://cdn.example.com/".to_string(),
+                    anchor_text: Some("CDN logo".to_string()),
+                    nofollow: false,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_duplicate_links_are_merged() {
+        let found = links(
+            r#"<a href="/docs" rel="nofollow"><img src="x.png"></a>
+               <a href="/docs">Documentation</a>"#,
+        );
+
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].anchor_text.as_deref(), Some("Documentation"));
+        assert!(!found[0].nofollow);
+    }
+
+    #[test]
+    fn test_base_href_and_meta_nofollow() {
+        let found = links(
+            r#"<head><base href="https://static.example.com/docs/"><meta name="robots" content="noindex, nofollow"></head>
+               <body><a href="intro">Intro</a></body>"#,
+        );
+
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].target_url, "https://static.example.com/docs/intro");
+        assert!(found[0].nofollow);
+    }
+
+    #[test]
+    fn test_aggregate_anchors() {
+        let anchors = aggregate_anchors([
+            "Rust  Book",
+            "rust book",
+            "click here",
+            "Read more »",
+            "https://doc.rust-lang.org/book/",
+            "The Rust Programming Language",
+        ]);
+        assert_eq!(anchors, vec!["Rust Book", "The Rust Programming Language"]);
+
+        let many: Vec<String> = (0..50).map(|i| format!("anchor {}", i)).collect();
+        let capped = aggregate_anchors(many.iter().map(String::as_str));
+        assert_eq!(capped.len(), MAX_INBOUND_ANCHORS);
+        assert_eq!(capped[0], "anchor 0");
+
+        let long = "x".repeat(MAX_ANCHOR_CHARS);
+        let long_anchors: Vec<String> = (0..15).map(|i| format!("{}{}", long, i)).collect();
+        let total: usize = aggregate_anchors(long_anchors.iter().map(String::as_str))
+            .iter()
+            .map(|anchor| anchor.chars().count())
+            .sum();
+        assert!(total <= MAX_INBOUND_ANCHOR_CHARS);
+    }
+
+    #[test]
+    fn test_group_anchors() {
+        let grouped = group_anchors(vec![
+            ("https://a.com/".to_string(), "Alpha".to_string()),
+            ("https://b.com/".to_string(), "here".to_string()),
+            ("https://a.com/".to_string(), "alpha".to_string()),
+            ("https://a.com/".to_string(), "First letter".to_string()),
+        ]);
+
+        assert_eq!(grouped.len(), 1);
+        assert_eq!(grouped["https://a.com/"], vec!["Alpha", "First letter"]);
+    }
+
+    #[test]
+    fn test_truncate_chars() {
+        assert_eq!(truncate_chars("héllo world", 5), "héllo");
+        assert_eq!(truncate_chars("short", 10), "short");
+    }
+}
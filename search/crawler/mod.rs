@@ -1,34 +1,96 @@
+mod budget;
 mod circuit_breaker;
+mod content_extractor;
+mod errors;
+mod feeds;
+mod filter_rules;
 mod filters;
+mod fingerprint;
+mod frontier;
 mod headers;
 mod image_extractor;
+mod language;
+mod links;
+mod options;
+mod page_state;
+mod parsers;
 mod politeness;
 mod rate_limiter;
+mod replay;
 mod retry;
 mod robots;
+mod robots_meta;
 mod scheduler;
+mod sitemap;
+mod structured_data;
 mod url_processor;
+mod util;
+mod warc;
 
+pub use budget::{BudgetExhausted, BudgetTracker, BudgetUsage, CancellationToken, CrawlBudget, CrawlSession, SessionUsage};
 pub use circuit_breaker::{CircuitBreakerManager, CircuitBreakerStats, CircuitState, DomainCircuitStats};
+pub use content_extractor::ContentExtractor;
+pub use errors::{
+    CrawlErrorCount, CrawlErrorKind, CrawlErrorQuery, CrawlErrorRepository, CrawlErrorSummary, CrawlFailure,
+    DomainErrors,
+};
+pub use feeds::{
+    discover_feed_links, parse_feed, DiscoveredFeed, FeedEntry, FeedFetch, FeedFormat, FeedManager, ParsedFeed,
+    COMMON_FEED_PATHS,
+};
+pub use filter_rules::{
+    spawn_filter_rule_listener, CreateFilterRuleRequest, FilterDecision, FilterRule, FilterRuleKind,
+    FilterRuleRepository, FilterRuleSet, FilterRules, UpdateFilterRuleRequest,
+};
 pub use filters::{ContentFilter, FilterStats};
+pub use fingerprint::{FingerprintEntry, FingerprintRepository, FingerprintStats, FingerprintStore};
+pub use frontier::{url_priority, FetchPermit, FrontierBudgetUsage, FrontierConfig, FrontierEntry, UrlFrontier};
 pub use headers::HeaderManager;
 pub use image_extractor::{ImageData, ImageExtractor};
-pub use politeness::{PolitenessManager, PolitenessStats};
-pub use rate_limiter::{RateLimiter, RateLimiterStats};
-pub use retry::{RetryConfig, RetryPolicy, RetryStats};
+pub use language::{detect_language, normalize_tag as normalize_language_tag, LanguageHints};
+pub use links::{aggregate_anchors, extract_links, LinkRepository, PageLink, MAX_INBOUND_ANCHORS};
+pub use options::{CrawlOptions, CrawlScope};
+pub use page_state::{content_hash, PageState, PageStateRepository, PageValidators};
+pub use parsers::{
+    base_content_type, detect_content_type, is_html_content_type, DocumentParser, MarkdownParser,
+    ParsedDocument, ParserRegistry, PdfParser, PlainTextParser, HTML_CONTENT_TYPE,
+};
+pub use politeness::{DomainDelay, PolitenessManager, PolitenessStats, RequestOutcome};
+pub use rate_limiter::{RateLimiter, RateLimiterStats, RequestGate};
+pub use replay::{ReplayPage, ReplaySource};
+pub use retry::{is_overload_status, parse_retry_after, RetryConfig, RetryPolicy, RetryStats};
 pub use robots::{RobotsManager, RobotsStats};
+pub use robots_meta::RobotsDirectives;
 pub use scheduler::{CrawlFrequency, CrawlScheduler, ScheduledCrawl, SchedulerStats};
+pub use sitemap::{ChangeFreq, ParsedSitemap, SitemapEntry, SitemapManager, SitemapStats};
+pub use structured_data::StructuredData;
 pub use url_processor::UrlProcessor;
+pub use warc::{ArchivedResponse, HttpExchange, WarcReader, WarcRecord, WarcWriter};
+pub(crate) use util::host_of;
 
 use anyhow::Result;
 use chrono::Utc;
+use redis::aio::ConnectionManager;
 use scraper::{Html, Selector};
 use serde::{Deserialize, Serialize};
-use spider::website::Website;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::time::Instant;
 use tracing::{debug, info, warn};
 use url::Url;
-use uuid::Uuid;
+
+/// Largest response body downloaded while crawling (larger ones fail the page)
+const MAX_PAGE_BYTES: usize = 10 * 1024 * 1024;
+
+/// Times a page answered with 429/503 is queued again before giving up
+const MAX_OVERLOAD_REQUEUES: u32 = 3;
+
+/// Wait before retrying an overloaded page that sent no `Retry-After`
+const OVERLOAD_REQUEUE_DELAY: Duration = Duration::from_secs(30);
+
+/// Longest wait before retrying an overloaded page
+const MAX_OVERLOAD_REQUEUE_DELAY: Duration = Duration::from_secs(5 * 60);
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CrawlJob {
@@ -67,6 +129,52 @@ pub struct CrawledDocument {
     // Phase 9: Favicon URL
     #[serde(skip_serializing_if = "Option::is_none")]
     pub favicon_url: Option<String>,
+    // Near-duplicate detection: ID of the representative document
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duplicate_of: Option<String>,
+    // SimHash fingerprint, registered with the fingerprint store once the page is indexed
+    #[serde(skip)]
+    pub simhash: Option<u64>,
+    // ISO 639-1 language code, used as a search facet
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lang: Option<String>,
+    // JSON-LD / microdata / OpenGraph fields, stored as top-level attributes
+    #[serde(flatten)]
+    pub structured: StructuredData,
+    // MIME type of the source document (text/html, application/pdf, ...)
+    #[serde(default = "default_content_type")]
+    pub content_type: String,
+    // Link authority bucket (0-10) from the PageRank job, used as a ranking rule
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authority: Option<u8>,
+    // Aggregated anchor texts of links pointing at this page
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub anchor_text: Option<Vec<String>>,
+    // Robots meta / X-Robots-Tag directives; only the snippet settings are indexed
+    #[serde(flatten)]
+    pub robots: RobotsDirectives,
+    // Outgoing links, persisted to the link graph rather than the search index
+    #[serde(skip)]
+    pub links: Vec<PageLink>,
+}
+
+fn default_content_type() -> String {
+    HTML_CONTENT_TYPE.to_string()
+}
+
+/// Result of a conditional single-page fetch
+#[derive(Debug, Clone)]
+pub enum FetchOutcome {
+    /// Server answered 304, the stored copy is still current
+    NotModified { validators: PageValidators },
+    /// Page was downloaded (document is `None` if nothing indexable was extracted)
+    Fetched {
+        document: Option<CrawledDocument>,
+        images: Vec<ImageData>,
+        validators: PageValidators,
+        /// Size of the response body
+        bytes: u64,
+    },
 }
 
 #[derive(Debug, Clone)]
@@ -79,6 +187,17 @@ pub struct CrawlerConfig {
     pub requests_per_second: u32,
     pub min_delay_ms: u64,
     pub max_retries: u32,
+    // Sitemap discovery: feed sitemap entries into the crawl scheduler
+    pub discover_sitemaps: bool,
+    // RSS/Atom discovery: collect feeds for the feed poll loop
+    pub discover_feeds: bool,
+    // Write every fetched response to <warc_dir>/<job id>.warc.gz (disabled if None)
+    pub warc_dir: Option<PathBuf>,
+    // Default limits per domain within a job (jobs may override them)
+    pub domain_budget: CrawlBudget,
+    // Hosts links are followed to, relative to each seed URL
+    pub scope: CrawlScope,
+    pub extract_images: bool,
 }
 
 impl Default for CrawlerConfig {
@@ -91,6 +210,12 @@ impl Default for CrawlerConfig {
             requests_per_second: 2,
             min_delay_ms: 1000,
             max_retries: 3,
+            discover_sitemaps: true,
+            discover_feeds: true,
+            warc_dir: None,
+            domain_budget: CrawlBudget::default(),
+            scope: CrawlScope::default(),
+            extract_images: true,
         }
     }
 }
@@ -103,10 +228,17 @@ pub struct Crawler {
     headers: HeaderManager,
     robots: RobotsManager,
     filters: ContentFilter,
+    filter_rules: FilterRules,
     url_processor: UrlProcessor,
+    // Normalizes document URLs (and so document IDs); unaffected by job options
+    document_urls: UrlProcessor,
     retry_policy: RetryPolicy,
     circuit_breaker: CircuitBreakerManager,
     scheduler: CrawlScheduler,
+    sitemaps: SitemapManager,
+    feeds: FeedManager,
+    fingerprints: FingerprintStore,
+    parsers: ParserRegistry,
 }
 
 impl Crawler {
@@ -126,6 +258,8 @@ impl Crawler {
         let retry_policy = RetryPolicy::new();
         let circuit_breaker = CircuitBreakerManager::default();
         let scheduler = CrawlScheduler::new();
+        let sitemaps = SitemapManager::new("EngineSearchBot/1.0".to_string());
+        let feeds = FeedManager::new("EngineSearchBot/1.0".to_string());
 
         Self {
             config,
@@ -134,11 +268,18 @@ impl Crawler {
             headers,
             robots,
             filters,
+            filter_rules: FilterRules::default(),
             url_processor,
+            document_urls: UrlProcessor::default(),
             retry_policy,
             circuit_breaker,
             scheduler,
+            sitemaps,
+            feeds,
+            fingerprints: FingerprintStore::default(),
+            parsers: ParserRegistry::default(),
         }
+        .with_gates()
     }
 
     pub fn with_config(config: CrawlerConfig) -> Self {
@@ -151,6 +292,8 @@ impl Crawler {
         let retry_policy = RetryPolicy::new();
         let circuit_breaker = CircuitBreakerManager::default();
         let scheduler = CrawlScheduler::new();
+        let sitemaps = SitemapManager::new("EngineSearchBot/1.0".to_string());
+        let feeds = FeedManager::new("EngineSearchBot/1.0".to_string());
 
         Self {
             config,
@@ -159,11 +302,18 @@ impl Crawler {
             headers,
             robots,
             filters,
+            filter_rules: FilterRules::default(),
             url_processor,
+            document_urls: UrlProcessor::default(),
             retry_policy,
             circuit_breaker,
             scheduler,
+            sitemaps,
+            feeds,
+            fingerprints: FingerprintStore::default(),
+            parsers: ParserRegistry::default(),
         }
+        .with_gates()
     }
 
     /// Create crawler with full configuration including headers
@@ -177,6 +327,8 @@ impl Crawler {
         let rate_limiter = RateLimiter::new(config.requests_per_second, config.min_delay_ms);
         let politeness = PolitenessManager::new(config.min_delay_ms, config.max_retries);
         let robots = RobotsManager::new(user_agent.clone());
+        let sitemaps = SitemapManager::new(user_agent.clone());
+        let feeds = FeedManager::new(user_agent.clone());
         let headers = HeaderManager::with_config(user_agent, contact_email, bot_url, accept_language);
         let filters = ContentFilter::default();
         let url_processor = UrlProcessor::default();
@@ -191,87 +343,166 @@ impl Crawler {
             headers,
             robots,
             filters,
+            filter_rules: FilterRules::default(),
             url_processor,
+            document_urls: UrlProcessor::default(),
             retry_policy,
             circuit_breaker,
             scheduler,
+            sitemaps,
+            feeds,
+            fingerprints: FingerprintStore::default(),
+            parsers: ParserRegistry::default(),
         }
+        .with_gates()
     }
 
-    pub async fn crawl_urls(&self, urls: Vec<String>) -> Result<(Vec<CrawledDocument>, Vec<ImageData>)> {
+    /// Share rate limits, politeness delays and circuit breakers with other
+    /// workers through Redis
+    ///
+    /// Without it every worker paces its requests and tracks domain health on
+    /// its own, so N workers hit a domain N times as often as configured.
+    pub fn with_redis(mut self, conn: ConnectionManager) -> Self {
+        self.rate_limiter = self.rate_limiter.with_redis(conn.clone());
+        self.politeness = self.politeness.with_redis(conn.clone());
+        self.circuit_breaker = self.circuit_breaker.with_redis(conn);
+        self.with_gates()
+    }
+
+    /// Send robots.txt, sitemap and feed requests through this crawler's
+    /// rate limiter and politeness delays
+    fn with_gates(mut self) -> Self {
+        let gate = self.request_gate();
+        self.robots = self.robots.with_gate(gate.clone());
+        self.sitemaps = self.sitemaps.with_gate(gate.clone());
+        self.feeds = self.feeds.with_gate(gate);
+        self
+    }
+
+    /// Rate limiter and politeness delay every request waits for
+    fn request_gate(&self) -> RequestGate {
+        RequestGate::new(self.rate_limiter.clone(), self.politeness.clone())
+    }
+
+    /// Open the WARC archive of a crawl job, if archiving is enabled
+    pub async fn open_archive(&self, job_id: &str) -> Result<Option<WarcWriter>> {
+        match &self.config.warc_dir {
+            Some(directory) => {
+                let software = self.headers.user_agent_string();
+                Ok(Some(WarcWriter::create(directory, job_id, &software).await?))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// A copy of this crawler with a job's depth and options applied
+    ///
+    /// Politeness, circuit breakers, fingerprints and the robots cache stay
+    /// shared with this crawler. A job with its own request rate gets its own
+    /// rate limiter. A job's user agent is only sent with page requests:
+    /// robots.txt is still matched against our own product token, and query
+    /// options only change which URLs are fetched, not document URLs and IDs.
+    pub fn for_job(&self, max_depth: usize, options: &CrawlOptions) -> Result<Self> {
+        options.validate()?;
+
+        let mut crawler = self.clone();
+        crawler.config.max_depth = max_depth;
+        crawler.config.scope = options.scope;
+        if let Some(extract_images) = options.extract_images {
+            crawler.config.extract_images = extract_images;
+        }
+
+        for pattern in &options.include_patterns {
+            crawler.filters.add_url_include_pattern(pattern)?;
+        }
+        for pattern in &options.exclude_patterns {
+            crawler.filters.add_url_exclude_pattern(pattern)?;
+        }
+
+        for param in &options.strip_query_params {
+            crawler.url_processor.add_remove_param(param.clone());
+        }
+        if options.strip_all_query_params {
+            crawler.url_processor.set_remove_all_params(true);
+        }
+
+        if let Some(requests_per_second) = options.requests_per_second {
+            crawler.config.requests_per_second = requests_per_second;
+            crawler.rate_limiter = self.rate_limiter.with_rate(requests_per_second);
+        }
+
+        if let Some(user_agent) = &options.user_agent {
+            crawler.headers.set_user_agent(user_agent.clone());
+        }
+
+        Ok(crawler)
+    }
+
+    /// Default per-domain budget for jobs that don't set their own
+    pub fn domain_budget(&self) -> &CrawlBudget {
+        &self.config.domain_budget
+    }
+
+    /// Crawl URLs and the pages they link to, up to `max_depth`
+    ///
+    /// Stops early once the session's job or domain budget runs out or the
+    /// session is cancelled, keeping the pages fetched so far. With an archive
+    /// in the session, every fetched response is also written to it. URLs that
+    /// fail are recorded in the session.
+    pub async fn crawl_urls(
+        &self,
+        urls: Vec<String>,
+        session: &mut CrawlSession,
+    ) -> Result<(Vec<CrawledDocument>, Vec<ImageData>)> {
         let mut all_documents = Vec::new();
         let mut all_images = Vec::new();
         let mut seen_urls = HashSet::new();
 
         for url in urls {
+            if session.is_cancelled() {
+                info!("Crawl cancelled, skipping {}", url);
+                break;
+            }
             info!("Starting crawl for: {}", url);
 
-            // Normalize URL (Phase 6.5)
-            let normalized_url = match self.url_processor.normalize(&url) {
-                Ok(u) => u,
-                Err(e) => {
-                    warn!("Invalid URL {}: {}", url, e);
+            let (normalized_url, domain) = match self.admit_url(&url).await {
+                Ok(admitted) => admitted,
+                Err(failure) => {
+                    session.record_failure(failure);
                     continue;
                 }
             };
 
-            // Extract domain for circuit breaker (Phase 6.6)
-            let domain = match Url::parse(&normalized_url) {
-                Ok(parsed) => parsed.host_str().unwrap_or("").to_string(),
-                Err(e) => {
-                    warn!("Failed to parse URL {}: {}", normalized_url, e);
-                    continue;
-                }
-            };
-
-            // Check circuit breaker (Phase 6.6)
-            if !self.circuit_breaker.can_proceed(&domain) {
-                warn!("Circuit breaker is open for domain: {}", domain);
-                continue;
-            }
-
-            // Check content filters (Phase 6.4)
-            if !self.filters.is_url_allowed(&normalized_url) {
-                warn!("URL filtered out: {}", normalized_url);
+            if let Some(reason) = session.domain_exhausted(&domain) {
+                info!("Skipping {}: {}", normalized_url, reason);
                 continue;
             }
 
-            // Check robots.txt (Phase 6.1)
-            match self.robots.is_allowed(&normalized_url).await {
-                Ok(false) => {
-                    warn!("URL blocked by robots.txt: {}", normalized_url);
-                    continue;
-                }
-                Err(e) => {
-                    warn!("Error checking robots.txt for {}: {}", normalized_url, e);
-                    // Continue on error (permissive approach)
-                }
-                Ok(true) => {} // Allowed, continue
-            }
-
-            // Apply crawl delay from robots.txt
-            if let Ok(parsed_url) = Url::parse(&normalized_url) {
-                if let Some(domain) = parsed_url.host_str() {
-                    if let Some(delay) = self.robots.get_crawl_delay(domain).await {
-                        info!("Applying robots.txt crawl delay for {}: {}s", domain, delay);
-                        self.politeness.set_crawl_delay(domain, delay);
+            // Feed sitemap entries into the scheduler so pages beyond max_depth get
+            // discovered; large sitemaps take a while, so the crawl doesn't wait
+            if self.config.discover_sitemaps && self.sitemaps.should_ingest(&domain) {
+                // Mark first so the next seed of the domain doesn't start another ingestion
+                self.sitemaps.mark_ingested(&domain);
+                let crawler = self.clone();
+                let domain = domain.clone();
+                tokio::spawn(async move {
+                    match crawler.schedule_from_sitemaps(&domain).await {
+                        Ok(added) => {
+                            if added > 0 {
+                                info!("Scheduled {} new URLs from sitemaps of {}", added, domain);
+                            }
+                        }
+                        Err(e) => {
+                            warn!("Sitemap ingestion failed for {}: {}", domain, e);
+                        }
                     }
-                }
+                });
             }
 
-            // Apply rate limiting and politeness
-            if let Err(e) = self.wait_for_request(&normalized_url).await {
-                warn!("Rate limiting error for {}: {}", normalized_url, e);
-                continue;
-            }
-
-            match self.crawl_single_url(&normalized_url).await {
+            match self.crawl_single_url(&normalized_url, &domain, session).await {
                 Ok((documents, images)) => {
                     info!("Successfully crawled {} pages and {} images from {}", documents.len(), images.len(), normalized_url);
 
-                    // Record success in circuit breaker (Phase 6.6)
-                    self.circuit_breaker.record_success(&domain);
-
                     // Deduplicate documents using normalized URLs (Phase 6.5)
                     for doc in documents {
                         let normalized_doc_url = self.url_processor.normalize(&doc.url)
@@ -286,11 +517,24 @@ impl Crawler {
 
                     // Collect all images
                     all_images.extend(images);
+
+                    // Sites whose pages advertise no feed may still serve one at a
+                    // common path; probing takes several requests, so the crawl doesn't wait
+                    if self.config.discover_feeds && self.feeds.should_probe(&domain) {
+                        // Mark first so the next seed of the domain doesn't start another probe
+                        self.feeds.mark_probed(&domain);
+                        let crawler = self.clone();
+                        let domain = domain.clone();
+                        tokio::spawn(async move {
+                            if let Some(feed_url) = crawler.probe_feeds(&domain).await {
+                                info!("Found feed {} on {}", feed_url, domain);
+                            }
+                        });
+                    }
                 }
                 Err(e) => {
                     warn!("Failed to crawl {}: {}", normalized_url, e);
-                    // Record failure in circuit breaker (Phase 6.6)
-                    self.circuit_breaker.record_failure(&domain);
+                    session.record_failure(CrawlFailure::from_error(&normalized_url, &e));
                 }
             }
         }
@@ -303,71 +547,602 @@ impl Crawler {
         Ok((all_documents, all_images))
     }
 
-    async fn crawl_single_url(&self, url: &str) -> Result<(Vec<CrawledDocument>, Vec<ImageData>)> {
-        let mut website = Website::new(url);
+    /// Run the per-URL checks shared by all fetch paths
+    ///
+    /// Normalizes the URL and checks the circuit breaker, content filters and
+    /// robots.txt (applying its crawl delay). Returns the normalized URL and its
+    /// domain, or why the URL must not be fetched.
+    async fn admit_url(&self, url: &str) -> Result<(String, String), CrawlFailure> {
+        // Normalize URL (Phase 6.5)
+        let normalized_url = match self.url_processor.normalize(url) {
+            Ok(u) => u,
+            Err(e) => {
+                warn!("Invalid URL {}: {}", url, e);
+                return Err(CrawlFailure::new(url, CrawlErrorKind::ParseError, format!("Invalid URL: {}", e)));
+            }
+        };
 
-        // Configure spider settings with professional headers
-        let user_agent = self.headers.user_agent_string();
-        website
-            .with_respect_robots_txt(self.config.respect_robots_txt)
-            .with_depth(self.config.max_depth)
-            .with_user_agent(Some(user_agent.as_str().into()))
-            .with_budget(None);
+        // Extract domain for circuit breaker (Phase 6.6)
+        let domain = match Url::parse(&normalized_url) {
+            Ok(parsed) => parsed.host_str().unwrap_or("").to_string(),
+            Err(e) => {
+                warn!("Failed to parse URL {}: {}", normalized_url, e);
+                return Err(CrawlFailure::new(url, CrawlErrorKind::ParseError, format!("Invalid URL: {}", e)));
+            }
+        };
 
-        // Start crawling and subscribe to pages
-        let mut rx = website.subscribe(0).unwrap();
+        // Check circuit breaker (Phase 6.6)
+        if !self.circuit_breaker.can_proceed(&domain).await {
+            warn!("Circuit breaker is open for domain: {}", domain);
+            return Err(CrawlFailure::new(
+                &normalized_url,
+                CrawlErrorKind::CircuitOpen,
+                format!("Circuit breaker is open for {}", domain),
+            ));
+        }
 
-        // Start the crawl in the background
-        let handle = tokio::spawn(async move {
-            website.crawl().await;
-        });
+        // Check admin filter rules and content filters (Phase 6.4)
+        let decision = self.check_url(&normalized_url);
+        if !decision.allowed {
+            warn!("URL filtered out: {} ({})", normalized_url, decision.reason);
+            return Err(CrawlFailure::new(&normalized_url, CrawlErrorKind::Filtered, decision.reason));
+        }
+
+        // Check robots.txt (Phase 6.1)
+        match self.robots.is_allowed(&normalized_url).await {
+            Ok(false) => {
+                warn!("URL blocked by robots.txt: {}", normalized_url);
+                return Err(CrawlFailure::new(
+                    &normalized_url,
+                    CrawlErrorKind::RobotsBlocked,
+                    "Disallowed by robots.txt",
+                ));
+            }
+            Err(e) => {
+                warn!("Error checking robots.txt for {}: {}", normalized_url, e);
+                // Continue on error (permissive approach)
+            }
+            Ok(true) => {} // Allowed, continue
+        }
+
+        // Apply crawl delay from robots.txt
+        if let Some(delay) = self.robots.get_crawl_delay(&domain).await {
+            info!("Applying robots.txt crawl delay for {}: {}s", domain, delay);
+            self.politeness.set_crawl_delay(&domain, delay);
+        }
+
+        Ok((normalized_url, domain))
+    }
+
+    /// Fetch a single page with a conditional request (used for recrawls)
+    ///
+    /// Sends `If-None-Match` / `If-Modified-Since` from the stored validators and
+    /// returns `FetchOutcome::NotModified` on a 304. Unlike `crawl_urls`, links
+    /// are not followed. Rejections, error statuses and unprocessable content
+    /// fail with a `CrawlFailure` (see `CrawlFailure::from_error`). With an
+    /// archive, the response (including a 304) is written to it unless the
+    /// page is marked noarchive.
+    pub async fn fetch_page(
+        &self,
+        url: &str,
+        validators: &PageValidators,
+        archive: Option<&WarcWriter>,
+    ) -> Result<FetchOutcome> {
+        let (normalized_url, domain) = self.admit_url(url).await?;
+
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(30))
+            .build()?;
+
+        let headers = self.request_headers();
+        let gate = self.request_gate();
+
+        // Every attempt waits for the rate limiter and politeness delay
+        let result = self
+            .retry_policy
+            .execute_http(&normalized_url, || {
+                let mut request = client.get(&normalized_url);
+                for (name, value) in &headers {
+                    request = request.header(name.as_str(), value.as_str());
+                }
+                gate.send(&normalized_url, validators.apply(request))
+            })
+            .await;
+
+        let response = match result {
+            Ok(response) => response,
+            Err(e) => {
+                self.circuit_breaker.record_failure(&domain).await;
+                return Err(e);
+            }
+        };
+
+        let status = response.status();
+        let received = PageValidators::from_headers(response.headers());
+        let fetched_at = Utc::now();
+        let header_robots = RobotsDirectives::from_headers(
+            response
+                .headers()
+                .get_all("x-robots-tag")
+                .iter()
+                .filter_map(|value| value.to_str().ok()),
+            &self.robots_token(),
+        );
+
+        if status == reqwest::StatusCode::NOT_MODIFIED {
+            if let Some(archive) = archive.filter(|_| !header_robots.noarchive) {
+                let request_headers = [headers.clone(), validators.request_headers()].concat();
+                let exchange = HttpExchange {
+                    url: &normalized_url,
+                    request_headers,
+                    status: status.as_u16(),
+                    response_headers: header_pairs(response.headers()),
+                    body: &[],
+                    fetched_at,
+                    metadata: Vec::new(),
+                };
+                if let Err(e) = archive.write_exchange(&exchange).await {
+                    warn!("Failed to archive {}: {}", normalized_url, e);
+                }
+            }
+            self.circuit_breaker.record_success(&domain).await;
+            debug!("Not modified: {}", normalized_url);
+            return Ok(FetchOutcome::NotModified {
+                validators: validators.merge(&received),
+            });
+        }
+
+        if !status.is_success() {
+            if status.is_server_error() {
+                self.circuit_breaker.record_failure(&domain).await;
+            }
+            return Err(CrawlFailure::http_status(&normalized_url, status.as_u16()).into());
+        }
+
+        self.circuit_breaker.record_success(&domain).await;
+
+        let header = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|value: &reqwest::header::HeaderValue| value.to_str().ok())
+                .map(|value| value.to_string())
+        };
+        let declared_type = header(reqwest::header::CONTENT_TYPE);
+        let content_language = header(reqwest::header::CONTENT_LANGUAGE);
+        let parse_failure =
+            |e: anyhow::Error| CrawlFailure::new(&normalized_url, CrawlErrorKind::ParseError, format!("{:#}", e));
+
+        let response_headers = header_pairs(response.headers());
+        let body = read_body_limited(response, MAX_PAGE_BYTES).await?;
+
+        // Processed before archiving, so meta robots noarchive is known
+        let result = self
+            .process_body(
+                &normalized_url,
+                declared_type.as_deref(),
+                &body,
+                content_language.as_deref(),
+                &header_robots,
+            )
+            .await;
+
+        let noarchive = match &result {
+            Ok((Some(doc), _)) => doc.robots.noarchive,
+            _ => header_robots.noarchive,
+        };
+        if let Some(archive) = archive.filter(|_| !noarchive) {
+            let request_headers = [headers.clone(), validators.request_headers()].concat();
+            let exchange = HttpExchange {
+                url: &normalized_url,
+                request_headers,
+                status: status.as_u16(),
+                response_headers,
+                body: &body,
+                fetched_at,
+                metadata: Vec::new(),
+            };
+            if let Err(e) = archive.write_exchange(&exchange).await {
+                warn!("Failed to archive {}: {}", normalized_url, e);
+            }
+        }
+
+        let (document, images) = result.map_err(parse_failure)?;
+
+        Ok(FetchOutcome::Fetched {
+            document,
+            images,
+            validators: received,
+            bytes: body.len() as u64,
+        })
+    }
+
+    /// Process a response body whose type is only known from its header or
+    /// its bytes: HTML is decoded with its declared charset and goes through
+    /// the page pipeline, other supported formats through the parsers
+    async fn process_body(
+        &self,
+        url: &str,
+        declared_type: Option<&str>,
+        body: &[u8],
+        content_language: Option<&str>,
+        header_robots: &RobotsDirectives,
+    ) -> Result<(Option<CrawledDocument>, Vec<ImageData>)> {
+        let content_type = detect_content_type(declared_type, url, body);
+
+        if is_html_content_type(&content_type) {
+            let html = decode_text(body, declared_type);
+            self.process_html(url, &html, content_language, header_robots)
+        } else if self.accepts_document(&content_type, body.len()) {
+            let document = self
+                .process_document(url, &content_type, body, content_language, header_robots)
+                .await?;
+            Ok((document, Vec::new()))
+        } else {
+            debug!("Skipping unsupported {} response from {}", content_type, url);
+            Ok((None, Vec::new()))
+        }
+    }
+
+    /// Run a stored page (WARC response or saved file) through the same
+    /// extraction pipeline as a live fetch, without touching the network
+    pub async fn replay_page(&self, page: &ReplayPage) -> Result<(Option<CrawledDocument>, Vec<ImageData>)> {
+        let header_robots = RobotsDirectives::from_headers(
+            page.robots_tags.iter().map(String::as_str),
+            &self.robots_token(),
+        );
+        self.process_body(
+            &page.url,
+            page.content_type.as_deref(),
+            &page.body,
+            page.content_language.as_deref(),
+            &header_robots,
+        )
+        .await
+    }
+
+    /// Replay every page of a source, skipping (and logging) pages that fail
+    pub async fn replay(&self, source: &ReplaySource) -> Result<(Vec<CrawledDocument>, Vec<ImageData>)> {
+        let mut documents = Vec::new();
+        let mut images = Vec::new();
+        let mut seen_ids = HashSet::new();
+
+        for page in source.pages()? {
+            let page = match page {
+                Ok(page) => page,
+                Err(e) => {
+                    warn!("Failed to read replay page: {}", e);
+                    continue;
+                }
+            };
+
+            match self.replay_page(&page).await {
+                Ok((Some(doc), _)) if doc.robots.noindex => {
+                    debug!("Skipped replayed page (noindex): {}", page.url)
+                }
+                Ok((Some(doc), page_images)) => {
+                    // Archives may hold several captures of a URL; the first one wins
+                    if seen_ids.insert(doc.id.clone()) {
+                        images.extend(page_images);
+                        documents.push(doc);
+                    }
+                }
+                Ok((None, _)) => debug!("Skipped replayed page (empty content): {}", page.url),
+                Err(e) => warn!("Failed to process replayed page {}: {}", page.url, e),
+            }
+        }
+
+        info!("Replayed {} documents and {} images", documents.len(), images.len());
+        Ok((documents, images))
+    }
+
+    /// Process an HTML page and extract its images
+    fn process_html(
+        &self,
+        url: &str,
+        html: &str,
+        content_language: Option<&str>,
+        header_robots: &RobotsDirectives,
+    ) -> Result<(Option<CrawledDocument>, Vec<ImageData>)> {
+        let document = self.process_page(url, html, content_language, header_robots)?;
+        let images = match &document {
+            Some(doc) if self.config.extract_images && !doc.robots.noindex => {
+                self.extract_page_images(html, url, doc)
+            }
+            _ => Vec::new(),
+        };
+        Ok((document, images))
+    }
+
+    /// Extract images from a processed page, logging (not failing) on errors
+    ///
+    /// Relative image URLs are resolved against the fetched URL, but images are
+    /// attributed to (and keyed by) the document's canonical URL.
+    fn extract_page_images(&self, html: &str, page_url: &str, doc: &CrawledDocument) -> Vec<ImageData> {
+        match ImageExtractor::extract_images(html, page_url, &doc.title, &doc.content) {
+            Ok(mut page_images) => {
+                if !page_images.is_empty() {
+                    debug!("Extracted {} images from {}", page_images.len(), page_url);
+                }
+                if doc.url != page_url {
+                    for image in &mut page_images {
+                        image.id = ImageExtractor::image_id(&doc.url, &image.image_url);
+                        image.source_url = doc.url.clone();
+                    }
+                }
+                page_images
+            }
+            Err(e) => {
+                warn!("Failed to extract images from {}: {}", page_url, e);
+                Vec::new()
+            }
+        }
+    }
+
+    /// Crawl a seed URL and the pages it links to, breadth first up to `max_depth`
+    ///
+    /// Every URL but the (already admitted) seed goes through `admit_url`
+    /// before it is requested, and every request waits for the rate limiter
+    /// and politeness delay, so filtered or disallowed URLs are never fetched
+    /// and their links never followed. Redirects are not followed blindly:
+    /// their targets are queued and checked like links. Pages answered with
+    /// 429/503 are queued again to be retried once the host had a break.
+    async fn crawl_single_url(
+        &self,
+        url: &str,
+        domain: &str,
+        session: &mut CrawlSession,
+    ) -> Result<(Vec<CrawledDocument>, Vec<ImageData>)> {
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(30))
+            .redirect(reqwest::redirect::Policy::none())
+            .build()?;
+        let request_headers = self.request_headers();
+        let gate = self.request_gate();
+        let cancellation = session.cancellation().clone();
+
+        // (URL, depth, already admitted, not before)
+        let mut queue = VecDeque::from([(url.to_string(), 0, true, None)]);
+        let mut seen = HashSet::from([url.to_string()]);
+        // Times each URL was answered with 429/503
+        let mut overloaded: HashMap<String, u32> = HashMap::new();
 
         let mut documents = Vec::new();
         let mut images = Vec::new();
         let mut page_count = 0;
+        let mut stopped = None;
+
+        while let Some((page_url, depth, admitted, not_before)) = queue.pop_front() {
+            if cancellation.is_cancelled() {
+                stopped = Some("job stopped".to_string());
+                break;
+            }
+            if let Some(not_before) = not_before {
+                tokio::select! {
+                    _ = tokio::time::sleep_until(not_before) => {}
+                    _ = cancellation.cancelled() => {
+                        stopped = Some("job stopped".to_string());
+                        break;
+                    }
+                }
+            }
+            if let Some(reason) = session.domain_exhausted(domain) {
+                stopped = Some(reason.to_string());
+                break;
+            }
 
-        // Collect pages as they arrive
-        while let Ok(page) = rx.recv().await {
+            let page_url = if admitted {
+                page_url
+            } else {
+                match self.admit_url(&page_url).await {
+                    // The job's normalization may fold this URL onto one already queued
+                    Ok((normalized, _)) if normalized == page_url || seen.insert(normalized.clone()) => normalized,
+                    Ok(_) => continue,
+                    Err(failure) => {
+                        debug!("Skipping {}: {}", page_url, failure);
+                        session.record_failure(failure);
+                        continue;
+                    }
+                }
+            };
+
+            let Ok(parsed_url) = Url::parse(&page_url) else {
+                continue;
+            };
+            let path = parsed_url.path().to_string();
+            if !session.admits(domain, &path) {
+                debug!("Skipping {}: path budget used up", page_url);
+                continue;
+            }
+
+            // Every attempt waits for the rate limiter and politeness delay
+            let request = self.retry_policy.execute_http(&page_url, || {
+                let mut request = client.get(&page_url);
+                for (name, value) in &request_headers {
+                    request = request.header(name.as_str(), value.as_str());
+                }
+                gate.send(&page_url, request)
+            });
+            let result = tokio::select! {
+                result = request => result,
+                _ = cancellation.cancelled() => {
+                    stopped = Some("job stopped".to_string());
+                    break;
+                }
+            };
+            let response = match result {
+                Ok(response) => response,
+                Err(e) => {
+                    warn!("Failed to fetch {}: {}", page_url, e);
+                    if let Some(host) = parsed_url.host_str() {
+                        self.circuit_breaker.record_failure(host).await;
+                    }
+                    session.record_failure(CrawlFailure::from_error(&page_url, &e));
+                    continue;
+                }
+            };
+
+            let fetched_at = Utc::now();
+            let status = response.status();
+            let host = parsed_url.host_str().unwrap_or(domain).to_string();
+
+            // Each page reports to its host's circuit; only server errors count against it
+            if status.is_server_error() {
+                self.circuit_breaker.record_failure(&host).await;
+            } else {
+                self.circuit_breaker.record_success(&host).await;
+            }
+            let header = |name: reqwest::header::HeaderName| {
+                response
+                    .headers()
+                    .get(name)
+                    .and_then(|value| value.to_str().ok())
+                    .map(|value| value.to_string())
+            };
+            let declared_type = header(reqwest::header::CONTENT_TYPE);
+            let content_language = header(reqwest::header::CONTENT_LANGUAGE);
+            let retry_after = header(reqwest::header::RETRY_AFTER);
+            let location = header(reqwest::header::LOCATION);
+            let header_robots = RobotsDirectives::from_headers(
+                response
+                    .headers()
+                    .get_all("x-robots-tag")
+                    .iter()
+                    .filter_map(|value| value.to_str().ok()),
+                &self.robots_token(),
+            );
+            let response_headers = header_pairs(response.headers());
+
+            let body = match read_body_limited(response, MAX_PAGE_BYTES).await {
+                Ok(body) => body,
+                Err(e) => {
+                    session.record_failure(CrawlFailure::from_error(&page_url, &e));
+                    continue;
+                }
+            };
+            session.record(domain, &path, body.len() as u64);
             page_count += 1;
-            let page_url = page.get_url();
             debug!("Processing page {}: {}", page_count, page_url);
 
-            let html = page.get_html();
-
-            // Process page content
-            match self.process_page(page_url, &html) {
-                Ok(Some(doc)) => {
-                    // Extract images from this page
-                    match ImageExtractor::extract_images(
-                        &html,
-                        page_url,
-                        &doc.title,
-                        &doc.content,
-                    ) {
-                        Ok(page_images) => {
-                            if !page_images.is_empty() {
-                                debug!("Extracted {} images from {}", page_images.len(), page_url);
-                                images.extend(page_images);
+            let content_type = detect_content_type(declared_type.as_deref(), &page_url, &body);
+            let supported = is_html_content_type(&content_type) || self.accepts_document(&content_type, body.len());
+
+            // Processed before archiving, so meta robots noarchive is known
+            let result = if status.is_success() && supported {
+                Some(
+                    self.process_body(
+                        &page_url,
+                        declared_type.as_deref(),
+                        &body,
+                        content_language.as_deref(),
+                        &header_robots,
+                    )
+                    .await,
+                )
+            } else {
+                None
+            };
+
+            let noarchive = match &result {
+                Some(Ok((Some(doc), _))) => doc.robots.noarchive,
+                _ => header_robots.noarchive,
+            };
+            if let Some(archive) = session.archive().filter(|_| !noarchive) {
+                let exchange = HttpExchange {
+                    url: &page_url,
+                    request_headers: request_headers.clone(),
+                    status: status.as_u16(),
+                    response_headers,
+                    body: &body,
+                    fetched_at,
+                    metadata: vec![("seedUrl".to_string(), url.to_string())],
+                };
+                if let Err(e) = archive.write_exchange(&exchange).await {
+                    warn!("Failed to archive {}: {}", page_url, e);
+                }
+            }
+
+            // The gate already slowed the host down; try the page again later
+            if let Some(RequestOutcome::Overload(retry_after)) =
+                RequestOutcome::from_status(status.as_u16(), retry_after.as_deref())
+            {
+                let attempts = overloaded.entry(page_url.clone()).or_insert(0);
+                *attempts += 1;
+                if *attempts > MAX_OVERLOAD_REQUEUES {
+                    session.record_failure(CrawlFailure::http_status(&page_url, status.as_u16()));
+                } else {
+                    let delay = retry_after.unwrap_or(OVERLOAD_REQUEUE_DELAY).min(MAX_OVERLOAD_REQUEUE_DELAY);
+                    debug!("{} is overloaded, retrying {} in {:?}", host, page_url, delay);
+                    queue.push_back((page_url, depth, true, Some(Instant::now() + delay)));
+                }
+                continue;
+            }
+
+            if status.is_redirection() {
+                let target = location
+                    .and_then(|location| parsed_url.join(&location).ok())
+                    .filter(|target| target.host_str().is_some_and(|host| self.config.scope.contains(domain, host)))
+                    .map(|target| self.url_processor.normalize(target.as_str()).unwrap_or_else(|_| target.to_string()));
+                match target {
+                    Some(target) if seen.insert(target.clone()) => {
+                        debug!("Following redirect from {} to {}", page_url, target);
+                        queue.push_front((target, depth, false, None));
+                    }
+                    _ => debug!("Not following redirect from {}", page_url),
+                }
+                continue;
+            }
+            if !status.is_success() {
+                debug!("Skipping {} page: {}", status, page_url);
+                session.record_failure(CrawlFailure::http_status(&page_url, status.as_u16()));
+                continue;
+            }
+
+            let Some(result) = result else {
+                debug!("Skipping unsupported {} page: {}", content_type, page_url);
+                session.record_failure(CrawlFailure::new(
+                    &page_url,
+                    CrawlErrorKind::Filtered,
+                    format!("Unsupported content type {}", content_type),
+                ));
+                continue;
+            };
+
+            match result {
+                Ok((Some(doc), page_images)) => {
+                    if depth < self.config.max_depth {
+                        for link in self.follow_links(&doc, domain) {
+                            if seen.insert(link.clone()) {
+                                queue.push_back((link, depth + 1, false, None));
                             }
                         }
-                        Err(e) => {
-                            warn!("Failed to extract images from {}: {}", page_url, e);
-                        }
                     }
 
+                    // noindex pages are returned too, so callers can drop them from the index
+                    if doc.robots.noindex {
+                        debug!("Page is noindex: {}", page_url);
+                    }
+                    images.extend(page_images);
                     documents.push(doc);
                 }
-                Ok(None) => {
+                Ok((None, _)) => {
                     debug!("Skipped page (empty content): {}", page_url);
+                    session.record_failure(CrawlFailure::new(
+                        &page_url,
+                        CrawlErrorKind::EmptyContent,
+                        "No indexable content",
+                    ));
                 }
                 Err(e) => {
                     warn!("Failed to process page {}: {}", page_url, e);
+                    session.record_failure(CrawlFailure::new(&page_url, CrawlErrorKind::ParseError, format!("{:#}", e)));
                 }
             }
         }
 
-        // Wait for crawl to complete
-        handle.await?;
+        if let Some(reason) = stopped {
+            info!("Stopped crawl of {} after {} pages: {}", url, page_count, reason);
+        }
 
         if documents.is_empty() {
             warn!("No valid documents extracted from {}", url);
@@ -378,13 +1153,29 @@ impl Crawler {
         Ok((documents, images))
     }
 
-    fn process_page(&self, url: &str, html: &str) -> Result<Option<CrawledDocument>> {
+    /// Build a document from an HTML page
+    ///
+    /// Pages marked noindex still yield a document (flagged in `robots`) so
+    /// their links can be followed; they are not indexed.
+    fn process_page(
+        &self,
+        url: &str,
+        html: &str,
+        content_language: Option<&str>,
+        header_robots: &RobotsDirectives,
+    ) -> Result<Option<CrawledDocument>> {
+        // Documents are identified by their canonical URL so recrawls overwrite them
+        let canonical_url = self
+            .document_urls
+            .canonical_url(url, html)
+            .unwrap_or_else(|_| url.to_string());
+
         let document = Html::parse_document(html);
 
         // Extract title
         let title = self
             .extract_title(&document)
-            .unwrap_or_else(|| url.to_string());
+            .unwrap_or_else(|| canonical_url.clone());
 
         // Extract meta description
         let description = self.extract_meta_description(&document);
@@ -395,6 +1186,9 @@ impl Crawler {
         // Phase 9: Extract favicon
         let favicon_url = self.extract_favicon(&document, url);
 
+        // Author, dates, price, rating, breadcrumbs, site name
+        let structured = StructuredData::extract(&document);
+
         // Extract main content
         let content = self.extract_content(&document)?;
 
@@ -405,14 +1199,51 @@ impl Crawler {
 
         let word_count = content.split_whitespace().count();
 
+        // Declared language, checked against the text itself
+        let lang_hints = LanguageHints::from_document(&document, content_language);
+        let lang = detect_language(&lang_hints, &format!("{} {}", title, content));
+
         // Phase 7.4: Extract domain for faceted search
-        let domain = Url::parse(url)
+        let domain = Url::parse(&canonical_url)
             .ok()
             .and_then(|parsed_url| parsed_url.domain().map(|d| d.to_string()));
 
+        let id = UrlProcessor::document_id(&canonical_url);
+
+        // Robots meta tags (generic and for our bot) on top of X-Robots-Tag
+        let mut robots = RobotsDirectives::from_meta(&document, &self.robots_token());
+        robots.merge(header_robots);
+
+        // Near-duplicates (mirrors, print views) are collapsed onto one representative,
+        // which must be an indexed page
+        let fingerprint = if robots.noindex {
+            None
+        } else {
+            self.fingerprints.check(&id, &canonical_url, &content)
+        };
+        let (simhash, duplicate_of) = match fingerprint {
+            Some(entry) => (Some(entry.simhash), entry.duplicate_of),
+            None => (None, None),
+        };
+
+        // Advertised feeds are polled for new posts (see `FeedPollLoop`)
+        if self.config.discover_feeds {
+            if let Some(host) = Url::parse(url).ok().and_then(|page| page.host_str().map(str::to_string)) {
+                self.feeds.add_discovered(&host, discover_feed_links(&document, url));
+            }
+        }
+
+        // Outgoing links for the link graph; a nofollow page vouches for none of them
+        let mut links = self.normalize_links(extract_links(&document, url), &canonical_url);
+        if robots.nofollow {
+            for link in &mut links {
+                link.nofollow = true;
+            }
+        }
+
         Ok(Some(CrawledDocument {
-            id: Uuid::new_v4().to_string(),
-            url: url.to_string(),
+            id,
+            url: canonical_url,
             title,
             content,
             description,
@@ -421,6 +1252,137 @@ impl Crawler {
             word_count,
             domain,
             favicon_url,
+            duplicate_of,
+            simhash,
+            lang,
+            structured,
+            content_type: HTML_CONTENT_TYPE.to_string(),
+            authority: None,
+            anchor_text: None,
+            robots,
+            links,
+        }))
+    }
+
+    /// Normalize link targets so they match the URLs documents are stored
+    /// under, dropping links back to the page itself
+    fn normalize_links(&self, links: Vec<PageLink>, page_url: &str) -> Vec<PageLink> {
+        let mut normalized: Vec<PageLink> = Vec::with_capacity(links.len());
+
+        for mut link in links {
+            if let Ok(target) = self.document_urls.normalize(&link.target_url) {
+                link.target_url = target;
+            }
+            if link.target_url == page_url {
+                continue;
+            }
+
+            match normalized.iter_mut().find(|existing| existing.target_url == link.target_url) {
+                Some(existing) => {
+                    existing.nofollow &= link.nofollow;
+                    if existing.anchor_text.is_none() {
+                        existing.anchor_text = link.anchor_text;
+                    }
+                }
+                None => normalized.push(link),
+            }
+        }
+
+        normalized
+    }
+
+    /// Check if a non-HTML response should be parsed: a parser exists and the
+    /// content filters allow its type and size
+    fn accepts_document(&self, content_type: &str, size: usize) -> bool {
+        self.parsers.supports(content_type)
+            && self.filters.is_content_type_allowed(content_type)
+            && self.filters.is_file_size_allowed(size)
+    }
+
+    /// Build a document from a non-HTML response (PDF, plain text, Markdown)
+    async fn process_document(
+        &self,
+        url: &str,
+        content_type: &str,
+        body: &[u8],
+        content_language: Option<&str>,
+        header_robots: &RobotsDirectives,
+    ) -> Result<Option<CrawledDocument>> {
+        let parsed = self.parsers.parse_blocking(content_type, body.to_vec()).await?;
+
+        let content = self.truncate_text(&parsed.content, self.config.max_content_length);
+        if content.len() < 50 {
+            return Ok(None);
+        }
+
+        let document_url = self.document_urls.normalize(url).unwrap_or_else(|_| url.to_string());
+
+        // Untitled files fall back to their file name
+        let title = parsed.title.clone().unwrap_or_else(|| {
+            Url::parse(&document_url)
+                .ok()
+                .and_then(|parsed_url| {
+                    parsed_url
+                        .path_segments()
+                        .and_then(|mut segments| segments.next_back().map(|s| s.to_string()))
+                })
+                .filter(|name| !name.is_empty())
+                .unwrap_or_else(|| document_url.clone())
+        });
+
+        let word_count = content.split_whitespace().count();
+        let domain = Url::parse(&document_url)
+            .ok()
+            .and_then(|parsed_url| parsed_url.domain().map(|d| d.to_string()));
+
+        let lang_hints = LanguageHints {
+            html_lang: parsed.lang.clone(),
+            content_language: content_language.map(|lang| lang.to_string()),
+        };
+        let lang = detect_language(&lang_hints, &format!("{} {}", title, content));
+
+        let mut structured = StructuredData {
+            author: parsed.author.clone(),
+            ..Default::default()
+        };
+        if let Some(published) = &parsed.published_at {
+            structured.set_published(published);
+        }
+        if let Some(modified) = &parsed.modified_at {
+            structured.set_modified(modified);
+        }
+
+        let id = UrlProcessor::document_id(&document_url);
+        let fingerprint = if header_robots.noindex {
+            None
+        } else {
+            self.fingerprints.check(&id, &document_url, &content)
+        };
+        let (simhash, duplicate_of) = match fingerprint {
+            Some(entry) => (Some(entry.simhash), entry.duplicate_of),
+            None => (None, None),
+        };
+
+        Ok(Some(CrawledDocument {
+            id,
+            url: document_url,
+            title,
+            content,
+            description: parsed.description,
+            keywords: parsed.keywords,
+            crawled_at: Utc::now().to_rfc3339(),
+            word_count,
+            domain,
+            favicon_url: None,
+            duplicate_of,
+            simhash,
+            lang,
+            structured,
+            content_type: content_type.to_string(),
+            authority: None,
+            anchor_text: None,
+            robots: header_robots.clone(),
+            links: Vec::new(),
         }))
     }
 
@@ -513,54 +1475,14 @@ impl Crawler {
     }
 
     fn extract_content(&self, document: &Html) -> Result<String> {
-        // Remove script and style elements
-        let mut html_string = document.html();
-
-        // Remove scripts
-        let script_selector = Selector::parse("script, style, noscript").unwrap();
-        for element in document.select(&script_selector) {
-            let element_html = element.html();
-            html_string = html_string.replace(&element_html, "");
-        }
-
-        // Parse cleaned HTML
-        let cleaned_doc = Html::parse_document(&html_string);
-
-        // Try to extract main content area first
-        let content_selectors = vec![
-            "main",
-            "article",
-            "[role='main']",
-            ".content",
-            "#content",
-            "body",
-        ];
-
-        for selector_str in content_selectors {
-            if let Ok(selector) = Selector::parse(selector_str) {
-                if let Some(element) = cleaned_doc.select(&selector).next() {
-                    let text = element.text().collect::<Vec<_>>().join(" ");
-                    let cleaned = self.clean_text(&text);
-
-                    if cleaned.len() > 100 {
-                        // Only use if substantial content
-                        return Ok(self.truncate_text(&cleaned, self.config.max_content_length));
-                    }
-                }
-            }
-        }
-
-        // Fallback: extract all text from body
-        if let Ok(body_selector) = Selector::parse("body") {
-            if let Some(body) = cleaned_doc.select(&body_selector).next() {
-                let text = body.text().collect::<Vec<_>>().join(" ");
-                let cleaned = self.clean_text(&text);
-                return Ok(self.truncate_text(&cleaned, self.config.max_content_length));
-            }
+        // Score DOM blocks and keep the main content, one block per paragraph
+        let content = ContentExtractor::extract(document);
+        if !content.is_empty() {
+            return Ok(self.truncate_text(&content, self.config.max_content_length));
         }
 
         // Last resort: use html2text
-        let text = html2text::from_read(html_string.as_bytes(), self.config.max_content_length);
+        let text = html2text::from_read(document.html().as_bytes(), self.config.max_content_length);
         Ok(self.clean_text(&text))
     }
 
@@ -587,24 +1509,35 @@ impl Crawler {
             return text.to_string();
         }
 
+        // Back off to a char boundary so multi-byte text can't split a character
+        let mut end = max_length;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+
         // Try to truncate at a word boundary
-        let truncated = &text[..max_length];
-        if let Some(last_space) = truncated.rfind(' ') {
+        let truncated = &text[..end];
+        if let Some(last_space) = truncated.rfind(char::is_whitespace) {
             truncated[..last_space].to_string()
         } else {
             truncated.to_string()
         }
     }
 
+    /// Headers sent with every page request
+    ///
+    /// Accept-Encoding is left to reqwest, which only advertises what it can decode.
+    fn request_headers(&self) -> Vec<(String, String)> {
+        self.headers
+            .build_headers(None)
+            .into_iter()
+            .filter(|(name, _)| name != "Accept-Encoding" && name != "Cache-Control")
+            .collect()
+    }
+
     /// Apply rate limiting and politeness before making a request
     pub async fn wait_for_request(&self, url: &str) -> Result<()> {
-        // Apply rate limiter (token bucket)
-        self.rate_limiter.wait_for(url).await?;
-
-        // Apply politeness delay
-        self.politeness.wait_before_request(url).await?;
-
-        Ok(())
+        self.request_gate().wait(url).await
     }
 
     /// Get rate limiter statistics
@@ -637,6 +1570,13 @@ impl Crawler {
         self.headers.user_agent_string()
     }
 
+    /// Our product token, matched against robots.txt groups and robots meta tags
+    ///
+    /// Taken from the robots manager, so a job's own user agent can't change it.
+    fn robots_token(&self) -> String {
+        self.robots.product_token()
+    }
+
     /// Get the content filter
     pub fn filters(&self) -> &ContentFilter {
         &self.filters
@@ -652,24 +1592,85 @@ impl Crawler {
         self.filters.stats()
     }
 
+    /// Admin-managed filter rules, shared by all clones of this crawler
+    pub fn filter_rules(&self) -> &FilterRules {
+        &self.filter_rules
+    }
+
+    /// Decide whether a URL may be crawled
+    ///
+    /// Admin filter rules are checked first, then the content filter (built-in
+    /// defaults and per-job patterns).
+    pub fn check_url(&self, url: &str) -> FilterDecision {
+        self.apply_content_filter(url, self.filter_rules.evaluate(url))
+    }
+
+    /// Decide whether a URL may be crawled under the given admin rules
+    ///
+    /// Same checks as `check_url`, for rules not (yet) loaded by this crawler.
+    pub fn check_url_with(&self, rules: &FilterRuleSet, url: &str) -> FilterDecision {
+        self.apply_content_filter(url, rules.evaluate(url))
+    }
+
+    fn apply_content_filter(&self, url: &str, decision: FilterDecision) -> FilterDecision {
+        if decision.allowed && !self.filters.is_url_allowed(url) {
+            return FilterDecision::denied(None, "Excluded by the crawler's content filter");
+        }
+        decision
+    }
+
+    /// Links of a page to crawl next
+    ///
+    /// Nothing is followed from nofollow pages or through nofollow links, and
+    /// only hosts in the crawl scope of `seed_host` that pass the filters are
+    /// kept. Targets are already normalized.
+    pub fn follow_links(&self, doc: &CrawledDocument, seed_host: &str) -> Vec<String> {
+        if doc.robots.nofollow {
+            return Vec::new();
+        }
+
+        doc.links
+            .iter()
+            .filter(|link| !link.nofollow)
+            .filter(|link| {
+                Url::parse(&link.target_url)
+                    .ok()
+                    .and_then(|url| url.host_str().map(|host| self.config.scope.contains(seed_host, host)))
+                    .unwrap_or(false)
+            })
+            .filter(|link| self.check_url(&link.target_url).allowed)
+            .map(|link| link.target_url.clone())
+            .collect()
+    }
+
+    /// Get the URL normalizer
+    pub fn url_processor(&self) -> &UrlProcessor {
+        &self.url_processor
+    }
+
     /// Get the retry policy
     pub fn retry_policy(&self) -> &RetryPolicy {
         &self.retry_policy
     }
 
+    /// Get the politeness manager
+    pub fn politeness(&self) -> &PolitenessManager {
+        &self.politeness
+    }
+
     /// Get the circuit breaker manager
     pub fn circuit_breaker(&self) -> &CircuitBreakerManager {
         &self.circuit_breaker
     }
 
     /// Get circuit breaker statistics
-    pub fn circuit_breaker_stats(&self) -> CircuitBreakerStats {
-        self.circuit_breaker.stats()
+    pub async fn circuit_breaker_stats(&self) -> CircuitBreakerStats {
+        self.circuit_breaker.stats().await
     }
 
     /// Manually reset circuit breaker for a domain
-    pub fn reset_circuit_breaker(&self, domain: &str) {
-        self.circuit_breaker.reset(domain);
+    pub async fn reset_circuit_breaker(&self, domain: &str) {
+        self.circuit_breaker.reset(domain).await;
     }
 
     /// Get the crawl scheduler
@@ -686,6 +1687,171 @@ impl Crawler {
     pub fn schedule_url(&self, url: String, frequency: CrawlFrequency, priority: u8) -> Result<()> {
         self.scheduler.schedule(url, frequency, priority)
     }
+
+    /// Ingest a domain's sitemaps and schedule every listed page
+    ///
+    /// Sitemap locations come from robots.txt, falling back to /sitemap.xml.
+    /// Returns the number of URLs newly added to the scheduler.
+    pub async fn schedule_from_sitemaps(&self, domain: &str) -> Result<usize> {
+        // Mark first so concurrent workers don't ingest the same sitemaps twice
+        self.sitemaps.mark_ingested(domain);
+
+        let mut sitemap_urls = self.robots.get_sitemaps(domain).await;
+        if sitemap_urls.is_empty() {
+            sitemap_urls.push(format!("https://{}/sitemap.xml", domain));
+        }
+
+        let entries = self.sitemaps.fetch_entries(&sitemap_urls).await;
+
+        let tasks: Vec<ScheduledCrawl> = entries
+            .iter()
+            .filter_map(|entry| {
+                let normalized = self.url_processor.normalize(&entry.loc).ok()?;
+
+                // Sitemaps may only list URLs on their own host
+                let host = Url::parse(&normalized).ok()?.host_str()?.to_lowercase();
+                if host != domain.to_lowercase() {
+                    debug!("Skipping off-host sitemap URL: {}", entry.loc);
+                    return None;
+                }
+
+                if !self.filters.is_url_allowed(&normalized) {
+                    return None;
+                }
+
+                let mut task = entry.to_scheduled_crawl();
+                task.url = normalized;
+                Some(task)
+            })
+            .collect();
+
+        let added = self.scheduler.merge_tasks(tasks);
+        Ok(added)
+    }
+
+    /// Look for a feed at the common feed paths of a domain
+    ///
+    /// Stops at the first path serving a valid RSS or Atom document and
+    /// records it for the feed poll loop.
+    pub async fn probe_feeds(&self, domain: &str) -> Option<String> {
+        // Mark first so concurrent workers don't probe the same domain twice
+        self.feeds.mark_probed(domain);
+
+        for path in COMMON_FEED_PATHS {
+            let url = format!("https://{}{}", domain, path);
+
+            match self.fetch_feed(&url, &PageValidators::default()).await {
+                Ok(FeedFetch::Fetched { .. }) => {
+                    self.feeds.add_discovered(domain, vec![url.clone()]);
+                    return Some(url);
+                }
+                Ok(FeedFetch::NotModified { .. }) => {}
+                Err(e) => debug!("No feed at {}: {}", url, e),
+            }
+        }
+
+        None
+    }
+
+    /// Fetch a feed politely: robots.txt, rate limits and politeness delays
+    /// apply as for pages
+    pub async fn fetch_feed(&self, url: &str, validators: &PageValidators) -> Result<FeedFetch> {
+        if !self.robots.is_allowed(url).await? {
+            anyhow::bail!("Disallowed by robots.txt");
+        }
+
+        self.feeds.fetch(url, validators).await
+    }
+
+    /// Get the feed manager (feeds discovered while crawling)
+    pub fn feeds(&self) -> &FeedManager {
+        &self.feeds
+    }
+
+    /// Get sitemap statistics
+    pub fn sitemap_stats(&self) -> SitemapStats {
+        self.sitemaps.stats()
+    }
+
+    /// Get the near-duplicate fingerprint store
+    pub fn fingerprints(&self) -> &FingerprintStore {
+        &self.fingerprints
+    }
+
+    /// Get the document parsers for non-HTML content
+    pub fn parsers(&self) -> &ParserRegistry {
+        &self.parsers
+    }
+
+    /// Get mutable reference to the document parsers, e.g. to register new formats
+    pub fn parsers_mut(&mut self) -> &mut ParserRegistry {
+        &mut self.parsers
+    }
+
+    /// Get fingerprint store statistics
+    pub fn fingerprint_stats(&self) -> FingerprintStats {
+        self.fingerprints.stats()
+    }
+}
+
+/// Response headers as name/value pairs, for the archive
+fn header_pairs(headers: &reqwest::header::HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .filter_map(|(name, value)| value.to_str().ok().map(|value| (name.to_string(), value.to_string())))
+        .collect()
+}
+
+/// Decode a text body with the charset of its content type (UTF-8 by default)
+fn decode_text(body: &[u8], content_type: Option<&str>) -> String {
+    let encoding = content_type
+        .and_then(|content_type| {
+            content_type
+                .split(';')
+                .filter_map(|param| param.trim().split_once('='))
+                .find(|(name, _)| name.trim().eq_ignore_ascii_case("charset"))
+                .map(|(_, charset)| charset.trim().trim_matches('"').to_string())
+        })
+        .and_then(|charset| encoding_rs::Encoding::for_label(charset.as_bytes()))
+        .unwrap_or(encoding_rs::UTF_8);
+
+    encoding.decode(body).0.into_owned()
+}
+
+/// Read a response body, failing once it grows past `max_bytes`
+///
+/// The body is streamed, so an oversized response is dropped without being
+/// buffered in full.
+pub(crate) async fn read_body_limited(mut response: reqwest::Response, max_bytes: usize) -> Result<Vec<u8>> {
+    if response.content_length().is_some_and(|length| length > max_bytes as u64) {
+        anyhow::bail!("response from {} exceeds {} bytes", response.url(), max_bytes);
+    }
+
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        if body.len() + chunk.len() > max_bytes {
+            anyhow::bail!("response from {} exceeds {} bytes", response.url(), max_bytes);
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body)
+}
+
+/// Read at most the first `max_bytes` of a response body
+///
+/// Streaming stops once the cap is reached; the rest of the body is never
+/// downloaded.
+pub(crate) async fn read_body_prefix(mut response: reqwest::Response, max_bytes: usize) -> Result<Vec<u8>> {
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        let remaining = max_bytes - body.len();
+        if chunk.len() >= remaining {
+            body.extend_from_slice(&chunk[..remaining]);
+            break;
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body)
 }
 
 #[cfg(test)]
@@ -700,6 +1866,13 @@ mod tests {
         assert_eq!(crawler.clean_text(text), "Hello World This is a test");
     }
 
+    #[test]
+    fn test_decode_text() {
+        assert_eq!(decode_text("café".as_bytes(), None), "café");
+        assert_eq!(decode_text(b"caf\xe9", Some("text/html; charset=ISO-8859-1")), "café");
+        assert_eq!(decode_text(b"caf\xe9", Some("text/html; Charset=\"windows-1252\"")), "café");
+    }
+
     #[test]
     fn test_truncate_text() {
         let crawler = Crawler::new(3, 10);
@@ -707,5 +1880,86 @@ mod tests {
         let text = "Hello World This is a test";
         assert_eq!(crawler.truncate_text(text, 15), "Hello World");
         assert_eq!(crawler.truncate_text(text, 100), text);
+
+        // Never splits a multi-byte character
+        assert_eq!(crawler.truncate_text("Le marché européen", 17), "Le marché");
+    }
+
+    #[tokio::test]
+    async fn test_replay_fixture_directory() {
+        let crawler = Crawler::new(3, 10);
+        let fixtures = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("search/crawler/fixtures");
+        let source = ReplaySource::from_path(&fixtures, Some("https://fixtures.test/")).unwrap();
+
+        let (documents, _images) = crawler.replay(&source).await.unwrap();
+        assert_eq!(documents.len(), 4);
+
+        let article = documents
+            .iter()
+            .find(|doc| doc.url == "https://fixtures.test/div_layout.html")
+            .expect("div_layout.html replayed under the base URL");
+        assert_eq!(article.title, "Les marchés européens en hausse");
+        assert_eq!(article.lang.as_deref(), Some("fr"));
+        assert_eq!(article.domain.as_deref(), Some("fixtures.test"));
+        assert!(article.content.contains("la banque centrale a maintenu ses taux"));
+    }
+
+    #[tokio::test]
+    async fn test_follow_links() {
+        let crawler = Crawler::new(3, 10);
+        let text = "Crawlers follow links from page to page to discover new content. ".repeat(20);
+        let html = format!(
+            r#"<html><head><title>Links</title></head><body><article><h1>Links</h1><p>{}</p>
+            <a href="/next">Next</a>
+            <a href="/sponsored" rel="nofollow">Sponsored</a>
+            <a href="https://other.test/page">Elsewhere</a>
+            </article></body></html>"#,
+            text
+        );
+        let page = ReplayPage {
+            url: "https://links.test/start".to_string(),
+            content_type: Some("text/html".to_string()),
+            content_language: None,
+            robots_tags: Vec::new(),
+            body: html.into_bytes(),
+        };
+
+        let (doc, _) = crawler.replay_page(&page).await.unwrap();
+        let mut doc = doc.expect("page has content");
+        assert_eq!(crawler.follow_links(&doc, "links.test"), vec!["https://links.test/next".to_string()]);
+
+        doc.robots.nofollow = true;
+        assert!(crawler.follow_links(&doc, "links.test").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_job_options_keep_robots_token_and_document_ids() {
+        let crawler = Crawler::new(3, 10);
+        let options = CrawlOptions {
+            strip_all_query_params: true,
+            user_agent: Some("PartnerBot/2.0".to_string()),
+            ..Default::default()
+        };
+        let job_crawler = crawler.for_job(3, &options).unwrap();
+        assert_eq!(job_crawler.robots_token(), crawler.robots_token());
+
+        let text = "Job options must not change which document a page is indexed as. ".repeat(20);
+        let page = ReplayPage {
+            url: "https://ids.test/list?page=2".to_string(),
+            content_type: Some("text/html".to_string()),
+            content_language: None,
+            robots_tags: Vec::new(),
+            body: format!(
+                "<html><head><title>List</title></head><body><article><h1>List</h1><p>{}</p></article></body></html>",
+                text
+            )
+            .into_bytes(),
+        };
+
+        let (doc, _) = crawler.replay_page(&page).await.unwrap();
+        let (job_doc, _) = job_crawler.replay_page(&page).await.unwrap();
+        let (doc, job_doc) = (doc.unwrap(), job_doc.unwrap());
+        assert_eq!(job_doc.url, doc.url);
+        assert_eq!(job_doc.id, doc.id);
     }
 }
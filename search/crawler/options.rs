@@ -0,0 +1,137 @@
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// Which hosts a crawl may follow links to, relative to the seed URL
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CrawlScope {
+    /// Only the seed's host
+    #[default]
+    SameHost,
+    /// The seed's host with or without a leading `www.`
+    SameDomain,
+    /// The seed's domain (without `www.`) and every subdomain of it
+    Subdomains,
+}
+
+impl CrawlScope {
+    /// Whether `host` is in scope of a crawl seeded on `seed_host`
+    pub fn contains(&self, seed_host: &str, host: &str) -> bool {
+        let seed_host = seed_host.to_ascii_lowercase();
+        let host = host.to_ascii_lowercase();
+        let strip_www = |host: &str| host.strip_prefix("www.").unwrap_or(host).to_string();
+
+        match self {
+            Self::SameHost => host == seed_host,
+            Self::SameDomain => strip_www(&host) == strip_www(&seed_host),
+            Self::Subdomains => {
+                let domain = strip_www(&seed_host);
+                host == domain || host.ends_with(&format!(".{}", domain))
+            }
+        }
+    }
+}
+
+/// Settings a crawl job may override on the worker's crawler
+///
+/// Unset fields keep the service configuration.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CrawlOptions {
+    #[serde(default)]
+    pub scope: CrawlScope,
+    /// Regexes a URL must match one of (checked against the full URL)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include_patterns: Vec<String>,
+    /// Regexes excluding a URL; they win over include patterns
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude_patterns: Vec<String>,
+    /// Query parameters removed during URL normalization, on top of the
+    /// tracking defaults; `prefix*` removes every parameter with that prefix
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub strip_query_params: Vec<String>,
+    /// Drop query strings entirely
+    #[serde(default)]
+    pub strip_all_query_params: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requests_per_second: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_agent: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extract_images: Option<bool>,
+}
+
+impl CrawlOptions {
+    /// Reject options the crawler could not apply
+    pub fn validate(&self) -> Result<()> {
+        for pattern in self.include_patterns.iter().chain(&self.exclude_patterns) {
+            Regex::new(pattern).with_context(|| format!("Invalid URL pattern {:?}", pattern))?;
+        }
+        if self.requests_per_second == Some(0) {
+            anyhow::bail!("requests_per_second must be at least 1");
+        }
+        if let Some(user_agent) = &self.user_agent {
+            if user_agent.trim().is_empty() || user_agent.chars().any(|c| c.is_control()) {
+                anyhow::bail!("Invalid user agent {:?}", user_agent);
+            }
+        }
+        if self.strip_query_params.iter().any(|param| param.trim_end_matches('*').is_empty()) {
+            anyhow::bail!("Query parameter rules must name a parameter or prefix");
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scope() {
+        assert!(CrawlScope::SameHost.contains("www.example.com", "www.example.com"));
+        assert!(!CrawlScope::SameHost.contains("www.example.com", "example.com"));
+
+        assert!(CrawlScope::SameDomain.contains("www.example.com", "example.com"));
+        assert!(CrawlScope::SameDomain.contains("example.com", "WWW.Example.com"));
+        assert!(!CrawlScope::SameDomain.contains("example.com", "blog.example.com"));
+
+        assert!(CrawlScope::Subdomains.contains("www.example.com", "blog.example.com"));
+        assert!(CrawlScope::Subdomains.contains("example.com", "example.com"));
+        assert!(!CrawlScope::Subdomains.contains("example.com", "notexample.com"));
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(CrawlOptions::default().validate().is_ok());
+        assert!(CrawlOptions {
+            include_patterns: vec!["/docs/(".to_string()],
+            ..Default::default()
+        }
+        .validate()
+        .is_err());
+        assert!(CrawlOptions {
+            requests_per_second: Some(0),
+            ..Default::default()
+        }
+        .validate()
+        .is_err());
+        assert!(CrawlOptions {
+            user_agent: Some("Bot\r\nX-Injected: 1".to_string()),
+            ..Default::default()
+        }
+        .validate()
+        .is_err());
+    }
+
+    #[test]
+    fn test_deserialize() {
+        let options: CrawlOptions = serde_json::from_str(
+            r#"{"scope": "subdomains", "exclude_patterns": ["\\.pdf$"], "extract_images": false}"#,
+        )
+        .unwrap();
+        assert_eq!(options.scope, CrawlScope::Subdomains);
+        assert_eq!(options.exclude_patterns, vec!["\\.pdf$"]);
+        assert_eq!(options.extract_images, Some(false));
+        assert!(options.user_agent.is_none());
+    }
+}
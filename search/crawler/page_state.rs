@@ -0,0 +1,330 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use reqwest::header::{HeaderMap, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::{FromRow, PgPool};
+use std::collections::HashMap;
+
+/// Minimum observation window before the change rate is trusted
+const MIN_OBSERVATION_DAYS: f64 = 1.0;
+
+/// Minimum number of checks before the change rate is trusted
+const MIN_CHECKS: i32 = 3;
+
+/// HTTP validators used for conditional requests
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PageValidators {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+impl PageValidators {
+    /// Read validators from response headers
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let header = |name| {
+            headers
+                .get(name)
+                .and_then(|value: &reqwest::header::HeaderValue| value.to_str().ok())
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+
+        Self {
+            etag: header(ETAG),
+            last_modified: header(LAST_MODIFIED),
+        }
+    }
+
+    /// Check if there is nothing to validate against
+    pub fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+
+    /// Add `If-None-Match` / `If-Modified-Since` to a request
+    pub fn apply(&self, mut request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        for (name, value) in self.request_headers() {
+            request = request.header(name, value);
+        }
+        request
+    }
+
+    /// The conditional request headers, as sent by `apply`
+    pub fn request_headers(&self) -> Vec<(String, String)> {
+        let mut headers = Vec::new();
+        if let Some(etag) = &self.etag {
+            headers.push((IF_NONE_MATCH.to_string(), etag.clone()));
+        }
+        if let Some(last_modified) = &self.last_modified {
+            headers.push((IF_MODIFIED_SINCE.to_string(), last_modified.clone()));
+        }
+        headers
+    }
+
+    /// Prefer freshly received validators, keeping old ones the server did not resend
+    pub fn merge(&self, newer: &PageValidators) -> PageValidators {
+        PageValidators {
+            etag: newer.etag.clone().or_else(|| self.etag.clone()),
+            last_modified: newer.last_modified.clone().or_else(|| self.last_modified.clone()),
+        }
+    }
+}
+
+/// Hash of the extracted page text, used to detect content changes
+///
+/// Hashing the extracted text rather than the raw HTML ignores markup-only
+/// churn such as rotating nonces or inline timestamps in scripts.
+pub fn content_hash(title: &str, content: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(title.as_bytes());
+    hasher.update([0u8]);
+    hasher.update(content.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Stored change detection state for a URL
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct PageState {
+    pub url: String,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub content_hash: Option<String>,
+    pub first_seen_at: DateTime<Utc>,
+    pub last_checked_at: DateTime<Utc>,
+    pub last_changed_at: Option<DateTime<Utc>>,
+    pub check_count: i32,
+    pub change_count: i32,
+    /// URL the page was last fetched from, if not its canonical `url`
+    #[sqlx(default)]
+    pub fetch_url: Option<String>,
+}
+
+impl PageState {
+    /// Validators to send on the next request
+    pub fn validators(&self) -> PageValidators {
+        PageValidators {
+            etag: self.etag.clone(),
+            last_modified: self.last_modified.clone(),
+        }
+    }
+
+    /// Check if the given hash matches the stored content
+    pub fn is_unchanged(&self, hash: &str) -> bool {
+        self.content_hash.as_deref() == Some(hash)
+    }
+
+    /// Observed content changes per day
+    ///
+    /// Returns `None` until the page has been checked often enough, over a
+    /// long enough window, for the rate to mean anything.
+    pub fn changes_per_day(&self) -> Option<f64> {
+        if self.check_count < MIN_CHECKS {
+            return None;
+        }
+
+        let observed_days =
+            (self.last_checked_at - self.first_seen_at).num_seconds() as f64 / 86400.0;
+        if observed_days < MIN_OBSERVATION_DAYS {
+            return None;
+        }
+
+        Some(self.change_count as f64 / observed_days)
+    }
+}
+
+/// Repository for per-URL change detection state
+#[derive(Clone)]
+pub struct PageStateRepository {
+    pool: PgPool,
+}
+
+impl PageStateRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Get the state of a single URL
+    pub async fn get(&self, url: &str) -> Result<Option<PageState>> {
+        let state = sqlx::query_as::<_, PageState>("SELECT * FROM page_states WHERE url = $1")
+            .bind(url)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(state)
+    }
+
+    /// Get the state of the page served at `url`
+    ///
+    /// States are stored under canonical URLs, so a URL whose page declared
+    /// another canonical URL is found through `fetch_url`.
+    pub async fn get_for_fetch(&self, url: &str) -> Result<Option<PageState>> {
+        let state = sqlx::query_as::<_, PageState>(
+            r#"
+            SELECT * FROM page_states
+            WHERE url = $1 OR fetch_url = $1
+            ORDER BY url = $1 DESC, last_checked_at DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(url)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(state)
+    }
+
+    /// Remember that the page stored under `url` was fetched from `fetch_url`
+    pub async fn record_fetch_url(&self, url: &str, fetch_url: &str) -> Result<()> {
+        sqlx::query("UPDATE page_states SET fetch_url = $2 WHERE url = $1")
+            .bind(url)
+            .bind((fetch_url != url).then_some(fetch_url))
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Forget the indexed content of pages removed from the index
+    ///
+    /// Link signal jobs only consider pages with a content hash, and a page
+    /// without one is indexed again on its next crawl.
+    pub async fn clear_content_hash(&self, urls: &[String]) -> Result<()> {
+        sqlx::query("UPDATE page_states SET content_hash = NULL WHERE url = ANY($1)")
+            .bind(urls)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Get the state of several URLs at once, keyed by URL
+    pub async fn get_many(&self, urls: &[String]) -> Result<HashMap<String, PageState>> {
+        if urls.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let states = sqlx::query_as::<_, PageState>("SELECT * FROM page_states WHERE url = ANY($1)")
+            .bind(urls)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(states.into_iter().map(|state| (state.url.clone(), state)).collect())
+    }
+
+    /// Record a check of a URL
+    ///
+    /// `content_hash` is `None` when the content was not downloaded (304), in
+    /// which case the page counts as unchanged.
+    pub async fn record_check(
+        &self,
+        url: &str,
+        validators: &PageValidators,
+        content_hash: Option<&str>,
+    ) -> Result<PageState> {
+        let state = sqlx::query_as::<_, PageState>(
+            r#"
+            INSERT INTO page_states (url, etag, last_modified, content_hash, last_changed_at)
+            VALUES ($1, $2, $3, $4, NOW())
+            ON CONFLICT (url) DO UPDATE SET
+                etag = COALESCE(EXCLUDED.etag, page_states.etag),
+                last_modified = COALESCE(EXCLUDED.last_modified, page_states.last_modified),
+                check_count = page_states.check_count + 1,
+                change_count = page_states.change_count + CASE
+                    WHEN EXCLUDED.content_hash IS NOT NULL
+                        AND page_states.content_hash IS DISTINCT FROM EXCLUDED.content_hash
+                    THEN 1 ELSE 0 END,
+                last_changed_at = CASE
+                    WHEN EXCLUDED.content_hash IS NOT NULL
+                        AND page_states.content_hash IS DISTINCT FROM EXCLUDED.content_hash
+                    THEN NOW() ELSE page_states.last_changed_at END,
+                content_hash = COALESCE(EXCLUDED.content_hash, page_states.content_hash),
+                last_checked_at = NOW()
+            RETURNING *
+            "#,
+        )
+        .bind(url)
+        .bind(&validators.etag)
+        .bind(&validators.last_modified)
+        .bind(content_hash)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use reqwest::header::HeaderValue;
+
+    fn state(check_count: i32, change_count: i32, observed: Duration) -> PageState {
+        let now = Utc::now();
+        PageState {
+            url: "https://example.com/".to_string(),
+            etag: None,
+            last_modified: None,
+            content_hash: None,
+            first_seen_at: now - observed,
+            last_checked_at: now,
+            last_changed_at: None,
+            check_count,
+            change_count,
+            fetch_url: None,
+        }
+    }
+
+    #[test]
+    fn test_content_hash() {
+        let hash = content_hash("Title", "Some content");
+        assert_eq!(hash.len(), 64);
+        assert_eq!(hash, content_hash("Title", "Some content"));
+        assert_ne!(hash, content_hash("Title", "Other content"));
+        // Title/content boundary is part of the hash
+        assert_ne!(content_hash("ab", "c"), content_hash("a", "bc"));
+    }
+
+    #[test]
+    fn test_validators_from_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert(ETAG, HeaderValue::from_static("\"abc123\""));
+        headers.insert(LAST_MODIFIED, HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"));
+
+        let validators = PageValidators::from_headers(&headers);
+        assert_eq!(validators.etag.as_deref(), Some("\"abc123\""));
+        assert_eq!(validators.last_modified.as_deref(), Some("Wed, 21 Oct 2015 07:28:00 GMT"));
+        assert!(!validators.is_empty());
+
+        assert!(PageValidators::from_headers(&HeaderMap::new()).is_empty());
+    }
+
+    #[test]
+    fn test_validators_merge() {
+        let old = PageValidators {
+            etag: Some("\"v1\"".to_string()),
+            last_modified: Some("Wed, 21 Oct 2015 07:28:00 GMT".to_string()),
+        };
+        let newer = PageValidators {
+            etag: Some("\"v2\"".to_string()),
+            last_modified: None,
+        };
+
+        let merged = old.merge(&newer);
+        assert_eq!(merged.etag.as_deref(), Some("\"v2\""));
+        assert_eq!(merged.last_modified, old.last_modified);
+    }
+
+    #[test]
+    fn test_changes_per_day() {
+        // Not enough checks yet
+        assert_eq!(state(2, 1, Duration::days(10)).changes_per_day(), None);
+        // Not observed for long enough
+        assert_eq!(state(5, 1, Duration::hours(6)).changes_per_day(), None);
+
+        let rate = state(10, 5, Duration::days(10)).changes_per_day().unwrap();
+        assert!((rate - 0.5).abs() < 0.01);
+
+        assert_eq!(state(10, 0, Duration::days(10)).changes_per_day(), Some(0.0));
+    }
+}
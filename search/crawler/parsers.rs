@@ -0,0 +1,579 @@
+use anyhow::{anyhow, Result};
+use pulldown_cmark::{Event, HeadingLevel, Parser, Tag, TagEnd};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use super::util::collapse_whitespace;
+
+/// MIME type of pages handled by the HTML pipeline
+pub const HTML_CONTENT_TYPE: &str = "text/html";
+
+/// Longest first line of a text file that is still treated as its title
+const MAX_TITLE_CHARS: usize = 120;
+
+/// Text, title and metadata extracted from a non-HTML document
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParsedDocument {
+    pub title: Option<String>,
+    /// Blank-line separated blocks, like the HTML content extractor
+    pub content: String,
+    pub description: Option<String>,
+    pub keywords: Option<Vec<String>>,
+    pub author: Option<String>,
+    /// Creation/publication date in any format `StructuredData` understands
+    pub published_at: Option<String>,
+    pub modified_at: Option<String>,
+    /// Declared language, if the format carries one
+    pub lang: Option<String>,
+}
+
+/// Parser for one family of document formats
+pub trait DocumentParser: Send + Sync {
+    /// MIME types handled by this parser (lowercase, without parameters)
+    fn content_types(&self) -> &'static [&'static str];
+
+    /// Extract text and metadata from a response body
+    fn parse(&self, body: &[u8]) -> Result<ParsedDocument>;
+}
+
+/// Document parsers keyed by MIME type
+///
+/// HTML is not registered here: it goes through the full page pipeline
+/// (canonical URLs, structured data, images) in `Crawler::process_page`.
+#[derive(Clone)]
+pub struct ParserRegistry {
+    parsers: HashMap<String, Arc<dyn DocumentParser>>,
+}
+
+impl Default for ParserRegistry {
+    fn default() -> Self {
+        let mut registry = Self::empty();
+        registry.register(Arc::new(PlainTextParser));
+        registry.register(Arc::new(MarkdownParser));
+        registry.register(Arc::new(PdfParser));
+        registry
+    }
+}
+
+impl ParserRegistry {
+    /// Create a registry with the built-in parsers
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a registry without any parsers
+    pub fn empty() -> Self {
+        Self {
+            parsers: HashMap::new(),
+        }
+    }
+
+    /// Register a parser for all of its content types, replacing existing ones
+    pub fn register(&mut self, parser: Arc<dyn DocumentParser>) {
+        for content_type in parser.content_types() {
+            self.parsers.insert(content_type.to_string(), parser.clone());
+        }
+    }
+
+    /// Get the parser for a content type
+    pub fn get(&self, content_type: &str) -> Option<Arc<dyn DocumentParser>> {
+        self.parsers.get(&base_content_type(content_type)).cloned()
+    }
+
+    /// Check if a content type can be parsed
+    pub fn supports(&self, content_type: &str) -> bool {
+        self.parsers.contains_key(&base_content_type(content_type))
+    }
+
+    /// All registered content types
+    pub fn content_types(&self) -> Vec<String> {
+        let mut types: Vec<String> = self.parsers.keys().cloned().collect();
+        types.sort();
+        types
+    }
+
+    /// Parse a body with the parser registered for its content type
+    pub fn parse(&self, content_type: &str, body: &[u8]) -> Result<ParsedDocument> {
+        let parser = self
+            .get(content_type)
+            .ok_or_else(|| anyhow!("No parser for content type {}", content_type))?;
+        parser.parse(body)
+    }
+
+    /// Parse a body on the blocking thread pool
+    ///
+    /// Extraction is CPU-bound, and third-party extractors (notably
+    /// `pdf_extract`) can panic on malformed files; a panic is reported as a
+    /// parse error instead of taking down the worker.
+    pub async fn parse_blocking(&self, content_type: &str, body: Vec<u8>) -> Result<ParsedDocument> {
+        let parser = self
+            .get(content_type)
+            .ok_or_else(|| anyhow!("No parser for content type {}", content_type))?;
+
+        tokio::task::spawn_blocking(move || parser.parse(&body))
+            .await
+            .map_err(|e| anyhow!("Parser for {} failed: {}", content_type, e))?
+    }
+}
+
+/// Check if a content type goes through the HTML pipeline
+pub fn is_html_content_type(content_type: &str) -> bool {
+    matches!(
+        base_content_type(content_type).as_str(),
+        HTML_CONTENT_TYPE | "application/xhtml+xml"
+    )
+}
+
+/// Strip parameters such as `charset` from a content type
+pub fn base_content_type(content_type: &str) -> String {
+    content_type
+        .split(';')
+        .next()
+        .unwrap_or(content_type)
+        .trim()
+        .to_lowercase()
+}
+
+/// Work out the content type of a response
+///
+/// Servers often label documents `application/octet-stream` or send no type
+/// at all, so the body's magic bytes and the URL extension are consulted
+/// when the header is missing or generic.
+pub fn detect_content_type(header: Option<&str>, url: &str, body: &[u8]) -> String {
+    let declared = header.map(base_content_type).filter(|t| !t.is_empty());
+
+    match declared.as_deref() {
+        Some("application/octet-stream") | Some("binary/octet-stream") | None => {}
+        Some(declared) => return declared.to_string(),
+    }
+
+    if body.starts_with(b"%PDF-") {
+        return "application/pdf".to_string();
+    }
+
+    let path = url.split(['?', '#']).next().unwrap_or(url).to_lowercase();
+    let sniffed = if path.ends_with(".pdf") {
+        Some("application/pdf")
+    } else if path.ends_with(".md") || path.ends_with(".markdown") {
+        Some("text/markdown")
+    } else if path.ends_with(".txt") {
+        Some("text/plain")
+    } else {
+        None
+    };
+
+    sniffed
+        .map(|t| t.to_string())
+        .or(declared)
+        .unwrap_or_else(|| HTML_CONTENT_TYPE.to_string())
+}
+
+/// `text/plain`: paragraphs are separated by blank lines
+pub struct PlainTextParser;
+
+impl DocumentParser for PlainTextParser {
+    fn content_types(&self) -> &'static [&'static str] {
+        &["text/plain"]
+    }
+
+    fn parse(&self, body: &[u8]) -> Result<ParsedDocument> {
+        let text = String::from_utf8_lossy(body);
+
+        // A short first line followed by a blank line reads as a title (README, RFC)
+        let mut paragraphs = paragraphs(&text);
+        let title = match paragraphs.first() {
+            Some(first) if paragraphs.len() > 1 && first.chars().count() <= MAX_TITLE_CHARS => {
+                Some(paragraphs.remove(0))
+            }
+            _ => None,
+        };
+
+        Ok(ParsedDocument {
+            title,
+            content: paragraphs.join("\n\n"),
+            ..Default::default()
+        })
+    }
+}
+
+/// `text/markdown`: rendered to text blocks, with optional YAML front matter
+pub struct MarkdownParser;
+
+impl DocumentParser for MarkdownParser {
+    fn content_types(&self) -> &'static [&'static str] {
+        &["text/markdown", "text/x-markdown"]
+    }
+
+    fn parse(&self, body: &[u8]) -> Result<ParsedDocument> {
+        let text = String::from_utf8_lossy(body);
+        let (front_matter, markdown) = split_front_matter(&text);
+
+        let mut blocks = Vec::new();
+        let mut current = String::new();
+        let mut title = None;
+        let mut in_title = false;
+
+        for event in Parser::new(markdown) {
+            match event {
+                Event::Start(Tag::Heading { level: HeadingLevel::H1, .. }) if title.is_none() => {
+                    in_title = true;
+                }
+                Event::Text(text) | Event::Code(text) => current.push_str(&text),
+                Event::SoftBreak | Event::HardBreak => current.push(' '),
+                Event::End(TagEnd::Paragraph)
+                | Event::End(TagEnd::Heading(_))
+                | Event::End(TagEnd::Item)
+                | Event::End(TagEnd::CodeBlock)
+                | Event::End(TagEnd::TableCell) => {
+                    let block = collapse_whitespace(&current);
+                    current.clear();
+                    if block.is_empty() {
+                        continue;
+                    }
+                    if in_title {
+                        title = Some(block.clone());
+                        in_title = false;
+                    }
+                    blocks.push(block);
+                }
+                _ => {}
+            }
+        }
+        let trailing = collapse_whitespace(&current);
+        if !trailing.is_empty() {
+            blocks.push(trailing);
+        }
+
+        let field = |name: &str| front_matter.get(name).cloned();
+        Ok(ParsedDocument {
+            title: field("title").or(title),
+            content: blocks.join("\n\n"),
+            description: field("description").or_else(|| field("summary")),
+            keywords: field("tags")
+                .or_else(|| field("keywords"))
+                .map(|tags| split_list(&tags))
+                .filter(|tags| !tags.is_empty()),
+            author: field("author"),
+            published_at: field("date").or_else(|| field("published")),
+            modified_at: field("updated").or_else(|| field("lastmod")),
+            lang: field("lang").or_else(|| field("language")),
+        })
+    }
+}
+
+/// `application/pdf`: text layer plus the document information dictionary
+pub struct PdfParser;
+
+impl DocumentParser for PdfParser {
+    fn content_types(&self) -> &'static [&'static str] {
+        &["application/pdf"]
+    }
+
+    fn parse(&self, body: &[u8]) -> Result<ParsedDocument> {
+        let text = pdf_extract::extract_text_from_mem(body)
+            .map_err(|e| anyhow!("Failed to extract PDF text: {}", e))?;
+
+        // Metadata is optional; a broken info dictionary shouldn't drop the text
+        let info = pdf_info(body).unwrap_or_default();
+        let field = |name: &str| info.get(name).cloned().filter(|value| !value.is_empty());
+
+        Ok(ParsedDocument {
+            title: field("Title"),
+            content: paragraphs(&dehyphenate(&text)).join("\n\n"),
+            description: field("Subject"),
+            keywords: field("Keywords")
+                .map(|keywords| split_list(&keywords))
+                .filter(|keywords| !keywords.is_empty()),
+            author: field("Author"),
+            published_at: field("CreationDate").and_then(|date| pdf_date(&date)),
+            modified_at: field("ModDate").and_then(|date| pdf_date(&date)),
+            lang: None,
+        })
+    }
+}
+
+/// Read the string entries of a PDF's `/Info` dictionary
+fn pdf_info(body: &[u8]) -> Result<HashMap<String, String>> {
+    let document = lopdf::Document::load_mem(body)?;
+    let info = match document.trailer.get(b"Info")? {
+        lopdf::Object::Reference(id) => document.get_dictionary(*id)?,
+        lopdf::Object::Dictionary(dictionary) => dictionary,
+        _ => return Ok(HashMap::new()),
+    };
+
+    Ok(info
+        .iter()
+        .filter_map(|(key, value)| match value {
+            lopdf::Object::String(bytes, _) => Some((
+                String::from_utf8_lossy(key).to_string(),
+                collapse_whitespace(&decode_pdf_string(bytes)),
+            )),
+            _ => None,
+        })
+        .collect())
+}
+
+/// PDF text strings are UTF-16BE with a BOM, or PDFDocEncoding (~Latin-1)
+fn decode_pdf_string(bytes: &[u8]) -> String {
+    if let Some(utf16) = bytes.strip_prefix(&[0xFE, 0xFF]) {
+        let units: Vec<u16> = utf16
+            .chunks_exact(2)
+            .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+            .collect();
+        return String::from_utf16_lossy(&units);
+    }
+    bytes.iter().map(|&b| b as char).collect()
+}
+
+/// Convert a PDF date (`D:20240301093000+01'00'`) to RFC 3339
+fn pdf_date(value: &str) -> Option<String> {
+    let value = value.trim().trim_start_matches("D:");
+    let digits: String = value.chars().take_while(|c| c.is_ascii_digit()).collect();
+    if digits.len() < 8 {
+        return None;
+    }
+
+    // Missing components default to the start of the period
+    let part = |start: usize, end: usize, default: &str| {
+        digits.get(start..end).unwrap_or(default).to_string()
+    };
+    let offset = match value[digits.len()..].chars().next() {
+        Some(sign @ ('+' | '-')) => {
+            let rest: String = value[digits.len() + 1..].chars().filter(|c| c.is_ascii_digit()).collect();
+            format!(
+                "{}{}:{}",
+                sign,
+                rest.get(0..2).unwrap_or("00"),
+                rest.get(2..4).unwrap_or("00")
+            )
+        }
+        _ => "Z".to_string(),
+    };
+
+    Some(format!(
+        "{}-{}-{}T{}:{}:{}{}",
+        part(0, 4, "0000"),
+        part(4, 6, "01"),
+        part(6, 8, "01"),
+        part(8, 10, "00"),
+        part(10, 12, "00"),
+        part(12, 14, "00"),
+        offset
+    ))
+}
+
+/// Re-join words hyphenated across line breaks
+fn dehyphenate(text: &str) -> String {
+    text.replace("-\n", "")
+}
+
+/// Split text into normalized paragraphs on blank lines
+fn paragraphs(text: &str) -> Vec<String> {
+    text.replace("\r\n", "\n")
+        .split("\n\n")
+        .map(collapse_whitespace)
+        .filter(|paragraph| !paragraph.is_empty())
+        .collect()
+}
+
+/// Split `---` delimited `key: value` front matter from a Markdown body
+fn split_front_matter(text: &str) -> (HashMap<String, String>, &str) {
+    let mut fields = HashMap::new();
+
+    let Some(rest) = text.strip_prefix("---\n").or_else(|| text.strip_prefix("---\r\n")) else {
+        return (fields, text);
+    };
+    let Some(end) = rest.find("\n---") else {
+        return (fields, text);
+    };
+
+    for line in rest[..end].lines() {
+        if let Some((key, value)) = line.split_once(':') {
+            let value = value.trim().trim_matches(|c| c == '"' || c == '\'');
+            if !value.is_empty() {
+                fields.insert(key.trim().to_lowercase(), value.to_string());
+            }
+        }
+    }
+
+    let body = rest[end + 4..].trim_start_matches(['-', '\r', '\n']);
+    (fields, body)
+}
+
+/// Split `a, b` or `[a, b]` lists
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .trim_matches(|c| c == '[' || c == ']')
+        .split([',', ';'])
+        .map(|item| item.trim().trim_matches(|c| c == '"' || c == '\'').to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lopdf::content::{Content, Operation};
+    use lopdf::{dictionary, Document, Object, Stream};
+
+    /// Build a one-page PDF with an info dictionary
+    fn sample_pdf() -> Vec<u8> {
+        let mut doc = Document::with_version("1.5");
+        let pages_id = doc.new_object_id();
+        let font_id = doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Helvetica",
+            "Encoding" => "WinAnsiEncoding",
+        });
+        let resources_id = doc.add_object(dictionary! {
+            "Font" => dictionary! { "F1" => font_id },
+        });
+        let content = Content {
+            operations: vec![
+                Operation::new("BT", vec![]),
+                Operation::new("Tf", vec!["F1".into(), 12.into()]),
+                Operation::new("Td", vec![72.into(), 720.into()]),
+                Operation::new("Tj", vec![Object::string_literal("Installing the search service")]),
+                Operation::new("ET", vec![]),
+            ],
+        };
+        let content_id = doc.add_object(Stream::new(dictionary! {}, content.encode().unwrap()));
+        let page_id = doc.add_object(dictionary! {
+            "Type" => "Page",
+            "Parent" => pages_id,
+            "Contents" => content_id,
+        });
+        doc.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Kids" => vec![page_id.into()],
+                "Count" => 1,
+                "Resources" => resources_id,
+                "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+            }),
+        );
+        let catalog_id = doc.add_object(dictionary! {
+            "Type" => "Catalog",
+            "Pages" => pages_id,
+        });
+        let info_id = doc.add_object(dictionary! {
+            "Title" => Object::string_literal("Operator Guide"),
+            "Author" => Object::string_literal("Platform Team"),
+            "Keywords" => Object::string_literal("search, operations"),
+            "CreationDate" => Object::string_literal("D:20240301093000+01'00'"),
+        });
+        doc.trailer.set("Root", catalog_id);
+        doc.trailer.set("Info", info_id);
+
+        let mut bytes = Vec::new();
+        doc.save_to(&mut bytes).unwrap();
+        bytes
+    }
+
+    struct PanickingParser;
+
+    impl DocumentParser for PanickingParser {
+        fn content_types(&self) -> &'static [&'static str] {
+            &["application/x-broken"]
+        }
+
+        fn parse(&self, _body: &[u8]) -> Result<ParsedDocument> {
+            panic!("malformed input")
+        }
+    }
+
+    #[tokio::test]
+    async fn test_parse_blocking_catches_panics() {
+        let mut registry = ParserRegistry::new();
+        registry.register(Arc::new(PanickingParser));
+
+        assert!(registry.parse_blocking("application/x-broken", b"%%".to_vec()).await.is_err());
+        let parsed = registry.parse_blocking("text/plain", b"Still works".to_vec()).await.unwrap();
+        assert_eq!(parsed.content, "Still works");
+    }
+
+    #[test]
+    fn test_registry_lookup() {
+        let registry = ParserRegistry::new();
+        assert!(registry.supports("application/pdf"));
+        assert!(registry.supports("text/plain; charset=utf-8"));
+        assert!(registry.supports("Text/Markdown"));
+        assert!(!registry.supports(HTML_CONTENT_TYPE));
+        assert!(!registry.supports("image/png"));
+        assert!(ParserRegistry::empty().content_types().is_empty());
+
+        assert!(is_html_content_type("text/html; charset=utf-8"));
+        assert!(is_html_content_type("application/xhtml+xml"));
+        assert!(!is_html_content_type("text/plain"));
+    }
+
+    #[test]
+    fn test_detect_content_type() {
+        assert_eq!(detect_content_type(Some("application/pdf"), "https://a.com/x", b""), "application/pdf");
+        assert_eq!(
+            detect_content_type(Some("application/octet-stream"), "https://a.com/x", b"%PDF-1.7"),
+            "application/pdf"
+        );
+        assert_eq!(detect_content_type(None, "https://a.com/guide.PDF?dl=1", b""), "application/pdf");
+        assert_eq!(detect_content_type(None, "https://a.com/README.md", b"# Hi"), "text/markdown");
+        assert_eq!(detect_content_type(None, "https://a.com/", b"<html>"), HTML_CONTENT_TYPE);
+        assert_eq!(
+            detect_content_type(Some("text/html; charset=utf-8"), "https://a.com/a.pdf", b""),
+            HTML_CONTENT_TYPE
+        );
+    }
+
+    #[test]
+    fn test_plain_text() {
+        let parsed = PlainTextParser
+            .parse(b"Release notes\n\nThis release adds PDF support\nfor the crawler.\n\n\nBug fixes.")
+            .unwrap();
+
+        assert_eq!(parsed.title.as_deref(), Some("Release notes"));
+        assert_eq!(parsed.content, "This release adds PDF support for the crawler.\n\nBug fixes.");
+    }
+
+    #[test]
+    fn test_markdown() {
+        let markdown = "---\ntitle: \"Deploying\"\nauthor: Ops\ndate: 2024-02-01\ntags: [deploy, docker]\n---\n\
+            # Deploy guide\n\nBuild the image with `docker build`.\n\n## Steps\n\n- Push the image\n- Restart\n\n\
+            ```\ndocker compose up -d\n```\n";
+        let parsed = MarkdownParser.parse(markdown.as_bytes()).unwrap();
+
+        assert_eq!(parsed.title.as_deref(), Some("Deploying"));
+        assert_eq!(parsed.author.as_deref(), Some("Ops"));
+        assert_eq!(parsed.published_at.as_deref(), Some("2024-02-01"));
+        assert_eq!(parsed.keywords, Some(vec!["deploy".to_string(), "docker".to_string()]));
+        assert_eq!(
+            parsed.content,
+            "Deploy guide\n\nBuild the image with docker build.\n\nSteps\n\nPush the image\n\nRestart\n\ndocker compose up -d"
+        );
+
+        // Without front matter the first H1 is the title
+        let parsed = MarkdownParser.parse(b"Intro text.\n\n# Real title\n\nBody.").unwrap();
+        assert_eq!(parsed.title.as_deref(), Some("Real title"));
+    }
+
+    #[test]
+    fn test_pdf() {
+        let parsed = PdfParser.parse(&sample_pdf()).unwrap();
+
+        assert!(parsed.content.contains("Installing the search service"), "{:?}", parsed.content);
+        assert_eq!(parsed.title.as_deref(), Some("Operator Guide"));
+        assert_eq!(parsed.author.as_deref(), Some("Platform Team"));
+        assert_eq!(parsed.keywords, Some(vec!["search".to_string(), "operations".to_string()]));
+        assert_eq!(parsed.published_at.as_deref(), Some("2024-03-01T09:30:00+01:00"));
+
+        assert!(PdfParser.parse(b"not a pdf").is_err());
+    }
+
+    #[test]
+    fn test_pdf_helpers() {
+        assert_eq!(pdf_date("D:2024"), None);
+        assert_eq!(pdf_date("D:20240301").as_deref(), Some("2024-03-01T00:00:00Z"));
+        assert_eq!(decode_pdf_string(&[0xFE, 0xFF, 0x00, 0x48, 0x00, 0xE9]), "Hé");
+        assert_eq!(dehyphenate("docu-\nmentation"), "documentation");
+    }
+}
@@ -1,17 +1,224 @@
 use anyhow::Result;
+use chrono::{DateTime, TimeZone, Utc};
 use dashmap::DashMap;
+use redis::aio::ConnectionManager;
+use redis::{AsyncCommands, Script};
 use serde::Serialize;
+use std::collections::BTreeSet;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::time::{sleep, Instant};
 use tracing::{debug, info, warn};
 use url::Url;
 
+use super::retry::{is_overload_status, parse_retry_after};
+
+/// Adaptive delay a domain starts from when it first signals overload
+const MIN_ADAPTIVE_DELAY: Duration = Duration::from_secs(1);
+
+/// Ceiling for the adaptive delay
+const MAX_ADAPTIVE_DELAY: Duration = Duration::from_secs(120);
+
+/// How much each successful request takes off the adaptive delay
+const ADDITIVE_DECREASE: Duration = Duration::from_millis(500);
+
+/// Consecutive failed requests that count as an error spike
+const ERROR_SPIKE_THRESHOLD: u32 = 3;
+
+/// Longest `Retry-After` pause honoured
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(3600);
+
+/// Shared state of a domain is dropped after this long without requests
+const SHARED_STATE_TTL_MS: u64 = 60 * 60 * 1000;
+
+/// Redis set indexing every domain with shared politeness state
+const DOMAINS_KEY: &str = "crawler:politeness:domains";
+
+/// Reserve the next request slot of a domain across all workers
+///
+/// The domain's hash holds the time of its last reserved request (`last`),
+/// its adaptive delay (`delay`) and any `Retry-After` pause (`paused_until`),
+/// in milliseconds of Redis server time. Each caller reserves the first slot
+/// at least the larger of `ARGV[1]` (the base delay) and the adaptive delay
+/// after the last one, and not before the pause ends. Returns how long to
+/// wait for that slot.
+const RESERVE_SLOT_SCRIPT: &str = r#"
+local time = redis.call('TIME')
+local now = tonumber(time[1]) * 1000 + math.floor(tonumber(time[2]) / 1000)
+local base = tonumber(ARGV[1])
+local fields = redis.call('HMGET', KEYS[1], 'last', 'delay', 'paused_until')
+local last = tonumber(fields[1])
+local delay = math.max(base, tonumber(fields[2]) or 0)
+local slot = now
+if last and last + delay > slot then slot = last + delay end
+local paused_until = tonumber(fields[3]) or 0
+if paused_until > slot then slot = paused_until end
+redis.call('HSET', KEYS[1], 'last', slot, 'base', base)
+redis.call('PEXPIRE', KEYS[1], slot - now + delay + tonumber(ARGV[2]))
+redis.call('SADD', KEYS[2], ARGV[3])
+return slot - now
+"#;
+
+/// Apply the outcome of a request to a domain's adaptive delay (AIMD)
+///
+/// ARGV: outcome (`overload`, `error`, `success`), base delay ms, pause ms,
+/// minimum and maximum adaptive delay ms, additive decrease ms, error spike
+/// threshold, state TTL ms and the domain. Mirrors `AdaptiveDelay::record`.
+/// Returns the domain's effective delay in ms.
+const FEEDBACK_SCRIPT: &str = r#"
+local time = redis.call('TIME')
+local now = tonumber(time[1]) * 1000 + math.floor(tonumber(time[2]) / 1000)
+local outcome = ARGV[1]
+local base = tonumber(ARGV[2])
+local fields = redis.call('HMGET', KEYS[1], 'delay', 'errors', 'paused_until')
+local delay = tonumber(fields[1]) or 0
+local errors = tonumber(fields[2]) or 0
+local paused_until = tonumber(fields[3]) or 0
+
+local function increase()
+    delay = math.min(math.max(delay, base, tonumber(ARGV[4])) * 2, tonumber(ARGV[5]))
+    errors = 0
+end
+
+if outcome == 'overload' then
+    increase()
+    local pause = tonumber(ARGV[3])
+    if pause > 0 then paused_until = math.max(paused_until, now + pause) end
+elseif outcome == 'error' then
+    errors = errors + 1
+    if errors >= tonumber(ARGV[7]) then increase() end
+else
+    errors = 0
+    delay = delay - tonumber(ARGV[6])
+    if delay <= base then delay = 0 end
+end
+
+redis.call('HSET', KEYS[1], 'delay', delay, 'errors', errors, 'paused_until', paused_until, 'base', base)
+redis.call('PEXPIRE', KEYS[1], math.max(paused_until - now, 0) + tonumber(ARGV[8]))
+redis.call('SADD', KEYS[2], ARGV[9])
+return math.max(delay, base)
+"#;
+
+/// What a response says about a domain's load
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RequestOutcome {
+    /// 429 or 503, with the `Retry-After` pause if the server sent one
+    Overload(Option<Duration>),
+    /// Server error, timeout or network failure
+    Error,
+    /// The server answered normally
+    Success,
+}
+
+impl RequestOutcome {
+    /// What a response status says about the domain's load
+    ///
+    /// Client errors other than 408 and 429 say nothing about it.
+    pub fn from_status(status_code: u16, retry_after: Option<&str>) -> Option<Self> {
+        match status_code {
+            status if is_overload_status(status) => Some(RequestOutcome::Overload(retry_after.and_then(parse_retry_after))),
+            408 | 500..=599 => Some(RequestOutcome::Error),
+            200..=399 => Some(RequestOutcome::Success),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            RequestOutcome::Overload(_) => "overload",
+            RequestOutcome::Error => "error",
+            RequestOutcome::Success => "success",
+        }
+    }
+
+    fn pause(&self) -> Duration {
+        match self {
+            RequestOutcome::Overload(Some(pause)) => (*pause).min(MAX_RETRY_AFTER),
+            _ => Duration::ZERO,
+        }
+    }
+}
+
+/// Adaptive delay of a domain, on top of its base delay
+///
+/// Overload and error spikes double the delay; every successful request
+/// takes `ADDITIVE_DECREASE` off until it is back at the base delay.
+#[derive(Debug, Clone, Default)]
+struct AdaptiveDelay {
+    /// Delay while the domain is slowed down; zero when at the base delay
+    delay: Duration,
+    /// Consecutive failed requests
+    errors: u32,
+    /// No requests before this time (`Retry-After`)
+    paused_until: Option<Instant>,
+}
+
+impl AdaptiveDelay {
+    fn record(&mut self, outcome: RequestOutcome, base: Duration) {
+        match outcome {
+            RequestOutcome::Overload(_) => {
+                self.increase(base);
+                let pause = outcome.pause();
+                if !pause.is_zero() {
+                    let until = Instant::now() + pause;
+                    self.paused_until = Some(self.paused_until.map_or(until, |paused| paused.max(until)));
+                }
+            }
+            RequestOutcome::Error => {
+                self.errors += 1;
+                if self.errors >= ERROR_SPIKE_THRESHOLD {
+                    self.increase(base);
+                }
+            }
+            RequestOutcome::Success => {
+                self.errors = 0;
+                self.delay = self.delay.saturating_sub(ADDITIVE_DECREASE);
+                if self.delay <= base {
+                    self.delay = Duration::ZERO;
+                }
+            }
+        }
+    }
+
+    fn increase(&mut self, base: Duration) {
+        self.delay = (self.delay.max(base).max(MIN_ADAPTIVE_DELAY) * 2).min(MAX_ADAPTIVE_DELAY);
+        self.errors = 0;
+    }
+
+    /// The delay to keep between requests
+    fn effective(&self, base: Duration) -> Duration {
+        self.delay.max(base)
+    }
+}
+
+/// Current pacing of one domain
+#[derive(Debug, Clone, Serialize)]
+pub struct DomainDelay {
+    pub domain: String,
+    /// Delay currently kept between requests
+    pub delay_ms: u64,
+    /// robots.txt crawl-delay or the default delay
+    pub base_delay_ms: u64,
+    /// Requests are paused until then (`Retry-After`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paused_until: Option<DateTime<Utc>>,
+}
+
 /// Politeness manager for respectful crawling
+///
+/// Each domain is paced by its base delay (robots.txt crawl-delay or the
+/// default), raised adaptively while it signals overload or errors. With
+/// Redis configured, last-request times, adaptive delays and pauses are
+/// shared, so workers together keep the pace. Crawl delays themselves stay
+/// local: every worker reads them from the same robots.txt.
 #[derive(Clone)]
 pub struct PolitenessManager {
     /// Map of domain -> last request time
     last_request: Arc<DashMap<String, Instant>>,
+    /// Map of domain -> adaptive delay, when not shared
+    adaptive: Arc<DashMap<String, AdaptiveDelay>>,
+    /// Shared politeness store
+    redis: Option<ConnectionManager>,
     /// Map of domain -> custom crawl delay (from robots.txt)
     crawl_delays: Arc<DashMap<String, Duration>>,
     /// Default crawl delay
@@ -34,6 +241,8 @@ impl PolitenessManager {
 
         Self {
             last_request: Arc::new(DashMap::new()),
+            adaptive: Arc::new(DashMap::new()),
+            redis: None,
             crawl_delays: Arc::new(DashMap::new()),
             default_delay: Duration::from_millis(default_delay_ms),
             max_retries,
@@ -42,19 +251,46 @@ impl PolitenessManager {
         }
     }
 
+    /// Share last-request times and adaptive delays with other workers through Redis
+    pub fn with_redis(mut self, conn: ConnectionManager) -> Self {
+        info!("Politeness delays shared through Redis");
+        self.redis = Some(conn);
+        self
+    }
+
     /// Wait appropriate time before making request to URL
     pub async fn wait_before_request(&self, url: &str) -> Result<()> {
         let domain = self.extract_domain(url)?;
 
         // Get crawl delay for this domain
-        let delay = self
-            .crawl_delays
-            .get(&domain)
-            .map(|d| *d)
-            .unwrap_or(self.default_delay);
+        let base = self.base_delay(&domain);
+
+        if let Some(conn) = &self.redis {
+            match self.reserve_slot(conn.clone(), &domain, base).await {
+                Ok(wait_time) => {
+                    if !wait_time.is_zero() {
+                        debug!("Politeness delay for {}: waiting {:?}", domain, wait_time);
+                        sleep(wait_time).await;
+                    }
+                    return Ok(());
+                }
+                Err(e) => warn!("Shared politeness state unavailable for {}, using local state: {}", domain, e),
+            }
+        }
+
+        let (delay, paused_until) = match self.adaptive.get(&domain) {
+            Some(adaptive) => (adaptive.effective(base), adaptive.paused_until),
+            None => (base, None),
+        };
+
+        // Honour a Retry-After pause first
+        if let Some(until) = paused_until.filter(|until| *until > Instant::now()) {
+            debug!("{} is paused, waiting {:?}", domain, until - Instant::now());
+            tokio::time::sleep_until(until).await;
+        }
 
         // Check if we need to wait based on last request
-        if let Some(last_time) = self.last_request.get(&domain) {
+        if let Some(last_time) = self.last_request.get(&domain).map(|last| *last) {
             let elapsed = last_time.elapsed();
 
             if elapsed < delay {
@@ -73,6 +309,169 @@ impl PolitenessManager {
         Ok(())
     }
 
+    /// Reserve the domain's next request slot in Redis, returning the wait
+    async fn reserve_slot(&self, mut conn: ConnectionManager, domain: &str, base: Duration) -> Result<Duration> {
+        let wait_ms: u64 = Script::new(RESERVE_SLOT_SCRIPT)
+            .key(Self::domain_key(domain))
+            .key(DOMAINS_KEY)
+            .arg(base.as_millis() as u64)
+            .arg(SHARED_STATE_TTL_MS)
+            .arg(domain)
+            .invoke_async(&mut conn)
+            .await?;
+
+        Ok(Duration::from_millis(wait_ms))
+    }
+
+    /// Adjust the domain's adaptive delay after a request
+    pub async fn record_outcome(&self, domain: &str, outcome: RequestOutcome) {
+        let base = self.base_delay(domain);
+
+        if let Some(conn) = &self.redis {
+            match Self::record_shared(conn.clone(), domain, outcome, base).await {
+                Ok(delay) => {
+                    if let RequestOutcome::Overload(_) = outcome {
+                        warn!("{} is overloaded, slowing down to {:?}", domain, delay);
+                    }
+                    return;
+                }
+                Err(e) => warn!("Shared politeness state unavailable for {}, using local state: {}", domain, e),
+            }
+        }
+
+        let mut adaptive = self.adaptive.entry(domain.to_string()).or_default();
+        adaptive.record(outcome, base);
+        if let RequestOutcome::Overload(_) = outcome {
+            warn!("{} is overloaded, slowing down to {:?}", domain, adaptive.effective(base));
+        }
+    }
+
+    async fn record_shared(
+        mut conn: ConnectionManager,
+        domain: &str,
+        outcome: RequestOutcome,
+        base: Duration,
+    ) -> Result<Duration> {
+        let delay_ms: u64 = Script::new(FEEDBACK_SCRIPT)
+            .key(Self::domain_key(domain))
+            .key(DOMAINS_KEY)
+            .arg(outcome.as_str())
+            .arg(base.as_millis() as u64)
+            .arg(outcome.pause().as_millis() as u64)
+            .arg(MIN_ADAPTIVE_DELAY.as_millis() as u64)
+            .arg(MAX_ADAPTIVE_DELAY.as_millis() as u64)
+            .arg(ADDITIVE_DECREASE.as_millis() as u64)
+            .arg(ERROR_SPIKE_THRESHOLD)
+            .arg(SHARED_STATE_TTL_MS)
+            .arg(domain)
+            .invoke_async(&mut conn)
+            .await?;
+
+        Ok(Duration::from_millis(delay_ms))
+    }
+
+    /// Delay currently kept between requests to a domain
+    pub async fn current_delay(&self, domain: &str) -> Duration {
+        let base = self.base_delay(domain);
+
+        if let Some(conn) = &self.redis {
+            let mut conn = conn.clone();
+            let delay: redis::RedisResult<Option<u64>> = conn.hget(Self::domain_key(domain), "delay").await;
+            match delay {
+                Ok(delay) => return Duration::from_millis(delay.unwrap_or(0)).max(base),
+                Err(e) => warn!("Shared politeness state unavailable for {}, using local state: {}", domain, e),
+            }
+        }
+
+        self.adaptive
+            .get(domain)
+            .map_or(base, |adaptive| adaptive.effective(base))
+    }
+
+    /// Current pacing of every domain crawled recently
+    pub async fn domain_delays(&self) -> Vec<DomainDelay> {
+        if let Some(conn) = &self.redis {
+            match Self::load_shared_delays(conn.clone()).await {
+                Ok(delays) => return delays,
+                Err(e) => warn!("Shared politeness state unavailable: {}", e),
+            }
+        }
+
+        let domains: BTreeSet<String> = self
+            .last_request
+            .iter()
+            .map(|entry| entry.key().clone())
+            .chain(self.adaptive.iter().map(|entry| entry.key().clone()))
+            .collect();
+
+        domains
+            .into_iter()
+            .map(|domain| {
+                let base = self.base_delay(&domain);
+                let adaptive = self.adaptive.get(&domain).map(|entry| entry.clone()).unwrap_or_default();
+                let paused_until = adaptive
+                    .paused_until
+                    .map(|until| until.saturating_duration_since(Instant::now()))
+                    .filter(|remaining| !remaining.is_zero())
+                    .and_then(|remaining| Some(Utc::now() + chrono::Duration::from_std(remaining).ok()?));
+
+                DomainDelay {
+                    delay_ms: adaptive.effective(base).as_millis() as u64,
+                    base_delay_ms: base.as_millis() as u64,
+                    paused_until,
+                    domain,
+                }
+            })
+            .collect()
+    }
+
+    async fn load_shared_delays(mut conn: ConnectionManager) -> Result<Vec<DomainDelay>> {
+        let mut domains: Vec<String> = conn.smembers(DOMAINS_KEY).await?;
+        domains.sort();
+
+        let now = Utc::now();
+        let mut delays = Vec::with_capacity(domains.len());
+        for domain in domains {
+            let (delay, base, paused_until): (Option<u64>, Option<u64>, Option<i64>) = redis::cmd("HMGET")
+                .arg(Self::domain_key(&domain))
+                .arg("delay")
+                .arg("base")
+                .arg("paused_until")
+                .query_async(&mut conn)
+                .await?;
+
+            // The state expired; drop the domain from the index
+            let Some(base) = base else {
+                let _: () = conn.srem(DOMAINS_KEY, &domain).await?;
+                continue;
+            };
+
+            delays.push(DomainDelay {
+                delay_ms: delay.unwrap_or(0).max(base),
+                base_delay_ms: base,
+                paused_until: paused_until
+                    .and_then(|until| Utc.timestamp_millis_opt(until).single())
+                    .filter(|until| *until > now),
+                domain,
+            });
+        }
+
+        Ok(delays)
+    }
+
+    /// robots.txt crawl-delay of a domain, or the default delay
+    fn base_delay(&self, domain: &str) -> Duration {
+        self.crawl_delays
+            .get(domain)
+            .map(|d| *d)
+            .unwrap_or(self.default_delay)
+    }
+
+    fn domain_key(domain: &str) -> String {
+        format!("crawler:politeness:{}", domain)
+    }
+
+
     /// Set custom crawl delay for a domain (typically from robots.txt)
     pub fn set_crawl_delay(&self, domain: &str, delay_secs: f64) {
         let delay = Duration::from_secs_f64(delay_secs);
@@ -153,6 +552,7 @@ impl PolitenessManager {
         PolitenessStats {
             tracked_domains: self.last_request.len(),
             domains_with_custom_delays: self.crawl_delays.len(),
+            shared: self.redis.is_some(),
             default_delay_ms: self.default_delay.as_millis() as u64,
             max_retries: self.max_retries,
         }
@@ -161,6 +561,7 @@ impl PolitenessManager {
     /// Clear all tracking data (useful for testing)
     pub fn clear(&self) {
         self.last_request.clear();
+        self.adaptive.clear();
         self.crawl_delays.clear();
         info!("Cleared politeness tracking data");
     }
@@ -170,6 +571,8 @@ impl PolitenessManager {
 pub struct PolitenessStats {
     pub tracked_domains: usize,
     pub domains_with_custom_delays: usize,
+    /// Whether last-request times are shared through Redis
+    pub shared: bool,
     pub default_delay_ms: u64,
     pub max_retries: u32,
 }
@@ -256,4 +659,94 @@ mod tests {
         assert!(result.is_ok());
         assert_eq!(attempt_count, 3);
     }
+
+    #[test]
+    fn test_adaptive_delay_aimd() {
+        let base = Duration::from_millis(500);
+        let mut adaptive = AdaptiveDelay::default();
+        assert_eq!(adaptive.effective(base), base);
+
+        // Overload doubles from at least the minimum adaptive delay
+        adaptive.record(RequestOutcome::Overload(None), base);
+        assert_eq!(adaptive.effective(base), Duration::from_secs(2));
+        adaptive.record(RequestOutcome::Overload(None), base);
+        assert_eq!(adaptive.effective(base), Duration::from_secs(4));
+
+        // Successes take it back down step by step, to the base delay
+        adaptive.record(RequestOutcome::Success, base);
+        assert_eq!(adaptive.effective(base), Duration::from_millis(3500));
+        for _ in 0..10 {
+            adaptive.record(RequestOutcome::Success, base);
+        }
+        assert_eq!(adaptive.effective(base), base);
+        assert_eq!(adaptive.delay, Duration::ZERO);
+
+        // Capped at the maximum
+        for _ in 0..20 {
+            adaptive.record(RequestOutcome::Overload(None), base);
+        }
+        assert_eq!(adaptive.effective(base), MAX_ADAPTIVE_DELAY);
+    }
+
+    #[test]
+    fn test_error_spike_slows_down() {
+        let base = Duration::from_secs(1);
+        let mut adaptive = AdaptiveDelay::default();
+
+        adaptive.record(RequestOutcome::Error, base);
+        adaptive.record(RequestOutcome::Error, base);
+        assert_eq!(adaptive.effective(base), base);
+
+        // A success in between resets the spike
+        adaptive.record(RequestOutcome::Success, base);
+        adaptive.record(RequestOutcome::Error, base);
+        adaptive.record(RequestOutcome::Error, base);
+        assert_eq!(adaptive.effective(base), base);
+
+        adaptive.record(RequestOutcome::Error, base);
+        assert_eq!(adaptive.effective(base), Duration::from_secs(2));
+    }
+
+    #[tokio::test]
+    async fn test_retry_after_pause() {
+        let manager = PolitenessManager::new(10, 3);
+        let url = "https://example.com/page";
+
+        manager.wait_before_request(url).await.unwrap();
+        manager
+            .record_outcome("example.com", RequestOutcome::Overload(Some(Duration::from_millis(300))))
+            .await;
+        assert_eq!(manager.current_delay("example.com").await, Duration::from_secs(2));
+
+        let delays = manager.domain_delays().await;
+        assert_eq!(delays.len(), 1);
+        assert_eq!(delays[0].delay_ms, 2000);
+        assert_eq!(delays[0].base_delay_ms, 10);
+        assert!(delays[0].paused_until.is_some());
+
+        // The pause is honoured even though the adaptive delay is cut back
+        manager.adaptive.get_mut("example.com").unwrap().delay = Duration::ZERO;
+        let start = Instant::now();
+        manager.wait_before_request(url).await.unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(250));
+    }
+
+    #[test]
+    fn test_outcome_from_status() {
+        assert_eq!(
+            RequestOutcome::from_status(429, Some("30")),
+            Some(RequestOutcome::Overload(Some(Duration::from_secs(30))))
+        );
+        assert_eq!(RequestOutcome::from_status(503, None), Some(RequestOutcome::Overload(None)));
+        assert_eq!(RequestOutcome::from_status(502, Some("30")), Some(RequestOutcome::Error));
+        assert_eq!(RequestOutcome::from_status(304, None), Some(RequestOutcome::Success));
+        assert_eq!(RequestOutcome::from_status(404, None), None);
+    }
+
+    #[test]
+    fn test_retry_after_is_capped() {
+        let outcome = RequestOutcome::Overload(Some(Duration::from_secs(86400)));
+        assert_eq!(outcome.pause(), MAX_RETRY_AFTER);
+        assert_eq!(RequestOutcome::Error.pause(), Duration::ZERO);
+    }
 }
@@ -5,18 +5,48 @@ use governor::{
     state::{InMemoryState, NotKeyed},
     Quota, RateLimiter as GovernorRateLimiter,
 };
+use redis::aio::ConnectionManager;
+use redis::Script;
 use serde::Serialize;
 use std::num::NonZeroU32;
 use std::sync::Arc;
 use std::time::Duration;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 use url::Url;
 
+use super::politeness::{PolitenessManager, RequestOutcome};
+
+/// GCRA token bucket shared by all workers
+///
+/// The key holds the bucket's theoretical arrival time in milliseconds of
+/// Redis server time. Returns 0 when the request may proceed, otherwise the
+/// milliseconds to wait before trying again.
+const TOKEN_BUCKET_SCRIPT: &str = r#"
+local time = redis.call('TIME')
+local now = tonumber(time[1]) * 1000 + math.floor(tonumber(time[2]) / 1000)
+local interval = tonumber(ARGV[1])
+local tolerance = interval * (tonumber(ARGV[2]) - 1)
+local tat = tonumber(redis.call('GET', KEYS[1])) or now
+if tat < now then tat = now end
+if now < tat - tolerance then
+    return tat - tolerance - now
+end
+local next_tat = tat + interval
+redis.call('SET', KEYS[1], next_tat, 'PX', next_tat - now + 1000)
+return 0
+"#;
+
 /// Per-domain rate limiter using token bucket algorithm
+///
+/// Buckets live in Redis when a connection is configured, so every worker
+/// sharing it draws from the same per-domain bucket. Without Redis, or when
+/// Redis is unreachable, each process keeps its own buckets.
 #[derive(Clone)]
 pub struct RateLimiter {
     /// Map of domain -> rate limiter
     limiters: Arc<DashMap<String, Arc<GovernorRateLimiter<NotKeyed, InMemoryState, DefaultClock>>>>,
+    /// Shared bucket store
+    redis: Option<ConnectionManager>,
     /// Requests per second per domain
     requests_per_second: u32,
     /// Minimum delay between requests in milliseconds
@@ -33,20 +63,50 @@ impl RateLimiter {
 
         Self {
             limiters: Arc::new(DashMap::new()),
+            redis: None,
             requests_per_second,
             min_delay_ms,
         }
     }
 
+    /// Share per-domain buckets with other workers through Redis
+    pub fn with_redis(mut self, conn: ConnectionManager) -> Self {
+        info!("Rate limiter buckets shared through Redis");
+        self.redis = Some(conn);
+        self
+    }
+
+    /// A limiter with a different rate sharing this one's Redis buckets
+    pub fn with_rate(&self, requests_per_second: u32) -> Self {
+        let limiter = Self::new(requests_per_second, self.min_delay_ms);
+        match &self.redis {
+            Some(conn) => limiter.with_redis(conn.clone()),
+            None => limiter,
+        }
+    }
+
     /// Wait until a request to the given URL can proceed
     pub async fn wait_for(&self, url: &str) -> Result<()> {
         let domain = self.extract_domain(url)?;
 
-        // Get or create rate limiter for this domain
-        let limiter = self.get_or_create_limiter(&domain);
+        let shared = match &self.redis {
+            Some(conn) => match self.wait_shared(conn.clone(), &domain).await {
+                Ok(()) => true,
+                Err(e) => {
+                    warn!("Shared rate limiter unavailable for {}, using local limiter: {}", domain, e);
+                    false
+                }
+            },
+            None => false,
+        };
 
-        // Wait until we can proceed
-        limiter.until_ready().await;
+        if !shared {
+            // Get or create rate limiter for this domain
+            let limiter = self.get_or_create_limiter(&domain);
+
+            // Wait until we can proceed
+            limiter.until_ready().await;
+        }
 
         // Additional minimum delay enforcement
         if self.min_delay_ms > 0 {
@@ -57,7 +117,43 @@ impl RateLimiter {
         Ok(())
     }
 
+    /// Take a token from the domain's Redis bucket, waiting until one is free
+    async fn wait_shared(&self, mut conn: ConnectionManager, domain: &str) -> Result<()> {
+        let script = Script::new(TOKEN_BUCKET_SCRIPT);
+        let key = self.bucket_key(domain);
+
+        loop {
+            let wait_ms: u64 = script
+                .key(&key)
+                .arg(self.interval_ms())
+                .arg(self.requests_per_second.max(1))
+                .invoke_async(&mut conn)
+                .await?;
+
+            if wait_ms == 0 {
+                return Ok(());
+            }
+            debug!("Rate limiter: waiting {}ms for {}", wait_ms, domain);
+            tokio::time::sleep(Duration::from_millis(wait_ms)).await;
+        }
+    }
+
+    /// Redis key of the domain's bucket at this limiter's rate
+    ///
+    /// A bucket's state only makes sense for the rate it is drained at, so
+    /// limiters with different rates don't share one.
+    fn bucket_key(&self, domain: &str) -> String {
+        format!("crawler:rate:{}:{}", self.requests_per_second.max(1), domain)
+    }
+
+    /// Milliseconds between requests at the configured rate
+    fn interval_ms(&self) -> u64 {
+        (1000 / u64::from(self.requests_per_second.max(1))).max(1)
+    }
+
     /// Check if a request can proceed without waiting
+    ///
+    /// Only consults this process's buckets.
     pub fn check(&self, url: &str) -> Result<bool> {
         let domain = self.extract_domain(url)?;
         let limiter = self.get_or_create_limiter(&domain);
@@ -102,6 +198,7 @@ impl RateLimiter {
     pub fn stats(&self) -> RateLimiterStats {
         RateLimiterStats {
             tracked_domains: self.limiters.len(),
+            shared: self.redis.is_some(),
             requests_per_second: self.requests_per_second,
             min_delay_ms: self.min_delay_ms,
         }
@@ -114,9 +211,69 @@ impl RateLimiter {
     }
 }
 
+/// The waits every crawler request goes through: the domain's rate limit,
+/// then its politeness delay
+///
+/// Requests send each attempt, retries included, through the gate, which
+/// reports how the server answered to the adaptive politeness delay. The
+/// default gate doesn't wait.
+#[derive(Clone, Default)]
+pub struct RequestGate {
+    limits: Option<(RateLimiter, PolitenessManager)>,
+}
+
+impl RequestGate {
+    pub fn new(rate_limiter: RateLimiter, politeness: PolitenessManager) -> Self {
+        Self {
+            limits: Some((rate_limiter, politeness)),
+        }
+    }
+
+    /// Wait until a request to the given URL can proceed
+    pub async fn wait(&self, url: &str) -> Result<()> {
+        if let Some((rate_limiter, politeness)) = &self.limits {
+            rate_limiter.wait_for(url).await?;
+            politeness.wait_before_request(url).await?;
+        }
+        Ok(())
+    }
+
+    /// Wait for the gate, send the request and record its outcome
+    pub async fn send(&self, url: &str, request: reqwest::RequestBuilder) -> reqwest::Result<reqwest::Response> {
+        if let Err(e) = self.wait(url).await {
+            warn!("Rate limiting error for {}: {}", url, e);
+        }
+        let result = request.send().await;
+
+        let Some((_, politeness)) = &self.limits else {
+            return result;
+        };
+        let Some(host) = Url::parse(url).ok().and_then(|url| url.host_str().map(str::to_string)) else {
+            return result;
+        };
+        let outcome = match &result {
+            Ok(response) => RequestOutcome::from_status(
+                response.status().as_u16(),
+                response
+                    .headers()
+                    .get(reqwest::header::RETRY_AFTER)
+                    .and_then(|value| value.to_str().ok()),
+            ),
+            Err(_) => Some(RequestOutcome::Error),
+        };
+        if let Some(outcome) = outcome {
+            politeness.record_outcome(&host, outcome).await;
+        }
+
+        result
+    }
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct RateLimiterStats {
     pub tracked_domains: usize,
+    /// Whether buckets are shared through Redis
+    pub shared: bool,
     pub requests_per_second: u32,
     pub min_delay_ms: u64,
 }
@@ -171,4 +328,18 @@ mod tests {
         let stats = limiter.stats();
         assert_eq!(stats.tracked_domains, 2);
     }
+
+    #[test]
+    fn test_bucket_key_includes_rate() {
+        let limiter = RateLimiter::new(2, 0);
+        assert_eq!(limiter.bucket_key("example.com"), "crawler:rate:2:example.com");
+        assert_ne!(limiter.with_rate(5).bucket_key("example.com"), limiter.bucket_key("example.com"));
+    }
+
+    #[test]
+    fn test_interval() {
+        assert_eq!(RateLimiter::new(2, 0).interval_ms(), 500);
+        assert_eq!(RateLimiter::new(0, 0).interval_ms(), 1000);
+        assert_eq!(RateLimiter::new(5000, 0).interval_ms(), 1);
+    }
 }
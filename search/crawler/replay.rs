@@ -0,0 +1,261 @@
+use anyhow::{Context, Result};
+use scraper::{Html, Selector};
+use std::fs;
+use std::path::{Path, PathBuf};
+use url::Url;
+
+use super::url_processor::UrlProcessor;
+use super::warc::{ArchivedResponse, WarcReader, WarcRecord};
+
+/// Base URL given to saved files that don't declare their own URL
+pub const DEFAULT_REPLAY_BASE_URL: &str = "http://localhost/";
+
+/// File extensions picked up from a replay directory
+const REPLAY_EXTENSIONS: &[&str] = &["html", "htm", "xhtml", "pdf", "md", "markdown", "txt"];
+
+/// A stored response, ready to go through the extraction pipeline
+#[derive(Debug, Clone)]
+pub struct ReplayPage {
+    pub url: String,
+    /// Declared `Content-Type`; sniffed from the URL and body when `None`
+    pub content_type: Option<String>,
+    pub content_language: Option<String>,
+    /// `X-Robots-Tag` header values
+    pub robots_tags: Vec<String>,
+    pub body: Vec<u8>,
+}
+
+/// Where replayed pages come from
+#[derive(Debug, Clone)]
+pub enum ReplaySource {
+    /// `response` records of a WARC file (`.warc` or `.warc.gz`)
+    Warc(PathBuf),
+    /// Saved pages under a directory, addressed relative to `base_url`
+    /// unless they carry a canonical or `og:url` link
+    Directory { path: PathBuf, base_url: Url },
+}
+
+impl ReplaySource {
+    /// Pick the source type from the path: directories are read as saved
+    /// pages, anything else as a WARC file
+    pub fn from_path(path: &Path, base_url: Option<&str>) -> Result<Self> {
+        if path.is_dir() {
+            let base_url = Url::parse(base_url.unwrap_or(DEFAULT_REPLAY_BASE_URL))
+                .context("Invalid replay base URL")?;
+            Ok(Self::Directory {
+                path: path.to_path_buf(),
+                base_url,
+            })
+        } else if path.is_file() {
+            Ok(Self::Warc(path.to_path_buf()))
+        } else {
+            anyhow::bail!("Replay source {} does not exist", path.display())
+        }
+    }
+
+    /// Stored pages, read lazily
+    pub fn pages(&self) -> Result<Box<dyn Iterator<Item = Result<ReplayPage>> + Send>> {
+        match self {
+            Self::Warc(path) => {
+                let records = WarcReader::open(path)?;
+                Ok(Box::new(records.filter_map(|record| match record {
+                    Ok(record) => warc_page(&record).transpose(),
+                    Err(e) => Some(Err(e)),
+                })))
+            }
+            Self::Directory { path, base_url } => {
+                let mut files = Vec::new();
+                collect_files(path, &mut files)?;
+                files.sort();
+
+                let root = path.clone();
+                let base_url = base_url.clone();
+                Ok(Box::new(
+                    files.into_iter().map(move |file| directory_page(&root, &file, &base_url)),
+                ))
+            }
+        }
+    }
+}
+
+/// Turn a successful `response` record into a page; other records are skipped
+fn warc_page(record: &WarcRecord) -> Result<Option<ReplayPage>> {
+    if record.warc_type != "response" {
+        return Ok(None);
+    }
+    let Some(url) = record.header("WARC-Target-URI") else {
+        return Ok(None);
+    };
+    // Some writers wrap the URI in angle brackets (WARC 1.0 examples)
+    let url = url.trim_start_matches('<').trim_end_matches('>').to_string();
+
+    let response = ArchivedResponse::parse(&record.block)
+        .with_context(|| format!("Invalid HTTP response for {}", url))?;
+    if !(200..300).contains(&response.status) {
+        return Ok(None);
+    }
+
+    Ok(Some(ReplayPage {
+        content_type: response.header("Content-Type").map(|value| value.to_string()),
+        content_language: response.header("Content-Language").map(|value| value.to_string()),
+        robots_tags: response
+            .headers
+            .iter()
+            .filter(|(name, _)| name.eq_ignore_ascii_case("X-Robots-Tag"))
+            .map(|(_, value)| value.clone())
+            .collect(),
+        url,
+        body: response.body,
+    }))
+}
+
+fn collect_files(directory: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(directory)
+        .with_context(|| format!("Failed to read replay directory {}", directory.display()))?
+    {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(&path, files)?;
+        } else if path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| REPLAY_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+        {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn directory_page(root: &Path, file: &Path, base_url: &Url) -> Result<ReplayPage> {
+    let body = fs::read(file).with_context(|| format!("Failed to read {}", file.display()))?;
+
+    let relative: Vec<String> = file
+        .strip_prefix(root)
+        .unwrap_or(file)
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    let fallback = base_url
+        .join(&relative.join("/"))
+        .with_context(|| format!("Cannot address {} under {}", file.display(), base_url))?;
+
+    let is_html = file
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| matches!(ext.to_ascii_lowercase().as_str(), "html" | "htm" | "xhtml"));
+
+    // Saved pages usually still say where they came from
+    let url = is_html
+        .then(|| declared_url(&String::from_utf8_lossy(&body)))
+        .flatten()
+        .unwrap_or_else(|| fallback.to_string());
+
+    Ok(ReplayPage {
+        url,
+        content_type: None,
+        content_language: None,
+        robots_tags: Vec::new(),
+        body,
+    })
+}
+
+/// Absolute `<link rel="canonical">` or `og:url` of a saved page
+fn declared_url(html: &str) -> Option<String> {
+    let is_absolute = |href: &str| {
+        Url::parse(href.trim())
+            .ok()
+            .filter(|url| matches!(url.scheme(), "http" | "https"))
+            .map(|url| url.to_string())
+    };
+
+    UrlProcessor::extract_canonical(html)
+        .and_then(|href| is_absolute(&href))
+        .or_else(|| {
+            let document = Html::parse_document(html);
+            let selector = Selector::parse(r#"meta[property="og:url"]"#).ok()?;
+            let meta = document.select(&selector).next()?;
+            is_absolute(meta.value().attr("content")?)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::crawler::warc::{HttpExchange, WarcWriter};
+    use chrono::Utc;
+
+    fn temp_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("replay-test-{}", uuid::Uuid::new_v4()))
+    }
+
+    #[test]
+    fn test_directory_source() {
+        let dir = temp_dir();
+        fs::create_dir_all(dir.join("docs")).unwrap();
+        fs::write(dir.join("index.html"), "<html><head><title>Home</title></head></html>").unwrap();
+        fs::write(
+            dir.join("docs/guide.html"),
+            r#"<html><head><link rel="canonical" href="https://example.com/guide"></head></html>"#,
+        )
+        .unwrap();
+        fs::write(dir.join("docs/notes.md"), "# Notes").unwrap();
+        fs::write(dir.join("style.css"), "body {}").unwrap();
+
+        let source = ReplaySource::from_path(&dir, Some("https://mirror.test/site/")).unwrap();
+        let pages: Vec<ReplayPage> = source.pages().unwrap().collect::<Result<_>>().unwrap();
+        let urls: Vec<&str> = pages.iter().map(|page| page.url.as_str()).collect();
+
+        assert_eq!(
+            urls,
+            vec![
+                "https://example.com/guide",
+                "https://mirror.test/site/docs/notes.md",
+                "https://mirror.test/site/index.html",
+            ]
+        );
+        assert_eq!(pages[1].body, b"# Notes");
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_warc_source() {
+        let dir = temp_dir();
+        let writer = WarcWriter::create(&dir, "job", "test").await.unwrap();
+        for (url, status) in [("https://example.com/", 200), ("https://example.com/missing", 404)] {
+            writer
+                .write_exchange(&HttpExchange {
+                    url,
+                    request_headers: Vec::new(),
+                    status,
+                    response_headers: vec![
+                        ("Content-Type".to_string(), "text/html".to_string()),
+                        ("Content-Language".to_string(), "fr".to_string()),
+                    ],
+                    body: b"<html>Bonjour</html>",
+                    fetched_at: Utc::now(),
+                    metadata: Vec::new(),
+                })
+                .await
+                .unwrap();
+        }
+
+        let source = ReplaySource::from_path(writer.path(), None).unwrap();
+        let pages: Vec<ReplayPage> = source.pages().unwrap().collect::<Result<_>>().unwrap();
+
+        // Only the successful response is replayed
+        assert_eq!(pages.len(), 1);
+        assert_eq!(pages[0].url, "https://example.com/");
+        assert_eq!(pages[0].content_type.as_deref(), Some("text/html"));
+        assert_eq!(pages[0].content_language.as_deref(), Some("fr"));
+        assert_eq!(pages[0].body, b"<html>Bonjour</html>");
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_missing_source() {
+        assert!(ReplaySource::from_path(Path::new("/nonexistent/replay"), None).is_err());
+    }
+}
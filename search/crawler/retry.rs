@@ -1,4 +1,5 @@
 use anyhow::Result;
+use chrono::{DateTime, NaiveDateTime, Utc};
 use std::time::Duration;
 use tokio::time::sleep;
 use tracing::{debug, warn};
@@ -59,6 +60,34 @@ impl RetryConfig {
     }
 }
 
+/// Whether a status says the server is overloaded (429, 503)
+pub fn is_overload_status(status_code: u16) -> bool {
+    matches!(status_code, 429 | 503)
+}
+
+/// Obsolete HTTP date formats recipients must accept (RFC 9110 §5.6.7):
+/// RFC 850 and ANSI C asctime, both in GMT
+const OBSOLETE_HTTP_DATE_FORMATS: &[&str] = &["%A, %d-%b-%y %H:%M:%S GMT", "%a %b %e %H:%M:%S %Y"];
+
+/// Parse a `Retry-After` value: delay seconds or an HTTP date
+///
+/// Dates in the past give a zero delay.
+pub fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+
+    let date = match DateTime::parse_from_rfc2822(value) {
+        Ok(date) => date.with_timezone(&Utc),
+        Err(_) => OBSOLETE_HTTP_DATE_FORMATS
+            .iter()
+            .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())?
+            .and_utc(),
+    };
+    Some((date - Utc::now()).to_std().unwrap_or(Duration::ZERO))
+}
+
 /// Retry policy for HTTP requests
 #[derive(Clone, Debug)]
 pub struct RetryPolicy {
@@ -141,7 +170,24 @@ impl RetryPolicy {
 
                     // If status is retryable and we haven't exceeded max retries
                     if self.config.is_retryable(status.as_u16()) && attempt < self.config.max_retries {
-                        let delay = self.config.calculate_delay(attempt);
+                        let mut delay = self.config.calculate_delay(attempt);
+
+                        if is_overload_status(status.as_u16()) {
+                            let retry_after = response
+                                .headers()
+                                .get(reqwest::header::RETRY_AFTER)
+                                .and_then(|value| value.to_str().ok())
+                                .and_then(parse_retry_after);
+                            if let Some(retry_after) = retry_after {
+                                // Longer pauses are left to the caller
+                                if retry_after.as_secs_f64() > self.config.max_delay_secs {
+                                    debug!("{} asks to retry after {:?}, not retrying", url, retry_after);
+                                    return Ok(response);
+                                }
+                                delay = delay.max(retry_after);
+                            }
+                        }
+
                         warn!(
                             "HTTP request to {} returned retryable status {} (attempt {}), retrying in {:?}",
                             url,
@@ -168,7 +214,8 @@ impl RetryPolicy {
                             attempt + 1,
                             e
                         );
-                        return Err(anyhow::anyhow!("Max retries exceeded: {}", e));
+                        // Keep the request error so callers can tell timeouts apart
+                        return Err(anyhow::Error::new(e).context("Max retries exceeded"));
                     }
 
                     let delay = self.config.calculate_delay(attempt);
@@ -424,4 +471,32 @@ mod tests {
         assert!(!config.is_retryable(403));
         assert!(!config.is_retryable(301));
     }
+
+    #[test]
+    fn test_parse_retry_after() {
+        assert_eq!(parse_retry_after("120"), Some(Duration::from_secs(120)));
+        assert_eq!(parse_retry_after(" 0 "), Some(Duration::ZERO));
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"), Some(Duration::ZERO));
+        assert_eq!(parse_retry_after("soon"), None);
+        assert_eq!(parse_retry_after("-5"), None);
+
+        let date = (Utc::now() + chrono::Duration::seconds(90)).to_rfc2822();
+        let delay = parse_retry_after(&date).unwrap();
+        assert!(delay > Duration::from_secs(85) && delay <= Duration::from_secs(90));
+
+        // Obsolete RFC 850 and asctime dates
+        assert_eq!(parse_retry_after("Sunday, 06-Nov-94 08:49:37 GMT"), Some(Duration::ZERO));
+        assert_eq!(parse_retry_after("Sun Nov  6 08:49:37 1994"), Some(Duration::ZERO));
+        let later = Utc::now() + chrono::Duration::seconds(90);
+        let rfc850 = later.format("%A, %d-%b-%y %H:%M:%S GMT").to_string();
+        let asctime = later.format("%a %b %e %H:%M:%S %Y").to_string();
+        for date in [rfc850, asctime] {
+            let delay = parse_retry_after(&date).unwrap();
+            assert!(delay > Duration::from_secs(85) && delay <= Duration::from_secs(90), "{}", date);
+        }
+
+        assert!(is_overload_status(429));
+        assert!(is_overload_status(503));
+        assert!(!is_overload_status(500));
+    }
 }
@@ -2,16 +2,58 @@ use anyhow::Result;
 use dashmap::DashMap;
 use serde::Serialize;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tracing::{debug, info, warn};
 use url::Url;
 
+use super::rate_limiter::RequestGate;
+use super::read_body_prefix;
 use super::retry::RetryPolicy;
 
+/// Longest a fetched robots.txt is used before it is fetched again (RFC 9309 §2.4)
+const ROBOTS_CACHE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// How soon an unreachable robots.txt is tried again
+const UNREACHABLE_RETRY: Duration = Duration::from_secs(10 * 60);
+
+/// How long an unreachable robots.txt means "disallow all"; after that the
+/// site is treated as having none (RFC 9309 §2.3.1.4)
+const UNREACHABLE_GRACE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// Bytes of robots.txt that are parsed, the minimum RFC 9309 §2.5 requires
+const MAX_ROBOTS_SIZE: usize = 500 * 1024;
+
+/// Redirects followed when fetching robots.txt (RFC 9309 §2.3.1.2)
+const MAX_REDIRECTS: usize = 5;
+
+/// One `Allow` or `Disallow` line
+#[derive(Debug, Clone, PartialEq)]
+struct RobotsRule {
+    allow: bool,
+    /// Percent-encoding normalized; may contain `*` and a trailing `$`
+    pattern: String,
+}
+
+impl RobotsRule {
+    /// Octets matched, used to pick the most specific rule
+    fn specificity(&self) -> usize {
+        self.pattern.len()
+    }
+
+    fn matches(&self, path: &str) -> bool {
+        match self.pattern.strip_suffix('$') {
+            Some(pattern) => wildcard_match(pattern.as_bytes(), path.as_bytes()),
+            // Patterns match as prefixes unless anchored with `$`
+            None => wildcard_match(format!("{}*", self.pattern).as_bytes(), path.as_bytes()),
+        }
+    }
+}
+
 /// Robots.txt rules for a domain
 #[derive(Debug, Clone)]
 pub struct RobotsRules {
-    /// Disallowed paths for our user agent
-    disallowed_paths: Vec<String>,
+    /// Rules of every group for our user agent, merged
+    rules: Vec<RobotsRule>,
     /// Crawl delay in seconds
     crawl_delay: Option<f64>,
     /// Sitemap URLs
@@ -22,120 +64,243 @@ impl RobotsRules {
     /// Create a permissive robots rules (allow all)
     fn permissive() -> Self {
         Self {
-            disallowed_paths: Vec::new(),
+            rules: Vec::new(),
             crawl_delay: None,
             sitemaps: Vec::new(),
         }
     }
 
-    /// Parse robots.txt content
+    /// Rules for a site whose robots.txt is unreachable (disallow all)
+    fn disallow_all() -> Self {
+        Self {
+            rules: vec![RobotsRule {
+                allow: false,
+                pattern: "/".to_string(),
+            }],
+            crawl_delay: None,
+            sitemaps: Vec::new(),
+        }
+    }
+
+    /// Parse robots.txt content (RFC 9309)
+    ///
+    /// The groups whose user-agent line names our product token (compared
+    /// case-insensitively) are merged; without one, all `*` groups are. A
+    /// group is one or more consecutive user-agent lines followed by rules.
     fn parse(content: &str, user_agent: &str) -> Self {
-        let mut disallowed_paths = Vec::new();
-        let mut crawl_delay = None;
-        let mut sitemaps = Vec::new();
-        let mut current_user_agent = String::new();
-        let mut matches_our_agent = false;
+        let product = product_token(user_agent);
+
+        let mut specific = Vec::new();
+        let mut wildcard = Vec::new();
+        let mut specific_delay = None;
+        let mut wildcard_delay = None;
         let mut has_specific_match = false;
+        let mut sitemaps = Vec::new();
 
-        // First pass: check if there's a specific user-agent match
+        // User agents of the group being read, and whether its rules started
+        let mut group_agents: Vec<String> = Vec::new();
+        let mut in_rules = false;
+
+        let content = content.strip_prefix('\u{feff}').unwrap_or(content);
         for line in content.lines() {
-            let line = line.trim();
-            if line.is_empty() || line.starts_with('#') {
+            // Comments run from `#` to the end of the line
+            let line = line.split('#').next().unwrap_or_default().trim();
+            let Some((key, value)) = line.split_once(':') else {
                 continue;
-            }
-
-            if let Some((key, value)) = line.split_once(':') {
-                let key = key.trim().to_lowercase();
-                let value = value.trim();
-
-                if key == "user-agent" {
-                    let agent = value.to_lowercase();
-                    if agent == user_agent.to_lowercase() || user_agent.to_lowercase().contains(&agent) && agent != "*" {
-                        has_specific_match = true;
-                        break;
+            };
+            let key = key.trim().to_ascii_lowercase();
+            let value = value.trim();
+
+            let matches_specific = group_agents.iter().any(|agent| agent == &product);
+            let matches_wildcard = group_agents.iter().any(|agent| agent == "*");
+
+            match key.as_str() {
+                "user-agent" | "useragent" | "user agent" => {
+                    if in_rules {
+                        group_agents.clear();
+                        in_rules = false;
                     }
+                    let agent = product_token(value);
+                    has_specific_match |= !product.is_empty() && agent == product;
+                    group_agents.push(if value == "*" { "*".to_string() } else { agent });
                 }
-            }
-        }
-
-        // Second pass: collect rules
-        for line in content.lines() {
-            let line = line.trim();
-
-            // Skip empty lines and comments
-            if line.is_empty() || line.starts_with('#') {
-                continue;
-            }
-
-            // Parse key-value pairs
-            if let Some((key, value)) = line.split_once(':') {
-                let key = key.trim().to_lowercase();
-                let value = value.trim();
-
-                match key.as_str() {
-                    "user-agent" => {
-                        current_user_agent = value.to_lowercase();
-                        // If we have a specific match, only match that specific agent
-                        // If no specific match, use wildcard
-                        if has_specific_match {
-                            matches_our_agent = current_user_agent == user_agent.to_lowercase()
-                                || (user_agent.to_lowercase().contains(&current_user_agent) && current_user_agent != "*");
-                        } else {
-                            matches_our_agent = current_user_agent == "*";
-                        }
+                "allow" | "disallow" if !group_agents.is_empty() => {
+                    in_rules = true;
+                    // An empty rule matches nothing
+                    if value.is_empty() {
+                        continue;
                     }
-                    "disallow" if matches_our_agent => {
-                        if !value.is_empty() {
-                            disallowed_paths.push(value.to_string());
-                        }
+                    let rule = RobotsRule {
+                        allow: key == "allow",
+                        pattern: normalize_encoding(value),
+                    };
+                    if matches_specific {
+                        specific.push(rule.clone());
                     }
-                    "crawl-delay" if matches_our_agent && crawl_delay.is_none() => {
-                        if let Ok(delay) = value.parse::<f64>() {
-                            crawl_delay = Some(delay);
-                        }
+                    if matches_wildcard {
+                        wildcard.push(rule);
                     }
-                    "sitemap" => {
-                        sitemaps.push(value.to_string());
+                }
+                "crawl-delay" if !group_agents.is_empty() => {
+                    in_rules = true;
+                    if let Ok(delay) = value.parse::<f64>() {
+                        if matches_specific && specific_delay.is_none() {
+                            specific_delay = Some(delay);
+                        }
+                        if matches_wildcard && wildcard_delay.is_none() {
+                            wildcard_delay = Some(delay);
+                        }
                     }
-                    _ => {}
                 }
+                // Sitemaps don't belong to any group
+                "sitemap" if !value.is_empty() => {
+                    sitemaps.push(value.to_string());
+                }
+                _ => {}
             }
         }
 
+        let (rules, crawl_delay) = if has_specific_match {
+            (specific, specific_delay)
+        } else {
+            (wildcard, wildcard_delay)
+        };
+
         Self {
-            disallowed_paths,
+            rules,
             crawl_delay,
             sitemaps,
         }
     }
 
-    /// Check if a path is allowed
+    /// Check if a path (with its query string) is allowed
+    ///
+    /// The rule matching the most octets wins; between an allow and a
+    /// disallow rule of the same length, allow wins. `/robots.txt` is always
+    /// allowed.
     fn is_path_allowed(&self, path: &str) -> bool {
-        // If no disallow rules, allow everything
-        if self.disallowed_paths.is_empty() {
+        if path == "/robots.txt" {
             return true;
         }
 
-        // Check if path matches any disallow rule
-        for disallowed in &self.disallowed_paths {
-            if path.starts_with(disallowed) {
-                return false;
+        let path = normalize_encoding(path);
+        self.rules
+            .iter()
+            .filter(|rule| rule.matches(&path))
+            .max_by_key(|rule| (rule.specificity(), rule.allow))
+            .is_none_or(|rule| rule.allow)
+    }
+}
+
+/// The product token of a user agent: `ArackBot` for `ArackBot/1.0 (+https://arack.io/bot)`
+///
+/// Lowercased; only letters, `-` and `_` are part of a product token.
+pub(crate) fn product_token(user_agent: &str) -> String {
+    user_agent
+        .trim()
+        .chars()
+        .take_while(|c| c.is_ascii_alphabetic() || *c == '-' || *c == '_')
+        .collect::<String>()
+        .to_ascii_lowercase()
+}
+
+/// Bring a path or pattern to one percent-encoding (RFC 9309 §2.2.2)
+///
+/// Escapes of unreserved characters are decoded, other escapes get uppercase
+/// hex digits, and non-ASCII or control bytes are encoded.
+fn normalize_encoding(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut normalized = String::with_capacity(value.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let byte = bytes[i];
+        let escaped = (byte == b'%')
+            .then(|| value.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        match escaped {
+            Some(decoded) if decoded.is_ascii_alphanumeric() || b"-._~".contains(&decoded) => {
+                normalized.push(decoded as char);
+                i += 3;
+            }
+            Some(decoded) => {
+                normalized.push_str(&format!("%{:02X}", decoded));
+                i += 3;
+            }
+            None if byte >= 0x80 || byte.is_ascii_control() || byte == b' ' => {
+                normalized.push_str(&format!("%{:02X}", byte));
+                i += 1;
+            }
+            None => {
+                normalized.push(byte as char);
+                i += 1;
             }
         }
+    }
+
+    normalized
+}
 
-        true
+/// Match `text` against `pattern` in full, where `*` matches any sequence
+fn wildcard_match(pattern: &[u8], text: &[u8]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    // Position after the last `*`, and the text position it was tried at
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && pattern[p] == b'*' {
+            p += 1;
+            backtrack = Some((p, t));
+        } else if p < pattern.len() && pattern[p] == text[t] {
+            p += 1;
+            t += 1;
+        } else if let Some((star_p, star_t)) = backtrack {
+            // Let the last `*` absorb one more byte
+            p = star_p;
+            t = star_t + 1;
+            backtrack = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
     }
+
+    pattern[p..].iter().all(|&c| c == b'*')
+}
+
+/// Result of fetching robots.txt (RFC 9309 §2.3.1)
+enum RobotsFetch {
+    /// 2xx: the rules apply
+    Fetched(String),
+    /// 4xx or too many redirects: crawl without restrictions
+    Unavailable(String),
+    /// 5xx, 429 or a network error: crawl nothing for now
+    Unreachable(String),
+    /// No connection at all; over https, http is tried next
+    NoConnection(String),
+}
+
+/// Cached rules of a domain
+#[derive(Debug, Clone)]
+struct CachedRobots {
+    rules: RobotsRules,
+    expires_at: Instant,
+    /// Since when robots.txt has been unreachable, if it is
+    unreachable_since: Option<Instant>,
 }
 
 /// Robots.txt manager for respectful crawling
 #[derive(Clone)]
 pub struct RobotsManager {
     /// Cache of robots.txt rules per domain
-    robots_cache: Arc<DashMap<String, RobotsRules>>,
+    robots_cache: Arc<DashMap<String, CachedRobots>>,
     /// User agent string to use when checking robots.txt
     user_agent: String,
     /// Retry policy for fetching robots.txt
     retry_policy: RetryPolicy,
+    /// Rate limit and politeness delay of robots.txt requests
+    gate: RequestGate,
 }
 
 impl RobotsManager {
@@ -147,9 +312,21 @@ impl RobotsManager {
             robots_cache: Arc::new(DashMap::new()),
             user_agent,
             retry_policy: RetryPolicy::new(),
+            gate: RequestGate::default(),
         }
     }
 
+    /// Send robots.txt requests through the crawler's request gate
+    pub fn with_gate(mut self, gate: RequestGate) -> Self {
+        self.gate = gate;
+        self
+    }
+
+    /// Product token matched against robots.txt groups
+    pub fn product_token(&self) -> String {
+        product_token(&self.user_agent)
+    }
+
     /// Check if a URL is allowed to be crawled according to robots.txt
     pub async fn is_allowed(&self, url: &str) -> Result<bool> {
         let parsed_url = Url::parse(url)?;
@@ -161,9 +338,12 @@ impl RobotsManager {
         // Get or fetch robots.txt for this domain
         let rules = self.get_or_fetch_robots(&domain).await?;
 
-        // Check if URL is allowed
-        let path = parsed_url.path();
-        let allowed = rules.is_path_allowed(path);
+        // Rules match the path and query string
+        let path = match parsed_url.query() {
+            Some(query) => format!("{}?{}", parsed_url.path(), query),
+            None => parsed_url.path().to_string(),
+        };
+        let allowed = rules.is_path_allowed(&path);
 
         if !allowed {
             debug!("URL blocked by robots.txt: {}", url);
@@ -205,66 +385,128 @@ impl RobotsManager {
     }
 
     /// Get or fetch robots.txt for a domain
+    ///
+    /// Rules are cached for 24 hours. An unreachable robots.txt disallows
+    /// everything and is retried after a few minutes; once it has been
+    /// unreachable for 30 days, the site is crawled as if it had none.
     async fn get_or_fetch_robots(&self, domain: &str) -> Result<RobotsRules> {
         // Check cache first
-        if let Some(rules) = self.robots_cache.get(domain) {
-            return Ok(rules.clone());
+        let cached = self.robots_cache.get(domain).map(|entry| entry.clone());
+        if let Some(cached) = &cached {
+            if cached.expires_at > Instant::now() {
+                return Ok(cached.rules.clone());
+            }
         }
 
-        // Fetch robots.txt
+        let now = Instant::now();
+        let entry = match self.fetch_robots(domain).await {
+            RobotsFetch::Fetched(content) => {
+                info!("Cached robots.txt for {}", domain);
+                CachedRobots {
+                    rules: RobotsRules::parse(&content, &self.user_agent),
+                    expires_at: now + ROBOTS_CACHE_TTL,
+                    unreachable_since: None,
+                }
+            }
+            RobotsFetch::Unavailable(reason) => {
+                debug!("No robots.txt for {} ({}), allowing all", domain, reason);
+                CachedRobots {
+                    rules: RobotsRules::permissive(),
+                    expires_at: now + ROBOTS_CACHE_TTL,
+                    unreachable_since: None,
+                }
+            }
+            RobotsFetch::Unreachable(reason) | RobotsFetch::NoConnection(reason) => {
+                let since = cached
+                    .and_then(|cached| cached.unreachable_since)
+                    .unwrap_or(now);
+                let rules = if now.duration_since(since) >= UNREACHABLE_GRACE {
+                    warn!("robots.txt for {} unreachable for 30 days ({}), allowing all", domain, reason);
+                    RobotsRules::permissive()
+                } else {
+                    warn!("robots.txt for {} unreachable ({}), disallowing all", domain, reason);
+                    RobotsRules::disallow_all()
+                };
+                CachedRobots {
+                    rules,
+                    expires_at: now + UNREACHABLE_RETRY,
+                    unreachable_since: Some(since),
+                }
+            }
+        };
+
+        let rules = entry.rules.clone();
+        self.robots_cache.insert(domain.to_string(), entry);
+        Ok(rules)
+    }
+
+    /// Fetch robots.txt over https, or over http for hosts without https
+    async fn fetch_robots(&self, domain: &str) -> RobotsFetch {
         let robots_url = format!("https://{}/robots.txt", domain);
         debug!("Fetching robots.txt from {}", robots_url);
 
         match self.fetch_robots_txt(&robots_url).await {
-            Ok(content) => {
-                let rules = RobotsRules::parse(&content, &self.user_agent);
-                self.robots_cache.insert(domain.to_string(), rules.clone());
-                info!("Cached robots.txt for {}", domain);
-                Ok(rules)
-            }
-            Err(e) => {
-                warn!("Failed to fetch robots.txt for {}: {}", domain, e);
-                // On error, create permissive rules (allow all)
-                let permissive = RobotsRules::permissive();
-                self.robots_cache
-                    .insert(domain.to_string(), permissive.clone());
-                Ok(permissive)
+            RobotsFetch::NoConnection(reason) => {
+                let robots_url = format!("http://{}/robots.txt", domain);
+                debug!("No https connection to {} ({}), fetching {}", domain, reason, robots_url);
+                self.fetch_robots_txt(&robots_url).await
             }
+            fetch => fetch,
         }
     }
 
-    /// Fetch robots.txt content from URL with retry logic
-    async fn fetch_robots_txt(&self, url: &str) -> Result<String> {
-        let client = reqwest::Client::builder()
+    async fn fetch_robots_txt(&self, url: &str) -> RobotsFetch {
+        let client = match reqwest::Client::builder()
             .timeout(std::time::Duration::from_secs(10))
-            .build()?;
+            .redirect(reqwest::redirect::Policy::limited(MAX_REDIRECTS))
+            .build()
+        {
+            Ok(client) => client,
+            Err(e) => return RobotsFetch::Unreachable(e.to_string()),
+        };
 
         let user_agent = self.user_agent.clone();
         let url_owned = url.to_string();
 
         // Use retry policy for HTTP request
-        let response = self.retry_policy.execute_http(&url_owned, || {
-            let client = client.clone();
-            let url = url_owned.clone();
-            let user_agent = user_agent.clone();
-            async move {
-                client
-                    .get(&url)
-                    .header("User-Agent", user_agent)
-                    .send()
-                    .await
-            }
-        }).await?;
+        let result = self.retry_policy.execute_http(&url_owned, || {
+            let request = client.get(&url_owned).header("User-Agent", user_agent.clone());
+            self.gate.send(&url_owned, request)
+        }).await;
 
-        if response.status().is_success() {
-            let content = response.text().await?;
-            Ok(content)
+        let response = match result {
+            Ok(response) => response,
+            Err(e) => {
+                let too_many_redirects = e
+                    .chain()
+                    .filter_map(|cause| cause.downcast_ref::<reqwest::Error>())
+                    .any(|e| e.is_redirect());
+                let no_connection = e
+                    .chain()
+                    .filter_map(|cause| cause.downcast_ref::<reqwest::Error>())
+                    .any(|e| e.is_connect());
+                return if too_many_redirects {
+                    RobotsFetch::Unavailable(format!("{:#}", e))
+                } else if no_connection {
+                    RobotsFetch::NoConnection(format!("{:#}", e))
+                } else {
+                    RobotsFetch::Unreachable(format!("{:#}", e))
+                };
+            }
+        };
+
+        let status = response.status();
+        if status.is_success() {
+            // Parse at most the first 500 KiB
+            match read_body_prefix(response, MAX_ROBOTS_SIZE).await {
+                Ok(body) => RobotsFetch::Fetched(String::from_utf8_lossy(&body).into_owned()),
+                Err(e) => RobotsFetch::Unreachable(e.to_string()),
+            }
+        } else if status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            RobotsFetch::Unreachable(format!("status {}", status))
         } else {
-            // If robots.txt doesn't exist (404), allow crawling
-            Err(anyhow::anyhow!(
-                "robots.txt not found (status: {})",
-                response.status()
-            ))
+            // If robots.txt doesn't exist (404) or is forbidden, allow crawling
+            RobotsFetch::Unavailable(format!("status {}", status))
         }
     }
 
@@ -284,6 +526,11 @@ impl RobotsManager {
     pub fn stats(&self) -> RobotsStats {
         RobotsStats {
             cached_domains: self.robots_cache.len(),
+            unreachable_domains: self
+                .robots_cache
+                .iter()
+                .filter(|entry| entry.unreachable_since.is_some())
+                .count(),
         }
     }
 }
@@ -291,6 +538,8 @@ impl RobotsManager {
 #[derive(Debug, Clone, Serialize)]
 pub struct RobotsStats {
     pub cached_domains: usize,
+    /// Domains whose robots.txt could not be fetched, crawled as disallow-all
+    pub unreachable_domains: usize,
 }
 
 #[cfg(test)]
@@ -393,4 +642,181 @@ Disallow: /admin/
         assert_eq!(rules.crawl_delay, None);
         assert!(rules.sitemaps.is_empty());
     }
+
+    #[test]
+    fn test_disallow_all_rules() {
+        let rules = RobotsRules::disallow_all();
+
+        assert!(!rules.is_path_allowed("/"));
+        assert!(!rules.is_path_allowed("/page"));
+        assert!(rules.is_path_allowed("/robots.txt"));
+    }
+
+    #[test]
+    fn test_product_token() {
+        assert_eq!(product_token("ArackBot/1.0 (+https://arack.io/bot)"), "arackbot");
+        assert_eq!(product_token("Googlebot-News"), "googlebot-news");
+        assert_eq!(product_token("*"), "");
+    }
+
+    // Conformance examples from RFC 9309 and Google's robots.txt specification
+
+    /// RFC 9309 §5.1
+    const RFC_EXAMPLE: &str = r#"
+User-Agent: *
+Disallow: *.gif$
+Disallow: /example/
+Allow: /publications/
+
+User-Agent: foobot
+Disallow:/
+Allow:/example/page.html
+Allow:/example/allowed.gif
+
+User-Agent: barbot
+User-Agent: bazbot
+Disallow: /example/page.html
+
+User-Agent: quxbot
+
+EOF
+"#;
+
+    #[test]
+    fn test_rfc_simple_example() {
+        let foobot = RobotsRules::parse(RFC_EXAMPLE, "FooBot/1.0");
+        assert!(foobot.is_path_allowed("/example/page.html"));
+        assert!(foobot.is_path_allowed("/example/allowed.gif"));
+        assert!(!foobot.is_path_allowed("/example/disallowed.gif"));
+        assert!(!foobot.is_path_allowed("/publications/"));
+
+        // Both agents of a group share its rules, and don't fall back to `*`
+        for agent in ["barbot", "BazBot/2.1"] {
+            let rules = RobotsRules::parse(RFC_EXAMPLE, agent);
+            assert!(!rules.is_path_allowed("/example/page.html"));
+            assert!(rules.is_path_allowed("/example/other.html"));
+            assert!(rules.is_path_allowed("/image.gif"));
+        }
+
+        // An empty group allows everything
+        let quxbot = RobotsRules::parse(RFC_EXAMPLE, "quxbot");
+        assert!(quxbot.is_path_allowed("/example/page.html"));
+
+        let other = RobotsRules::parse(RFC_EXAMPLE, "OtherBot");
+        assert!(!other.is_path_allowed("/example/page.html"));
+        assert!(!other.is_path_allowed("/images/cat.gif"));
+        assert!(other.is_path_allowed("/images/cat.gif?size=large"));
+        assert!(other.is_path_allowed("/publications/"));
+    }
+
+    #[test]
+    fn test_rfc_longest_match() {
+        // RFC 9309 §5.2
+        let rules = RobotsRules::parse(
+            "User-Agent: foobot\nAllow: /example/page/\nDisallow: /example/page/disallowed.gif\n",
+            "foobot",
+        );
+        assert!(rules.is_path_allowed("/example/page/"));
+        assert!(!rules.is_path_allowed("/example/page/disallowed.gif"));
+    }
+
+    #[test]
+    fn test_precedence() {
+        let allowed = |robots: &str, path: &str| RobotsRules::parse(robots, "TestBot").is_path_allowed(path);
+
+        assert!(allowed("User-agent: *\nAllow: /p\nDisallow: /\n", "/page"));
+        // Equally specific: allow wins
+        assert!(allowed("User-agent: *\nAllow: /folder\nDisallow: /folder\n", "/folder/page"));
+        assert!(!allowed("User-agent: *\nAllow: /page\nDisallow: /*.htm\n", "/page.htm"));
+        assert!(allowed("User-agent: *\nAllow: /$\nDisallow: /\n", "/"));
+        assert!(!allowed("User-agent: *\nAllow: /$\nDisallow: /\n", "/page.htm"));
+        // An empty disallow disallows nothing
+        assert!(allowed("User-agent: *\nDisallow:\n", "/anything"));
+    }
+
+    #[test]
+    fn test_wildcards() {
+        let rules = |pattern: &str| RobotsRules::parse(&format!("User-agent: *\nDisallow: {}\n", pattern), "TestBot");
+
+        let fish = rules("/fish*");
+        assert!(!fish.is_path_allowed("/fish"));
+        assert!(!fish.is_path_allowed("/fish.html"));
+        assert!(!fish.is_path_allowed("/fishheads/yummy.html"));
+        assert!(fish.is_path_allowed("/Fish.asp"));
+        assert!(fish.is_path_allowed("/catfish"));
+
+        let php_end = rules("/*.php$");
+        assert!(!php_end.is_path_allowed("/filename.php"));
+        assert!(!php_end.is_path_allowed("/folder/filename.php"));
+        assert!(php_end.is_path_allowed("/filename.php?parameters"));
+        assert!(php_end.is_path_allowed("/filename.php5"));
+        assert!(php_end.is_path_allowed("/windows.PHP"));
+
+        let fish_php = rules("/fish*.php");
+        assert!(!fish_php.is_path_allowed("/fish.php"));
+        assert!(!fish_php.is_path_allowed("/fishheads/catfish.php?parameters"));
+        assert!(fish_php.is_path_allowed("/Fish.PHP"));
+    }
+
+    #[test]
+    fn test_group_merging() {
+        let robots = r#"
+user-agent: googlebot-news
+disallow: /fish
+
+user-agent: *
+disallow: /carrots
+
+user-agent: googlebot-news
+disallow: /shrimp
+"#;
+        let rules = RobotsRules::parse(robots, "Googlebot-News");
+        assert!(!rules.is_path_allowed("/fish"));
+        assert!(!rules.is_path_allowed("/shrimp"));
+        assert!(rules.is_path_allowed("/carrots"));
+
+        // Product tokens match exactly, not by prefix
+        let rules = RobotsRules::parse(robots, "Googlebot");
+        assert!(rules.is_path_allowed("/fish"));
+        assert!(!rules.is_path_allowed("/carrots"));
+    }
+
+    #[test]
+    fn test_syntax_tolerance() {
+        let robots = "\u{feff}USER-AGENT: testbot # our bot\nDISALLOW: /private # not for us\nSitemap: https://example.com/s.xml\nDisallow: /tmp\nAllow /no-colon\n";
+        let rules = RobotsRules::parse(robots, "TestBot");
+
+        assert!(!rules.is_path_allowed("/private"));
+        // A sitemap line doesn't end the group
+        assert!(!rules.is_path_allowed("/tmp/file"));
+        assert!(rules.is_path_allowed("/no-colon"));
+        assert_eq!(rules.sitemaps, vec!["https://example.com/s.xml"]);
+
+        // Rules before any user-agent line are ignored
+        let rules = RobotsRules::parse("Disallow: /\nUser-agent: *\nDisallow: /x\n", "TestBot");
+        assert!(rules.is_path_allowed("/page"));
+        assert!(!rules.is_path_allowed("/x"));
+    }
+
+    #[test]
+    fn test_percent_encoding() {
+        // RFC 9309 §2.2.2
+        let rules = RobotsRules::parse(
+            "User-agent: *\nDisallow: /foo/bar/ツ\nDisallow: /foo/bar/%62%61%7A\nDisallow: /a%2fb\n",
+            "TestBot",
+        );
+        assert!(!rules.is_path_allowed("/foo/bar/%E3%83%84"));
+        assert!(!rules.is_path_allowed("/foo/bar/baz"));
+        assert!(!rules.is_path_allowed("/a%2Fb"));
+        assert!(rules.is_path_allowed("/a/b"));
+
+        assert_eq!(normalize_encoding("/%7euser/%e3%83%84"), "/~user/%E3%83%84");
+    }
+
+    #[test]
+    fn test_robots_txt_always_allowed() {
+        let rules = RobotsRules::parse("User-agent: *\nDisallow: /\n", "TestBot");
+        assert!(rules.is_path_allowed("/robots.txt"));
+        assert!(!rules.is_path_allowed("/index.html"));
+    }
 }
@@ -0,0 +1,193 @@
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+
+use super::links::robots_directives;
+use super::robots::product_token;
+
+/// Directives that take a value (`max-snippet: 50`), so a name before a
+/// colon in `X-Robots-Tag` is not always a user agent
+const VALUED_DIRECTIVES: &[&str] = &["max-snippet", "max-image-preview", "max-video-preview", "unavailable_after"];
+
+/// Page-level robots directives from `<meta name="robots">`, meta tags
+/// naming our bot and `X-Robots-Tag` headers
+///
+/// Directives from all sources add up; the most restrictive wins. Only
+/// the snippet settings are stored with indexed documents.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RobotsDirectives {
+    /// Don't index the page
+    #[serde(skip)]
+    pub noindex: bool,
+    /// Don't follow the page's links
+    #[serde(skip)]
+    pub nofollow: bool,
+    /// Don't keep a cached copy of the page
+    #[serde(skip)]
+    pub noarchive: bool,
+    /// Don't show a text snippet in search results
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub nosnippet: bool,
+    /// Longest text snippet, in characters
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_snippet: Option<usize>,
+}
+
+impl RobotsDirectives {
+    /// Parse a comma-separated directive list (`noindex, max-snippet:50`)
+    pub fn parse(content: &str) -> Self {
+        let mut directives = Self::default();
+        for directive in robots_directives(content) {
+            directives.apply(&directive);
+        }
+        directives
+    }
+
+    /// Parse one `X-Robots-Tag` header value for the bot `product`
+    ///
+    /// Values scoped to a user agent (`otherbot: noindex`) only apply when
+    /// the agent is us.
+    pub fn from_header(value: &str, product: &str) -> Self {
+        if let Some((name, rest)) = value.split_once(':') {
+            let name = name.trim().to_ascii_lowercase();
+            if !VALUED_DIRECTIVES.contains(&name.as_str()) {
+                return if !product.is_empty() && product_token(&name) == product {
+                    Self::parse(rest)
+                } else {
+                    Self::default()
+                };
+            }
+        }
+        Self::parse(value)
+    }
+
+    /// Merge all `X-Robots-Tag` values of a response
+    pub fn from_headers<'a>(values: impl IntoIterator<Item = &'a str>, product: &str) -> Self {
+        let mut directives = Self::default();
+        for value in values {
+            directives.merge(&Self::from_header(value, product));
+        }
+        directives
+    }
+
+    /// Merge `<meta name="robots">` and `<meta name="{product}">` tags
+    pub fn from_meta(document: &Html, product: &str) -> Self {
+        let mut directives = Self::default();
+        let Ok(selector) = Selector::parse("meta[name][content]") else {
+            return directives;
+        };
+
+        for meta in document.select(&selector) {
+            let name = meta.value().attr("name").unwrap_or_default().trim().to_ascii_lowercase();
+            if name == "robots" || (!product.is_empty() && name == product) {
+                let content = meta.value().attr("content").unwrap_or_default();
+                directives.merge(&Self::parse(content));
+            }
+        }
+
+        directives
+    }
+
+    /// Add the directives of `other`, keeping the most restrictive
+    pub fn merge(&mut self, other: &Self) {
+        self.noindex |= other.noindex;
+        self.nofollow |= other.nofollow;
+        self.noarchive |= other.noarchive;
+        self.nosnippet |= other.nosnippet;
+        self.max_snippet = match (self.max_snippet, other.max_snippet) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+    }
+
+    fn apply(&mut self, directive: &str) {
+        match directive.split_once(':') {
+            Some((name, value)) if name.trim() == "max-snippet" => {
+                // -1 means no limit, 0 means no snippet
+                match value.trim().parse::<i64>() {
+                    Ok(0) => self.nosnippet = true,
+                    Ok(limit) if limit > 0 => {
+                        let limit = limit as usize;
+                        self.max_snippet = Some(self.max_snippet.map_or(limit, |max| max.min(limit)));
+                    }
+                    _ => {}
+                }
+            }
+            Some(_) => {}
+            None => match directive {
+                "noindex" => self.noindex = true,
+                "nofollow" => self.nofollow = true,
+                "none" => {
+                    self.noindex = true;
+                    self.nofollow = true;
+                }
+                "noarchive" | "nocache" => self.noarchive = true,
+                "nosnippet" => self.nosnippet = true,
+                _ => {}
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let directives = RobotsDirectives::parse("NOINDEX, follow, max-snippet: 120, noarchive");
+        assert!(directives.noindex);
+        assert!(!directives.nofollow);
+        assert!(directives.noarchive);
+        assert_eq!(directives.max_snippet, Some(120));
+
+        let directives = RobotsDirectives::parse("none");
+        assert!(directives.noindex && directives.nofollow);
+
+        assert!(RobotsDirectives::parse("max-snippet:0").nosnippet);
+        assert_eq!(RobotsDirectives::parse("max-snippet:-1"), RobotsDirectives::default());
+    }
+
+    #[test]
+    fn test_meta_tags() {
+        let html = Html::parse_document(
+            r#"<head>
+                <meta name="robots" content="nosnippet">
+                <meta name="ArackBot" content="noindex">
+                <meta name="otherbot" content="nofollow">
+            </head>"#,
+        );
+
+        let directives = RobotsDirectives::from_meta(&html, "arackbot");
+        assert!(directives.nosnippet);
+        assert!(directives.noindex);
+        assert!(!directives.nofollow);
+    }
+
+    #[test]
+    fn test_headers() {
+        let values = ["noarchive", "arackbot: nofollow", "otherbot: noindex", "max-snippet: 50"];
+        let directives = RobotsDirectives::from_headers(values, "arackbot");
+
+        assert!(directives.noarchive);
+        assert!(directives.nofollow);
+        assert!(!directives.noindex);
+        assert_eq!(directives.max_snippet, Some(50));
+    }
+
+    #[test]
+    fn test_merge_keeps_most_restrictive() {
+        let mut directives = RobotsDirectives::parse("max-snippet:100");
+        directives.merge(&RobotsDirectives::parse("max-snippet:40, noindex"));
+
+        assert!(directives.noindex);
+        assert_eq!(directives.max_snippet, Some(40));
+    }
+
+    #[test]
+    fn test_serialization_keeps_snippet_settings_only() {
+        let directives = RobotsDirectives::parse("noindex, nosnippet, max-snippet:30");
+        let json = serde_json::to_value(&directives).unwrap();
+        assert_eq!(json, serde_json::json!({"nosnippet": true, "max_snippet": 30}));
+        assert_eq!(serde_json::to_value(RobotsDirectives::default()).unwrap(), serde_json::json!({}));
+    }
+}
@@ -2,7 +2,7 @@ use anyhow::Result;
 use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
-use std::collections::BinaryHeap;
+use std::collections::{BinaryHeap, HashMap};
 use std::sync::{Arc, Mutex};
 use tracing::{debug, info};
 
@@ -18,6 +18,29 @@ pub enum CrawlFrequency {
 }
 
 impl CrawlFrequency {
+    /// Lowercase name, matching the serde representation
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CrawlFrequency::Hourly => "hourly",
+            CrawlFrequency::Daily => "daily",
+            CrawlFrequency::Weekly => "weekly",
+            CrawlFrequency::Monthly => "monthly",
+            CrawlFrequency::Never => "never",
+        }
+    }
+
+    /// Parse a lowercase frequency name (inverse of `as_str`)
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "hourly" => Some(CrawlFrequency::Hourly),
+            "daily" => Some(CrawlFrequency::Daily),
+            "weekly" => Some(CrawlFrequency::Weekly),
+            "monthly" => Some(CrawlFrequency::Monthly),
+            "never" => Some(CrawlFrequency::Never),
+            _ => None,
+        }
+    }
+
     /// Convert frequency to a Duration
     pub fn to_duration(&self) -> Option<Duration> {
         match self {
@@ -118,6 +141,17 @@ impl ScheduledCrawl {
         score
     }
 
+    /// Adapt the crawl frequency to the observed content change rate
+    ///
+    /// Pages that never changed are still revisited monthly rather than dropped.
+    pub fn apply_change_rate(&mut self, changes_per_day: f64) {
+        self.frequency = match CrawlFrequency::from_change_frequency(changes_per_day) {
+            CrawlFrequency::Never => CrawlFrequency::Monthly,
+            frequency => frequency,
+        };
+        self.update_freshness();
+    }
+
     /// Update freshness score based on time since last crawl
     pub fn update_freshness(&mut self) {
         if let Some(last_crawled) = self.last_crawled_at {
@@ -158,6 +192,8 @@ impl PartialEq for ScheduledCrawl {
 pub struct CrawlScheduler {
     /// Priority queue of scheduled crawls
     queue: Arc<Mutex<BinaryHeap<ScheduledCrawl>>>,
+    /// Tasks added since the last `take_pending` call (not yet persisted)
+    pending: Arc<Mutex<Vec<ScheduledCrawl>>>,
 }
 
 impl CrawlScheduler {
@@ -165,6 +201,7 @@ impl CrawlScheduler {
     pub fn new() -> Self {
         Self {
             queue: Arc::new(Mutex::new(BinaryHeap::new())),
+            pending: Arc::new(Mutex::new(Vec::new())),
         }
     }
 
@@ -172,6 +209,7 @@ impl CrawlScheduler {
     pub fn schedule(&self, url: String, frequency: CrawlFrequency, priority: u8) -> Result<()> {
         let crawl = ScheduledCrawl::new(url.clone(), frequency, priority);
 
+        self.pending.lock().unwrap().push(crawl.clone());
         let mut queue = self.queue.lock().unwrap();
         queue.push(crawl);
 
@@ -184,6 +222,7 @@ impl CrawlScheduler {
         let url = task.url.clone();
         let priority = task.priority;
 
+        self.pending.lock().unwrap().push(task.clone());
         let mut queue = self.queue.lock().unwrap();
         queue.push(task);
 
@@ -191,6 +230,39 @@ impl CrawlScheduler {
         Ok(())
     }
 
+    /// Merge a batch of tasks into the schedule (e.g. from a sitemap)
+    ///
+    /// URLs that are already scheduled keep their crawl history and only have
+    /// their priority and frequency updated. Returns the number of new URLs added.
+    pub fn merge_tasks(&self, tasks: Vec<ScheduledCrawl>) -> usize {
+        self.pending.lock().unwrap().extend(tasks.iter().cloned());
+        let mut queue = self.queue.lock().unwrap();
+
+        let mut existing: HashMap<String, ScheduledCrawl> = queue
+            .drain()
+            .map(|task| (task.url.clone(), task))
+            .collect();
+
+        let mut added = 0;
+        for task in tasks {
+            match existing.get_mut(&task.url) {
+                Some(current) => {
+                    current.priority = task.priority;
+                    current.frequency = task.frequency;
+                }
+                None => {
+                    existing.insert(task.url.clone(), task);
+                    added += 1;
+                }
+            }
+        }
+
+        queue.extend(existing.into_values());
+
+        debug!("Merged tasks into schedule: {} new, {} total", added, queue.len());
+        added
+    }
+
     /// Get the next URL to crawl (if any are due)
     pub fn pop_due(&self) -> Option<ScheduledCrawl> {
         let mut queue = self.queue.lock().unwrap();
@@ -232,7 +304,9 @@ impl CrawlScheduler {
     }
 
     /// Reschedule a task after crawling
-    pub fn reschedule(&self, mut task: ScheduledCrawl) -> Result<()> {
+    ///
+    /// Returns the updated task so callers can persist the new crawl times.
+    pub fn reschedule(&self, mut task: ScheduledCrawl) -> Result<ScheduledCrawl> {
         task.mark_crawled();
 
         // Only reschedule if frequency is not Never
@@ -242,7 +316,19 @@ impl CrawlScheduler {
             info!("Rescheduled {} for {}", task.url, task.next_crawl_at);
         }
 
-        Ok(())
+        Ok(task)
+    }
+
+    /// Replace the whole schedule (e.g. after reloading it from persistent storage)
+    pub fn replace_all(&self, tasks: Vec<ScheduledCrawl>) {
+        let mut queue = self.queue.lock().unwrap();
+        queue.clear();
+        queue.extend(tasks);
+    }
+
+    /// Take the tasks added since the last call, so they can be persisted
+    pub fn take_pending(&self) -> Vec<ScheduledCrawl> {
+        std::mem::take(&mut *self.pending.lock().unwrap())
     }
 
     /// Get the number of scheduled tasks
@@ -263,6 +349,7 @@ impl CrawlScheduler {
 
     /// Clear all scheduled tasks
     pub fn clear(&self) {
+        self.pending.lock().unwrap().clear();
         let mut queue = self.queue.lock().unwrap();
         queue.clear();
         info!("Cleared all scheduled tasks");
@@ -376,6 +463,25 @@ mod tests {
         assert!(crawl1.scheduling_score() > crawl2.scheduling_score());
     }
 
+    #[test]
+    fn test_apply_change_rate() {
+        let mut crawl = ScheduledCrawl::new(
+            "https://example.com".to_string(),
+            CrawlFrequency::Hourly,
+            50,
+        );
+
+        crawl.apply_change_rate(2.0);
+        assert_eq!(crawl.frequency, CrawlFrequency::Daily);
+
+        // Stable pages are visited less often, but never dropped
+        crawl.apply_change_rate(0.0);
+        assert_eq!(crawl.frequency, CrawlFrequency::Monthly);
+
+        crawl.mark_crawled();
+        assert!(crawl.next_crawl_at > Utc::now() + Duration::days(29));
+    }
+
     #[test]
     fn test_scheduler_basic() {
         let scheduler = CrawlScheduler::new();
@@ -472,6 +578,69 @@ mod tests {
         assert_eq!(scheduler.len(), 5);
     }
 
+    #[test]
+    fn test_scheduler_merge_tasks() {
+        let scheduler = CrawlScheduler::new();
+
+        scheduler.schedule(
+            "https://example.com/a".to_string(),
+            CrawlFrequency::Daily,
+            50,
+        ).unwrap();
+
+        let added = scheduler.merge_tasks(vec![
+            ScheduledCrawl::new("https://example.com/a".to_string(), CrawlFrequency::Weekly, 80),
+            ScheduledCrawl::new("https://example.com/b".to_string(), CrawlFrequency::Monthly, 20),
+        ]);
+
+        // Only the new URL is added, the existing one is updated in place
+        assert_eq!(added, 1);
+        assert_eq!(scheduler.len(), 2);
+
+        let task = scheduler.pop_due().unwrap();
+        assert_eq!(task.url, "https://example.com/a");
+        assert_eq!(task.priority, 80);
+        assert_eq!(task.frequency, CrawlFrequency::Weekly);
+    }
+
+    #[test]
+    fn test_scheduler_pending_and_replace() {
+        let scheduler = CrawlScheduler::new();
+
+        scheduler.schedule(
+            "https://example.com/a".to_string(),
+            CrawlFrequency::Daily,
+            50,
+        ).unwrap();
+
+        let pending = scheduler.take_pending();
+        assert_eq!(pending.len(), 1);
+        assert!(scheduler.take_pending().is_empty());
+
+        // Replacing the schedule does not mark tasks as pending
+        scheduler.replace_all(vec![
+            ScheduledCrawl::new("https://example.com/b".to_string(), CrawlFrequency::Weekly, 10),
+            ScheduledCrawl::new("https://example.com/c".to_string(), CrawlFrequency::Weekly, 90),
+        ]);
+        assert_eq!(scheduler.len(), 2);
+        assert!(scheduler.take_pending().is_empty());
+        assert_eq!(scheduler.pop_due().unwrap().url, "https://example.com/c");
+    }
+
+    #[test]
+    fn test_crawl_frequency_names() {
+        for frequency in [
+            CrawlFrequency::Hourly,
+            CrawlFrequency::Daily,
+            CrawlFrequency::Weekly,
+            CrawlFrequency::Monthly,
+            CrawlFrequency::Never,
+        ] {
+            assert_eq!(CrawlFrequency::parse(frequency.as_str()), Some(frequency));
+        }
+        assert_eq!(CrawlFrequency::parse("yearly"), None);
+    }
+
     #[test]
     fn test_scheduler_stats() {
         let scheduler = CrawlScheduler::new();
@@ -0,0 +1,582 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use dashmap::DashMap;
+use flate2::read::GzDecoder;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use serde::Serialize;
+use std::collections::{HashSet, VecDeque};
+use std::io::Read;
+use std::sync::Arc;
+use tracing::{debug, info, warn};
+use url::Url;
+
+use super::rate_limiter::RequestGate;
+use super::read_body_limited;
+use super::retry::RetryPolicy;
+use super::scheduler::{CrawlFrequency, ScheduledCrawl};
+
+/// Maximum number of URLs allowed in a single sitemap (sitemaps.org protocol)
+const MAX_URLS_PER_SITEMAP: usize = 50_000;
+/// Maximum uncompressed sitemap size in bytes (sitemaps.org protocol)
+const MAX_SITEMAP_BYTES: usize = 50 * 1024 * 1024;
+/// Priority used when a sitemap entry has no <priority> (sitemaps.org default)
+const DEFAULT_SITEMAP_PRIORITY: f32 = 0.5;
+
+/// <changefreq> values from the sitemaps.org protocol
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeFreq {
+    Always,
+    Hourly,
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+    Never,
+}
+
+impl ChangeFreq {
+    /// Parse a <changefreq> value (case-insensitive)
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "always" => Some(ChangeFreq::Always),
+            "hourly" => Some(ChangeFreq::Hourly),
+            "daily" => Some(ChangeFreq::Daily),
+            "weekly" => Some(ChangeFreq::Weekly),
+            "monthly" => Some(ChangeFreq::Monthly),
+            "yearly" => Some(ChangeFreq::Yearly),
+            "never" => Some(ChangeFreq::Never),
+            _ => None,
+        }
+    }
+
+    /// Map to the closest crawl frequency supported by the scheduler
+    pub fn to_crawl_frequency(self) -> CrawlFrequency {
+        match self {
+            ChangeFreq::Always | ChangeFreq::Hourly => CrawlFrequency::Hourly,
+            ChangeFreq::Daily => CrawlFrequency::Daily,
+            ChangeFreq::Weekly => CrawlFrequency::Weekly,
+            ChangeFreq::Monthly | ChangeFreq::Yearly => CrawlFrequency::Monthly,
+            // "never" means archived content: crawl it once, then stop
+            ChangeFreq::Never => CrawlFrequency::Never,
+        }
+    }
+}
+
+/// A single <url> entry from a sitemap
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SitemapEntry {
+    pub loc: String,
+    pub lastmod: Option<DateTime<Utc>>,
+    pub changefreq: Option<ChangeFreq>,
+    pub priority: Option<f32>,
+}
+
+impl SitemapEntry {
+    /// Derive the crawl frequency from <changefreq>, falling back to <lastmod> age
+    pub fn crawl_frequency(&self) -> CrawlFrequency {
+        if let Some(changefreq) = self.changefreq {
+            return changefreq.to_crawl_frequency();
+        }
+
+        match self.lastmod {
+            Some(lastmod) => {
+                let age = Utc::now() - lastmod;
+                if age <= Duration::days(1) {
+                    CrawlFrequency::Daily
+                } else if age <= Duration::days(30) {
+                    CrawlFrequency::Weekly
+                } else {
+                    CrawlFrequency::Monthly
+                }
+            }
+            None => CrawlFrequency::Weekly,
+        }
+    }
+
+    /// Derive the scheduler priority (0-100) from <priority>, boosted for recent <lastmod>
+    pub fn crawl_priority(&self) -> u8 {
+        let sitemap_priority = self
+            .priority
+            .unwrap_or(DEFAULT_SITEMAP_PRIORITY)
+            .clamp(0.0, 1.0);
+        let mut priority = (sitemap_priority * 100.0).round() as u8;
+
+        // Recently modified pages are more likely to have content we don't have yet
+        if let Some(lastmod) = self.lastmod {
+            if Utc::now() - lastmod <= Duration::days(7) {
+                priority = priority.saturating_add(10).min(100);
+            }
+        }
+
+        priority
+    }
+
+    /// Convert into a scheduled crawl task (due immediately)
+    pub fn to_scheduled_crawl(&self) -> ScheduledCrawl {
+        ScheduledCrawl::new(self.loc.clone(), self.crawl_frequency(), self.crawl_priority())
+    }
+}
+
+/// Result of parsing a sitemap document
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParsedSitemap {
+    /// <urlset> containing page entries
+    UrlSet(Vec<SitemapEntry>),
+    /// <sitemapindex> pointing at child sitemaps
+    Index(Vec<String>),
+}
+
+/// Sitemap fetcher and parser (sitemap index files, gzip, lastmod/changefreq/priority)
+#[derive(Clone)]
+pub struct SitemapManager {
+    /// User agent string used when fetching sitemaps
+    user_agent: String,
+    /// Retry policy for fetching sitemaps
+    retry_policy: RetryPolicy,
+    /// Rate limit and politeness delay of sitemap requests
+    gate: RequestGate,
+    /// Maximum nesting depth for sitemap index files
+    max_index_depth: usize,
+    /// Maximum number of sitemap documents fetched per ingestion
+    max_sitemaps: usize,
+    /// Map of domain -> last time its sitemaps were ingested
+    last_ingested: Arc<DashMap<String, DateTime<Utc>>>,
+    /// How long to wait before re-reading a domain's sitemaps
+    refresh_interval: Duration,
+}
+
+impl SitemapManager {
+    /// Create a new sitemap manager
+    pub fn new(user_agent: String) -> Self {
+        info!("Initializing SitemapManager with User-Agent: {}", user_agent);
+
+        Self {
+            user_agent,
+            retry_policy: RetryPolicy::new(),
+            gate: RequestGate::default(),
+            max_index_depth: 3,
+            max_sitemaps: 100,
+            last_ingested: Arc::new(DashMap::new()),
+            refresh_interval: Duration::hours(24),
+        }
+    }
+
+    /// Send sitemap requests through the crawler's request gate
+    pub fn with_gate(mut self, gate: RequestGate) -> Self {
+        self.gate = gate;
+        self
+    }
+
+    /// Check whether a domain's sitemaps are due to be (re-)ingested
+    pub fn should_ingest(&self, domain: &str) -> bool {
+        match self.last_ingested.get(domain) {
+            Some(last) => Utc::now() - *last >= self.refresh_interval,
+            None => true,
+        }
+    }
+
+    /// Record that a domain's sitemaps have been ingested
+    pub fn mark_ingested(&self, domain: &str) {
+        self.last_ingested.insert(domain.to_string(), Utc::now());
+    }
+
+    /// Fetch all page entries reachable from the given sitemap URLs,
+    /// following sitemap index files up to `max_index_depth` levels deep
+    pub async fn fetch_entries(&self, sitemap_urls: &[String]) -> Vec<SitemapEntry> {
+        let mut entries = Vec::new();
+        let mut seen_sitemaps = HashSet::new();
+        let mut queue: VecDeque<(String, usize)> =
+            sitemap_urls.iter().map(|url| (url.clone(), 0)).collect();
+        let mut fetched = 0;
+
+        while let Some((sitemap_url, depth)) = queue.pop_front() {
+            if !seen_sitemaps.insert(sitemap_url.clone()) {
+                continue;
+            }
+
+            if fetched >= self.max_sitemaps {
+                warn!(
+                    "Sitemap limit ({}) reached, skipping remaining sitemaps",
+                    self.max_sitemaps
+                );
+                break;
+            }
+            fetched += 1;
+
+            let body = match self.fetch_sitemap(&sitemap_url).await {
+                Ok(body) => body,
+                Err(e) => {
+                    warn!("Failed to fetch sitemap {}: {}", sitemap_url, e);
+                    continue;
+                }
+            };
+
+            match Self::parse(&body) {
+                Ok(ParsedSitemap::UrlSet(urls)) => {
+                    debug!("Sitemap {} contains {} URLs", sitemap_url, urls.len());
+                    entries.extend(urls);
+                }
+                Ok(ParsedSitemap::Index(children)) => {
+                    if depth >= self.max_index_depth {
+                        warn!(
+                            "Sitemap index {} exceeds max nesting depth {}, skipping {} children",
+                            sitemap_url,
+                            self.max_index_depth,
+                            children.len()
+                        );
+                        continue;
+                    }
+                    debug!("Sitemap index {} lists {} sitemaps", sitemap_url, children.len());
+                    let children: Vec<String> = children
+                        .into_iter()
+                        .filter(|child| {
+                            // An index may only point at sitemaps on its own host
+                            let same_host = same_host(&sitemap_url, child);
+                            if !same_host {
+                                debug!("Skipping off-host child sitemap {} of {}", child, sitemap_url);
+                            }
+                            same_host
+                        })
+                        .collect();
+                    queue.extend(children.into_iter().map(|child| (child, depth + 1)));
+                }
+                Err(e) => {
+                    warn!("Failed to parse sitemap {}: {}", sitemap_url, e);
+                }
+            }
+        }
+
+        info!(
+            "Collected {} sitemap entries from {} sitemap(s)",
+            entries.len(),
+            fetched
+        );
+        entries
+    }
+
+    /// Fetch a sitemap document, transparently decompressing gzipped sitemaps
+    async fn fetch_sitemap(&self, url: &str) -> Result<Vec<u8>> {
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(30))
+            .build()?;
+
+        let user_agent = self.user_agent.clone();
+        let url_owned = url.to_string();
+
+        let response = self.retry_policy.execute_http(&url_owned, || {
+            let request = client.get(&url_owned).header("User-Agent", user_agent.clone());
+            self.gate.send(&url_owned, request)
+        }).await?;
+
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!(
+                "sitemap not available (status: {})",
+                response.status()
+            ));
+        }
+
+        // Compressed sitemaps are smaller than their XML, so the same cap applies
+        let bytes = read_body_limited(response, MAX_SITEMAP_BYTES).await?;
+        Self::decode_body(&bytes)
+    }
+
+    /// Decompress a sitemap body if it is gzipped (`.xml.gz` files are served as raw gzip)
+    fn decode_body(bytes: &[u8]) -> Result<Vec<u8>> {
+        if bytes.starts_with(&[0x1f, 0x8b]) {
+            let mut decoded = Vec::new();
+            GzDecoder::new(bytes)
+                .take(MAX_SITEMAP_BYTES as u64 + 1)
+                .read_to_end(&mut decoded)?;
+
+            if decoded.len() > MAX_SITEMAP_BYTES {
+                return Err(anyhow::anyhow!(
+                    "decompressed sitemap exceeds {} bytes",
+                    MAX_SITEMAP_BYTES
+                ));
+            }
+            Ok(decoded)
+        } else {
+            Ok(bytes.to_vec())
+        }
+    }
+
+    /// Parse a sitemap (<urlset>) or sitemap index (<sitemapindex>) document
+    pub fn parse(xml: &[u8]) -> Result<ParsedSitemap> {
+        let mut reader = Reader::from_reader(xml);
+        reader.trim_text(true);
+
+        let mut buf = Vec::new();
+        let mut is_index = false;
+        let mut entries = Vec::new();
+        let mut children = Vec::new();
+        let mut current: Option<SitemapEntry> = None;
+        let mut in_sitemap = false;
+        let mut field: Option<Vec<u8>> = None;
+        let mut text = String::new();
+
+        loop {
+            match reader.read_event_into(&mut buf)? {
+                Event::Start(e) => {
+                    let name = e.local_name().as_ref().to_ascii_lowercase();
+                    match name.as_slice() {
+                        b"sitemapindex" => is_index = true,
+                        b"url" => {
+                            current = Some(SitemapEntry {
+                                loc: String::new(),
+                                lastmod: None,
+                                changefreq: None,
+                                priority: None,
+                            });
+                        }
+                        b"sitemap" => in_sitemap = true,
+                        b"loc" | b"lastmod" | b"changefreq" | b"priority" => {
+                            field = Some(name);
+                            text.clear();
+                        }
+                        _ => {}
+                    }
+                }
+                Event::Text(t) if field.is_some() => {
+                    text.push_str(&t.unescape()?);
+                }
+                Event::CData(c) if field.is_some() => {
+                    text.push_str(&String::from_utf8_lossy(&c.into_inner()));
+                }
+                Event::End(e) => {
+                    let name = e.local_name().as_ref().to_ascii_lowercase();
+                    match name.as_slice() {
+                        b"url" => {
+                            if let Some(entry) = current.take() {
+                                if !entry.loc.is_empty() && entries.len() < MAX_URLS_PER_SITEMAP {
+                                    entries.push(entry);
+                                }
+                            }
+                        }
+                        b"sitemap" => in_sitemap = false,
+                        _ if field.as_deref() == Some(name.as_slice()) => {
+                            let value = text.trim();
+                            match (name.as_slice(), current.as_mut()) {
+                                (b"loc", Some(entry)) => entry.loc = value.to_string(),
+                                (b"loc", None) if in_sitemap && !value.is_empty() => {
+                                    children.push(value.to_string())
+                                }
+                                (b"lastmod", Some(entry)) => entry.lastmod = parse_lastmod(value),
+                                (b"changefreq", Some(entry)) => {
+                                    entry.changefreq = ChangeFreq::parse(value)
+                                }
+                                (b"priority", Some(entry)) => {
+                                    entry.priority = value.parse::<f32>().ok()
+                                }
+                                _ => {}
+                            }
+                            field = None;
+                        }
+                        _ => {}
+                    }
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+            buf.clear();
+        }
+
+        if is_index {
+            Ok(ParsedSitemap::Index(children))
+        } else {
+            Ok(ParsedSitemap::UrlSet(entries))
+        }
+    }
+
+    /// Get statistics
+    pub fn stats(&self) -> SitemapStats {
+        SitemapStats {
+            ingested_domains: self.last_ingested.len(),
+            max_index_depth: self.max_index_depth,
+            max_sitemaps: self.max_sitemaps,
+        }
+    }
+}
+
+/// Whether two URLs are on the same host (case-insensitive)
+fn same_host(a: &str, b: &str) -> bool {
+    let host = |url: &str| Url::parse(url).ok().and_then(|url| url.host_str().map(str::to_lowercase));
+    matches!((host(a), host(b)), (Some(a), Some(b)) if a == b)
+}
+
+/// Parse a W3C datetime <lastmod> (full timestamp or date-only)
+fn parse_lastmod(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return Some(dt.with_timezone(&Utc));
+    }
+
+    // W3C datetime allows minute precision without seconds, e.g. 2024-01-15T10:30+00:00
+    if let Ok(dt) = DateTime::parse_from_str(value, "%Y-%m-%dT%H:%M%:z") {
+        return Some(dt.with_timezone(&Utc));
+    }
+
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|naive| naive.and_utc())
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SitemapStats {
+    pub ingested_domains: usize,
+    pub max_index_depth: usize,
+    pub max_sitemaps: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    #[test]
+    fn test_parse_urlset() {
+        let xml = br#"<?xml version="1.0" encoding="UTF-8"?>
+<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+  <url>
+    <loc>https://example.com/docs/intro</loc>
+    <lastmod>2024-01-15</lastmod>
+    <changefreq>daily</changefreq>
+    <priority>0.8</priority>
+  </url>
+  <url>
+    <loc>https://example.com/docs/a?x=1&amp;y=2</loc>
+  </url>
+</urlset>"#;
+
+        let parsed = SitemapManager::parse(xml).unwrap();
+        let entries = match parsed {
+            ParsedSitemap::UrlSet(entries) => entries,
+            other => panic!("expected urlset, got {:?}", other),
+        };
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].loc, "https://example.com/docs/intro");
+        assert_eq!(entries[0].changefreq, Some(ChangeFreq::Daily));
+        assert_eq!(entries[0].priority, Some(0.8));
+        assert_eq!(
+            entries[0].lastmod.unwrap().date_naive(),
+            NaiveDate::from_ymd_opt(2024, 1, 15).unwrap()
+        );
+
+        // Entities are unescaped, missing hints stay empty
+        assert_eq!(entries[1].loc, "https://example.com/docs/a?x=1&y=2");
+        assert_eq!(entries[1].lastmod, None);
+        assert_eq!(entries[1].changefreq, None);
+        assert_eq!(entries[1].priority, None);
+    }
+
+    #[test]
+    fn test_parse_sitemap_index() {
+        let xml = br#"<?xml version="1.0" encoding="UTF-8"?>
+<sitemapindex xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+  <sitemap>
+    <loc>https://example.com/sitemap-docs.xml.gz</loc>
+    <lastmod>2024-01-15T10:30:00+00:00</lastmod>
+  </sitemap>
+  <sitemap>
+    <loc><![CDATA[https://example.com/sitemap-blog.xml]]></loc>
+  </sitemap>
+</sitemapindex>"#;
+
+        let parsed = SitemapManager::parse(xml).unwrap();
+        assert_eq!(
+            parsed,
+            ParsedSitemap::Index(vec![
+                "https://example.com/sitemap-docs.xml.gz".to_string(),
+                "https://example.com/sitemap-blog.xml".to_string(),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_decode_gzipped_body() {
+        let xml = b"<urlset><url><loc>https://example.com/</loc></url></urlset>";
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(xml).unwrap();
+        let gzipped = encoder.finish().unwrap();
+
+        let decoded = SitemapManager::decode_body(&gzipped).unwrap();
+        assert_eq!(decoded, xml.to_vec());
+
+        // Plain XML is passed through unchanged
+        assert_eq!(SitemapManager::decode_body(xml).unwrap(), xml.to_vec());
+    }
+
+    #[test]
+    fn test_parse_lastmod_formats() {
+        assert!(parse_lastmod("2024-01-15").is_some());
+        assert!(parse_lastmod("2024-01-15T10:30:00Z").is_some());
+        assert!(parse_lastmod("2024-01-15T10:30:00+02:00").is_some());
+        assert!(parse_lastmod("2024-01-15T10:30+02:00").is_some());
+        assert!(parse_lastmod("last tuesday").is_none());
+    }
+
+    #[test]
+    fn test_changefreq_mapping() {
+        assert_eq!(ChangeFreq::Always.to_crawl_frequency(), CrawlFrequency::Hourly);
+        assert_eq!(ChangeFreq::Daily.to_crawl_frequency(), CrawlFrequency::Daily);
+        assert_eq!(ChangeFreq::Weekly.to_crawl_frequency(), CrawlFrequency::Weekly);
+        assert_eq!(ChangeFreq::Yearly.to_crawl_frequency(), CrawlFrequency::Monthly);
+        assert_eq!(ChangeFreq::Never.to_crawl_frequency(), CrawlFrequency::Never);
+        assert_eq!(ChangeFreq::parse("WEEKLY"), Some(ChangeFreq::Weekly));
+        assert_eq!(ChangeFreq::parse("sometimes"), None);
+    }
+
+    #[test]
+    fn test_entry_to_scheduled_crawl() {
+        let entry = SitemapEntry {
+            loc: "https://example.com/docs/deep/page".to_string(),
+            lastmod: Some(Utc::now() - Duration::days(2)),
+            changefreq: None,
+            priority: Some(0.6),
+        };
+
+        // No changefreq: recent lastmod suggests weekly recrawls
+        assert_eq!(entry.crawl_frequency(), CrawlFrequency::Weekly);
+        // 0.6 -> 60, boosted for being modified in the last week
+        assert_eq!(entry.crawl_priority(), 70);
+
+        let task = entry.to_scheduled_crawl();
+        assert_eq!(task.url, "https://example.com/docs/deep/page");
+        assert_eq!(task.priority, 70);
+        assert!(task.is_due());
+    }
+
+    #[test]
+    fn test_entry_defaults() {
+        let entry = SitemapEntry {
+            loc: "https://example.com/".to_string(),
+            lastmod: None,
+            changefreq: None,
+            priority: Some(7.5), // Out of range values are clamped
+        };
+
+        assert_eq!(entry.crawl_frequency(), CrawlFrequency::Weekly);
+        assert_eq!(entry.crawl_priority(), 100);
+    }
+
+    #[test]
+    fn test_should_ingest() {
+        let manager = SitemapManager::new("TestBot/1.0".to_string());
+
+        assert!(manager.should_ingest("example.com"));
+        manager.mark_ingested("example.com");
+        assert!(!manager.should_ingest("example.com"));
+        assert_eq!(manager.stats().ingested_domains, 1);
+    }
+
+    #[test]
+    fn test_same_host() {
+        assert!(same_host("https://Example.com/sitemap.xml", "https://example.com/posts.xml"));
+        assert!(!same_host("https://example.com/sitemap.xml", "https://cdn.example.com/posts.xml"));
+        assert!(!same_host("https://example.com/sitemap.xml", "not a url"));
+    }
+}
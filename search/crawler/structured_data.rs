@@ -0,0 +1,584 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use scraper::{ElementRef, Html, Selector};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::util::collapse_whitespace;
+
+/// schema.org types treated as articles
+const ARTICLE_TYPES: &[&str] = &[
+    "Article",
+    "NewsArticle",
+    "BlogPosting",
+    "TechArticle",
+    "ScholarlyArticle",
+    "Report",
+    "WebPage",
+];
+
+/// Typed fields extracted from JSON-LD, microdata and OpenGraph/Twitter tags
+///
+/// Sources are merged in order of reliability: JSON-LD first, then microdata,
+/// then meta tags. A field is only taken from a later source when the earlier
+/// ones did not provide it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StructuredData {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    /// RFC 3339, UTC
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub published_at: Option<String>,
+    /// Unix seconds, for range filters and sorting
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub published_timestamp: Option<i64>,
+    /// RFC 3339, UTC
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified_at: Option<String>,
+    /// Unix seconds, for range filters and sorting
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified_timestamp: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub article_section: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub price: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub price_currency: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rating: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rating_count: Option<u64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub breadcrumbs: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub site_name: Option<String>,
+}
+
+impl StructuredData {
+    /// Extract structured data from a parsed page
+    pub fn extract(document: &Html) -> Self {
+        let mut data = Self::from_json_ld(document);
+        data.merge(Self::from_microdata(document));
+        data.merge(Self::from_meta_tags(document));
+        data
+    }
+
+    /// Check if nothing was found
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Fill fields that are still missing from another source
+    pub fn merge(&mut self, other: StructuredData) {
+        fn fill<T>(field: &mut Option<T>, value: Option<T>) {
+            if field.is_none() {
+                *field = value;
+            }
+        }
+
+        fill(&mut self.author, other.author);
+        if self.published_at.is_none() {
+            self.published_at = other.published_at;
+            self.published_timestamp = other.published_timestamp;
+        }
+        if self.modified_at.is_none() {
+            self.modified_at = other.modified_at;
+            self.modified_timestamp = other.modified_timestamp;
+        }
+        fill(&mut self.article_section, other.article_section);
+        if self.price.is_none() {
+            self.price = other.price;
+            self.price_currency = other.price_currency;
+        }
+        if self.rating.is_none() {
+            self.rating = other.rating;
+            self.rating_count = other.rating_count;
+        }
+        if self.breadcrumbs.is_empty() {
+            self.breadcrumbs = other.breadcrumbs;
+        }
+        fill(&mut self.site_name, other.site_name);
+    }
+
+    /// Set the publication date if not already known (ignored if unparseable)
+    pub fn set_published(&mut self, value: &str) {
+        if self.published_at.is_none() {
+            if let Some(date) = parse_date(value) {
+                self.published_at = Some(date.to_rfc3339());
+                self.published_timestamp = Some(date.timestamp());
+            }
+        }
+    }
+
+    /// Set the modification date if not already known (ignored if unparseable)
+    pub fn set_modified(&mut self, value: &str) {
+        if self.modified_at.is_none() {
+            if let Some(date) = parse_date(value) {
+                self.modified_at = Some(date.to_rfc3339());
+                self.modified_timestamp = Some(date.timestamp());
+            }
+        }
+    }
+
+    /// Parse `<script type="application/ld+json">` blocks
+    fn from_json_ld(document: &Html) -> Self {
+        let mut data = Self::default();
+        let Ok(selector) = Selector::parse(r#"script[type="application/ld+json"]"#) else {
+            return data;
+        };
+
+        for script in document.select(&selector) {
+            let text = script.text().collect::<String>();
+            // Invalid blocks are common in the wild; skip rather than fail the page
+            let Ok(value) = serde_json::from_str::<Value>(text.trim()) else {
+                continue;
+            };
+
+            for node in json_ld_nodes(&value) {
+                data.apply_json_ld_node(node);
+            }
+        }
+
+        data
+    }
+
+    fn apply_json_ld_node(&mut self, node: &Value) {
+        let types = json_ld_types(node);
+        let is_type = |name: &str| types.iter().any(|t| t == name);
+
+        if types.iter().any(|t| ARTICLE_TYPES.contains(&t.as_str())) {
+            if self.author.is_none() {
+                self.author = node.get("author").and_then(json_name);
+            }
+            if let Some(published) = node.get("datePublished").and_then(json_string) {
+                self.set_published(&published);
+            }
+            if let Some(modified) = node.get("dateModified").and_then(json_string) {
+                self.set_modified(&modified);
+            }
+            if self.article_section.is_none() {
+                self.article_section = node.get("articleSection").and_then(json_string);
+            }
+            if self.site_name.is_none() {
+                self.site_name = node.get("publisher").and_then(json_name);
+            }
+            if self.breadcrumbs.is_empty() {
+                if let Some(breadcrumb) = node.get("breadcrumb") {
+                    self.breadcrumbs = json_breadcrumbs(breadcrumb);
+                }
+            }
+        }
+
+        if is_type("Product") && self.price.is_none() {
+            if let Some(offer) = node.get("offers").map(first_value) {
+                self.price = offer
+                    .get("price")
+                    .or_else(|| offer.get("lowPrice"))
+                    .and_then(json_number);
+                self.price_currency = offer.get("priceCurrency").and_then(json_string);
+            }
+        }
+
+        if self.rating.is_none() {
+            if let Some(rating) = node.get("aggregateRating") {
+                self.rating = rating.get("ratingValue").and_then(json_number);
+                self.rating_count = rating
+                    .get("ratingCount")
+                    .or_else(|| rating.get("reviewCount"))
+                    .and_then(json_number)
+                    .map(|count| count as u64);
+            }
+        }
+
+        if is_type("BreadcrumbList") && self.breadcrumbs.is_empty() {
+            self.breadcrumbs = json_breadcrumbs(node);
+        }
+
+        if is_type("WebSite") && self.site_name.is_none() {
+            self.site_name = node.get("name").and_then(json_string);
+        }
+    }
+
+    /// Parse schema.org microdata (`itemprop` attributes)
+    fn from_microdata(document: &Html) -> Self {
+        let mut data = Self::default();
+        let Ok(selector) = Selector::parse("[itemprop]") else {
+            return data;
+        };
+
+        for element in document.select(&selector) {
+            let Some(props) = element.value().attr("itemprop") else {
+                continue;
+            };
+
+            for prop in props.split_whitespace() {
+                match prop {
+                    "author" if data.author.is_none() => {
+                        data.author = microdata_name(element);
+                    }
+                    "datePublished" => {
+                        if let Some(value) = microdata_value(element) {
+                            data.set_published(&value);
+                        }
+                    }
+                    "dateModified" => {
+                        if let Some(value) = microdata_value(element) {
+                            data.set_modified(&value);
+                        }
+                    }
+                    "articleSection" if data.article_section.is_none() => {
+                        data.article_section = microdata_value(element);
+                    }
+                    "price" | "lowPrice" if data.price.is_none() => {
+                        data.price = microdata_value(element).and_then(|value| parse_number(&value));
+                    }
+                    "priceCurrency" if data.price_currency.is_none() => {
+                        data.price_currency = microdata_value(element);
+                    }
+                    "ratingValue" if data.rating.is_none() => {
+                        data.rating = microdata_value(element).and_then(|value| parse_number(&value));
+                    }
+                    "ratingCount" | "reviewCount" if data.rating_count.is_none() => {
+                        data.rating_count = microdata_value(element)
+                            .and_then(|value| parse_number(&value))
+                            .map(|count| count as u64);
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        if let Ok(selector) = Selector::parse(r#"[itemtype$="BreadcrumbList"] [itemprop="itemListElement"]"#) {
+            let name_selector = Selector::parse(r#"[itemprop="name"]"#).ok();
+            data.breadcrumbs = document
+                .select(&selector)
+                .filter_map(|item| {
+                    let name = name_selector
+                        .as_ref()
+                        .and_then(|s| item.select(s).next())
+                        .and_then(microdata_value)?;
+                    Some(name)
+                })
+                .collect();
+        }
+
+        data
+    }
+
+    /// Parse OpenGraph, Twitter card and plain meta tags
+    fn from_meta_tags(document: &Html) -> Self {
+        let mut data = Self::default();
+        let Ok(selector) = Selector::parse("meta[content]") else {
+            return data;
+        };
+
+        for meta in document.select(&selector) {
+            let element = meta.value();
+            let Some(key) = element.attr("property").or_else(|| element.attr("name")) else {
+                continue;
+            };
+            let Some(content) = element.attr("content").map(str::trim).filter(|c| !c.is_empty()) else {
+                continue;
+            };
+
+            match key.to_ascii_lowercase().as_str() {
+                "og:site_name" if data.site_name.is_none() => {
+                    data.site_name = Some(content.to_string());
+                }
+                // article:author is often a profile URL; only keep plain names
+                "article:author" | "author" | "twitter:creator"
+                    if data.author.is_none() && !content.starts_with("http") =>
+                {
+                    data.author = Some(content.trim_start_matches('@').to_string());
+                }
+                "article:published_time" | "og:published_time" => data.set_published(content),
+                "article:modified_time" | "og:updated_time" => data.set_modified(content),
+                "article:section" if data.article_section.is_none() => {
+                    data.article_section = Some(content.to_string());
+                }
+                "product:price:amount" | "og:price:amount" if data.price.is_none() => {
+                    data.price = parse_number(content);
+                }
+                "product:price:currency" | "og:price:currency" if data.price_currency.is_none() => {
+                    data.price_currency = Some(content.to_string());
+                }
+                _ => {}
+            }
+        }
+
+        data
+    }
+}
+
+/// Flatten a JSON-LD document into its nodes (top-level arrays and `@graph`)
+fn json_ld_nodes(value: &Value) -> Vec<&Value> {
+    match value {
+        Value::Array(items) => items.iter().flat_map(json_ld_nodes).collect(),
+        Value::Object(object) => {
+            let mut nodes = vec![value];
+            if let Some(graph) = object.get("@graph") {
+                nodes.extend(json_ld_nodes(graph));
+            }
+            nodes
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// `@type` may be a string or an array of strings
+fn json_ld_types(node: &Value) -> Vec<String> {
+    match node.get("@type") {
+        Some(Value::String(t)) => vec![t.clone()],
+        Some(Value::Array(types)) => types
+            .iter()
+            .filter_map(|t| t.as_str().map(|s| s.to_string()))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn first_value(value: &Value) -> &Value {
+    match value {
+        Value::Array(items) => items.first().unwrap_or(value),
+        _ => value,
+    }
+}
+
+fn json_string(value: &Value) -> Option<String> {
+    match first_value(value) {
+        Value::String(s) => Some(s.trim().to_string()).filter(|s| !s.is_empty()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+fn json_number(value: &Value) -> Option<f64> {
+    match first_value(value) {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => parse_number(s),
+        _ => None,
+    }
+}
+
+/// A person or organization: either a plain string or an object with `name`
+fn json_name(value: &Value) -> Option<String> {
+    let value = first_value(value);
+    match value {
+        Value::Object(_) => value.get("name").and_then(json_string),
+        _ => json_string(value),
+    }
+}
+
+/// Breadcrumb names ordered by `position`
+fn json_breadcrumbs(list: &Value) -> Vec<String> {
+    let Some(Value::Array(items)) = list.get("itemListElement") else {
+        return Vec::new();
+    };
+
+    let mut crumbs: Vec<(f64, String)> = items
+        .iter()
+        .enumerate()
+        .filter_map(|(index, item)| {
+            let name = item
+                .get("name")
+                .and_then(json_string)
+                .or_else(|| item.get("item").and_then(json_name))?;
+            let position = item
+                .get("position")
+                .and_then(json_number)
+                .unwrap_or(index as f64);
+            Some((position, name))
+        })
+        .collect();
+
+    crumbs.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+    crumbs.into_iter().map(|(_, name)| name).collect()
+}
+
+/// Value of a microdata property: `content`, `datetime`, or the element text
+fn microdata_value(element: ElementRef) -> Option<String> {
+    let value = element
+        .value()
+        .attr("content")
+        .or_else(|| element.value().attr("datetime"))
+        .map(|value| value.to_string())
+        .unwrap_or_else(|| element.text().collect::<String>());
+
+    let value = collapse_whitespace(&value);
+    Some(value).filter(|value| !value.is_empty())
+}
+
+/// Name of a nested `Person`/`Organization` item, or the property's own value
+fn microdata_name(element: ElementRef) -> Option<String> {
+    if element.value().attr("itemscope").is_some() {
+        let selector = Selector::parse(r#"[itemprop="name"]"#).ok()?;
+        if let Some(name) = element.select(&selector).next() {
+            return microdata_value(name);
+        }
+    }
+    microdata_value(element)
+}
+
+/// Parse a price or rating such as `"1,299.00"` or `"$19.99"`
+fn parse_number(value: &str) -> Option<f64> {
+    let cleaned: String = value
+        .chars()
+        .filter(|c| c.is_ascii_digit() || *c == '.' || *c == '-')
+        .collect();
+    cleaned.parse().ok().filter(|n: &f64| n.is_finite())
+}
+
+/// Parse the date formats seen in structured data into UTC
+fn parse_date(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+
+    if let Ok(date) = DateTime::parse_from_rfc3339(value) {
+        return Some(date.with_timezone(&Utc));
+    }
+    // Offsets without a colon, e.g. 2024-01-15T10:00:00+0000
+    if let Ok(date) = DateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%z") {
+        return Some(date.with_timezone(&Utc));
+    }
+    if let Ok(date) = NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S") {
+        return Some(date.and_utc());
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return date.and_hms_opt(0, 0, 0).map(|date| date.and_utc());
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn extract(html: &str) -> StructuredData {
+        StructuredData::extract(&Html::parse_document(html))
+    }
+
+    #[test]
+    fn test_json_ld_article() {
+        let data = extract(
+            r#"<html><head><script type="application/ld+json">
+            {
+                "@context": "https://schema.org",
+                "@graph": [
+                    {"@type": "WebSite", "name": "Example News"},
+                    {
+                        "@type": "NewsArticle",
+                        "author": [{"@type": "Person", "name": "Jane Doe"}],
+                        "datePublished": "2024-03-01T09:30:00+01:00",
+                        "dateModified": "2024-03-02",
+                        "articleSection": ["Technology", "AI"]
+                    },
+                    {
+                        "@type": "BreadcrumbList",
+                        "itemListElement": [
+                            {"@type": "ListItem", "position": 2, "name": "Technology"},
+                            {"@type": "ListItem", "position": 1, "item": {"name": "Home"}}
+                        ]
+                    }
+                ]
+            }
+            </script></head><body></body></html>"#,
+        );
+
+        assert_eq!(data.author.as_deref(), Some("Jane Doe"));
+        assert_eq!(data.published_at.as_deref(), Some("2024-03-01T08:30:00+00:00"));
+        assert_eq!(data.published_timestamp, Some(1709281800));
+        assert_eq!(data.modified_at.as_deref(), Some("2024-03-02T00:00:00+00:00"));
+        assert_eq!(data.article_section.as_deref(), Some("Technology"));
+        assert_eq!(data.breadcrumbs, vec!["Home", "Technology"]);
+        assert_eq!(data.site_name.as_deref(), Some("Example News"));
+    }
+
+    #[test]
+    fn test_json_ld_product() {
+        let data = extract(
+            r#"<script type="application/ld+json">
+            [{
+                "@type": "Product",
+                "name": "Widget",
+                "offers": {"@type": "Offer", "price": "1,299.00", "priceCurrency": "EUR"},
+                "aggregateRating": {"ratingValue": 4.6, "reviewCount": "128"}
+            }]
+            </script>"#,
+        );
+
+        assert_eq!(data.price, Some(1299.0));
+        assert_eq!(data.price_currency.as_deref(), Some("EUR"));
+        assert_eq!(data.rating, Some(4.6));
+        assert_eq!(data.rating_count, Some(128));
+    }
+
+    #[test]
+    fn test_invalid_json_ld_is_skipped() {
+        let data = extract(
+            r#"<script type="application/ld+json">{ not json</script>
+            <meta property="og:site_name" content="Fallback">"#,
+        );
+        assert_eq!(data.site_name.as_deref(), Some("Fallback"));
+    }
+
+    #[test]
+    fn test_microdata() {
+        let data = extract(
+            r#"<article itemscope itemtype="https://schema.org/BlogPosting">
+                <span itemprop="author" itemscope itemtype="https://schema.org/Person">
+                    <span itemprop="name">John Smith</span>
+                </span>
+                <time itemprop="datePublished" datetime="2023-11-05T14:00:00Z">Nov 5</time>
+                <div itemprop="aggregateRating" itemscope>
+                    <meta itemprop="ratingValue" content="4.2">
+                    <meta itemprop="ratingCount" content="17">
+                </div>
+            </article>
+            <ol itemscope itemtype="https://schema.org/BreadcrumbList">
+                <li itemprop="itemListElement" itemscope><span itemprop="name">Blog</span></li>
+                <li itemprop="itemListElement" itemscope><span itemprop="name">Rust</span></li>
+            </ol>"#,
+        );
+
+        assert_eq!(data.author.as_deref(), Some("John Smith"));
+        assert_eq!(data.published_at.as_deref(), Some("2023-11-05T14:00:00+00:00"));
+        assert_eq!(data.rating, Some(4.2));
+        assert_eq!(data.rating_count, Some(17));
+        assert_eq!(data.breadcrumbs, vec!["Blog", "Rust"]);
+    }
+
+    #[test]
+    fn test_meta_tags() {
+        let data = extract(
+            r#"<head>
+                <meta property="og:site_name" content="Shop">
+                <meta property="article:author" content="https://example.com/authors/jane">
+                <meta name="twitter:creator" content="@jane">
+                <meta property="article:published_time" content="2024-01-15T10:00:00+0000">
+                <meta property="article:section" content="Reviews">
+                <meta property="product:price:amount" content="19.99">
+                <meta property="product:price:currency" content="USD">
+            </head>"#,
+        );
+
+        assert_eq!(data.site_name.as_deref(), Some("Shop"));
+        assert_eq!(data.author.as_deref(), Some("jane"));
+        assert_eq!(data.published_at.as_deref(), Some("2024-01-15T10:00:00+00:00"));
+        assert_eq!(data.article_section.as_deref(), Some("Reviews"));
+        assert_eq!(data.price, Some(19.99));
+        assert_eq!(data.price_currency.as_deref(), Some("USD"));
+    }
+
+    #[test]
+    fn test_json_ld_takes_precedence() {
+        let data = extract(
+            r#"<head>
+                <script type="application/ld+json">
+                    {"@type": "Article", "author": "From JSON-LD"}
+                </script>
+                <meta name="author" content="From meta">
+            </head>"#,
+        );
+        assert_eq!(data.author.as_deref(), Some("From JSON-LD"));
+        assert!(extract("<html></html>").is_empty());
+    }
+}
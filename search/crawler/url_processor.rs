@@ -2,12 +2,17 @@ use anyhow::Result;
 use std::collections::HashSet;
 use tracing::debug;
 use url::Url;
+use uuid::Uuid;
 
 /// URL processor for normalization and canonicalization
 #[derive(Clone, Debug)]
 pub struct UrlProcessor {
     /// Query parameters to remove (tracking params)
     remove_params: HashSet<String>,
+    /// Prefixes of query parameters to remove (from `name*` rules)
+    remove_param_prefixes: Vec<String>,
+    /// Whether to drop the whole query string
+    remove_all_params: bool,
     /// Whether to remove fragments (#section)
     remove_fragments: bool,
     /// Whether to normalize to lowercase
@@ -42,6 +47,8 @@ impl Default for UrlProcessor {
 
         Self {
             remove_params,
+            remove_param_prefixes: Vec::new(),
+            remove_all_params: false,
             remove_fragments: true,
             lowercase: true,
             trailing_slash: false,
@@ -56,9 +63,27 @@ impl UrlProcessor {
         Self::default()
     }
 
-    /// Add a query parameter to be removed
+    /// Add a query parameter to be removed (`prefix*` removes every parameter
+    /// starting with `prefix`)
     pub fn add_remove_param(&mut self, param: String) {
-        self.remove_params.insert(param.to_lowercase());
+        let param = param.to_lowercase();
+        match param.strip_suffix('*') {
+            Some(prefix) => self.remove_param_prefixes.push(prefix.to_string()),
+            None => {
+                self.remove_params.insert(param);
+            }
+        }
+    }
+
+    /// Set whether to drop the whole query string
+    pub fn set_remove_all_params(&mut self, remove: bool) {
+        self.remove_all_params = remove;
+    }
+
+    fn is_removed_param(&self, key: &str) -> bool {
+        let key = key.to_lowercase();
+        self.remove_params.contains(&key)
+            || self.remove_param_prefixes.iter().any(|prefix| key.starts_with(prefix.as_str()))
     }
 
     /// Set whether to remove fragments
@@ -91,13 +116,12 @@ impl UrlProcessor {
         }
 
         // Filter query parameters
-        if !self.remove_params.is_empty() {
+        if self.remove_all_params {
+            parsed.set_query(None);
+        } else if !self.remove_params.is_empty() || !self.remove_param_prefixes.is_empty() {
             let filtered_params: Vec<(String, String)> = parsed
                 .query_pairs()
-                .filter(|(key, _)| {
-                    let key_lower = key.to_lowercase();
-                    !self.remove_params.contains(&key_lower)
-                })
+                .filter(|(key, _)| !self.is_removed_param(key))
                 .map(|(k, v)| (k.to_string(), v.to_string()))
                 .collect();
 
@@ -194,6 +218,44 @@ impl UrlProcessor {
         None
     }
 
+    /// Resolve the canonical URL of a page
+    ///
+    /// Uses `<link rel="canonical">` when it points to the same host as the page
+    /// (cross-host canonicals are ignored so one site cannot claim another
+    /// site's documents), falling back to the page URL. The result is normalized.
+    pub fn canonical_url(&self, page_url: &str, html: &str) -> Result<String> {
+        let normalized_page = self.normalize(page_url)?;
+
+        let canonical = Self::extract_canonical(html)
+            .and_then(|href| Url::parse(&normalized_page).ok()?.join(href.trim()).ok())
+            .filter(|canonical| matches!(canonical.scheme(), "http" | "https"))
+            .and_then(|canonical| self.normalize(canonical.as_str()).ok());
+
+        match canonical {
+            Some(canonical) if Self::same_host(&canonical, &normalized_page) => Ok(canonical),
+            Some(canonical) => {
+                debug!("Ignoring cross-host canonical {} for {}", canonical, page_url);
+                Ok(normalized_page)
+            }
+            None => Ok(normalized_page),
+        }
+    }
+
+    /// Deterministic document ID for a canonical URL
+    ///
+    /// A UUID v5 in the URL namespace, so recrawls overwrite the existing
+    /// document instead of adding a new one. The UUID form is required by Qdrant.
+    pub fn document_id(canonical_url: &str) -> String {
+        Uuid::new_v5(&Uuid::NAMESPACE_URL, canonical_url.as_bytes()).to_string()
+    }
+
+    fn same_host(url1: &str, url2: &str) -> bool {
+        match (Url::parse(url1), Url::parse(url2)) {
+            (Ok(a), Ok(b)) => a.host_str().is_some() && a.host_str() == b.host_str(),
+            _ => false,
+        }
+    }
+
     /// Check if URL matches a pattern (for deduplication)
     pub fn urls_match(&self, url1: &str, url2: &str) -> bool {
         match (self.normalize(url1), self.normalize(url2)) {
@@ -299,6 +361,21 @@ mod tests {
         assert!(!normalized.contains("custom_tracker"));
     }
 
+    #[test]
+    fn test_prefix_and_all_remove_params() {
+        let mut processor = UrlProcessor::new();
+        processor.add_remove_param("ref_*".to_string());
+
+        let normalized = processor
+            .normalize("https://example.com/page?ref_src=a&REF_ID=b&id=1")
+            .unwrap();
+        assert_eq!(normalized, "https://example.com/page?id=1");
+
+        processor.set_remove_all_params(true);
+        let normalized = processor.normalize("https://example.com/page?id=1&page=2").unwrap();
+        assert_eq!(normalized, "https://example.com/page");
+    }
+
     #[test]
     fn test_extract_canonical() {
         let html = r#"
@@ -327,6 +404,42 @@ mod tests {
         assert_eq!(canonical, Some("https://example.com/canonical-page".to_string()));
     }
 
+    #[test]
+    fn test_canonical_url() {
+        let processor = UrlProcessor::new();
+
+        // Relative canonical is resolved against the page and normalized
+        let html = r#"<html><head><link rel="canonical" href="/article?b=2&a=1"></head></html>"#;
+        let canonical = processor
+            .canonical_url("https://Example.com/article?a=1&b=2&utm_source=x", html)
+            .unwrap();
+        assert_eq!(canonical, "https://example.com/article?a=1&b=2");
+
+        // No canonical: the normalized page URL
+        let canonical = processor
+            .canonical_url("https://example.com/page#top", "<html></html>")
+            .unwrap();
+        assert_eq!(canonical, "https://example.com/page");
+
+        // Cross-host canonicals are ignored
+        let html = r#"<link rel="canonical" href="https://other.com/page">"#;
+        let canonical = processor
+            .canonical_url("https://example.com/page", html)
+            .unwrap();
+        assert_eq!(canonical, "https://example.com/page");
+    }
+
+    #[test]
+    fn test_document_id_is_stable() {
+        let id1 = UrlProcessor::document_id("https://example.com/page");
+        let id2 = UrlProcessor::document_id("https://example.com/page");
+        let id3 = UrlProcessor::document_id("https://example.com/other");
+
+        assert_eq!(id1, id2);
+        assert_ne!(id1, id3);
+        assert!(Uuid::parse_str(&id1).is_ok());
+    }
+
     #[test]
     fn test_urls_match() {
         let processor = UrlProcessor::new();
@@ -0,0 +1,29 @@
+use url::Url;
+
+/// Collapse runs of whitespace into single spaces and trim the ends
+pub(crate) fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Lowercased host of a URL, or `None` if it doesn't parse or has no host
+pub(crate) fn host_of(url: &str) -> Option<String> {
+    Url::parse(url).ok()?.host_str().map(|host| host.to_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collapse_whitespace() {
+        assert_eq!(collapse_whitespace("  a\n\tb   c "), "a b c");
+        assert_eq!(collapse_whitespace(" \n "), "");
+    }
+
+    #[test]
+    fn test_host_of() {
+        assert_eq!(host_of("https://Example.COM/path").as_deref(), Some("example.com"));
+        assert_eq!(host_of("mailto:someone@example.com"), None);
+        assert_eq!(host_of("not a url"), None);
+    }
+}
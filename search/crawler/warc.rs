@@ -0,0 +1,651 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use flate2::read::MultiGzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use sha2::{Digest, Sha256};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock, Weak};
+use tracing::warn;
+use url::Url;
+use uuid::Uuid;
+
+/// WARC version written by this crawler
+pub const WARC_VERSION: &str = "WARC/1.1";
+
+/// Response headers that no longer describe the stored body
+///
+/// Bodies are archived as received from the HTTP client, i.e. already
+/// decompressed and de-chunked, so these headers are dropped and
+/// Content-Length is rewritten to match.
+const STRIPPED_RESPONSE_HEADERS: &[&str] = &["content-encoding", "transfer-encoding", "content-length"];
+
+/// One fetched URL, as written to the archive
+#[derive(Debug, Clone)]
+pub struct HttpExchange<'a> {
+    pub url: &'a str,
+    pub request_headers: Vec<(String, String)>,
+    pub status: u16,
+    pub response_headers: Vec<(String, String)>,
+    pub body: &'a [u8],
+    pub fetched_at: DateTime<Utc>,
+    /// Extra `name: value` fields for the metadata record
+    pub metadata: Vec<(String, String)>,
+}
+
+/// A single WARC record
+#[derive(Debug, Clone)]
+pub struct WarcRecord {
+    pub warc_type: String,
+    pub record_id: String,
+    pub headers: Vec<(String, String)>,
+    pub block: Vec<u8>,
+}
+
+impl WarcRecord {
+    pub fn new(warc_type: &str, date: DateTime<Utc>, content_type: &str, block: Vec<u8>) -> Self {
+        let record_id = new_record_id();
+        Self {
+            warc_type: warc_type.to_string(),
+            record_id: record_id.clone(),
+            headers: vec![
+                ("WARC-Type".to_string(), warc_type.to_string()),
+                ("WARC-Record-ID".to_string(), record_id),
+                ("WARC-Date".to_string(), date.format("%Y-%m-%dT%H:%M:%SZ").to_string()),
+                ("Content-Type".to_string(), content_type.to_string()),
+            ],
+            block,
+        }
+    }
+
+    pub fn with_header(mut self, name: &str, value: impl Into<String>) -> Self {
+        self.headers.push((name.to_string(), value.into()));
+        self
+    }
+
+    /// Value of a WARC header (case-insensitive)
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Serialize the record: version line, headers, block and the two trailing CRLFs
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(self.block.len() + 512);
+        out.extend_from_slice(WARC_VERSION.as_bytes());
+        out.extend_from_slice(b"\r\n");
+        for (name, value) in &self.headers {
+            out.extend_from_slice(format!("{}: {}\r\n", name, value).as_bytes());
+        }
+        out.extend_from_slice(format!("Content-Length: {}\r\n\r\n", self.block.len()).as_bytes());
+        out.extend_from_slice(&self.block);
+        out.extend_from_slice(b"\r\n\r\n");
+        out
+    }
+}
+
+/// Appends gzip-compressed WARC records to one file per crawl job
+///
+/// Every record is its own gzip member, as standard WARC tooling expects,
+/// so the file can be read sequentially or seeked into by record offset.
+/// Compression and file I/O run on the blocking thread pool. Cloning shares
+/// the underlying file.
+#[derive(Clone)]
+pub struct WarcWriter {
+    path: PathBuf,
+    file: Arc<Mutex<File>>,
+    warcinfo_id: String,
+}
+
+impl WarcWriter {
+    /// Open (or continue) `<directory>/<job_id>.warc.gz`
+    ///
+    /// A `warcinfo` record is written when the file is new; a job that is
+    /// retried keeps appending to the same archive and reuses its warcinfo
+    /// ID. Writers opened on the same file share its handle.
+    pub async fn create(directory: &Path, job_id: &str, software: &str) -> Result<Self> {
+        let directory = directory.to_path_buf();
+        let job_id = job_id.to_string();
+        let software = software.to_string();
+
+        tokio::task::spawn_blocking(move || Self::create_blocking(&directory, &job_id, &software)).await?
+    }
+
+    fn create_blocking(directory: &Path, job_id: &str, software: &str) -> Result<Self> {
+        fs::create_dir_all(directory)
+            .with_context(|| format!("Failed to create WARC directory {}", directory.display()))?;
+
+        let file_name = format!("{}.warc.gz", job_id);
+        let path = directory.join(&file_name);
+
+        // Writers on the same archive share one file handle, so appends from
+        // different handles never interleave their gzip members
+        let mut open = open_archives()
+            .lock()
+            .map_err(|_| anyhow::anyhow!("WARC archive registry lock poisoned"))?;
+        open.retain(|_, (file, _)| file.strong_count() > 0);
+
+        let key = fs::canonicalize(directory)?.join(&file_name);
+        if let Some((file, warcinfo_id)) = open.get(&key) {
+            if let Some(file) = file.upgrade() {
+                return Ok(Self {
+                    path,
+                    file,
+                    warcinfo_id: warcinfo_id.clone(),
+                });
+            }
+        }
+
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open WARC file {}", path.display()))?;
+
+        // A continued archive keeps pointing at the warcinfo record it starts with
+        let existing_id = if file.metadata()?.len() > 0 {
+            match read_warcinfo_id(&path) {
+                Ok(id) => id,
+                Err(e) => {
+                    warn!("Failed to read warcinfo of {}: {}", path.display(), e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        let mut writer = Self {
+            path,
+            file: Arc::new(Mutex::new(file)),
+            warcinfo_id: existing_id.clone().unwrap_or_default(),
+        };
+        if existing_id.is_none() {
+            let fields = format!(
+                "software: {}\r\nformat: WARC File Format 1.1\r\nconformsTo: https://iipc.github.io/warc-specifications/specifications/warc-format/warc-1.1/\r\nisPartOf: {}\r\n",
+                software, job_id
+            );
+            let warcinfo = WarcRecord::new("warcinfo", Utc::now(), "application/warc-fields", fields.into_bytes())
+                .with_header("WARC-Filename", file_name);
+            writer.warcinfo_id = warcinfo.record_id.clone();
+            writer.append(&[warcinfo])?;
+        }
+
+        open.insert(key, (Arc::downgrade(&writer.file), writer.warcinfo_id.clone()));
+        Ok(writer)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Write the request, response and metadata records of one fetch
+    pub async fn write_exchange(&self, exchange: &HttpExchange<'_>) -> Result<()> {
+        let records = self.exchange_records(exchange);
+        self.write_records(records).await
+    }
+
+    /// Append records, keeping them together in the file
+    async fn write_records(&self, records: Vec<WarcRecord>) -> Result<()> {
+        let writer = self.clone();
+        tokio::task::spawn_blocking(move || writer.append(&records)).await?
+    }
+
+    /// Build the request, response and metadata records of one fetch
+    fn exchange_records(&self, exchange: &HttpExchange) -> Vec<WarcRecord> {
+        let request = WarcRecord::new(
+            "request",
+            exchange.fetched_at,
+            "application/http;msgtype=request",
+            http_request_block(exchange.url, &exchange.request_headers),
+        )
+        .with_header("WARC-Target-URI", exchange.url)
+        .with_header("WARC-Warcinfo-ID", self.warcinfo_id.clone());
+
+        let response = WarcRecord::new(
+            "response",
+            exchange.fetched_at,
+            "application/http;msgtype=response",
+            http_response_block(exchange.status, &exchange.response_headers, exchange.body),
+        )
+        .with_header("WARC-Target-URI", exchange.url)
+        .with_header("WARC-Warcinfo-ID", self.warcinfo_id.clone())
+        .with_header("WARC-Concurrent-To", request.record_id.clone())
+        .with_header("WARC-Payload-Digest", payload_digest(exchange.body));
+
+        let mut records = vec![request, response];
+
+        if !exchange.metadata.is_empty() {
+            let fields: String = exchange
+                .metadata
+                .iter()
+                .map(|(name, value)| format!("{}: {}\r\n", name, value))
+                .collect();
+            let response_id = records[1].record_id.clone();
+            records.push(
+                WarcRecord::new("metadata", exchange.fetched_at, "application/warc-fields", fields.into_bytes())
+                    .with_header("WARC-Target-URI", exchange.url)
+                    .with_header("WARC-Warcinfo-ID", self.warcinfo_id.clone())
+                    .with_header("WARC-Concurrent-To", response_id),
+            );
+        }
+
+        records
+    }
+
+    /// Compress each record as its own gzip member and append them (blocking)
+    fn append(&self, records: &[WarcRecord]) -> Result<()> {
+        let mut compressed = Vec::new();
+        for record in records {
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(&record.to_bytes())?;
+            compressed.extend(encoder.finish()?);
+        }
+
+        let mut file = self
+            .file
+            .lock()
+            .map_err(|_| anyhow::anyhow!("WARC writer lock poisoned"))?;
+        file.write_all(&compressed)?;
+        Ok(())
+    }
+
+    /// Flush buffered data to disk
+    pub async fn flush(&self) -> Result<()> {
+        let file = self.file.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let mut file = file
+                .lock()
+                .map_err(|_| anyhow::anyhow!("WARC writer lock poisoned"))?;
+            file.flush()?;
+            file.sync_data()?;
+            Ok(())
+        })
+        .await?
+    }
+}
+
+/// Sequential reader for `.warc` and `.warc.gz` files
+///
+/// Compression is detected from the gzip magic bytes, so both per-record
+/// and whole-file gzip archives are read.
+pub struct WarcReader {
+    reader: Box<dyn BufRead + Send>,
+}
+
+impl WarcReader {
+    pub fn open(path: &Path) -> Result<Self> {
+        let mut file = File::open(path)
+            .with_context(|| format!("Failed to open WARC file {}", path.display()))?;
+        let mut magic = [0u8; 2];
+        let is_gzip = file.read(&mut magic)? == 2 && magic == [0x1f, 0x8b];
+        let file = File::open(path)?;
+
+        let reader: Box<dyn BufRead + Send> = if is_gzip {
+            Box::new(BufReader::new(MultiGzDecoder::new(file)))
+        } else {
+            Box::new(BufReader::new(file))
+        };
+        Ok(Self { reader })
+    }
+
+    /// Read the next record, or `None` at the end of the file
+    pub fn next_record(&mut self) -> Result<Option<WarcRecord>> {
+        // Skip blank lines left between records
+        let version = loop {
+            let mut line = String::new();
+            if self.reader.read_line(&mut line)? == 0 {
+                return Ok(None);
+            }
+            let line = line.trim_end();
+            if !line.is_empty() {
+                break line.to_string();
+            }
+        };
+        if !version.starts_with("WARC/") {
+            anyhow::bail!("Expected a WARC version line, found {:?}", version);
+        }
+
+        let mut headers = Vec::new();
+        let mut content_length = None;
+        loop {
+            let mut line = String::new();
+            if self.reader.read_line(&mut line)? == 0 {
+                anyhow::bail!("Truncated WARC record headers");
+            }
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            let Some((name, value)) = line.split_once(':') else {
+                continue;
+            };
+            let (name, value) = (name.trim(), value.trim());
+            if name.eq_ignore_ascii_case("Content-Length") {
+                content_length = Some(value.parse::<usize>().context("Invalid WARC Content-Length")?);
+            } else {
+                headers.push((name.to_string(), value.to_string()));
+            }
+        }
+
+        let content_length = content_length.context("WARC record without Content-Length")?;
+        let mut block = vec![0u8; content_length];
+        self.reader
+            .read_exact(&mut block)
+            .context("Truncated WARC record block")?;
+
+        let header = |name: &str| {
+            headers
+                .iter()
+                .find(|(key, _): &&(String, String)| key.eq_ignore_ascii_case(name))
+                .map(|(_, value)| value.clone())
+                .unwrap_or_default()
+        };
+
+        Ok(Some(WarcRecord {
+            warc_type: header("WARC-Type"),
+            record_id: header("WARC-Record-ID"),
+            headers,
+            block,
+        }))
+    }
+}
+
+impl Iterator for WarcReader {
+    type Item = Result<WarcRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_record().transpose()
+    }
+}
+
+/// HTTP response stored in a `response` record
+#[derive(Debug, Clone)]
+pub struct ArchivedResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl ArchivedResponse {
+    /// Parse an `application/http;msgtype=response` block
+    pub fn parse(block: &[u8]) -> Result<Self> {
+        let head_end = block
+            .windows(4)
+            .position(|window| window == b"\r\n\r\n")
+            .context("HTTP response without header terminator")?;
+        let head = String::from_utf8_lossy(&block[..head_end]);
+        let mut lines = head.split("\r\n");
+
+        let status = lines
+            .next()
+            .and_then(|line| line.split_whitespace().nth(1))
+            .and_then(|code| code.parse::<u16>().ok())
+            .context("Invalid HTTP status line")?;
+
+        let headers = lines
+            .filter_map(|line| line.split_once(':'))
+            .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+            .collect();
+
+        Ok(Self {
+            status,
+            headers,
+            body: block[head_end + 4..].to_vec(),
+        })
+    }
+
+    /// Value of a response header (case-insensitive)
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Archives with a live writer, by path: their shared file and warcinfo ID
+type OpenArchives = HashMap<PathBuf, (Weak<Mutex<File>>, String)>;
+
+fn open_archives() -> &'static Mutex<OpenArchives> {
+    static OPEN_ARCHIVES: OnceLock<Mutex<OpenArchives>> = OnceLock::new();
+    OPEN_ARCHIVES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Record ID of the `warcinfo` record an archive starts with
+fn read_warcinfo_id(path: &Path) -> Result<Option<String>> {
+    let first = WarcReader::open(path)?.next_record()?;
+    Ok(first
+        .filter(|record| record.warc_type == "warcinfo" && !record.record_id.is_empty())
+        .map(|record| record.record_id))
+}
+
+fn new_record_id() -> String {
+    format!("<urn:uuid:{}>", Uuid::new_v4())
+}
+
+/// `sha256:<hex>` digest of the HTTP payload
+fn payload_digest(body: &[u8]) -> String {
+    let digest = Sha256::digest(body);
+    let hex: String = digest.iter().map(|byte| format!("{:02x}", byte)).collect();
+    format!("sha256:{}", hex)
+}
+
+/// Reconstruct the HTTP request sent for a URL
+fn http_request_block(url: &str, headers: &[(String, String)]) -> Vec<u8> {
+    let (target, host) = match Url::parse(url) {
+        Ok(parsed) => {
+            let mut target = parsed.path().to_string();
+            if let Some(query) = parsed.query() {
+                target.push('?');
+                target.push_str(query);
+            }
+            let host = match parsed.port() {
+                Some(port) => format!("{}:{}", parsed.host_str().unwrap_or_default(), port),
+                None => parsed.host_str().unwrap_or_default().to_string(),
+            };
+            (target, host)
+        }
+        Err(_) => ("/".to_string(), String::new()),
+    };
+
+    let mut block = format!("GET {} HTTP/1.1\r\nHost: {}\r\n", target, host);
+    for (name, value) in headers {
+        if !name.eq_ignore_ascii_case("host") {
+            block.push_str(&format!("{}: {}\r\n", name, value));
+        }
+    }
+    block.push_str("\r\n");
+    block.into_bytes()
+}
+
+/// Serialize the HTTP response as stored: status line, headers, body
+fn http_response_block(status: u16, headers: &[(String, String)], body: &[u8]) -> Vec<u8> {
+    let reason = reqwest::StatusCode::from_u16(status)
+        .ok()
+        .and_then(|status| status.canonical_reason())
+        .unwrap_or("");
+
+    let mut head = format!("HTTP/1.1 {} {}\r\n", status, reason);
+    for (name, value) in headers {
+        if !STRIPPED_RESPONSE_HEADERS.contains(&name.to_ascii_lowercase().as_str()) {
+            head.push_str(&format!("{}: {}\r\n", name, value));
+        }
+    }
+    head.push_str(&format!("Content-Length: {}\r\n\r\n", body.len()));
+
+    let mut block = head.into_bytes();
+    block.extend_from_slice(body);
+    block
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("warc-test-{}", Uuid::new_v4()))
+    }
+
+    fn read_archive(path: &Path) -> String {
+        let mut text = String::new();
+        MultiGzDecoder::new(File::open(path).unwrap())
+            .read_to_string(&mut text)
+            .unwrap();
+        text
+    }
+
+    fn exchange(body: &[u8]) -> HttpExchange<'_> {
+        HttpExchange {
+            url: "https://example.com/docs/page?lang=en",
+            request_headers: vec![("User-Agent".to_string(), "EngineSearchBot/1.0".to_string())],
+            status: 200,
+            response_headers: vec![
+                ("Content-Type".to_string(), "text/html; charset=utf-8".to_string()),
+                ("Content-Encoding".to_string(), "gzip".to_string()),
+                ("Content-Length".to_string(), "12".to_string()),
+            ],
+            body,
+            fetched_at: Utc::now(),
+            metadata: vec![("crawlJob".to_string(), "job-1".to_string())],
+        }
+    }
+
+    #[test]
+    fn test_record_serialization() {
+        let record = WarcRecord::new("resource", Utc::now(), "text/plain", b"hello".to_vec())
+            .with_header("WARC-Target-URI", "https://example.com/");
+        let text = String::from_utf8(record.to_bytes()).unwrap();
+
+        assert!(text.starts_with("WARC/1.1\r\nWARC-Type: resource\r\nWARC-Record-ID: <urn:uuid:"));
+        assert!(text.contains("WARC-Target-URI: https://example.com/\r\n"));
+        assert!(text.ends_with("Content-Length: 5\r\n\r\nhello\r\n\r\n"));
+        assert_eq!(record.header("warc-type"), Some("resource"));
+    }
+
+    #[tokio::test]
+    async fn test_write_exchange() {
+        let dir = temp_dir();
+        let body = b"<html><body>Hello</body></html>";
+
+        let writer = WarcWriter::create(&dir, "job-1", "EngineSearchBot/1.0").await.unwrap();
+        writer.write_exchange(&exchange(body)).await.unwrap();
+        writer.flush().await.unwrap();
+
+        let text = read_archive(writer.path());
+        assert_eq!(text.matches("WARC/1.1\r\n").count(), 4);
+        assert!(text.contains("WARC-Type: warcinfo"));
+        assert!(text.contains("isPartOf: job-1"));
+        assert!(text.contains("GET /docs/page?lang=en HTTP/1.1\r\nHost: example.com\r\nUser-Agent: EngineSearchBot/1.0\r\n"));
+        assert!(text.contains("HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\n"));
+        assert!(text.contains(&format!("Content-Length: {}\r\n\r\n<html>", body.len())));
+        assert!(!text.contains("Content-Encoding"));
+        assert!(text.contains("WARC-Payload-Digest: sha256:"));
+        assert!(text.contains("WARC-Type: metadata"));
+        assert!(text.contains("crawlJob: job-1"));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_read_back_exchange() {
+        let dir = temp_dir();
+        let body = b"<html><body>Hello</body></html>";
+
+        let writer = WarcWriter::create(&dir, "job-3", "test").await.unwrap();
+        writer.write_exchange(&exchange(body)).await.unwrap();
+
+        let records: Vec<WarcRecord> = WarcReader::open(writer.path())
+            .unwrap()
+            .collect::<Result<_>>()
+            .unwrap();
+        let types: Vec<&str> = records.iter().map(|record| record.warc_type.as_str()).collect();
+        assert_eq!(types, vec!["warcinfo", "request", "response", "metadata"]);
+
+        let response = &records[2];
+        assert_eq!(response.header("WARC-Target-URI"), Some("https://example.com/docs/page?lang=en"));
+        assert_eq!(response.header("WARC-Concurrent-To"), Some(records[1].record_id.as_str()));
+
+        let http = ArchivedResponse::parse(&response.block).unwrap();
+        assert_eq!(http.status, 200);
+        assert_eq!(http.header("content-type"), Some("text/html; charset=utf-8"));
+        assert_eq!(http.body, body);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_read_uncompressed_warc() {
+        let dir = temp_dir();
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("plain.warc");
+
+        let record = WarcRecord::new("resource", Utc::now(), "text/plain", b"one".to_vec());
+        let mut bytes = record.to_bytes();
+        bytes.extend(WarcRecord::new("resource", Utc::now(), "text/plain", b"two".to_vec()).to_bytes());
+        fs::write(&path, bytes).unwrap();
+
+        let blocks: Vec<Vec<u8>> = WarcReader::open(&path)
+            .unwrap()
+            .map(|record| record.unwrap().block)
+            .collect();
+        assert_eq!(blocks, vec![b"one".to_vec(), b"two".to_vec()]);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_records_are_separate_gzip_members() {
+        let dir = temp_dir();
+        let writer = WarcWriter::create(&dir, "job-2", "test").await.unwrap();
+        writer.write_exchange(&exchange(b"body")).await.unwrap();
+
+        // Reopening continues the archive without a second warcinfo
+        let reopened = WarcWriter::create(&dir, "job-2", "test").await.unwrap();
+        reopened.write_exchange(&exchange(b"body")).await.unwrap();
+
+        let bytes = fs::read(writer.path()).unwrap();
+        let mut cursor = std::io::Cursor::new(&bytes[..]);
+        let mut members = 0;
+        while (cursor.position() as usize) < bytes.len() {
+            let mut member = flate2::bufread::GzDecoder::new(&mut cursor);
+            std::io::copy(&mut member, &mut std::io::sink()).unwrap();
+            members += 1;
+        }
+        assert_eq!(members, 7);
+        assert_eq!(read_archive(writer.path()).matches("WARC-Type: warcinfo").count(), 1);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_reopened_archive_keeps_warcinfo_id() {
+        let dir = temp_dir();
+        let writer = WarcWriter::create(&dir, "job-4", "test").await.unwrap();
+        let shared = WarcWriter::create(&dir, "job-4", "test").await.unwrap();
+        assert!(Arc::ptr_eq(&writer.file, &shared.file));
+        let warcinfo_id = writer.warcinfo_id.clone();
+        drop((writer, shared));
+
+        // Once every writer is gone the ID is read back from the file
+        let reopened = WarcWriter::create(&dir, "job-4", "test").await.unwrap();
+        reopened.write_exchange(&exchange(b"body")).await.unwrap();
+
+        let records: Vec<WarcRecord> = WarcReader::open(reopened.path())
+            .unwrap()
+            .collect::<Result<_>>()
+            .unwrap();
+        assert_eq!(records[0].record_id, warcinfo_id);
+        assert!(records[1..]
+            .iter()
+            .all(|record| record.header("WARC-Warcinfo-ID") == Some(warcinfo_id.as_str())));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -333,19 +333,27 @@ impl QdrantService {
     }
 
     /// Index a page with its embedding
+    ///
+    /// `page_id` is the document's stable ID, so re-indexing a page overwrites its point.
     pub async fn index_page(
         &self,
         page_id: &str,
         url: &str,
         title: &str,
+        anchor_text: &[String],
         content: &str,
     ) -> Result<()> {
-        // Combine title + content for richer embeddings (limit to first 512 tokens ~2048 chars)
-        let text = format!("{}\n\n{}", title, content);
-        let truncated_text = if text.len() > 2048 {
-            &text[..2048]
+        // Combine title + inbound anchors + content for richer embeddings
+        // (limit to first 512 tokens ~2048 chars). Anchors come before the
+        // content so truncation never cuts them off.
+        let text = if anchor_text.is_empty() {
+            format!("{}\n\n{}", title, content)
         } else {
-            &text
+            format!("{}\n\n{}\n\n{}", title, anchor_text.join(" | "), content)
+        };
+        let truncated_text = match text.char_indices().nth(2048) {
+            Some((end, _)) => &text[..end],
+            None => &text,
         };
 
         // Generate embedding
@@ -1,11 +1,33 @@
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use redis::aio::ConnectionManager;
-use redis::AsyncCommands;
+use redis::{AsyncCommands, Script};
 use serde::{Deserialize, Serialize};
 use tracing::{error, info};
 use uuid::Uuid;
 
+use crate::search::crawler::{CrawlBudget, CrawlOptions};
+
+/// Jobs are dropped this long after they were (re)queued, unless updated
+const JOB_TTL_SECS: i64 = 86400;
+
+/// Replace a stored job, but only if nobody changed it since it was read
+///
+/// `KEYS[1]` is the job key, `ARGV[1]` the job as read and `ARGV[2]` the
+/// new job. With a TTL in `ARGV[3]`, the job expires after that many
+/// seconds. Returns 1 if the job was replaced.
+///
+/// The script only touches the job key, so it runs on Redis Cluster; the
+/// queue lives in another slot and is pushed to separately.
+const UPDATE_JOB_SCRIPT: &str = r#"
+if redis.call('GET', KEYS[1]) ~= ARGV[1] then return 0 end
+redis.call('SET', KEYS[1], ARGV[2])
+if ARGV[3] then
+    redis.call('EXPIRE', KEYS[1], tonumber(ARGV[3]))
+end
+return 1
+"#;
+
 /// Job queue manager for background task processing
 #[derive(Clone)]
 pub struct JobQueue {
@@ -20,6 +42,41 @@ pub enum JobStatus {
     Processing,
     Completed,
     Failed,
+    Cancelled,
+    /// Stopped on request; `urls` holds the URLs left to crawl
+    Paused,
+}
+
+impl JobStatus {
+    /// Whether the job can no longer change
+    pub fn is_finished(&self) -> bool {
+        matches!(self, Self::Completed | Self::Failed | Self::Cancelled)
+    }
+}
+
+/// Request to stop a job, acted on by the worker that dequeues or runs it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobControl {
+    Cancel,
+    Pause,
+}
+
+impl JobControl {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Cancel => "cancel",
+            Self::Pause => "pause",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "cancel" => Some(Self::Cancel),
+            "pause" => Some(Self::Pause),
+            _ => None,
+        }
+    }
 }
 
 /// Crawl job
@@ -36,6 +93,18 @@ pub struct CrawlJob {
     pub error: Option<String>,
     pub pages_crawled: usize,
     pub pages_indexed: usize,
+    /// Scheduled crawl this job was enqueued for (recrawl loop only)
+    #[serde(default)]
+    pub scheduled_crawl_id: Option<Uuid>,
+    /// Limits for the whole job
+    #[serde(default)]
+    pub budget: CrawlBudget,
+    /// Limits per domain; the crawler's default when `None`
+    #[serde(default)]
+    pub domain_budget: Option<CrawlBudget>,
+    /// Crawler settings applied by the worker for this job
+    #[serde(default)]
+    pub options: CrawlOptions,
 }
 
 impl CrawlJob {
@@ -52,6 +121,10 @@ impl CrawlJob {
             error: None,
             pages_crawled: 0,
             pages_indexed: 0,
+            scheduled_crawl_id: None,
+            budget: CrawlBudget::default(),
+            domain_budget: None,
+            options: CrawlOptions::default(),
         }
     }
 }
@@ -71,7 +144,7 @@ impl JobQueue {
         // Also store the job details with its ID for status tracking
         let job_key = format!("job:{}", job.id);
         self.conn.set(&job_key, &job_json).await?;
-        self.conn.expire(&job_key, 86400).await?; // Expire after 24 hours
+        self.conn.expire(&job_key, JOB_TTL_SECS).await?; // Expire after 24 hours
 
         info!("Enqueued job {} with {} URLs", job.id, job.urls.len());
         Ok(())
@@ -84,7 +157,8 @@ impl JobQueue {
                 match serde_json::from_str::<CrawlJob>(&job_json) {
                     Ok(mut job) => {
                         job.status = JobStatus::Processing;
-                        job.started_at = Some(Utc::now());
+                        // Resumed jobs keep their original start
+                        job.started_at.get_or_insert_with(Utc::now);
 
                         // Update job status
                         self.update_job(&job).await?;
@@ -114,22 +188,77 @@ impl JobQueue {
         Ok(())
     }
 
-    /// Mark job as completed
-    pub async fn complete_job(&mut self, job_id: Uuid, pages_crawled: usize, pages_indexed: usize) -> Result<()> {
+    /// Change a stored job without losing a concurrent update
+    ///
+    /// `change` returns false to leave the job alone; it runs again if
+    /// another writer changed the job first. With `requeue`, the changed job
+    /// is also put back on the queue. Returns the stored job, or `None` if
+    /// it does not exist or was left alone.
+    async fn modify_job<F>(&mut self, job_id: Uuid, requeue: bool, mut change: F) -> Result<Option<CrawlJob>>
+    where
+        F: FnMut(&mut CrawlJob) -> bool,
+    {
         let job_key = format!("job:{}", job_id);
 
-        if let Some(job_json) = self.conn.get::<_, Option<String>>(&job_key).await? {
-            if let Ok(mut job) = serde_json::from_str::<CrawlJob>(&job_json) {
+        loop {
+            let Some(job_json) = self.conn.get::<_, Option<String>>(&job_key).await? else {
+                return Ok(None);
+            };
+            let Ok(mut job) = serde_json::from_str::<CrawlJob>(&job_json) else {
+                return Ok(None);
+            };
+            if !change(&mut job) {
+                return Ok(None);
+            }
+
+            let updated_json = serde_json::to_string(&job)?;
+            let script = Script::new(UPDATE_JOB_SCRIPT);
+            let mut invocation = script.key(&job_key);
+            invocation.arg(&job_json).arg(&updated_json);
+            if requeue {
+                invocation.arg(JOB_TTL_SECS);
+            }
+            let replaced: bool = invocation.invoke_async(&mut self.conn).await?;
+            if replaced {
+                // Only the caller whose update won pushes the job back
+                if requeue {
+                    let _: () = self.conn.rpush(&self.queue_name, &updated_json).await?;
+                }
+                return Ok(Some(job));
+            }
+        }
+    }
+
+    /// Update a job's page counts, leaving its status alone
+    ///
+    /// Unlike `update_job`, this can't undo a status set meanwhile (e.g.
+    /// `Paused` by another worker).
+    pub async fn update_progress(&mut self, job_id: Uuid, pages_crawled: usize, pages_indexed: usize) -> Result<()> {
+        self.modify_job(job_id, false, |job| {
+            job.pages_crawled = pages_crawled;
+            job.pages_indexed = pages_indexed;
+            true
+        })
+        .await?;
+
+        Ok(())
+    }
+
+    /// Mark job as completed
+    pub async fn complete_job(&mut self, job_id: Uuid, pages_crawled: usize, pages_indexed: usize) -> Result<()> {
+        let completed = self
+            .modify_job(job_id, false, |job| {
                 job.status = JobStatus::Completed;
                 job.completed_at = Some(Utc::now());
                 job.pages_crawled = pages_crawled;
                 job.pages_indexed = pages_indexed;
+                true
+            })
+            .await?;
 
-                let updated_json = serde_json::to_string(&job)?;
-                self.conn.set(&job_key, updated_json).await?;
-
-                info!("Marked job {} as completed (crawled: {}, indexed: {})", job_id, pages_crawled, pages_indexed);
-            }
+        if completed.is_some() {
+            self.clear_control(job_id).await?;
+            info!("Marked job {} as completed (crawled: {}, indexed: {})", job_id, pages_crawled, pages_indexed);
         }
 
         Ok(())
@@ -137,21 +266,142 @@ impl JobQueue {
 
     /// Mark job as failed
     pub async fn fail_job(&mut self, job_id: Uuid, error: String) -> Result<()> {
-        let job_key = format!("job:{}", job_id);
-
-        if let Some(job_json) = self.conn.get::<_, Option<String>>(&job_key).await? {
-            if let Ok(mut job) = serde_json::from_str::<CrawlJob>(&job_json) {
+        let failed = self
+            .modify_job(job_id, false, |job| {
                 job.status = JobStatus::Failed;
                 job.completed_at = Some(Utc::now());
                 job.error = Some(error.clone());
+                true
+            })
+            .await?;
 
-                let updated_json = serde_json::to_string(&job)?;
-                self.conn.set(&job_key, updated_json).await?;
+        if failed.is_some() {
+            self.clear_control(job_id).await?;
+            error!("Marked job {} as failed: {}", job_id, error);
+        }
 
-                error!("Marked job {} as failed: {}", job_id, error);
-            }
+        Ok(())
+    }
+
+    /// Mark job as cancelled
+    pub async fn cancel_job(&mut self, job_id: Uuid, pages_crawled: usize, pages_indexed: usize) -> Result<()> {
+        let cancelled = self
+            .modify_job(job_id, false, |job| {
+                job.status = JobStatus::Cancelled;
+                job.completed_at = Some(Utc::now());
+                job.pages_crawled = pages_crawled;
+                job.pages_indexed = pages_indexed;
+                true
+            })
+            .await?;
+
+        if cancelled.is_some() {
+            self.clear_control(job_id).await?;
+            info!("Marked job {} as cancelled (crawled: {}, indexed: {})", job_id, pages_crawled, pages_indexed);
+        }
+
+        Ok(())
+    }
+
+    /// Cancel a job if it is still paused, returns false otherwise
+    ///
+    /// No worker holds a paused job, so nobody else will act on a request.
+    pub async fn cancel_paused_job(&mut self, job_id: Uuid) -> Result<bool> {
+        let cancelled = self
+            .modify_job(job_id, false, |job| {
+                if job.status != JobStatus::Paused {
+                    return false;
+                }
+                job.status = JobStatus::Cancelled;
+                job.completed_at = Some(Utc::now());
+                true
+            })
+            .await?;
+
+        if cancelled.is_none() {
+            return Ok(false);
+        }
+        self.clear_control(job_id).await?;
+
+        info!("Cancelled paused job {}", job_id);
+        Ok(true)
+    }
+
+    /// Mark job as paused, keeping the given URLs to crawl on resume
+    ///
+    /// A job finished meanwhile (e.g. cancelled) stays finished.
+    pub async fn pause_job(
+        &mut self,
+        job_id: Uuid,
+        remaining_urls: Vec<String>,
+        pages_crawled: usize,
+        pages_indexed: usize,
+    ) -> Result<()> {
+        let paused = self
+            .modify_job(job_id, false, |job| {
+                if job.status.is_finished() {
+                    return false;
+                }
+                job.status = JobStatus::Paused;
+                job.urls = remaining_urls.clone();
+                job.pages_crawled = pages_crawled;
+                job.pages_indexed = pages_indexed;
+                true
+            })
+            .await?;
+        self.clear_control(job_id).await?;
+
+        if paused.is_some() {
+            info!("Paused job {} with {} URLs left", job_id, remaining_urls.len());
+        }
+        Ok(())
+    }
+
+    /// Put a paused job back on the queue, returns false if it is not paused
+    ///
+    /// The status check and the requeue happen at once, so a job resumed
+    /// twice is only queued once.
+    pub async fn resume_job(&mut self, job_id: Uuid) -> Result<bool> {
+        let resumed = self
+            .modify_job(job_id, true, |job| {
+                if job.status != JobStatus::Paused {
+                    return false;
+                }
+                job.status = JobStatus::Pending;
+                true
+            })
+            .await?;
+
+        if resumed.is_none() {
+            return Ok(false);
         }
 
+        info!("Resumed job {}", job_id);
+        Ok(true)
+    }
+
+    /// Ask the worker holding a job to cancel or pause it
+    ///
+    /// Pending jobs are stopped when dequeued, running ones between pages.
+    /// The request does not expire, as a job may wait in the queue for long;
+    /// it is dropped once the job is stopped or finishes.
+    pub async fn request_control(&mut self, job_id: Uuid, control: JobControl) -> Result<()> {
+        let control_key = format!("job:{}:control", job_id);
+        let _: () = self.conn.set(&control_key, control.as_str()).await?;
+        Ok(())
+    }
+
+    /// Pending stop request of a job, if any
+    pub async fn control(&mut self, job_id: Uuid) -> Result<Option<JobControl>> {
+        let control_key = format!("job:{}:control", job_id);
+        let control: Option<String> = self.conn.get(&control_key).await?;
+        Ok(control.as_deref().and_then(JobControl::parse))
+    }
+
+    /// Drop a job's stop request (acted on, or withdrawn by a resume)
+    pub async fn clear_control(&mut self, job_id: Uuid) -> Result<()> {
+        let control_key = format!("job:{}:control", job_id);
+        let _: () = self.conn.del(&control_key).await?;
         Ok(())
     }
 
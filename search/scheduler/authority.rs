@@ -0,0 +1,430 @@
+use anyhow::Result;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::time::Instant;
+use tracing::info;
+
+use crate::search::crawler::{host_of, LinkRepository, UrlProcessor};
+use crate::search::search::SearchClient;
+
+/// Tuning for the link authority computation
+#[derive(Debug, Clone)]
+pub struct AuthorityConfig {
+    /// Probability of following a link rather than jumping to a random page
+    pub damping: f64,
+    pub max_iterations: usize,
+    /// Stop once the L1 change between iterations drops below this
+    pub tolerance: f64,
+    /// Share of the final score taken from the page graph (the rest is the domain's)
+    pub page_weight: f64,
+}
+
+impl Default for AuthorityConfig {
+    fn default() -> Self {
+        Self {
+            damping: 0.85,
+            max_iterations: 50,
+            tolerance: 1e-6,
+            page_weight: 0.7,
+        }
+    }
+}
+
+/// Iterative PageRank over a graph of `n` nodes
+///
+/// Edges are `(source, target)` node indexes; duplicate edges count once per
+/// occurrence and self-loops are ignored. Rank held by nodes without outgoing
+/// edges is spread evenly over the graph, so scores always sum to 1.
+pub fn compute_pagerank(
+    n: usize,
+    edges: &[(usize, usize)],
+    damping: f64,
+    max_iterations: usize,
+    tolerance: f64,
+) -> Vec<f64> {
+    if n == 0 {
+        return Vec::new();
+    }
+
+    let mut out_degree = vec![0usize; n];
+    let mut inbound: Vec<Vec<usize>> = vec![Vec::new(); n];
+    for &(source, target) in edges {
+        if source == target || source >= n || target >= n {
+            continue;
+        }
+        out_degree[source] += 1;
+        inbound[target].push(source);
+    }
+
+    let uniform = 1.0 / n as f64;
+    let mut ranks = vec![uniform; n];
+
+    for _ in 0..max_iterations {
+        let dangling: f64 = ranks
+            .iter()
+            .zip(&out_degree)
+            .filter(|(_, &degree)| degree == 0)
+            .map(|(rank, _)| rank)
+            .sum();
+        let base = (1.0 - damping) * uniform + damping * dangling * uniform;
+
+        let next: Vec<f64> = inbound
+            .iter()
+            .map(|sources| {
+                base + damping
+                    * sources
+                        .iter()
+                        .map(|&source| ranks[source] / out_degree[source] as f64)
+                        .sum::<f64>()
+            })
+            .collect();
+
+        let delta: f64 = next.iter().zip(&ranks).map(|(a, b)| (a - b).abs()).sum();
+        ranks = next;
+        if delta < tolerance {
+            break;
+        }
+    }
+
+    ranks
+}
+
+/// Map raw PageRank scores onto 0.0-1.0
+///
+/// PageRank is heavily skewed, so scores are compared on a log scale where
+/// an average page (`score * n == 1`) sits well below the best-linked one.
+pub fn normalize_scores(scores: &[f64]) -> Vec<f64> {
+    let n = scores.len() as f64;
+    let max = scores.iter().cloned().fold(0.0, f64::max);
+    let max_log = (1.0 + max * n).ln();
+    if max_log <= 0.0 {
+        return vec![0.0; scores.len()];
+    }
+
+    scores
+        .iter()
+        .map(|score| ((1.0 + score * n).ln() / max_log).clamp(0.0, 1.0))
+        .collect()
+}
+
+/// Graph with string nodes mapped to dense indexes
+struct Graph {
+    nodes: Vec<String>,
+    edges: Vec<(usize, usize)>,
+    inbound: Vec<usize>,
+}
+
+impl Graph {
+    fn build(pairs: &[(String, String)]) -> Self {
+        let mut index: HashMap<&str, usize> = HashMap::new();
+        let mut nodes = Vec::new();
+        let mut edges = Vec::with_capacity(pairs.len());
+
+        for (source, target) in pairs {
+            let mut ids = [0; 2];
+            for (slot, node) in ids.iter_mut().zip([source, target]) {
+                *slot = *index.entry(node.as_str()).or_insert_with(|| {
+                    nodes.push(node.clone());
+                    nodes.len() - 1
+                });
+            }
+            edges.push((ids[0], ids[1]));
+        }
+
+        let mut inbound = vec![0; nodes.len()];
+        for &(source, target) in &edges {
+            if source != target {
+                inbound[target] += 1;
+            }
+        }
+
+        Self { nodes, edges, inbound }
+    }
+}
+
+/// Nightly job computing page and domain authority from the link graph
+///
+/// Also refreshes the inbound anchor texts of indexed pages, since both are
+/// link signals that change as other pages are crawled.
+pub struct AuthorityJob {
+    pool: PgPool,
+    search_client: SearchClient,
+    config: AuthorityConfig,
+}
+
+impl AuthorityJob {
+    pub fn new(pool: PgPool, search_client: SearchClient) -> Self {
+        Self::with_config(pool, search_client, AuthorityConfig::default())
+    }
+
+    pub fn with_config(pool: PgPool, search_client: SearchClient, config: AuthorityConfig) -> Self {
+        Self {
+            pool,
+            search_client,
+            config,
+        }
+    }
+
+    /// Recompute authority scores and push them, with inbound anchors, to the search index
+    ///
+    /// Returns the number of pages scored.
+    pub async fn run(&self) -> Result<usize> {
+        let started = Instant::now();
+        let links = LinkRepository::new(self.pool.clone());
+
+        // Domain graph: one edge per linking domain pair, so a site can't
+        // inflate a target by linking to it from every page
+        let domain_graph = Graph::build(&links.followed_domain_edges().await?);
+        let domain_scores = compute_pagerank(
+            domain_graph.nodes.len(),
+            &domain_graph.edges,
+            self.config.damping,
+            self.config.max_iterations,
+            self.config.tolerance,
+        );
+        let domain_normalized = normalize_scores(&domain_scores);
+        let domain_lookup: HashMap<&str, f64> = domain_graph
+            .nodes
+            .iter()
+            .map(String::as_str)
+            .zip(domain_normalized.iter().copied())
+            .collect();
+
+        let page_graph = Graph::build(&links.followed_edges().await?);
+        let page_scores = compute_pagerank(
+            page_graph.nodes.len(),
+            &page_graph.edges,
+            self.config.damping,
+            self.config.max_iterations,
+            self.config.tolerance,
+        );
+        let page_normalized = normalize_scores(&page_scores);
+
+        let authority: Vec<i16> = page_graph
+            .nodes
+            .iter()
+            .zip(&page_normalized)
+            .map(|(url, page)| {
+                let domain = host_of(url)
+                    .and_then(|host| domain_lookup.get(host.as_str()).copied())
+                    .unwrap_or(0.0);
+                authority_bucket(*page, domain, self.config.page_weight)
+            })
+            .collect();
+
+        let repository = AuthorityRepository::new(self.pool.clone());
+        repository
+            .replace_domains(
+                &domain_graph.nodes,
+                &domain_scores,
+                &domain_normalized.iter().map(|score| authority_bucket(*score, *score, 1.0)).collect::<Vec<_>>(),
+                &domain_graph.inbound,
+            )
+            .await?;
+        repository
+            .replace_pages(&page_graph.nodes, &page_scores, &authority, &page_graph.inbound)
+            .await?;
+
+        // Pages crawled with content are the candidates; the search client only
+        // updates those still in the index, so link targets that were never
+        // indexed (or were removed as noindex) don't become stub documents
+        let indexed = repository.indexed_authority().await?;
+        let updates: Vec<(String, u8)> = indexed
+            .into_iter()
+            .map(|(url, authority)| (UrlProcessor::document_id(&url), authority.clamp(0, 10) as u8))
+            .collect();
+        let updated = self.search_client.update_authority(&updates).await?;
+
+        // Anchors recorded since a page was last indexed only reach it here
+        let anchors: Vec<(String, Vec<String>)> = links
+            .indexed_inbound_anchors()
+            .await?
+            .into_iter()
+            .map(|(url, anchors)| (UrlProcessor::document_id(&url), anchors))
+            .collect();
+        self.search_client.update_anchor_text(anchors).await?;
+
+        info!(
+            "Computed authority for {} pages and {} domains in {:?} ({} documents updated)",
+            page_graph.nodes.len(),
+            domain_graph.nodes.len(),
+            started.elapsed(),
+            updated
+        );
+
+        Ok(page_graph.nodes.len())
+    }
+}
+
+/// Combine normalized page and domain scores into a 0-10 bucket
+fn authority_bucket(page: f64, domain: f64, page_weight: f64) -> i16 {
+    let combined = page_weight * page + (1.0 - page_weight) * domain;
+    (combined * 10.0).round().clamp(0.0, 10.0) as i16
+}
+
+/// Repository for computed authority scores
+#[derive(Clone)]
+pub struct AuthorityRepository {
+    pool: PgPool,
+}
+
+impl AuthorityRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Replace all page scores with a fresh computation
+    pub async fn replace_pages(
+        &self,
+        urls: &[String],
+        scores: &[f64],
+        authority: &[i16],
+        inbound: &[usize],
+    ) -> Result<()> {
+        let inbound: Vec<i32> = inbound.iter().map(|count| *count as i32).collect();
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM page_authority").execute(&mut *tx).await?;
+        sqlx::query(
+            r#"
+            INSERT INTO page_authority (url, score, authority, inbound_links)
+            SELECT * FROM UNNEST($1::text[], $2::float8[], $3::int2[], $4::int4[])
+            "#,
+        )
+        .bind(urls)
+        .bind(scores)
+        .bind(authority)
+        .bind(&inbound)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Replace all domain scores with a fresh computation
+    pub async fn replace_domains(
+        &self,
+        domains: &[String],
+        scores: &[f64],
+        authority: &[i16],
+        inbound: &[usize],
+    ) -> Result<()> {
+        let inbound: Vec<i32> = inbound.iter().map(|count| *count as i32).collect();
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM domain_authority").execute(&mut *tx).await?;
+        sqlx::query(
+            r#"
+            INSERT INTO domain_authority (domain, score, authority, inbound_domains)
+            SELECT * FROM UNNEST($1::text[], $2::float8[], $3::int2[], $4::int4[])
+            "#,
+        )
+        .bind(domains)
+        .bind(scores)
+        .bind(authority)
+        .bind(&inbound)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Authority of pages crawled with content (have a content hash),
+    /// the candidates for a partial update of the search index
+    pub async fn indexed_authority(&self) -> Result<Vec<(String, i16)>> {
+        let rows = sqlx::query_as::<_, (String, i16)>(
+            r#"
+            SELECT a.url, a.authority
+            FROM page_authority a
+            JOIN page_states s ON s.url = a.url
+            WHERE s.content_hash IS NOT NULL
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    /// Authority of the given URLs, for documents about to be (re)indexed
+    pub async fn get_many(&self, urls: &[String]) -> Result<HashMap<String, u8>> {
+        let rows = sqlx::query_as::<_, (String, i16)>(
+            "SELECT url, authority FROM page_authority WHERE url = ANY($1)",
+        )
+        .bind(urls)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(url, authority)| (url, authority.clamp(0, 10) as u8))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pagerank(n: usize, edges: &[(usize, usize)]) -> Vec<f64> {
+        compute_pagerank(n, edges, 0.85, 100, 1e-9)
+    }
+
+    #[test]
+    fn test_pagerank_sums_to_one() {
+        // 3 is dangling: its rank must be redistributed, not lost
+        let ranks = pagerank(4, &[(0, 1), (1, 2), (2, 0), (0, 3)]);
+        let total: f64 = ranks.iter().sum();
+        assert!((total - 1.0).abs() < 1e-6, "total = {}", total);
+    }
+
+    #[test]
+    fn test_pagerank_favors_linked_pages() {
+        // Hub 0 is linked from every other page
+        let ranks = pagerank(4, &[(1, 0), (2, 0), (3, 0), (0, 1)]);
+        assert!(ranks[0] > ranks[1]);
+        assert!(ranks[1] > ranks[2]);
+        assert!((ranks[2] - ranks[3]).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_pagerank_symmetric_cycle() {
+        let ranks = pagerank(3, &[(0, 1), (1, 2), (2, 0), (1, 1)]);
+        for rank in ranks {
+            assert!((rank - 1.0 / 3.0).abs() < 1e-6);
+        }
+        assert!(pagerank(0, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_normalize_scores() {
+        let normalized = normalize_scores(&pagerank(4, &[(1, 0), (2, 0), (3, 0), (0, 1)]));
+        assert!((normalized[0] - 1.0).abs() < 1e-9);
+        assert!(normalized.iter().all(|score| (0.0..=1.0).contains(score)));
+        assert!(normalized[2] < normalized[1]);
+
+        assert_eq!(normalize_scores(&[0.0, 0.0]), vec![0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_authority_bucket() {
+        assert_eq!(authority_bucket(1.0, 1.0, 0.7), 10);
+        assert_eq!(authority_bucket(0.0, 0.0, 0.7), 0);
+        assert_eq!(authority_bucket(1.0, 0.0, 0.7), 7);
+        assert_eq!(authority_bucket(0.5, 1.0, 0.7), 7);
+    }
+
+    #[test]
+    fn test_graph_build() {
+        let graph = Graph::build(&[
+            ("a".to_string(), "b".to_string()),
+            ("c".to_string(), "b".to_string()),
+            ("b".to_string(), "a".to_string()),
+        ]);
+        assert_eq!(graph.nodes, vec!["a", "b", "c"]);
+        assert_eq!(graph.edges, vec![(0, 1), (2, 1), (1, 0)]);
+        assert_eq!(graph.inbound, vec![1, 2, 0]);
+    }
+}
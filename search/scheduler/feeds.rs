@@ -0,0 +1,489 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+use std::collections::HashSet;
+use tokio::time::sleep;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use super::recrawl::ScheduleRepository;
+use crate::search::crawler::{
+    CrawlFrequency, Crawler, DiscoveredFeed, FeedEntry, FeedFetch, PageValidators, ParsedFeed,
+};
+use crate::search::redis::{CrawlJob, JobQueue};
+
+/// Recrawl priority given to posts found in feeds
+const FEED_ENTRY_PRIORITY: u8 = 70;
+
+/// Persisted feed (row of `feeds`)
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct FeedRecord {
+    pub id: Uuid,
+    pub url: String,
+    pub domain: String,
+    pub title: Option<String>,
+    pub poll_interval_secs: i32,
+    pub next_poll_at: DateTime<Utc>,
+    pub last_polled_at: Option<DateTime<Utc>>,
+    pub last_entry_at: Option<DateTime<Utc>>,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub consecutive_failures: i32,
+    pub last_error: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl FeedRecord {
+    /// Validators for a conditional poll
+    pub fn validators(&self) -> PageValidators {
+        PageValidators {
+            etag: self.etag.clone(),
+            last_modified: self.last_modified.clone(),
+        }
+    }
+
+    pub fn poll_interval(&self) -> Duration {
+        Duration::seconds(i64::from(self.poll_interval_secs.max(1)))
+    }
+}
+
+/// Repository for discovered feeds and the entries seen in them
+#[derive(Clone)]
+pub struct FeedRepository {
+    pool: PgPool,
+}
+
+impl FeedRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Persist feeds found while crawling, returns how many were new
+    ///
+    /// New feeds are due for a poll right away.
+    pub async fn upsert_discovered(&self, feeds: &[DiscoveredFeed]) -> Result<u64> {
+        let mut inserted = 0;
+
+        for chunk in feeds.chunks(1000) {
+            let urls: Vec<String> = chunk.iter().map(|f| f.url.clone()).collect();
+            let domains: Vec<String> = chunk.iter().map(|f| f.domain.clone()).collect();
+
+            let result = sqlx::query(
+                r#"
+                INSERT INTO feeds (url, domain)
+                SELECT DISTINCT ON (url) url, domain
+                FROM UNNEST($1::text[], $2::text[]) AS t(url, domain)
+                ON CONFLICT (url) DO NOTHING
+                "#,
+            )
+            .bind(&urls)
+            .bind(&domains)
+            .execute(&self.pool)
+            .await?;
+
+            inserted += result.rows_affected();
+        }
+
+        Ok(inserted)
+    }
+
+    /// List feeds, optionally of one domain, most recently polled first
+    pub async fn list(&self, domain: Option<&str>, limit: i64, offset: i64) -> Result<(Vec<FeedRecord>, i64)> {
+        let records = sqlx::query_as::<_, FeedRecord>(
+            r#"
+            SELECT * FROM feeds
+            WHERE $1::text IS NULL OR domain = $1
+            ORDER BY last_polled_at DESC NULLS LAST, created_at DESC
+            LIMIT $2 OFFSET $3
+            "#,
+        )
+        .bind(domain)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let total = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM feeds WHERE $1::text IS NULL OR domain = $1")
+            .bind(domain)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok((records, total))
+    }
+
+    /// Load feeds due for a poll, most overdue first
+    pub async fn load_due(&self, limit: i64) -> Result<Vec<FeedRecord>> {
+        let records = sqlx::query_as::<_, FeedRecord>(
+            r#"
+            SELECT * FROM feeds
+            WHERE next_poll_at <= NOW()
+            ORDER BY next_poll_at ASC
+            LIMIT $1
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(records)
+    }
+
+    /// URLs of a feed not crawled from it before
+    pub async fn new_entry_urls(&self, feed_id: Uuid, urls: &[String]) -> Result<Vec<String>> {
+        if urls.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let new_urls = sqlx::query_scalar::<_, String>(
+            r#"
+            SELECT DISTINCT t.url
+            FROM UNNEST($2::text[]) AS t(url)
+            WHERE NOT EXISTS (
+                SELECT 1 FROM feed_entries e WHERE e.feed_id = $1 AND e.url = t.url
+            )
+            "#,
+        )
+        .bind(feed_id)
+        .bind(urls)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(new_urls)
+    }
+
+    /// Remember an entry of a feed and the job enqueued to crawl it
+    pub async fn record_entry(&self, feed_id: Uuid, entry: &FeedEntry, job_id: Uuid) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO feed_entries (feed_id, url, published_at, job_id)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (feed_id, url) DO UPDATE SET job_id = EXCLUDED.job_id
+            "#,
+        )
+        .bind(feed_id)
+        .bind(&entry.url)
+        .bind(entry.published_at)
+        .bind(job_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Record a successful poll and schedule the next one
+    pub async fn record_poll(
+        &self,
+        id: Uuid,
+        title: Option<&str>,
+        validators: &PageValidators,
+        interval: Duration,
+        found_new: bool,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE feeds
+            SET title = COALESCE($2, title),
+                etag = $3,
+                last_modified = $4,
+                poll_interval_secs = $5,
+                next_poll_at = NOW() + $5 * INTERVAL '1 second',
+                last_polled_at = NOW(),
+                last_entry_at = CASE WHEN $6 THEN NOW() ELSE last_entry_at END,
+                consecutive_failures = 0,
+                last_error = NULL,
+                updated_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(title)
+        .bind(&validators.etag)
+        .bind(&validators.last_modified)
+        .bind(interval.num_seconds() as i32)
+        .bind(found_new)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Record a failed poll; the feed is retried at `retry_at`
+    pub async fn record_failure(&self, id: Uuid, error: &str, retry_at: DateTime<Utc>) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE feeds
+            SET consecutive_failures = consecutive_failures + 1,
+                last_error = $2,
+                next_poll_at = $3,
+                last_polled_at = NOW(),
+                updated_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(error)
+        .bind(retry_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
+
+/// Feed poll loop configuration
+#[derive(Debug, Clone)]
+pub struct FeedPollConfig {
+    /// How often to look for due feeds
+    pub poll_interval: std::time::Duration,
+    /// Maximum number of feeds polled per tick
+    pub batch_size: usize,
+    /// Maximum number of new entries enqueued per poll of a feed
+    pub max_entries_per_poll: usize,
+    /// Shortest time between two polls of a feed
+    pub min_feed_interval: Duration,
+    /// Longest time between two polls of a feed
+    pub max_feed_interval: Duration,
+    /// How long an entry's recrawl is held back while its job is in flight
+    pub lease: Duration,
+}
+
+impl Default for FeedPollConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval: std::time::Duration::from_secs(60),
+            batch_size: 20,
+            max_entries_per_poll: 50,
+            min_feed_interval: Duration::minutes(15),
+            max_feed_interval: Duration::hours(24),
+            lease: Duration::hours(1),
+        }
+    }
+}
+
+impl FeedPollConfig {
+    /// Interval until the next poll of a feed
+    ///
+    /// Halves while the feed publishes new entries and grows by half while it
+    /// does not, within the configured bounds.
+    pub fn next_interval(&self, current: Duration, new_entries: usize) -> Duration {
+        let next = if new_entries > 0 { current / 2 } else { current * 3 / 2 };
+        next.clamp(self.min_feed_interval, self.max_feed_interval)
+    }
+
+    /// Delay before retrying a feed that failed `failures` times in a row
+    pub fn retry_delay(&self, interval: Duration, failures: u32) -> Duration {
+        let factor = 1i32 << failures.min(6);
+        (interval * factor).clamp(self.min_feed_interval, self.max_feed_interval)
+    }
+}
+
+/// Loop that polls discovered feeds and enqueues their new entries
+///
+/// Each tick persists feeds the crawler discovered since the last tick,
+/// polls the due ones with conditional requests, and enqueues a crawl of
+/// every new entry. Entries also join the recrawl schedule, so the job goes
+/// through the recrawl path and the post is revisited later.
+pub struct FeedPollLoop {
+    crawler: Crawler,
+    repo: FeedRepository,
+    schedules: ScheduleRepository,
+    job_queue: JobQueue,
+    config: FeedPollConfig,
+}
+
+impl FeedPollLoop {
+    pub fn new(crawler: Crawler, db_pool: PgPool, job_queue: JobQueue, config: FeedPollConfig) -> Self {
+        Self {
+            crawler,
+            repo: FeedRepository::new(db_pool.clone()),
+            schedules: ScheduleRepository::new(db_pool),
+            job_queue,
+            config,
+        }
+    }
+
+    /// Run the loop forever
+    pub async fn run(&mut self) {
+        info!(
+            "Feed poll loop started (interval: {:?}, batch size: {})",
+            self.config.poll_interval, self.config.batch_size
+        );
+
+        loop {
+            match self.tick().await {
+                Ok(0) => {}
+                Ok(enqueued) => info!("Feed poll loop enqueued {} jobs", enqueued),
+                Err(e) => error!("Feed poll loop error: {}", e),
+            }
+
+            sleep(self.config.poll_interval).await;
+        }
+    }
+
+    /// Run a single iteration, returns the number of jobs enqueued
+    pub async fn tick(&mut self) -> Result<usize> {
+        // 1. Persist feeds discovered while crawling
+        let discovered = self.crawler.feeds().take_pending();
+        if !discovered.is_empty() {
+            match self.repo.upsert_discovered(&discovered).await {
+                Ok(0) => {}
+                Ok(added) => info!("Stored {} new feeds", added),
+                Err(e) => {
+                    // Put them back so they are retried on the next tick
+                    error!("Failed to store discovered feeds: {}", e);
+                    self.crawler.feeds().restore_pending(discovered);
+                }
+            }
+        }
+
+        // 2. Poll due feeds; a feed whose poll could not be recorded is due
+        // again on the next tick, without holding up the others
+        let mut enqueued = 0;
+        for feed in self.repo.load_due(self.config.batch_size as i64).await? {
+            match self.poll_feed(&feed).await {
+                Ok(new_entries) => enqueued += new_entries,
+                Err(e) => error!("Failed to update feed {} after polling: {}", feed.url, e),
+            }
+        }
+
+        Ok(enqueued)
+    }
+
+    /// Poll one feed and schedule its next poll, returns the jobs enqueued
+    async fn poll_feed(&mut self, feed: &FeedRecord) -> Result<usize> {
+        match self.crawler.fetch_feed(&feed.url, &feed.validators()).await {
+            Ok(FeedFetch::NotModified { validators }) => {
+                let interval = self.config.next_interval(feed.poll_interval(), 0);
+                self.repo.record_poll(feed.id, None, &validators, interval, false).await?;
+                Ok(0)
+            }
+            Ok(FeedFetch::Fetched { feed: parsed, validators }) => {
+                let new_entries = self.enqueue_entries(feed, &parsed).await?;
+
+                let interval = self.config.next_interval(feed.poll_interval(), new_entries);
+                self.repo
+                    .record_poll(feed.id, parsed.title.as_deref(), &validators, interval, new_entries > 0)
+                    .await?;
+                Ok(new_entries)
+            }
+            Err(e) => {
+                warn!("Failed to poll feed {}: {}", feed.url, e);
+                let failures = feed.consecutive_failures.max(0) as u32 + 1;
+                let retry_at = Utc::now() + self.config.retry_delay(feed.poll_interval(), failures);
+                self.repo.record_failure(feed.id, &e.to_string(), retry_at).await?;
+                Ok(0)
+            }
+        }
+    }
+
+    /// Enqueue a crawl of each entry not seen before, newest first
+    async fn enqueue_entries(&mut self, feed: &FeedRecord, parsed: &ParsedFeed) -> Result<usize> {
+        let mut entries: Vec<FeedEntry> = parsed
+            .entries
+            .iter()
+            .filter_map(|entry| {
+                let url = self.crawler.url_processor().normalize(&entry.url).ok()?;
+                Some(FeedEntry { url, ..entry.clone() })
+            })
+            .collect();
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.published_at));
+
+        let urls: Vec<String> = entries.iter().map(|entry| entry.url.clone()).collect();
+        let new_urls: HashSet<String> = self.repo.new_entry_urls(feed.id, &urls).await?.into_iter().collect();
+        if new_urls.is_empty() {
+            return Ok(0);
+        }
+        debug!("Feed {} has {} new entries", feed.url, new_urls.len());
+
+        // A feed polled for the first time lists its whole backlog; only the
+        // newest entries are crawled per poll, the rest stay new for later polls
+        let new_entries: Vec<&FeedEntry> = entries
+            .iter()
+            .filter(|entry| new_urls.contains(&entry.url))
+            .filter(|entry| self.crawler.check_url(&entry.url).allowed)
+            .take(self.config.max_entries_per_poll)
+            .collect();
+        let new_urls: Vec<String> = new_entries.iter().map(|entry| entry.url.clone()).collect();
+
+        let scheduled = self.schedules.get_by_urls(&new_urls).await?;
+        let lease_until = Utc::now() + self.config.lease;
+        let mut enqueued = 0;
+
+        // An entry counts as seen once its crawl is enqueued
+        for entry in new_entries {
+            let url = &entry.url;
+            let id = match scheduled.get(url) {
+                Some(record) => record.id,
+                None => match self.schedules.upsert(url, CrawlFrequency::Daily, FEED_ENTRY_PRIORITY, None).await {
+                    Ok(record) => record.id,
+                    Err(e) => {
+                        warn!("Failed to schedule feed entry {}: {}", url, e);
+                        continue;
+                    }
+                },
+            };
+
+            let mut job = CrawlJob::new(vec![url.clone()], 1, None);
+            job.scheduled_crawl_id = Some(id);
+
+            if let Err(e) = self.job_queue.enqueue(&job).await {
+                warn!("Failed to enqueue crawl of feed entry {}: {}", url, e);
+                continue;
+            }
+            enqueued += 1;
+
+            if let Err(e) = self.schedules.mark_enqueued(id, job.id, lease_until).await {
+                warn!("Failed to lease scheduled crawl of {}: {}", url, e);
+            }
+            if let Err(e) = self.repo.record_entry(feed.id, entry, job.id).await {
+                warn!("Failed to record feed entry {}: {}", url, e);
+            }
+        }
+
+        Ok(enqueued)
+    }
+}
+
+/// Spawn the feed poll loop in the background
+pub async fn spawn_feed_poll_loop(
+    crawler: Crawler,
+    db_pool: PgPool,
+    job_queue: JobQueue,
+    config: FeedPollConfig,
+) -> Result<()> {
+    let mut feed_loop = FeedPollLoop::new(crawler, db_pool, job_queue, config);
+
+    tokio::spawn(async move {
+        feed_loop.run().await;
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_interval() {
+        let config = FeedPollConfig::default();
+
+        assert_eq!(config.next_interval(Duration::hours(1), 3), Duration::minutes(30));
+        assert_eq!(config.next_interval(Duration::hours(1), 0), Duration::minutes(90));
+
+        // Bounded on both ends
+        assert_eq!(config.next_interval(Duration::minutes(20), 1), Duration::minutes(15));
+        assert_eq!(config.next_interval(Duration::hours(20), 0), Duration::hours(24));
+    }
+
+    #[test]
+    fn test_retry_delay() {
+        let config = FeedPollConfig::default();
+
+        assert_eq!(config.retry_delay(Duration::hours(1), 1), Duration::hours(2));
+        assert_eq!(config.retry_delay(Duration::hours(1), 3), Duration::hours(8));
+        assert_eq!(config.retry_delay(Duration::hours(1), 30), Duration::hours(24));
+    }
+}
@@ -5,9 +5,30 @@ use tokio_cron_scheduler::{Job, JobScheduler};
 use tracing::{error, info};
 
 use crate::search::redis::CacheManager;
+use crate::search::search::SearchClient;
+
+// Link-graph PageRank and the authority scores pushed to the search index
+pub mod authority;
+// Discovered RSS/Atom feeds and the loop that polls them for new posts
+pub mod feeds;
+// Persistent recrawl schedule and the loop that enqueues due recrawls
+pub mod recrawl;
+
+pub use authority::{compute_pagerank, AuthorityConfig, AuthorityJob, AuthorityRepository};
+
+pub use feeds::{spawn_feed_poll_loop, FeedPollConfig, FeedPollLoop, FeedRecord, FeedRepository};
+
+pub use recrawl::{
+    spawn_recrawl_loop, CreateScheduledCrawlRequest, RecrawlConfig, RecrawlLoop,
+    ScheduleRepository, ScheduledCrawlRecord, UpdateScheduledCrawlRequest,
+};
 
 /// Initialize and start the job scheduler
-pub async fn start_scheduler(db_pool: PgPool, cache: CacheManager) -> Result<JobScheduler> {
+pub async fn start_scheduler(
+    db_pool: PgPool,
+    cache: CacheManager,
+    search_client: SearchClient,
+) -> Result<JobScheduler> {
     info!("Initializing job scheduler");
 
     let scheduler = JobScheduler::new().await?;
@@ -56,7 +77,22 @@ pub async fn start_scheduler(db_pool: PgPool, cache: CacheManager) -> Result<Job
         })?)
         .await?;
 
-    // Job 4: Health check every 5 minutes
+    // Job 4: Recompute link authority and inbound anchor text every day at 3 AM
+    let authority_job = std::sync::Arc::new(AuthorityJob::new(db_pool.clone(), search_client));
+    scheduler
+        .add(Job::new_async("0 0 3 * * *", move |_uuid, _l| {
+            let job = authority_job.clone();
+            Box::pin(async move {
+                info!("Computing link authority scores");
+                match job.run().await {
+                    Ok(count) => info!("Authority computed for {} pages", count),
+                    Err(e) => error!("Authority computation failed: {}", e),
+                }
+            })
+        })?)
+        .await?;
+
+    // Job 5: Health check every 5 minutes
     scheduler
         .add(Job::new_async("0 */5 * * * *", move |_uuid, _l| {
             Box::pin(async move {
@@ -0,0 +1,531 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+use std::collections::HashMap;
+use tokio::time::sleep;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::search::crawler::{CrawlFrequency, CrawlScheduler, ScheduledCrawl, SchedulerStats};
+use crate::search::redis::{CrawlJob, JobQueue};
+
+/// Longest URL `scheduled_crawls.url` holds
+const MAX_URL_LENGTH: usize = 2000;
+
+/// Persisted scheduled crawl (row of `scheduled_crawls`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow)]
+pub struct ScheduledCrawlRecord {
+    pub id: Uuid,
+    pub url: String,
+    pub priority: i16,
+    pub frequency: String,
+    pub next_crawl_at: DateTime<Utc>,
+    pub last_crawled_at: Option<DateTime<Utc>>,
+    pub freshness_score: f64,
+    pub last_job_id: Option<Uuid>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl ScheduledCrawlRecord {
+    /// Whether the recrawl loop picks this row up (same condition as `load_due`)
+    ///
+    /// `never` URLs are crawled once; `mark_crawled` then moves them out of reach.
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.next_crawl_at <= now
+            && (self.frequency != CrawlFrequency::Never.as_str() || self.last_crawled_at.is_none())
+    }
+
+    /// Convert to the in-memory scheduler representation
+    pub fn to_scheduled_crawl(&self) -> ScheduledCrawl {
+        ScheduledCrawl {
+            url: self.url.clone(),
+            priority: self.priority.clamp(0, 100) as u8,
+            next_crawl_at: self.next_crawl_at,
+            last_crawled_at: self.last_crawled_at,
+            frequency: CrawlFrequency::parse(&self.frequency).unwrap_or(CrawlFrequency::Daily),
+            freshness_score: self.freshness_score,
+        }
+    }
+}
+
+/// Request body for creating a scheduled crawl
+#[derive(Debug, Deserialize)]
+pub struct CreateScheduledCrawlRequest {
+    pub url: String,
+    pub frequency: CrawlFrequency,
+    #[serde(default = "default_priority")]
+    pub priority: u8,
+    /// First crawl time (defaults to now)
+    pub next_crawl_at: Option<DateTime<Utc>>,
+}
+
+/// Request body for updating a scheduled crawl (all fields optional)
+#[derive(Debug, Deserialize)]
+pub struct UpdateScheduledCrawlRequest {
+    pub frequency: Option<CrawlFrequency>,
+    pub priority: Option<u8>,
+    pub next_crawl_at: Option<DateTime<Utc>>,
+}
+
+fn default_priority() -> u8 {
+    50
+}
+
+/// Repository for the persistent recrawl schedule
+#[derive(Clone)]
+pub struct ScheduleRepository {
+    pool: PgPool,
+}
+
+impl ScheduleRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Create a scheduled crawl, or update priority/frequency if the URL is already scheduled
+    pub async fn upsert(
+        &self,
+        url: &str,
+        frequency: CrawlFrequency,
+        priority: u8,
+        next_crawl_at: Option<DateTime<Utc>>,
+    ) -> Result<ScheduledCrawlRecord> {
+        let record = sqlx::query_as::<_, ScheduledCrawlRecord>(
+            r#"
+            INSERT INTO scheduled_crawls (url, priority, frequency, next_crawl_at)
+            VALUES ($1, $2, $3, COALESCE($4, NOW()))
+            ON CONFLICT (url) DO UPDATE SET
+                priority = EXCLUDED.priority,
+                frequency = EXCLUDED.frequency,
+                next_crawl_at = COALESCE($4, scheduled_crawls.next_crawl_at),
+                updated_at = NOW()
+            RETURNING *
+            "#,
+        )
+        .bind(url)
+        .bind(priority.min(100) as i16)
+        .bind(frequency.as_str())
+        .bind(next_crawl_at)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(record)
+    }
+
+    /// Persist tasks scheduled in-process (e.g. from sitemaps)
+    ///
+    /// New URLs are inserted; existing rows only take the new priority and
+    /// frequency hints so their crawl history is preserved.
+    pub async fn upsert_tasks(&self, tasks: &[ScheduledCrawl]) -> Result<u64> {
+        let mut affected = 0;
+
+        // One URL too long for the column would fail its whole chunk
+        let tasks: Vec<&ScheduledCrawl> = tasks
+            .iter()
+            .filter(|task| {
+                let fits = task.url.len() <= MAX_URL_LENGTH;
+                if !fits {
+                    let start: String = task.url.chars().take(100).collect();
+                    warn!("Not scheduling URL longer than {} bytes: {}...", MAX_URL_LENGTH, start);
+                }
+                fits
+            })
+            .collect();
+
+        for chunk in tasks.chunks(1000) {
+            let urls: Vec<String> = chunk.iter().map(|t| t.url.clone()).collect();
+            let priorities: Vec<i16> = chunk.iter().map(|t| t.priority.min(100) as i16).collect();
+            let frequencies: Vec<String> = chunk.iter().map(|t| t.frequency.as_str().to_string()).collect();
+            let next_crawls: Vec<DateTime<Utc>> = chunk.iter().map(|t| t.next_crawl_at).collect();
+
+            let result = sqlx::query(
+                r#"
+                INSERT INTO scheduled_crawls (url, priority, frequency, next_crawl_at)
+                SELECT DISTINCT ON (url) url, priority, frequency, next_crawl_at
+                FROM UNNEST($1::text[], $2::smallint[], $3::text[], $4::timestamptz[])
+                    AS t(url, priority, frequency, next_crawl_at)
+                ON CONFLICT (url) DO UPDATE SET
+                    priority = EXCLUDED.priority,
+                    frequency = EXCLUDED.frequency,
+                    updated_at = NOW()
+                "#,
+            )
+            .bind(&urls)
+            .bind(&priorities)
+            .bind(&frequencies)
+            .bind(&next_crawls)
+            .execute(&self.pool)
+            .await?;
+
+            affected += result.rows_affected();
+        }
+
+        Ok(affected)
+    }
+
+    /// Get a scheduled crawl by ID
+    pub async fn get(&self, id: Uuid) -> Result<Option<ScheduledCrawlRecord>> {
+        let record = sqlx::query_as::<_, ScheduledCrawlRecord>(
+            "SELECT * FROM scheduled_crawls WHERE id = $1",
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(record)
+    }
+
+    /// Scheduled crawls of the given URLs, keyed by URL
+    pub async fn get_by_urls(&self, urls: &[String]) -> Result<HashMap<String, ScheduledCrawlRecord>> {
+        if urls.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let records = sqlx::query_as::<_, ScheduledCrawlRecord>(
+            "SELECT * FROM scheduled_crawls WHERE url = ANY($1)",
+        )
+        .bind(urls)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(records.into_iter().map(|record| (record.url.clone(), record)).collect())
+    }
+
+    /// List scheduled crawls, soonest first
+    pub async fn list(&self, limit: i64, offset: i64) -> Result<(Vec<ScheduledCrawlRecord>, i64)> {
+        let records = sqlx::query_as::<_, ScheduledCrawlRecord>(
+            r#"
+            SELECT * FROM scheduled_crawls
+            ORDER BY next_crawl_at ASC, priority DESC
+            LIMIT $1 OFFSET $2
+            "#,
+        )
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let total = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM scheduled_crawls")
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok((records, total))
+    }
+
+    /// Update a scheduled crawl; `None` fields are left unchanged
+    pub async fn update(
+        &self,
+        id: Uuid,
+        frequency: Option<CrawlFrequency>,
+        priority: Option<u8>,
+        next_crawl_at: Option<DateTime<Utc>>,
+    ) -> Result<Option<ScheduledCrawlRecord>> {
+        let record = sqlx::query_as::<_, ScheduledCrawlRecord>(
+            r#"
+            UPDATE scheduled_crawls
+            SET frequency = COALESCE($2, frequency),
+                priority = COALESCE($3, priority),
+                next_crawl_at = COALESCE($4, next_crawl_at),
+                updated_at = NOW()
+            WHERE id = $1
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .bind(frequency.map(|f| f.as_str()))
+        .bind(priority.map(|p| p.min(100) as i16))
+        .bind(next_crawl_at)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(record)
+    }
+
+    /// Delete a scheduled crawl, returns false if it did not exist
+    pub async fn delete(&self, id: Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM scheduled_crawls WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Load due crawls, highest priority first
+    pub async fn load_due(&self, limit: i64) -> Result<Vec<ScheduledCrawlRecord>> {
+        let records = sqlx::query_as::<_, ScheduledCrawlRecord>(
+            r#"
+            SELECT * FROM scheduled_crawls
+            WHERE next_crawl_at <= NOW() AND (frequency <> 'never' OR last_crawled_at IS NULL)
+            ORDER BY priority DESC, next_crawl_at ASC
+            LIMIT $1
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(records)
+    }
+
+    /// Record that a job was enqueued for this URL
+    ///
+    /// `next_crawl_at` is pushed to `lease_until` so the URL is not enqueued
+    /// again while the job is in flight. If the job never completes, the lease
+    /// expires and the URL becomes due again.
+    pub async fn mark_enqueued(&self, id: Uuid, job_id: Uuid, lease_until: DateTime<Utc>) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE scheduled_crawls
+            SET last_job_id = $2, next_crawl_at = $3, updated_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(job_id)
+        .bind(lease_until)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Persist crawl times after a task was rescheduled
+    pub async fn record_crawled(&self, id: Uuid, task: &ScheduledCrawl) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE scheduled_crawls
+            SET next_crawl_at = $2,
+                last_crawled_at = $3,
+                freshness_score = $4,
+                frequency = $5,
+                updated_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(task.next_crawl_at)
+        .bind(task.last_crawled_at)
+        .bind(task.freshness_score)
+        .bind(task.frequency.as_str())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Schedule statistics
+    pub async fn stats(&self) -> Result<SchedulerStats> {
+        let (total, due, overdue, average_freshness) = sqlx::query_as::<_, (i64, i64, i64, Option<f64>)>(
+            r#"
+            SELECT
+                COUNT(*),
+                COUNT(*) FILTER (WHERE next_crawl_at <= NOW() AND frequency <> 'never'),
+                COUNT(*) FILTER (WHERE next_crawl_at < NOW() - INTERVAL '1 hour' AND frequency <> 'never'),
+                AVG(freshness_score)
+            FROM scheduled_crawls
+            "#,
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(SchedulerStats {
+            total_tasks: total as usize,
+            due_tasks: due as usize,
+            overdue_tasks: overdue as usize,
+            average_freshness: average_freshness.unwrap_or(0.0),
+        })
+    }
+}
+
+/// Recrawl loop configuration
+#[derive(Debug, Clone)]
+pub struct RecrawlConfig {
+    /// How often to look for due crawls
+    pub poll_interval: std::time::Duration,
+    /// Maximum number of jobs enqueued per tick
+    pub batch_size: usize,
+    /// How long a URL is held back while its job is in flight
+    pub lease: Duration,
+}
+
+impl Default for RecrawlConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval: std::time::Duration::from_secs(60),
+            batch_size: 50,
+            lease: Duration::hours(1),
+        }
+    }
+}
+
+/// Loop that turns due scheduled crawls into `CrawlJob`s
+///
+/// Each tick persists tasks added to the in-memory scheduler since the last
+/// tick, reloads the due part of the schedule from Postgres, and enqueues the
+/// highest-priority due tasks on the job queue. Workers call `reschedule`
+/// when the job completes (see `Worker::reschedule_completed`).
+pub struct RecrawlLoop {
+    scheduler: CrawlScheduler,
+    repo: ScheduleRepository,
+    job_queue: JobQueue,
+    config: RecrawlConfig,
+    /// Due records the in-memory schedule was last rebuilt from
+    loaded: Option<Vec<ScheduledCrawlRecord>>,
+}
+
+impl RecrawlLoop {
+    pub fn new(
+        scheduler: CrawlScheduler,
+        db_pool: PgPool,
+        job_queue: JobQueue,
+        config: RecrawlConfig,
+    ) -> Self {
+        Self {
+            scheduler,
+            repo: ScheduleRepository::new(db_pool),
+            job_queue,
+            config,
+            loaded: None,
+        }
+    }
+
+    /// Run the loop forever
+    pub async fn run(&mut self) {
+        info!(
+            "Recrawl loop started (interval: {:?}, batch size: {})",
+            self.config.poll_interval, self.config.batch_size
+        );
+
+        loop {
+            match self.tick().await {
+                Ok(0) => {}
+                Ok(enqueued) => info!("Recrawl loop enqueued {} jobs", enqueued),
+                Err(e) => error!("Recrawl loop error: {}", e),
+            }
+
+            sleep(self.config.poll_interval).await;
+        }
+    }
+
+    /// Run a single iteration, returns the number of jobs enqueued
+    pub async fn tick(&mut self) -> Result<usize> {
+        // 1. Persist tasks scheduled in-process (sitemap discovery etc.)
+        let pending = self.scheduler.take_pending();
+        if !pending.is_empty() {
+            if let Err(e) = self.repo.upsert_tasks(&pending).await {
+                // Put them back so they are retried on the next tick
+                self.scheduler.merge_tasks(pending);
+                return Err(e);
+            }
+        }
+
+        // 2. Reload the due part of the schedule (picks up API edits and other workers)
+        let due = self
+            .repo
+            .load_due((self.config.batch_size * 4) as i64)
+            .await?;
+        let ids: HashMap<String, Uuid> =
+            due.iter().map(|r| (r.url.clone(), r.id)).collect();
+        if self.loaded.as_ref() != Some(&due) {
+            self.scheduler
+                .replace_all(due.iter().map(|r| r.to_scheduled_crawl()).collect());
+            self.loaded = Some(due);
+        }
+
+        // 3. Enqueue the highest-priority due tasks
+        let lease_until = Utc::now() + self.config.lease;
+        let mut enqueued = 0;
+
+        let batch = self.scheduler.pop_due_batch(self.config.batch_size);
+        if !batch.is_empty() {
+            // Popped tasks are gone from memory, so rebuild on the next tick
+            self.loaded = None;
+        }
+
+        for task in batch {
+            let Some(&id) = ids.get(&task.url) else {
+                continue;
+            };
+
+            // Recrawl the page itself; discovery happens through sitemaps
+            let mut job = CrawlJob::new(vec![task.url.clone()], 1, None);
+            job.scheduled_crawl_id = Some(id);
+
+            if let Err(e) = self.job_queue.enqueue(&job).await {
+                warn!("Failed to enqueue recrawl of {}: {}", task.url, e);
+                continue;
+            }
+
+            self.repo.mark_enqueued(id, job.id, lease_until).await?;
+            enqueued += 1;
+        }
+
+        Ok(enqueued)
+    }
+}
+
+/// Spawn the recrawl loop in the background
+pub async fn spawn_recrawl_loop(
+    scheduler: CrawlScheduler,
+    db_pool: PgPool,
+    job_queue: JobQueue,
+    config: RecrawlConfig,
+) -> Result<()> {
+    let mut recrawl_loop = RecrawlLoop::new(scheduler, db_pool, job_queue, config);
+
+    tokio::spawn(async move {
+        recrawl_loop.run().await;
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(priority: i16, frequency: &str) -> ScheduledCrawlRecord {
+        ScheduledCrawlRecord {
+            id: Uuid::new_v4(),
+            url: "https://example.com/page".to_string(),
+            priority,
+            frequency: frequency.to_string(),
+            next_crawl_at: Utc::now(),
+            last_crawled_at: None,
+            freshness_score: 0.0,
+            last_job_id: None,
+            created_at: None,
+            updated_at: None,
+        }
+    }
+
+    #[test]
+    fn test_record_to_scheduled_crawl() {
+        let task = record(80, "weekly").to_scheduled_crawl();
+        assert_eq!(task.url, "https://example.com/page");
+        assert_eq!(task.priority, 80);
+        assert_eq!(task.frequency, CrawlFrequency::Weekly);
+        assert!(task.is_due());
+
+        // Out-of-range values are clamped / defaulted
+        let task = record(500, "bogus").to_scheduled_crawl();
+        assert_eq!(task.priority, 100);
+        assert_eq!(task.frequency, CrawlFrequency::Daily);
+    }
+    #[test]
+    fn test_never_crawled_once() {
+        let mut never = record(50, "never");
+        let mut daily = record(50, "daily");
+        let now = Utc::now();
+        assert!(never.is_due(now));
+
+        never.last_crawled_at = Some(now);
+        assert!(!never.is_due(now));
+
+        daily.last_crawled_at = Some(now);
+        assert!(daily.is_due(now));
+    }
+}
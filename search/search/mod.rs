@@ -1,5 +1,6 @@
 use anyhow::Result;
 use meilisearch_sdk::client::Client;
+use meilisearch_sdk::documents::DocumentsQuery;
 use meilisearch_sdk::search::Selectors;
 use redis::aio::ConnectionManager;
 use serde::{Deserialize, Serialize};
@@ -7,7 +8,10 @@ use sqlx::PgPool;
 use std::time::Instant;
 use tracing::{info, warn};
 
-use crate::search::crawler::{CrawledDocument, ImageData};
+use crate::search::crawler::{
+    base_content_type, is_html_content_type, normalize_language_tag, CrawledDocument, ImageData,
+    ParserRegistry, StructuredData, HTML_CONTENT_TYPE,
+};
 use crate::types::SearchQuery;
 
 pub mod autocomplete;
@@ -16,6 +20,120 @@ pub use autocomplete::{AutocompleteResponse, AutocompleteSuggestion, Autocomplet
 const INDEX_NAME: &str = "documents";
 const IMAGES_INDEX_NAME: &str = "images";
 
+/// Filter that keeps only representative documents of near-duplicate clusters
+///
+/// A document whose representative was removed has `duplicate_of` set to null.
+const EXCLUDE_DUPLICATES_FILTER: &str = "(duplicate_of NOT EXISTS OR duplicate_of IS NULL)";
+
+/// French stop words, dropped from queries restricted to French
+const FRENCH_STOP_WORDS: &[&str] = &[
+    "le", "la", "les", "un", "une", "des", "du", "de", "et", "ou",
+    "mais", "dans", "avec", "pour", "sur", "par", "au", "aux", "ce",
+    "ces", "est", "sont",
+];
+
+/// Arabic stop words, dropped from queries restricted to Arabic
+const ARABIC_STOP_WORDS: &[&str] = &[
+    "في", "من", "على", "إلى", "عن", "مع", "هذا", "هذه", "التي",
+    "الذي", "أن", "أو", "ثم",
+];
+
+/// Filter matching a set of document IDs
+fn id_filter(ids: &[&str]) -> String {
+    let quoted: Vec<String> = ids
+        .iter()
+        .map(|id| format!("'{}'", id.replace('\\', "\\\\").replace('\'', "\\'")))
+        .collect();
+    format!("id IN [{}]", quoted.join(", "))
+}
+
+/// Filters shared by every search leg: word count, dates, domain, language,
+/// content type and near-duplicates
+fn search_filters(params: &SearchQuery, lang: Option<&str>) -> Result<Vec<String>> {
+    let mut filters = Vec::new();
+
+    // Word count filters
+    if let Some(min) = params.min_word_count {
+        filters.push(format!("word_count >= {}", min));
+    }
+    if let Some(max) = params.max_word_count {
+        filters.push(format!("word_count <= {}", max));
+    }
+
+    // Date filters
+    if let Some(from) = params.from_date.as_ref() {
+        filters.push(format!("crawled_at >= '{}'", from));
+    }
+    if let Some(to) = params.to_date.as_ref() {
+        filters.push(format!("crawled_at <= '{}'", to));
+    }
+
+    // Phase 7.4: Domain filter for faceted search
+    if let Some(domain) = params.domain.as_ref() {
+        filters.push(format!("domain = '{}'", domain));
+    }
+
+    // Language filter (ISO 639-1, e.g. "fr" or "fr-CA")
+    if let Some(lang) = lang {
+        filters.push(format!("lang = '{}'", lang));
+    }
+
+    // Content type filter (e.g. "application/pdf")
+    if let Some(content_type) = params.content_type.as_deref() {
+        let Some(content_type) = indexed_content_type(content_type) else {
+            anyhow::bail!("Unsupported content type filter: {}", content_type);
+        };
+        filters.push(format!("content_type = '{}'", content_type));
+    }
+
+    // Near-duplicates are hidden unless explicitly requested
+    if !params.include_duplicates {
+        filters.push(EXCLUDE_DUPLICATES_FILTER.to_string());
+    }
+
+    Ok(filters)
+}
+
+/// Map a requested content type onto one documents are indexed under
+///
+/// Returns `None` for types the crawler never indexes, so only known values
+/// end up in the filter expression.
+fn indexed_content_type(content_type: &str) -> Option<String> {
+    let content_type = base_content_type(content_type);
+    if is_html_content_type(&content_type) {
+        Some(HTML_CONTENT_TYPE.to_string())
+    } else {
+        ParserRegistry::new().supports(&content_type).then_some(content_type)
+    }
+}
+
+/// Remove the stop words of `lang` from a query
+///
+/// Meilisearch stop words apply to the whole index, where French and Arabic
+/// lists would also drop words like "la" or "de" from English queries, so
+/// they are only applied once the search is restricted to that language.
+/// Phrase queries and queries made only of stop words are left untouched.
+fn strip_stop_words(query: &str, lang: &str) -> String {
+    let stop_words = match lang {
+        "fr" => FRENCH_STOP_WORDS,
+        "ar" => ARABIC_STOP_WORDS,
+        _ => return query.to_string(),
+    };
+    if query.contains('"') {
+        return query.to_string();
+    }
+
+    let kept: Vec<&str> = query
+        .split_whitespace()
+        .filter(|word| !stop_words.contains(&word.to_lowercase().as_str()))
+        .collect();
+    if kept.is_empty() {
+        query.to_string()
+    } else {
+        kept.join(" ")
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SearchResult {
     pub id: String,
@@ -40,6 +158,127 @@ pub struct SearchResult {
     // Phase 9: Favicon URL extracted during crawl
     #[serde(skip_serializing_if = "Option::is_none")]
     pub favicon_url: Option<String>,
+    // Representative document when this page is a near-duplicate
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duplicate_of: Option<String>,
+    // ISO 639-1 language code detected at crawl time
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lang: Option<String>,
+    // Structured data for rich result cards
+    #[serde(flatten)]
+    pub structured: StructuredData,
+    // MIME type of the source document (text/html, application/pdf, ...)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+    // Link authority bucket (0-10) from the PageRank job
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub authority: Option<u8>,
+    // Anchor texts of links pointing at this page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub anchor_text: Option<Vec<String>>,
+    // Robots snippet directives (nosnippet, max-snippet) of the page
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub nosnippet: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_snippet: Option<usize>,
+}
+
+/// Partial document update carrying only the authority score
+#[derive(Debug, Serialize)]
+struct AuthorityUpdate {
+    id: String,
+    authority: u8,
+}
+
+/// Partial document update carrying only the inbound anchor texts
+#[derive(Debug, Serialize)]
+struct AnchorTextUpdate {
+    id: String,
+    anchor_text: Vec<String>,
+}
+
+/// Partial document update carrying only the representative document,
+/// `None` (null) when the document became a representative itself
+#[derive(Debug, Serialize)]
+struct DuplicateUpdate {
+    id: String,
+    duplicate_of: Option<String>,
+}
+
+/// Partial document update, applied only to documents already in the index
+trait PartialUpdate: Serialize + Send + Sync {
+    fn id(&self) -> &str;
+}
+
+impl PartialUpdate for AuthorityUpdate {
+    fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl PartialUpdate for AnchorTextUpdate {
+    fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl PartialUpdate for DuplicateUpdate {
+    fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+/// Documents per partial-update request when pushing link signals
+const PARTIAL_UPDATE_BATCH_SIZE: usize = 1000;
+
+impl SearchResult {
+    /// Enforce the page's `nosnippet` / `max-snippet` robots directives
+    pub fn apply_snippet_directives(&mut self) {
+        if self.nosnippet {
+            self.content.clear();
+            self.description = None;
+            if let Some(formatted) = self._formatted.as_mut() {
+                formatted.content = None;
+                formatted.description = None;
+            }
+            return;
+        }
+
+        if let Some(max) = self.max_snippet {
+            self.content = truncate_snippet(&self.content, max);
+            if let Some(content) = self._formatted.as_mut().and_then(|f| f.content.as_mut()) {
+                *content = truncate_snippet(content, max);
+            }
+        }
+    }
+}
+
+/// Cut a snippet to `max` visible characters, ignoring `<mark>` tags
+fn truncate_snippet(text: &str, max: usize) -> String {
+    let mut out = String::new();
+    let mut visible = 0;
+    let mut open_mark = false;
+    let mut rest = text;
+
+    while let Some(c) = rest.chars().next() {
+        if visible == max {
+            break;
+        }
+        if let Some(tag) = ["<mark>", "</mark>"].into_iter().find(|tag| rest.starts_with(tag)) {
+            open_mark = tag == "<mark>";
+            out.push_str(tag);
+            rest = &rest[tag.len()..];
+            continue;
+        }
+        out.push(c);
+        visible += 1;
+        rest = &rest[c.len_utf8()..];
+    }
+
+    if open_mark {
+        out.push_str("</mark>");
+    }
+    out
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -100,13 +339,16 @@ impl SearchClient {
         match self.client.create_index(INDEX_NAME, Some("id")).await {
             Ok(_) => {
                 info!("Index created successfully");
-                self.configure_index().await?;
             }
             Err(e) => {
                 warn!("Index might already exist: {}", e);
             }
         }
 
+        // Settings are applied on every start so existing indexes pick up
+        // new filterable attributes (duplicate_of, lang)
+        self.configure_index().await?;
+
         // Also initialize images index
         info!("Initializing images index: {}", IMAGES_INDEX_NAME);
         match self.client.create_index(IMAGES_INDEX_NAME, Some("id")).await {
@@ -129,6 +371,8 @@ impl SearchClient {
         index
             .set_searchable_attributes(&[
                 "title",
+                // What other pages call this one: below title, above the page's own text
+                "anchor_text",
                 "description",
                 "keywords",
                 "content",
@@ -148,25 +392,77 @@ impl SearchClient {
                 "crawled_at",
                 "word_count",
                 "domain", // Phase 7.4: Domain for faceting
+                "duplicate_of",
+                "lang",
+                // Structured data for rich result cards
+                "author",
+                "published_at",
+                "published_timestamp",
+                "modified_at",
+                "modified_timestamp",
+                "article_section",
+                "price",
+                "price_currency",
+                "rating",
+                "rating_count",
+                "breadcrumbs",
+                "site_name",
+                "content_type",
+                "authority",
+                "anchor_text",
+                "nosnippet",
+                "max_snippet",
             ])
             .await?;
 
         // Configure filterable attributes (Phase 7.4: Added domain for faceted search)
+        // duplicate_of is filterable so near-duplicates can be hidden by default,
+        // id so partial updates can check which documents exist
         index
-            .set_filterable_attributes(&["crawled_at", "word_count", "domain"])
+            .set_filterable_attributes(&[
+                "id",
+                "crawled_at",
+                "word_count",
+                "domain",
+                "duplicate_of",
+                "lang",
+                "author",
+                "published_timestamp",
+                "modified_timestamp",
+                "article_section",
+                "price",
+                "price_currency",
+                "rating",
+                "breadcrumbs",
+                "site_name",
+                "content_type",
+                "authority",
+            ])
             .await?;
 
         // Configure sortable attributes
+        // Structured dates are unix timestamps so they can be sorted and range-filtered
         index
-            .set_sortable_attributes(&["crawled_at", "word_count"])
+            .set_sortable_attributes(&[
+                "crawled_at",
+                "word_count",
+                "published_timestamp",
+                "modified_timestamp",
+                "price",
+                "rating",
+                "authority",
+            ])
             .await?;
 
         // Configure ranking rules
+        // Authority is a coarse 0-10 bucket, so it only separates pages whose
+        // keyword relevance is comparable; documents without a score rank last
         index
             .set_ranking_rules(&[
                 "words",
                 "typo",
                 "proximity",
+                "authority:desc",
                 "attribute",
                 "sort",
                 "exactness",
@@ -195,6 +491,7 @@ impl SearchClient {
         }
 
         // Phase 7.5: Configure stop words (common words to ignore)
+        // French and Arabic stop words are applied per query (see `strip_stop_words`)
         let stop_words = vec![
             "the", "a", "an", "and", "or", "but", "in", "with", "to", "for",
             "of", "on", "at", "from", "by", "about", "as", "into", "through",
@@ -275,6 +572,10 @@ impl SearchClient {
         Ok(())
     }
 
+    /// Add or replace documents
+    ///
+    /// Document IDs are derived from canonical URLs, so re-indexing a page
+    /// replaces the previous version instead of adding a duplicate.
     pub async fn index_documents(&self, documents: Vec<CrawledDocument>) -> Result<()> {
         if documents.is_empty() {
             return Ok(());
@@ -289,6 +590,137 @@ impl SearchClient {
         Ok(())
     }
 
+    /// Remove documents by ID (pages that became noindex)
+    pub async fn delete_documents(&self, ids: &[String]) -> Result<()> {
+        if ids.is_empty() {
+            return Ok(());
+        }
+
+        info!("Deleting {} documents", ids.len());
+        let index = self.client.index(INDEX_NAME);
+
+        index.delete_documents(ids).await?;
+
+        Ok(())
+    }
+
+    /// Update the authority score of already indexed documents
+    ///
+    /// Uses partial updates so the rest of each document is left untouched.
+    /// Returns the number of documents updated.
+    pub async fn update_authority(&self, scores: &[(String, u8)]) -> Result<usize> {
+        if scores.is_empty() {
+            return Ok(0);
+        }
+
+        info!("Updating authority for {} documents", scores.len());
+        let updates: Vec<AuthorityUpdate> = scores
+            .iter()
+            .map(|(id, authority)| AuthorityUpdate {
+                id: id.clone(),
+                authority: *authority,
+            })
+            .collect();
+
+        self.update_documents(&updates).await
+    }
+
+    /// Update the inbound anchor texts of already indexed documents,
+    /// returns the number of documents updated
+    pub async fn update_anchor_text(&self, anchors: Vec<(String, Vec<String>)>) -> Result<usize> {
+        if anchors.is_empty() {
+            return Ok(0);
+        }
+
+        info!("Updating anchor text for {} documents", anchors.len());
+        let updates: Vec<AnchorTextUpdate> = anchors
+            .into_iter()
+            .map(|(id, anchor_text)| AnchorTextUpdate { id, anchor_text })
+            .collect();
+
+        self.update_documents(&updates).await
+    }
+
+    /// Point already indexed near-duplicates at a new representative,
+    /// returns the number of documents updated
+    pub async fn update_duplicate_of(&self, duplicates: Vec<(String, Option<String>)>) -> Result<usize> {
+        if duplicates.is_empty() {
+            return Ok(0);
+        }
+
+        info!("Updating representatives of {} documents", duplicates.len());
+        let updates: Vec<DuplicateUpdate> = duplicates
+            .into_iter()
+            .map(|(id, duplicate_of)| DuplicateUpdate { id, duplicate_of })
+            .collect();
+
+        self.update_documents(&updates).await
+    }
+
+    /// Merge partial documents into existing ones, in batches
+    ///
+    /// `add_or_update` would create a stub document for an unknown ID, so
+    /// updates are limited to IDs found in the index. Returns the number of
+    /// documents updated.
+    async fn update_documents<T: PartialUpdate>(&self, updates: &[T]) -> Result<usize> {
+        let index = self.client.index(INDEX_NAME);
+        let mut updated = 0;
+
+        for chunk in updates.chunks(PARTIAL_UPDATE_BATCH_SIZE) {
+            let ids: Vec<&str> = chunk.iter().map(|update| update.id()).collect();
+            let existing = self.existing_document_ids(&ids).await?;
+
+            let chunk: Vec<&T> = chunk.iter().filter(|update| existing.contains(update.id())).collect();
+            if !chunk.is_empty() {
+                index.add_or_update(&chunk, Some("id")).await?;
+                updated += chunk.len();
+            }
+        }
+
+        Ok(updated)
+    }
+
+    /// The subset of `ids` present in the documents index
+    async fn existing_document_ids(&self, ids: &[&str]) -> Result<std::collections::HashSet<String>> {
+        self.document_ids_matching(ids, Vec::new()).await
+    }
+
+    /// The subset of `ids` that pass the same filters `search_with_params`
+    /// applies, so hits from another source can be narrowed consistently
+    pub async fn filter_document_ids(
+        &self,
+        ids: &[&str],
+        params: &SearchQuery,
+    ) -> Result<std::collections::HashSet<String>> {
+        if ids.is_empty() {
+            return Ok(Default::default());
+        }
+        let lang = params.lang.as_deref().and_then(normalize_language_tag);
+        let filters = search_filters(params, lang.as_deref())?;
+        self.document_ids_matching(ids, filters).await
+    }
+
+    async fn document_ids_matching(
+        &self,
+        ids: &[&str],
+        mut filters: Vec<String>,
+    ) -> Result<std::collections::HashSet<String>> {
+        #[derive(Deserialize)]
+        struct DocumentId {
+            id: String,
+        }
+
+        let index = self.client.index(INDEX_NAME);
+        filters.insert(0, id_filter(ids));
+        let filter = filters.join(" AND ");
+        let mut query = DocumentsQuery::new(&index);
+        query.with_filter(&filter).with_fields(["id"]).with_limit(ids.len());
+
+        let documents = index.get_documents_with::<DocumentId>(&query).await?;
+        Ok(documents.results.into_iter().map(|doc| doc.id).collect())
+    }
+
+    /// Add or replace images (keyed by page and image URL)
     pub async fn index_images(&self, images: Vec<ImageData>) -> Result<()> {
         if images.is_empty() {
             return Ok(());
@@ -319,10 +751,19 @@ impl SearchClient {
             .with_highlight_pre_tag("<mark>")
             .with_highlight_post_tag("</mark>")
             .with_show_matches_position(true)
+            .with_filter(EXCLUDE_DUPLICATES_FILTER)
             .execute::<SearchResult>()
             .await?;
 
-        let hits: Vec<SearchResult> = search_results.hits.into_iter().map(|h| h.result).collect();
+        let hits: Vec<SearchResult> = search_results
+            .hits
+            .into_iter()
+            .map(|h| {
+                let mut result = h.result;
+                result.apply_snippet_directives();
+                result
+            })
+            .collect();
         let total_hits = search_results.estimated_total_hits.unwrap_or(0);
 
         // Phase 7.2: Generate suggestions if zero results
@@ -344,9 +785,14 @@ impl SearchClient {
 
     pub async fn search_with_params(&self, params: SearchQuery) -> Result<SearchResponse> {
         let index = self.client.index(INDEX_NAME);
+        let lang = params.lang.as_deref().and_then(normalize_language_tag);
+        let query = match lang.as_deref() {
+            Some(lang) => strip_stop_words(&params.q, lang),
+            None => params.q.clone(),
+        };
 
         let mut search = index.search();
-        search.with_query(&params.q);
+        search.with_query(&query);
         search.with_limit(params.limit);
         search.with_offset(params.offset);
 
@@ -360,28 +806,7 @@ impl SearchClient {
         search.with_show_matches_position(true);
 
         // Build filter string - needs to live for the lifetime of search
-        let mut filters = Vec::new();
-
-        // Word count filters
-        if let Some(min) = params.min_word_count {
-            filters.push(format!("word_count >= {}", min));
-        }
-        if let Some(max) = params.max_word_count {
-            filters.push(format!("word_count <= {}", max));
-        }
-
-        // Date filters
-        if let Some(from) = params.from_date.as_ref() {
-            filters.push(format!("crawled_at >= '{}'", from));
-        }
-        if let Some(to) = params.to_date.as_ref() {
-            filters.push(format!("crawled_at <= '{}'", to));
-        }
-
-        // Phase 7.4: Domain filter for faceted search
-        if let Some(domain) = params.domain.as_ref() {
-            filters.push(format!("domain = '{}'", domain));
-        }
+        let filters = search_filters(&params, lang.as_deref())?;
 
         // Apply filters if any
         let filter_str = filters.join(" AND ");
@@ -389,8 +814,8 @@ impl SearchClient {
             search.with_filter(&filter_str);
         }
 
-        // Phase 7.4: Enable facets for domain distribution
-        search.with_facets(Selectors::Some(&["domain"]));
+        // Phase 7.4: Enable facets for domain, language and content type distribution
+        search.with_facets(Selectors::Some(&["domain", "lang", "content_type"]));
 
         // Build sort string outside the if block so it lives long enough
         let sort_str = params.sort_by.as_ref().map(|sort_by| {
@@ -407,7 +832,15 @@ impl SearchClient {
 
         let search_results = search.execute::<SearchResult>().await?;
 
-        let hits: Vec<SearchResult> = search_results.hits.into_iter().map(|h| h.result).collect();
+        let hits: Vec<SearchResult> = search_results
+            .hits
+            .into_iter()
+            .map(|h| {
+                let mut result = h.result;
+                result.apply_snippet_directives();
+                result
+            })
+            .collect();
         let total_hits = search_results.estimated_total_hits.unwrap_or(0);
 
         // Phase 7.2: Generate suggestions if zero results
@@ -682,3 +1115,59 @@ impl SearchClient {
         Ok(counts)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_snippet_skips_highlight_tags() {
+        assert_eq!(truncate_snippet("plain text", 5), "plain");
+        assert_eq!(truncate_snippet("a <mark>rust</mark> crate", 4), "a <mark>ru</mark>");
+        assert_eq!(truncate_snippet("<mark>ok</mark>", 10), "<mark>ok</mark>");
+        assert_eq!(truncate_snippet("héllo", 2), "hé");
+    }
+
+    #[test]
+    fn test_id_filter() {
+        assert_eq!(id_filter(&["a", "b"]), "id IN ['a', 'b']");
+        assert_eq!(id_filter(&["it's"]), "id IN ['it\\'s']");
+    }
+
+    #[test]
+    fn test_search_filters() {
+        let params: SearchQuery =
+            serde_json::from_str(r#"{"q": "rust", "lang": "fr", "content_type": "application/pdf"}"#).unwrap();
+        assert_eq!(
+            search_filters(&params, Some("fr")).unwrap(),
+            vec![
+                "lang = 'fr'".to_string(),
+                "content_type = 'application/pdf'".to_string(),
+                EXCLUDE_DUPLICATES_FILTER.to_string(),
+            ]
+        );
+
+        let params: SearchQuery =
+            serde_json::from_str(r#"{"q": "rust", "include_duplicates": true, "content_type": "image/png"}"#).unwrap();
+        assert!(search_filters(&params, None).is_err());
+    }
+
+    #[test]
+    fn test_indexed_content_type() {
+        assert_eq!(indexed_content_type("Application/PDF").as_deref(), Some("application/pdf"));
+        assert_eq!(indexed_content_type("application/xhtml+xml").as_deref(), Some(HTML_CONTENT_TYPE));
+        assert_eq!(indexed_content_type("image/png"), None);
+        assert_eq!(indexed_content_type("text/html' OR domain EXISTS OR content_type = 'x"), None);
+    }
+
+    #[test]
+    fn test_strip_stop_words_per_language() {
+        assert_eq!(strip_stop_words("La tour de Paris", "fr"), "tour Paris");
+        assert_eq!(strip_stop_words("التاريخ في الجزائر", "ar"), "التاريخ الجزائر");
+        // Other languages keep words that are French stop words
+        assert_eq!(strip_stop_words("la lakers de facto", "en"), "la lakers de facto");
+        // Phrases and stop-word-only queries are left alone
+        assert_eq!(strip_stop_words("\"la vie en rose\"", "fr"), "\"la vie en rose\"");
+        assert_eq!(strip_stop_words("le la", "fr"), "le la");
+    }
+}
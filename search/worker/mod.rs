@@ -1,17 +1,46 @@
 use anyhow::Result;
 use sqlx::PgPool;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tokio::time::{sleep, Duration};
-use tracing::{error, info, warn};
+use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
 use crate::{
-    search::crawler::Crawler,
+    search::crawler::{
+        content_hash, url_priority, CancellationToken, CrawlErrorKind, CrawlErrorRepository, CrawlFailure, CrawlSession,
+        CrawledDocument, Crawler, FetchOutcome, FetchPermit, FingerprintEntry, FingerprintRepository, FrontierEntry,
+        ImageData, LinkRepository, PageStateRepository, PageValidators, SessionUsage, UrlFrontier,
+    },
     search::qdrant::QdrantService,
-    search::redis::{CrawlJob, JobQueue, JobStatus},
+    search::redis::{CrawlJob, JobControl, JobQueue, JobStatus},
+    search::scheduler::{AuthorityRepository, ScheduleRepository},
     search::search::SearchClient,
 };
 
+/// Job crawlers a worker keeps for frontier jobs before dropping them all
+const MAX_JOB_CRAWLERS: usize = 64;
+
+/// How often a running job is checked for cancel and pause requests
+const CONTROL_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// How a run of a job ended
+enum JobOutcome {
+    Completed {
+        pages_crawled: usize,
+        pages_indexed: usize,
+    },
+    /// Seeds queued on the frontier; whoever crawls the last URL completes the job
+    Queued,
+    /// Cancelled or paused on request, with the URLs left to crawl
+    Stopped {
+        control: JobControl,
+        remaining: Vec<String>,
+        pages_crawled: usize,
+        pages_indexed: usize,
+    },
+}
+
 /// Background worker for processing crawl jobs
 pub struct Worker {
     crawler: Crawler,
@@ -20,6 +49,9 @@ pub struct Worker {
     job_queue: JobQueue,
     db_pool: PgPool,
     worker_id: String,
+    /// Shared URL frontier; jobs crawl whole sites per worker when `None`
+    frontier: Option<UrlFrontier>,
+    job_crawlers: HashMap<Uuid, Crawler>,
 }
 
 impl Worker {
@@ -29,6 +61,7 @@ impl Worker {
         qdrant_service: Arc<QdrantService>,
         job_queue: JobQueue,
         db_pool: PgPool,
+        frontier: Option<UrlFrontier>,
     ) -> Self {
         let worker_id = format!("worker-{}", Uuid::new_v4());
         Self {
@@ -38,6 +71,8 @@ impl Worker {
             job_queue,
             db_pool,
             worker_id,
+            frontier,
+            job_crawlers: HashMap::new(),
         }
     }
 
@@ -45,12 +80,24 @@ impl Worker {
     pub async fn start(&mut self) -> Result<()> {
         info!("Worker {} started", self.worker_id);
 
+        // Frontier URLs become ready as host intervals pass, so check more often
+        let idle_wait = if self.frontier.is_some() {
+            Duration::from_secs(1)
+        } else {
+            Duration::from_secs(5)
+        };
+
         loop {
-            match self.process_next_job().await {
+            let result = match self.process_next_job().await {
+                Ok(false) => self.process_next_url().await,
+                other => other,
+            };
+
+            match result {
                 Ok(processed) => {
                     if !processed {
                         // No jobs available, wait before checking again
-                        sleep(Duration::from_secs(5)).await;
+                        sleep(idle_wait).await;
                     }
                 }
                 Err(e) => {
@@ -81,27 +128,46 @@ impl Worker {
             warn!("Failed to save job to database: {}", e);
         }
 
-        // Process the job
-        match self.process_job(job.clone()).await {
-            Ok((pages_crawled, pages_indexed)) => {
+        let control = match self.job_queue.control(job.id).await {
+            Ok(control) => control,
+            Err(e) => {
+                warn!("Failed to check stop requests of job {}: {}", job.id, e);
+                None
+            }
+        };
+
+        let result = match control {
+            // Cancelled or paused while waiting in the queue
+            Some(control) => Ok(JobOutcome::Stopped {
+                control,
+                remaining: job.urls.clone(),
+                pages_crawled: job.pages_crawled,
+                pages_indexed: job.pages_indexed,
+            }),
+            // With a frontier, the job's URLs are crawled one by one by all workers
+            None if self.frontier.is_some() && job.scheduled_crawl_id.is_none() => {
+                self.start_frontier_job(&job).await
+            }
+            None => self.process_job(job.clone()).await,
+        };
+
+        match result {
+            Ok(JobOutcome::Completed { pages_crawled, pages_indexed }) => {
                 info!(
                     "Job {} completed: crawled {}, indexed {}",
                     job.id, pages_crawled, pages_indexed
                 );
-
-                // Mark as completed in Redis
-                if let Err(e) = self
-                    .job_queue
-                    .complete_job(job.id, pages_crawled, pages_indexed)
-                    .await
-                {
-                    error!("Failed to mark job as completed: {}", e);
-                }
-
-                // Update database
-                if let Err(e) = self.update_job_completed(&job.id, pages_crawled, pages_indexed).await {
-                    error!("Failed to update job in database: {}", e);
-                }
+                self.mark_completed(job.id, job.scheduled_crawl_id, pages_crawled, pages_indexed)
+                    .await;
+            }
+            Ok(JobOutcome::Queued) => {
+                info!("Job {} queued its URLs on the frontier", job.id);
+            }
+            Ok(JobOutcome::Stopped { control, remaining, pages_crawled, pages_indexed }) => {
+                let mut job = job.clone();
+                job.pages_crawled = pages_crawled;
+                job.pages_indexed = pages_indexed;
+                self.stop_job(&job, control, remaining).await;
             }
             Err(e) => {
                 let error_msg = format!("Job processing failed: {}", e);
@@ -122,59 +188,456 @@ impl Worker {
         Ok(true)
     }
 
+    /// Mark a job as completed and schedule its next recrawl
+    async fn mark_completed(
+        &mut self,
+        job_id: Uuid,
+        scheduled_crawl_id: Option<Uuid>,
+        pages_crawled: usize,
+        pages_indexed: usize,
+    ) {
+        // Mark as completed in Redis
+        if let Err(e) = self
+            .job_queue
+            .complete_job(job_id, pages_crawled, pages_indexed)
+            .await
+        {
+            error!("Failed to mark job as completed: {}", e);
+        }
+
+        // Update database
+        if let Err(e) = self.update_job_completed(&job_id, pages_crawled, pages_indexed).await {
+            error!("Failed to update job in database: {}", e);
+        }
+
+        // Schedule the next recrawl (recrawl loop jobs only)
+        if let Some(schedule_id) = scheduled_crawl_id {
+            if let Err(e) = self.reschedule_completed(schedule_id).await {
+                error!("Failed to reschedule crawl {}: {}", schedule_id, e);
+            }
+        }
+    }
+
+    /// Stop a job on request, keeping `remaining` URLs if it is paused
+    async fn stop_job(&mut self, job: &CrawlJob, control: JobControl, remaining: Vec<String>) {
+        let result = match control {
+            JobControl::Cancel => {
+                info!("Job {} cancelled: crawled {}, indexed {}", job.id, job.pages_crawled, job.pages_indexed);
+                self.job_queue
+                    .cancel_job(job.id, job.pages_crawled, job.pages_indexed)
+                    .await
+            }
+            JobControl::Pause => {
+                info!("Job {} paused with {} URLs left", job.id, remaining.len());
+                self.job_queue
+                    .pause_job(job.id, remaining, job.pages_crawled, job.pages_indexed)
+                    .await
+            }
+        };
+        if let Err(e) = result {
+            error!("Failed to mark job {} as {:?}: {}", job.id, control, e);
+        }
+
+        // A job cancelled before its resume reached a worker has URLs set aside
+        if let (JobControl::Cancel, Some(frontier)) = (control, &self.frontier) {
+            if let Err(e) = frontier.drop_parked(job.id).await {
+                warn!("Failed to drop set-aside URLs of job {}: {}", job.id, e);
+            }
+        }
+
+        if let Err(e) = self.update_job_stopped(&job.id, control, job.pages_crawled, job.pages_indexed).await {
+            error!("Failed to update stopped job in database: {}", e);
+        }
+    }
+
+    /// Queue the seed URLs of a job on the frontier
+    ///
+    /// Completes the job right away if there was nothing to queue; otherwise
+    /// the job stays processing until its last URL is crawled. A resumed job
+    /// queues the URLs set aside while it was paused instead.
+    async fn start_frontier_job(&mut self, job: &CrawlJob) -> Result<JobOutcome> {
+        let frontier = self
+            .frontier
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("URL frontier is not enabled"))?;
+
+        if frontier.is_started(job.id).await? {
+            let resumed = frontier.unpark(job.id).await?;
+            info!("Job {} resumed {} URLs on the frontier", job.id, resumed);
+
+            // Nothing was set aside and nothing is in flight: the job is done
+            if resumed == 0 && frontier.pending(job.id).await? <= 0 {
+                let (pages_crawled, pages_indexed) = frontier.progress(job.id).await?;
+                return Ok(JobOutcome::Completed { pages_crawled, pages_indexed });
+            }
+            return Ok(JobOutcome::Queued);
+        }
+
+        let crawler = self.job_crawler(job)?;
+        let options = serde_json::to_value(&job.options)?;
+        if let Err(e) = self.merge_job_metadata(&job.id, "options", options).await {
+            warn!("Failed to record options of job {}: {}", job.id, e);
+        }
+
+        let urls: Vec<String> = job
+            .urls
+            .iter()
+            .map(|url| crawler.url_processor().normalize(url).unwrap_or_else(|_| url.clone()))
+            .collect();
+        let priorities = self.frontier_priorities(&urls, 0).await;
+        let seeds: Vec<FrontierEntry> = urls
+            .into_iter()
+            .zip(priorities)
+            .filter_map(|(url, priority)| FrontierEntry::seed(url, job.id, priority))
+            .collect();
+
+        // Seeds were asked for explicitly, so they skip the seen-URL filter
+        match frontier.push(job.id, &seeds, true).await? {
+            0 => Ok(JobOutcome::Completed { pages_crawled: 0, pages_indexed: 0 }),
+            _ => Ok(JobOutcome::Queued),
+        }
+    }
+
+    /// Crawl the next URL of the frontier, returns false if none is ready
+    async fn process_next_url(&mut self) -> Result<bool> {
+        let Some(frontier) = self.frontier.clone() else {
+            return Ok(false);
+        };
+        let Some(entry) = frontier.pop().await? else {
+            return Ok(false);
+        };
+
+        if let Err(e) = self.process_frontier_entry(&frontier, &entry).await {
+            warn!("Failed to crawl {} for job {}: {}", entry.url, entry.job_id, e);
+        }
+
+        // Whoever finishes the job's last URL completes the job
+        if frontier.complete(entry.job_id).await? == 0 {
+            self.finish_frontier_job(&frontier, entry.job_id).await?;
+        }
+
+        Ok(true)
+    }
+
+    /// Fetch and index one frontier URL, queueing the links it leads to
+    async fn process_frontier_entry(&mut self, frontier: &UrlFrontier, entry: &FrontierEntry) -> Result<()> {
+        let Some(mut job) = self.job_queue.get_job(entry.job_id).await? else {
+            debug!("Skipping {}: job {} no longer exists", entry.url, entry.job_id);
+            return Ok(());
+        };
+
+        // The first worker to see a stop request acts on it for the whole job
+        if job.status == JobStatus::Processing {
+            if let Some(control) = self.job_queue.control(job.id).await? {
+                let (pages_crawled, pages_indexed) = frontier.progress(job.id).await?;
+                job.pages_crawled = pages_crawled;
+                job.pages_indexed = pages_indexed;
+                self.record_frontier_budget(frontier, &job).await;
+                let seeds = job.urls.clone();
+                self.stop_job(&job, control, seeds).await;
+                job.status = match control {
+                    JobControl::Cancel => JobStatus::Cancelled,
+                    JobControl::Pause => JobStatus::Paused,
+                };
+            }
+        }
+
+        match job.status {
+            JobStatus::Processing => {}
+            // Set aside until the job is resumed (and while a resumed job waits in the queue)
+            JobStatus::Paused | JobStatus::Pending => {
+                self.job_crawlers.remove(&job.id);
+                return frontier.park(entry).await;
+            }
+            _ => {
+                self.job_crawlers.remove(&job.id);
+                return Ok(());
+            }
+        }
+
+        let crawler = self.job_crawler(&job)?;
+        let host = entry.host().unwrap_or_default();
+        let domain_budget = job
+            .domain_budget
+            .clone()
+            .unwrap_or_else(|| crawler.domain_budget().clone());
+
+        // Budgets are shared by every worker crawling the job, so the
+        // frontier checks and counts them
+        match frontier.start_fetch(entry, &job.budget, &domain_budget).await? {
+            FetchPermit::Granted => {}
+            FetchPermit::Seen => {
+                debug!("Skipping {}: fetched within the freshness window", entry.url);
+                return Ok(());
+            }
+            FetchPermit::PathLimit => {
+                debug!("Skipping {}: path budget of job {} used up", entry.url, job.id);
+                return Ok(());
+            }
+            FetchPermit::JobExhausted(reason) | FetchPermit::DomainExhausted(reason) => {
+                debug!("Skipping {} of job {}: {}", entry.url, job.id, reason);
+                return Ok(());
+            }
+        }
+
+        let archive = match crawler.open_archive(&job.id.to_string()).await {
+            Ok(archive) => archive,
+            Err(e) => {
+                warn!("Failed to open WARC archive for job {}: {}", job.id, e);
+                None
+            }
+        };
+        let result = crawler.fetch_page(&entry.url, &PageValidators::default(), archive.as_ref()).await;
+        if let Some(archive) = &archive {
+            if let Err(e) = archive.flush().await {
+                warn!("Failed to flush WARC archive {}: {}", archive.path().display(), e);
+            }
+        }
+
+        let outcome = match result {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                self.record_failures(&job.id, vec![CrawlFailure::from_error(&entry.url, &e)]).await;
+                return Err(e);
+            }
+        };
+        let FetchOutcome::Fetched { document, images, bytes, .. } = outcome else {
+            return Ok(());
+        };
+        if let Err(e) = frontier.record_bytes(job.id, &host, bytes).await {
+            warn!("Failed to count downloaded bytes of job {}: {}", job.id, e);
+        }
+        let Some(doc) = document else {
+            let failure = CrawlFailure::new(&entry.url, CrawlErrorKind::EmptyContent, "No indexable content");
+            self.record_failures(&job.id, vec![failure]).await;
+            return Ok(());
+        };
+
+        if entry.depth < job.max_depth {
+            let links = crawler.follow_links(&doc, &entry.seed_host);
+            let priorities = self.frontier_priorities(&links, entry.depth + 1).await;
+            let children: Vec<FrontierEntry> = links
+                .into_iter()
+                .zip(priorities)
+                .map(|(url, priority)| entry.child(url, priority))
+                .collect();
+
+            match frontier.push(job.id, &children, false).await {
+                Ok(queued) => debug!("Queued {} of {} links from {}", queued, children.len(), entry.url),
+                Err(e) => warn!("Failed to queue links from {}: {}", entry.url, e),
+            }
+        }
+
+        let documents = vec![doc];
+        self.record_links(&documents).await;
+        let documents = self.remove_noindex(documents).await;
+        let (documents, images) = self.filter_unchanged(documents, images).await;
+        let indexed = self.index_documents(&documents).await;
+        self.record_page_states(&documents, indexed == documents.len()).await;
+        self.index_images(&images).await;
+
+        let (pages_crawled, pages_indexed) = frontier.record_progress(job.id, 1, indexed).await?;
+        if let Err(e) = self.job_queue.update_progress(job.id, pages_crawled, pages_indexed).await {
+            warn!("Failed to update job progress: {}", e);
+        }
+
+        Ok(())
+    }
+
+    /// Complete a frontier job once none of its URLs are left
+    async fn finish_frontier_job(&mut self, frontier: &UrlFrontier, job_id: Uuid) -> Result<()> {
+        self.job_crawlers.remove(&job_id);
+
+        let Some(job) = self.job_queue.get_job(job_id).await? else {
+            return Ok(());
+        };
+        if job.status != JobStatus::Processing {
+            return Ok(());
+        }
+
+        self.record_frontier_budget(frontier, &job).await;
+
+        let (pages_crawled, pages_indexed) = frontier.progress(job_id).await?;
+        info!(
+            "Job {} completed: crawled {}, indexed {}",
+            job_id, pages_crawled, pages_indexed
+        );
+        self.mark_completed(job_id, job.scheduled_crawl_id, pages_crawled, pages_indexed)
+            .await;
+
+        Ok(())
+    }
+
+    /// Record what a frontier job used of its budgets, and which limit stopped it
+    async fn record_frontier_budget(&self, frontier: &UrlFrontier, job: &CrawlJob) {
+        let domain_budget = job
+            .domain_budget
+            .clone()
+            .unwrap_or_else(|| self.crawler.domain_budget().clone());
+        let usage = match frontier.budget_usage(job.id, &job.budget, &domain_budget).await {
+            Ok(usage) => usage,
+            Err(e) => {
+                warn!("Failed to load budget usage of job {}: {}", job.id, e);
+                return;
+            }
+        };
+
+        let stopped = usage.job.exhausted;
+        let mut budget = match serde_json::to_value(usage) {
+            Ok(budget) => budget,
+            Err(e) => {
+                warn!("Failed to serialize budget usage of job {}: {}", job.id, e);
+                return;
+            }
+        };
+        if let Some(reason) = stopped {
+            budget["stopped"] = serde_json::json!({
+                "reason": reason,
+                "message": reason.to_string(),
+            });
+        }
+        if let Err(e) = self.merge_job_metadata(&job.id, "budget", budget).await {
+            warn!("Failed to record budget usage for job {}: {}", job.id, e);
+        }
+    }
+
+    /// The crawler for a job's options, reused across the job's URLs so its
+    /// robots cache and rate limiter carry over
+    fn job_crawler(&mut self, job: &CrawlJob) -> Result<Crawler> {
+        if let Some(crawler) = self.job_crawlers.get(&job.id) {
+            return Ok(crawler.clone());
+        }
+
+        if self.job_crawlers.len() >= MAX_JOB_CRAWLERS {
+            self.job_crawlers.clear();
+        }
+        let crawler = self.crawler.for_job(job.max_depth, &job.options)?;
+        self.job_crawlers.insert(job.id, crawler.clone());
+        Ok(crawler)
+    }
+
+    /// Frontier priorities of URLs `depth` hops from their seed
+    async fn frontier_priorities(&self, urls: &[String], depth: usize) -> Vec<i64> {
+        let scheduled = match ScheduleRepository::new(self.db_pool.clone()).get_by_urls(urls).await {
+            Ok(scheduled) => scheduled,
+            Err(e) => {
+                warn!("Failed to load scheduled crawls: {}", e);
+                HashMap::new()
+            }
+        };
+
+        urls.iter()
+            .map(|url| {
+                let task = scheduled.get(url).map(|record| record.to_scheduled_crawl());
+                url_priority(url, depth, task.as_ref())
+            })
+            .collect()
+    }
+
     /// Process a single job
-    async fn process_job(&mut self, mut job: CrawlJob) -> Result<(usize, usize)> {
+    ///
+    /// Stops between pages once the job gets a cancel or pause request; the
+    /// URL being crawled then counts as not crawled yet.
+    async fn process_job(&mut self, mut job: CrawlJob) -> Result<JobOutcome> {
+        // Scheduled recrawls revisit a single page with a conditional request
+        if job.scheduled_crawl_id.is_some() {
+            let (pages_crawled, pages_indexed) = self.process_recrawl(&mut job).await?;
+            return Ok(JobOutcome::Completed { pages_crawled, pages_indexed });
+        }
+
+        // Apply the job's own depth and crawler options
+        let crawler = self.crawler.for_job(job.max_depth, &job.options)?;
+        let options = serde_json::to_value(&job.options)?;
+        if let Err(e) = self.merge_job_metadata(&job.id, "options", options).await {
+            warn!("Failed to record options of job {}: {}", job.id, e);
+        }
+
+        // Every response of the job goes to one WARC file when archiving is enabled
+        let archive = match crawler.open_archive(&job.id.to_string()).await {
+            Ok(archive) => archive,
+            Err(e) => {
+                warn!("Failed to open WARC archive for job {}: {}", job.id, e);
+                None
+            }
+        };
+
+        // Budgets are tracked across all URLs of the job
+        let domain_budget = job
+            .domain_budget
+            .clone()
+            .unwrap_or_else(|| crawler.domain_budget().clone());
+        let cancellation = CancellationToken::new();
+        let watcher = self.watch_control(job.id, cancellation.clone());
+        let mut session = CrawlSession::new(job.budget.clone(), domain_budget)
+            .with_archive(archive)
+            .with_cancellation(cancellation);
+        // A resumed job carries on with what its earlier runs used
+        match self.saved_budget_usage(&job.id).await {
+            Ok(Some(usage)) => session = session.with_usage(&usage),
+            Ok(None) => {}
+            Err(e) => warn!("Failed to load budget usage of job {}: {}", job.id, e),
+        }
+
         let total_urls = job.urls.len();
-        let mut all_documents = Vec::new();
         let mut all_images = Vec::new();
-        let mut total_pages_crawled = 0;
-        let mut total_pages_indexed = 0;
+        // Resumed jobs carry the counts of their earlier runs
+        let mut total_pages_crawled = job.pages_crawled;
+        let mut total_pages_indexed = job.pages_indexed;
         let mut urls_completed = 0;
+        let mut stopped_at = None;
 
         // Process URLs one by one with progress updates
         for (index, url) in job.urls.clone().iter().enumerate() {
+            if session.is_cancelled() {
+                stopped_at = Some(index);
+                break;
+            }
+            if let Some(reason) = session.exhausted() {
+                info!("Stopping job {} with {} URLs left: {}", job.id, total_urls - index, reason);
+                break;
+            }
+
             info!("Processing URL {}/{}: {}", index + 1, total_urls, url);
 
             // Crawl single URL
-            match self.crawler.crawl_urls(vec![url.clone()]).await {
+            let result = crawler.crawl_urls(vec![url.clone()], &mut session).await;
+            if let Err(e) = &result {
+                session.record_failure(CrawlFailure::from_error(url, e));
+            }
+            self.record_failures(&job.id, session.take_failures()).await;
+
+            // Pages fetched before a stop request are still indexed, but the
+            // URL is crawled again on resume
+            if session.is_cancelled() {
+                stopped_at = Some(index);
+            }
+
+            match result {
                 Ok((documents, images)) => {
                     let pages_crawled = documents.len();
-
-                    // Accumulate results
-                    all_documents.extend(documents.clone());
-                    all_images.extend(images);
                     total_pages_crawled += pages_crawled;
                     urls_completed += 1;
 
+                    // Links are recorded even for unchanged pages so the graph stays current
+                    self.record_links(&documents).await;
+
+                    let documents = self.remove_noindex(documents).await;
+
+                    // Skip re-indexing pages whose content hash did not change
+                    let (documents, images) = self.filter_unchanged(documents, images).await;
+                    all_images.extend(images);
+
                     // Index documents immediately (don't wait for all URLs)
-                    if !documents.is_empty() {
-                        // Index to Meilisearch first
-                        match self.search_client.index_documents(documents.clone()).await {
-                            Ok(_) => {
-                                // Successfully indexed to Meilisearch - count these
-                                total_pages_indexed += documents.len();
-                            }
-                            Err(e) => {
-                                warn!("Failed to index documents from {}: {}", url, e);
-                            }
-                        }
-
-                        // Also index to Qdrant (for semantic search)
-                        for doc in &documents {
-                            if let Err(e) = self.qdrant_service
-                                .index_page(&doc.id, &doc.url, &doc.title, &doc.content)
-                                .await
-                            {
-                                warn!("Failed to index page {} to Qdrant: {}", doc.url, e);
-                            }
-                        }
-                    }
+                    let indexed = self.index_documents(&documents).await;
+                    total_pages_indexed += indexed;
+                    self.record_page_states(&documents, indexed == documents.len()).await;
 
                     // Update job progress in Redis after each URL
-                    job.pages_crawled = total_pages_crawled;
-                    job.pages_indexed = total_pages_indexed;
-
-                    if let Err(e) = self.job_queue.update_job(&job).await {
+                    if let Err(e) = self
+                        .job_queue
+                        .update_progress(job.id, total_pages_crawled, total_pages_indexed)
+                        .await
+                    {
                         warn!("Failed to update job progress: {}", e);
                     }
 
@@ -186,57 +649,494 @@ impl Worker {
                     // Continue with next URL even if one fails
                 }
             }
-        }
 
-        let documents = all_documents;
-        let images = all_images;
-        let pages_crawled = total_pages_crawled;
-        let pages_indexed = total_pages_indexed;
+            if stopped_at.is_some() {
+                break;
+            }
+        }
+        watcher.abort();
 
         // Note: Documents are already indexed incrementally in the loop above
+        self.index_images(&all_images).await;
 
-        // Index images to Meilisearch AND Qdrant (Phase 10.5)
-        if !images.is_empty() {
-            info!("Indexing {} extracted images", images.len());
-
-            // Index to Meilisearch (existing)
-            if let Err(e) = self.search_client.index_images(images.clone()).await {
-                warn!("Failed to index images to Meilisearch: {}", e);
-                // Don't fail the entire job if image indexing fails
-            }
-
-            // Index to Qdrant for semantic search (Phase 10.5)
-            let mut qdrant_image_indexed = 0;
-            for image in &images {
-                match self.qdrant_service
-                    .index_image(
-                        &image.id,
-                        &image.image_url,
-                        &image.source_url,
-                        image.figcaption.as_deref(),
-                        image.alt_text.as_deref(),
-                        image.title.as_deref(),
-                        &image.page_title,
-                        &image.domain,
-                    )
-                    .await
-                {
-                    Ok(_) => {
-                        qdrant_image_indexed += 1;
-                    }
-                    Err(e) => {
-                        warn!("Failed to index image {} to Qdrant: {}", image.image_url, e);
-                        // Don't fail entire job if Qdrant indexing fails
+        // Record what the job used of its budgets, and which limit stopped it;
+        // the session started from the earlier usage, so this covers all runs
+        let mut budget = session.summary();
+        if let Some(reason) = session.exhausted() {
+            budget["stopped"] = serde_json::json!({
+                "reason": reason,
+                "message": reason.to_string(),
+            });
+        }
+        if let Err(e) = self.merge_job_metadata(&job.id, "budget", budget).await {
+            warn!("Failed to record budget usage for job {}: {}", job.id, e);
+        }
+
+        if let Some(archive) = session.archive() {
+            match archive.flush().await {
+                Ok(()) => info!("Archived job {} to {}", job.id, archive.path().display()),
+                Err(e) => warn!("Failed to flush WARC archive {}: {}", archive.path().display(), e),
+            }
+        }
+
+        if let Some(index) = stopped_at {
+            // Pausing keeps the job resumable if the request is gone meanwhile
+            let control = self.job_queue.control(job.id).await?.unwrap_or(JobControl::Pause);
+            return Ok(JobOutcome::Stopped {
+                control,
+                remaining: job.urls[index..].to_vec(),
+                pages_crawled: total_pages_crawled,
+                pages_indexed: total_pages_indexed,
+            });
+        }
+
+        Ok(JobOutcome::Completed {
+            pages_crawled: total_pages_crawled,
+            pages_indexed: total_pages_indexed,
+        })
+    }
+
+    /// Cancel `token` once the job gets a cancel or pause request
+    fn watch_control(&self, job_id: Uuid, token: CancellationToken) -> tokio::task::JoinHandle<()> {
+        let mut job_queue = self.job_queue.clone();
+
+        tokio::spawn(async move {
+            loop {
+                sleep(CONTROL_POLL_INTERVAL).await;
+                match job_queue.control(job_id).await {
+                    Ok(Some(control)) => {
+                        info!("Stopping job {}: {:?} requested", job_id, control);
+                        token.cancel();
+                        return;
                     }
+                    Ok(None) => {}
+                    Err(e) => debug!("Failed to check stop requests of job {}: {}", job_id, e),
+                }
+            }
+        })
+    }
+
+    /// Recrawl a single scheduled page, skipping indexing if it did not change
+    async fn process_recrawl(&mut self, job: &mut CrawlJob) -> Result<(usize, usize)> {
+        let url = job
+            .urls
+            .first()
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("Recrawl job {} has no URL", job.id))?;
+
+        // Page state is stored under the canonical URL, which may not be the scheduled one
+        let repo = PageStateRepository::new(self.db_pool.clone());
+        let mut previous = match repo.get_for_fetch(&url).await {
+            Ok(state) => state,
+            Err(e) => {
+                warn!("Failed to load page state for {}: {}", url, e);
+                None
+            }
+        };
+        let validators = previous
+            .as_ref()
+            .map(|state| state.validators())
+            .unwrap_or_default();
+
+        let archive = match self.crawler.open_archive(&job.id.to_string()).await {
+            Ok(archive) => archive,
+            Err(e) => {
+                warn!("Failed to open WARC archive for job {}: {}", job.id, e);
+                None
+            }
+        };
+        let result = self.crawler.fetch_page(&url, &validators, archive.as_ref()).await;
+        if let Some(archive) = &archive {
+            if let Err(e) = archive.flush().await {
+                warn!("Failed to flush WARC archive {}: {}", archive.path().display(), e);
+            }
+        }
+
+        let outcome = match result {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                self.record_failures(&job.id, vec![CrawlFailure::from_error(&url, &e)]).await;
+                return Err(e);
+            }
+        };
+
+        // Without a document, the canonical URL is the one we knew before
+        let known_url = previous.as_ref().map_or(url.clone(), |state| state.url.clone());
+
+        let (document, images, validators) = match outcome {
+            FetchOutcome::NotModified { validators } => {
+                info!("Recrawl of {} returned 304 Not Modified, skipping indexing", url);
+                repo.record_check(&known_url, &validators, None).await?;
+                return Ok((1, 0));
+            }
+            FetchOutcome::Fetched { document, images, validators, .. } => (document, images, validators),
+        };
+
+        let Some(doc) = document else {
+            let failure = CrawlFailure::new(&url, CrawlErrorKind::EmptyContent, "No indexable content");
+            self.record_failures(&job.id, vec![failure]).await;
+            repo.record_check(&known_url, &validators, None).await?;
+            return Ok((1, 0));
+        };
+
+        // The page may have declared a new canonical URL since the last check
+        if previous.as_ref().is_none_or(|state| state.url != doc.url) {
+            previous = repo.get(&doc.url).await?;
+        }
+
+        self.record_links(std::slice::from_ref(&doc)).await;
+
+        if doc.robots.noindex {
+            info!("{} is marked noindex, removing it from the index", url);
+            repo.record_check(&doc.url, &validators, None).await?;
+            repo.record_fetch_url(&doc.url, &url).await?;
+            self.remove_noindex(vec![doc]).await;
+            return Ok((1, 0));
+        }
+
+        let hash = content_hash(&doc.title, &doc.content);
+        if previous.as_ref().is_some_and(|state| state.is_unchanged(&hash)) {
+            info!("Content of {} is unchanged, skipping indexing", url);
+            repo.record_check(&doc.url, &validators, Some(&hash)).await?;
+            repo.record_fetch_url(&doc.url, &url).await?;
+            return Ok((1, 0));
+        }
+
+        let pages_indexed = self.index_documents(std::slice::from_ref(&doc)).await;
+        self.index_images(&images).await;
+
+        // Only remember the new hash once it is indexed, so failures are retried
+        let indexed_hash = (pages_indexed > 0).then_some(hash.as_str());
+        repo.record_check(&doc.url, &validators, indexed_hash).await?;
+        repo.record_fetch_url(&doc.url, &url).await?;
+
+        job.pages_crawled = 1;
+        job.pages_indexed = pages_indexed;
+        if let Err(e) = self.job_queue.update_progress(job.id, 1, pages_indexed).await {
+            warn!("Failed to update job progress: {}", e);
+        }
+
+        Ok((1, pages_indexed))
+    }
+
+    /// Drop documents (and their images) whose content hash matches the stored one
+    ///
+    /// Records a check for every unchanged document; changed ones are recorded
+    /// by `record_page_states` once indexed. If page state cannot be loaded,
+    /// everything is treated as changed.
+    async fn filter_unchanged(
+        &self,
+        documents: Vec<CrawledDocument>,
+        images: Vec<ImageData>,
+    ) -> (Vec<CrawledDocument>, Vec<ImageData>) {
+        let repo = PageStateRepository::new(self.db_pool.clone());
+
+        let urls: Vec<String> = documents.iter().map(|doc| doc.url.clone()).collect();
+        let previous = match repo.get_many(&urls).await {
+            Ok(states) => states,
+            Err(e) => {
+                warn!("Failed to load page states: {}", e);
+                return (documents, images);
+            }
+        };
+
+        let mut changed = Vec::with_capacity(documents.len());
+        let mut unchanged_urls = HashSet::new();
+
+        for doc in documents {
+            let hash = content_hash(&doc.title, &doc.content);
+            let is_unchanged = previous
+                .get(&doc.url)
+                .is_some_and(|state| state.is_unchanged(&hash));
+
+            if !is_unchanged {
+                changed.push(doc);
+                continue;
+            }
+
+            // Spider pages carry no validators, keep whatever is stored
+            if let Err(e) = repo.record_check(&doc.url, &PageValidators::default(), Some(&hash)).await {
+                warn!("Failed to record page state for {}: {}", doc.url, e);
+            }
+            unchanged_urls.insert(doc.url);
+        }
+
+        if !unchanged_urls.is_empty() {
+            info!("Skipping {} unchanged pages", unchanged_urls.len());
+        }
+
+        let images = images
+            .into_iter()
+            .filter(|image| !unchanged_urls.contains(&image.source_url))
+            .collect();
+
+        (changed, images)
+    }
+
+    /// Record content hashes of crawled documents
+    ///
+    /// Hashes are only stored when indexing succeeded, so a failed batch is
+    /// re-indexed on the next crawl instead of being skipped as unchanged.
+    async fn record_page_states(&self, documents: &[CrawledDocument], indexed: bool) {
+        let repo = PageStateRepository::new(self.db_pool.clone());
+
+        for doc in documents {
+            let hash = content_hash(&doc.title, &doc.content);
+            let hash = indexed.then_some(hash.as_str());
+
+            if let Err(e) = repo.record_check(&doc.url, &PageValidators::default(), hash).await {
+                warn!("Failed to record page state for {}: {}", doc.url, e);
+            }
+        }
+    }
+
+    /// Index documents to Meilisearch and Qdrant, returns the number indexed to Meilisearch
+    async fn index_documents(&self, documents: &[CrawledDocument]) -> usize {
+        if documents.is_empty() {
+            return 0;
+        }
+
+        let mut indexed = 0;
+
+        // Documents are replaced as a whole, so carry over authority and inbound anchors
+        let mut batch = documents.to_vec();
+        self.attach_link_signals(&mut batch).await;
+
+        // Index to Meilisearch first
+        match self.search_client.index_documents(batch.clone()).await {
+            Ok(_) => {
+                // Successfully indexed to Meilisearch - count these
+                indexed = documents.len();
+            }
+            Err(e) => {
+                warn!("Failed to index {} documents: {}", documents.len(), e);
+            }
+        }
+
+        // Also index to Qdrant (for semantic search)
+        // Near-duplicates are kept out of the vector index so they can't
+        // crowd the representative out of semantic results
+        for doc in &batch {
+            if doc.duplicate_of.is_some() {
+                if let Err(e) = self.qdrant_service.delete_page(&doc.id).await {
+                    warn!("Failed to remove duplicate page {} from Qdrant: {}", doc.url, e);
+                }
+                continue;
+            }
+
+            if let Err(e) = self.qdrant_service
+                .index_page(
+                    &doc.id,
+                    &doc.url,
+                    &doc.title,
+                    doc.anchor_text.as_deref().unwrap_or_default(),
+                    &doc.content,
+                )
+                .await
+            {
+                warn!("Failed to index page {} to Qdrant: {}", doc.url, e);
+            }
+        }
+
+        if indexed > 0 {
+            self.register_fingerprints(documents).await;
+        }
+
+        indexed
+    }
+
+    /// Remove pages marked noindex from the search and vector indexes
+    ///
+    /// Returns the documents that may be indexed.
+    async fn remove_noindex(&self, documents: Vec<CrawledDocument>) -> Vec<CrawledDocument> {
+        let (noindex, documents): (Vec<_>, Vec<_>) =
+            documents.into_iter().partition(|doc| doc.robots.noindex);
+        if noindex.is_empty() {
+            return documents;
+        }
+
+        let ids: Vec<String> = noindex.iter().map(|doc| doc.id.clone()).collect();
+        if let Err(e) = self.search_client.delete_documents(&ids).await {
+            warn!("Failed to remove {} noindex pages: {}", ids.len(), e);
+        }
+        for doc in &noindex {
+            if let Err(e) = self.qdrant_service.delete_page(&doc.id).await {
+                warn!("Failed to remove noindex page {} from Qdrant: {}", doc.url, e);
+            }
+        }
+
+        let urls: Vec<String> = noindex.iter().map(|doc| doc.url.clone()).collect();
+        if let Err(e) = PageStateRepository::new(self.db_pool.clone()).clear_content_hash(&urls).await {
+            warn!("Failed to clear page state of {} noindex pages: {}", urls.len(), e);
+        }
+        self.release_fingerprints(&ids).await;
+
+        info!("Skipped {} noindex pages", noindex.len());
+        documents
+    }
+
+    /// Drop the fingerprints of removed documents and point their
+    /// near-duplicates at a new representative, or unhide them
+    async fn release_fingerprints(&self, ids: &[String]) {
+        let repo = FingerprintRepository::new(self.db_pool.clone());
+        let mut duplicates = Vec::new();
+
+        for id in ids {
+            if let Err(e) = repo.delete(id).await {
+                warn!("Failed to delete fingerprint of {}: {}", id, e);
+            }
+
+            for alias in self.crawler.fingerprints().release(id) {
+                if let Err(e) = repo.upsert(&alias).await {
+                    warn!("Failed to persist fingerprint for {}: {}", alias.url, e);
                 }
+                duplicates.push((alias.document_id, alias.duplicate_of));
             }
+        }
 
-            if qdrant_image_indexed > 0 {
-                info!("Indexed {} images to Qdrant", qdrant_image_indexed);
+        if let Err(e) = self.search_client.update_duplicate_of(duplicates).await {
+            warn!("Failed to update near-duplicates of removed pages: {}", e);
+        }
+    }
+
+    /// Set each document's authority from the last PageRank run and its
+    /// aggregated inbound anchor texts
+    async fn attach_link_signals(&self, documents: &mut [CrawledDocument]) {
+        let urls: Vec<String> = documents.iter().map(|doc| doc.url.clone()).collect();
+
+        match AuthorityRepository::new(self.db_pool.clone()).get_many(&urls).await {
+            Ok(scores) => {
+                for doc in documents.iter_mut() {
+                    doc.authority = scores.get(&doc.url).copied();
+                }
             }
+            Err(e) => warn!("Failed to load authority scores: {}", e),
         }
 
-        Ok((pages_crawled, pages_indexed))
+        match LinkRepository::new(self.db_pool.clone()).inbound_anchors(&urls).await {
+            Ok(mut anchors) => {
+                for doc in documents.iter_mut() {
+                    doc.anchor_text = anchors.remove(&doc.url);
+                }
+            }
+            Err(e) => warn!("Failed to load inbound anchors: {}", e),
+        }
+    }
+
+    /// Replace the stored outgoing links of each crawled page
+    async fn record_links(&self, documents: &[CrawledDocument]) {
+        let repo = LinkRepository::new(self.db_pool.clone());
+
+        for doc in documents {
+            if let Err(e) = repo.replace_outlinks(&doc.url, &doc.links).await {
+                warn!("Failed to record links for {}: {}", doc.url, e);
+            }
+        }
+    }
+
+    /// Store the job's failed URLs in `crawl_errors`
+    async fn record_failures(&self, job_id: &Uuid, failures: Vec<CrawlFailure>) {
+        let repo = CrawlErrorRepository::new(self.db_pool.clone());
+
+        if let Err(e) = repo.record(*job_id, &failures).await {
+            warn!("Failed to record {} crawl errors for job {}: {}", failures.len(), job_id, e);
+        }
+    }
+
+    /// Register the SimHash fingerprints of indexed documents and persist them
+    /// so duplicate detection survives restarts
+    async fn register_fingerprints(&self, documents: &[CrawledDocument]) {
+        let repo = FingerprintRepository::new(self.db_pool.clone());
+        let fingerprints = self.crawler.fingerprints();
+
+        for doc in documents {
+            let Some(simhash) = doc.simhash else {
+                continue;
+            };
+            let entry = FingerprintEntry {
+                document_id: doc.id.clone(),
+                url: doc.url.clone(),
+                simhash,
+                duplicate_of: doc.duplicate_of.clone(),
+            };
+            fingerprints.register(entry.clone());
+            if let Err(e) = repo.upsert(&entry).await {
+                warn!("Failed to persist fingerprint for {}: {}", doc.url, e);
+            }
+        }
+    }
+
+    /// Index images to Meilisearch AND Qdrant (Phase 10.5)
+    async fn index_images(&self, images: &[ImageData]) {
+        if images.is_empty() {
+            return;
+        }
+
+        info!("Indexing {} extracted images", images.len());
+
+        // Index to Meilisearch (existing)
+        if let Err(e) = self.search_client.index_images(images.to_vec()).await {
+            warn!("Failed to index images to Meilisearch: {}", e);
+            // Don't fail the entire job if image indexing fails
+        }
+
+        // Index to Qdrant for semantic search (Phase 10.5)
+        let mut qdrant_image_indexed = 0;
+        for image in images {
+            match self.qdrant_service
+                .index_image(
+                    &image.id,
+                    &image.image_url,
+                    &image.source_url,
+                    image.figcaption.as_deref(),
+                    image.alt_text.as_deref(),
+                    image.title.as_deref(),
+                    &image.page_title,
+                    &image.domain,
+                )
+                .await
+            {
+                Ok(_) => {
+                    qdrant_image_indexed += 1;
+                }
+                Err(e) => {
+                    warn!("Failed to index image {} to Qdrant: {}", image.image_url, e);
+                    // Don't fail entire job if Qdrant indexing fails
+                }
+            }
+        }
+
+        if qdrant_image_indexed > 0 {
+            info!("Indexed {} images to Qdrant", qdrant_image_indexed);
+        }
+    }
+
+    /// Reschedule a completed recrawl and persist its next crawl time
+    ///
+    /// Failed recrawls are not rescheduled; their enqueue lease expires and the
+    /// recrawl loop picks them up again.
+    async fn reschedule_completed(&self, schedule_id: Uuid) -> Result<()> {
+        let repo = ScheduleRepository::new(self.db_pool.clone());
+
+        let Some(record) = repo.get(schedule_id).await? else {
+            // Deleted while the job was running
+            return Ok(());
+        };
+
+        let mut task = record.to_scheduled_crawl();
+
+        // Stable pages are visited less often, frequently changing ones more often
+        let page_state = PageStateRepository::new(self.db_pool.clone())
+            .get_for_fetch(&record.url)
+            .await?;
+        if let Some(changes_per_day) = page_state.and_then(|state| state.changes_per_day()) {
+            task.apply_change_rate(changes_per_day);
+        }
+
+        let task = self.crawler.scheduler().reschedule(task)?;
+        repo.record_crawled(schedule_id, &task).await?;
+
+        Ok(())
     }
 
     /// Save job to database
@@ -289,6 +1189,74 @@ impl Worker {
         Ok(())
     }
 
+    /// Update a cancelled or paused job in database
+    ///
+    /// Paused jobs get no `completed_at`; they continue when resumed.
+    async fn update_job_stopped(
+        &self,
+        job_id: &Uuid,
+        control: JobControl,
+        pages_crawled: usize,
+        pages_indexed: usize,
+    ) -> Result<()> {
+        let status = match control {
+            JobControl::Cancel => "cancelled",
+            JobControl::Pause => "paused",
+        };
+
+        sqlx::query(
+            r#"
+            UPDATE crawl_history
+            SET status = $2,
+                pages_crawled = $3,
+                pages_indexed = $4,
+                completed_at = CASE WHEN $2 = 'cancelled' THEN NOW() END
+            WHERE id = $1
+            "#,
+        )
+        .bind(job_id)
+        .bind(status)
+        .bind(pages_crawled as i32)
+        .bind(pages_indexed as i32)
+        .execute(&self.db_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Budget usage recorded by earlier runs of a job, if any
+    async fn saved_budget_usage(&self, job_id: &Uuid) -> Result<Option<SessionUsage>> {
+        let usage: Option<serde_json::Value> =
+            sqlx::query_scalar("SELECT metadata->'budget' FROM crawl_history WHERE id = $1")
+                .bind(job_id)
+                .fetch_optional(&self.db_pool)
+                .await?
+                .flatten();
+
+        Ok(match usage {
+            Some(usage) => Some(serde_json::from_value(usage)?),
+            None => None,
+        })
+    }
+
+    /// Set one key of the job's `crawl_history.metadata`
+    async fn merge_job_metadata(&self, job_id: &Uuid, key: &str, value: serde_json::Value) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE crawl_history
+            SET metadata = COALESCE(metadata, '{}'::jsonb) || jsonb_build_object($2::text, $3::jsonb)
+            WHERE id = $1
+            "#,
+        )
+        .bind(job_id)
+        .bind(key)
+        .bind(value)
+        .execute(&self.db_pool)
+        .await?;
+
+        Ok(())
+    }
+
     /// Update job as failed in database
     async fn update_job_failed(&self, job_id: &Uuid, error_message: &str) -> Result<()> {
         sqlx::query!(
@@ -310,6 +1278,9 @@ impl Worker {
 }
 
 /// Spawn multiple workers
+///
+/// With a frontier, crawl jobs are split into single URLs shared by all
+/// workers instead of each job being crawled by one worker.
 pub async fn spawn_workers(
     count: usize,
     crawler: Crawler,
@@ -317,6 +1288,7 @@ pub async fn spawn_workers(
     qdrant_service: Arc<QdrantService>,
     job_queue: JobQueue,
     db_pool: PgPool,
+    frontier: Option<UrlFrontier>,
 ) -> Result<()> {
     info!("Spawning {} background workers", count);
 
@@ -326,6 +1298,7 @@ pub async fn spawn_workers(
         let qdrant_clone = qdrant_service.clone();
         let queue_clone = job_queue.clone();
         let pool_clone = db_pool.clone();
+        let frontier_clone = frontier.clone();
 
         tokio::spawn(async move {
             let mut worker = Worker::new(
@@ -334,6 +1307,7 @@ pub async fn spawn_workers(
                 qdrant_clone,
                 queue_clone,
                 pool_clone,
+                frontier_clone,
             );
             if let Err(e) = worker.start().await {
                 error!("Worker {} crashed: {}", i, e);
@@ -18,6 +18,24 @@ export interface SearchResult {
 	_formatted?: FormattedResult; // Phase 7.3: Highlighted fields
 	image_count?: number; // Phase 9: Number of images indexed from this page
 	favicon_url?: string; // Phase 9: Favicon URL extracted during crawl
+	// Structured data (JSON-LD, microdata, OpenGraph) for rich result cards
+	author?: string;
+	published_at?: string; // RFC 3339
+	published_timestamp?: number; // Unix seconds
+	modified_at?: string; // RFC 3339
+	modified_timestamp?: number; // Unix seconds
+	article_section?: string;
+	price?: number;
+	price_currency?: string;
+	rating?: number;
+	rating_count?: number;
+	breadcrumbs?: string[];
+	site_name?: string;
+	content_type?: string; // MIME type of the source document
+	authority?: number; // link authority bucket, 0-10
+	anchor_text?: string[]; // anchor texts of links pointing at the page
+	nosnippet?: boolean; // page opted out of snippets; content is empty
+	max_snippet?: number; // longest snippet the page allows, in characters
 }
 
 export interface SearchResponse {
@@ -36,13 +54,72 @@ export interface SearchParams {
 	max_word_count?: number;
 	from_date?: string;
 	to_date?: string;
-	sort_by?: 'crawled_at' | 'word_count';
+	content_type?: string; // e.g. 'application/pdf'
+	sort_by?: 'crawled_at' | 'word_count' | 'published_timestamp' | 'modified_timestamp' | 'price' | 'rating' | 'authority';
 	sort_order?: 'asc' | 'desc';
 }
 
+export interface CrawlBudget {
+	max_pages?: number;
+	max_bytes?: number;
+	max_duration_secs?: number;
+	max_pages_per_path?: Record<string, number>;
+}
+
+export interface CrawlOptions {
+	scope?: 'same_host' | 'same_domain' | 'subdomains';
+	include_patterns?: string[];
+	exclude_patterns?: string[];
+	strip_query_params?: string[];
+	strip_all_query_params?: boolean;
+	requests_per_second?: number;
+	user_agent?: string;
+	extract_images?: boolean;
+}
+
 export interface CrawlRequest {
 	urls: string[];
 	max_depth: number;
+	budget?: CrawlBudget;
+	domain_budget?: CrawlBudget;
+	options?: CrawlOptions;
+}
+
+export type CrawlErrorType =
+	| 'robots_blocked'
+	| 'filtered'
+	| 'circuit_open'
+	| 'http_status'
+	| 'timeout'
+	| 'network'
+	| 'parse_error'
+	| 'empty_content';
+
+export interface CrawlError {
+	id: string;
+	crawl_id: string | null;
+	url: string;
+	domain: string | null;
+	error_type: CrawlErrorType;
+	status_code: number | null;
+	error_message: string | null;
+	occurred_at: string;
+}
+
+export interface DomainErrors {
+	domain: string | null;
+	count: number;
+	by_type: Partial<Record<CrawlErrorType, number>>;
+	last_occurred_at: string | null;
+}
+
+export interface CrawlErrorsResponse {
+	errors: CrawlError[];
+	total: number;
+	by_type: Partial<Record<CrawlErrorType, number>>;
+	by_domain: DomainErrors[];
+	limit: number;
+	offset: number;
 }
 
 export interface CrawlResponse {
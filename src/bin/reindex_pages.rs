@@ -42,6 +42,9 @@ async fn main() -> Result<()> {
             from_date: None,
             to_date: None,
             domain: None,
+            lang: None,
+            content_type: None,
+            include_duplicates: false, // Duplicates stay out of Qdrant
         };
 
         let results = search_client
@@ -63,7 +66,13 @@ async fn main() -> Result<()> {
             }
 
             match qdrant
-                .index_page(&hit.id, &hit.url, &hit.title, &hit.content)
+                .index_page(
+                    &hit.id,
+                    &hit.url,
+                    &hit.title,
+                    hit.anchor_text.as_deref().unwrap_or_default(),
+                    &hit.content,
+                )
                 .await
             {
                 Ok(_) => {
@@ -0,0 +1,168 @@
+//! Offline replay crawl
+//!
+//! Runs stored pages through the crawler's extraction pipeline and indexes
+//! them into Meilisearch and Qdrant, without touching the network. The
+//! source is either a WARC file written by the crawler (or any standard
+//! WARC tool) or a directory of saved HTML, PDF, Markdown and text files.
+//!
+//! Usage:
+//!   replay-crawl <warc-file | directory> [--base-url <url>] [--dry-run]
+//!
+//! `--base-url` addresses saved files that don't declare a canonical URL
+//! (default `http://localhost/`). `--dry-run` extracts and reports without
+//! indexing.
+
+use anyhow::{Context, Result};
+use arack_shared::config::Config;
+use arack_shared::search::crawler::{Crawler, CrawlerConfig, ReplaySource};
+use arack_shared::search::qdrant::QdrantService;
+use arack_shared::search::search::SearchClient;
+use std::path::PathBuf;
+use tracing::{info, warn};
+
+/// Documents per Meilisearch request
+const BATCH_SIZE: usize = 100;
+
+struct Args {
+    source: PathBuf,
+    base_url: Option<String>,
+    dry_run: bool,
+}
+
+fn parse_args() -> Result<Args> {
+    let mut source = None;
+    let mut base_url = None;
+    let mut dry_run = false;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--base-url" => base_url = Some(args.next().context("--base-url needs a value")?),
+            "--dry-run" => dry_run = true,
+            "-h" | "--help" => {
+                println!("Usage: replay-crawl <warc-file | directory> [--base-url <url>] [--dry-run]");
+                std::process::exit(0);
+            }
+            _ if source.is_none() => source = Some(PathBuf::from(arg)),
+            _ => anyhow::bail!("Unexpected argument: {}", arg),
+        }
+    }
+
+    Ok(Args {
+        source: source.context("Usage: replay-crawl <warc-file | directory> [--base-url <url>] [--dry-run]")?,
+        base_url,
+        dry_run,
+    })
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt::init();
+
+    let args = parse_args()?;
+    let config = Config::load()?;
+
+    // Same extraction settings as the crawler workers
+    let crawler_config = CrawlerConfig {
+        max_depth: config.crawler_max_depth,
+        max_concurrent: config.crawler_max_concurrent,
+        requests_per_second: config.crawler_requests_per_second,
+        min_delay_ms: config.crawler_min_delay_ms,
+        max_retries: config.crawler_max_retries,
+        // Nothing polls feeds found in replayed pages
+        discover_feeds: false,
+        ..Default::default()
+    };
+    let crawler = Crawler::with_headers(
+        crawler_config,
+        config.crawler_user_agent.clone(),
+        config.crawler_contact_email.clone(),
+        config.crawler_bot_url.clone(),
+        config.crawler_accept_language.clone(),
+    );
+
+    let source = ReplaySource::from_path(&args.source, args.base_url.as_deref())?;
+    info!("Replaying {}", args.source.display());
+
+    let (documents, images) = crawler.replay(&source).await?;
+
+    if args.dry_run {
+        for doc in &documents {
+            println!("{}\t{}\t{} words", doc.url, doc.title, doc.word_count);
+        }
+        info!("Dry run: extracted {} documents and {} images", documents.len(), images.len());
+        return Ok(());
+    }
+
+    // Initialize Meilisearch (creates and configures the index on fresh environments)
+    let search_client = SearchClient::new(&config.meilisearch_url, &config.meilisearch_key)?;
+    search_client.initialize_index().await?;
+
+    // Semantic indexing is skipped when Qdrant is unavailable
+    let qdrant_config = config.qdrant();
+    let qdrant = match QdrantService::new(&qdrant_config.url, qdrant_config.collection_name).await {
+        Ok(service) => Some(service),
+        Err(e) => {
+            warn!("Failed to connect to Qdrant: {}. Skipping semantic indexing", e);
+            None
+        }
+    };
+
+    let mut indexed = 0;
+    for batch in documents.chunks(BATCH_SIZE) {
+        match search_client.index_documents(batch.to_vec()).await {
+            Ok(_) => indexed += batch.len(),
+            Err(e) => warn!("Failed to index {} documents: {}", batch.len(), e),
+        }
+    }
+
+    let mut embedded = 0;
+    if let Some(qdrant) = &qdrant {
+        // Near-duplicates stay out of the vector index, as in the crawler workers
+        for doc in documents.iter().filter(|doc| doc.duplicate_of.is_none()) {
+            match qdrant
+                .index_page(
+                    &doc.id,
+                    &doc.url,
+                    &doc.title,
+                    doc.anchor_text.as_deref().unwrap_or_default(),
+                    &doc.content,
+                )
+                .await
+            {
+                Ok(_) => embedded += 1,
+                Err(e) => warn!("Failed to index page {} to Qdrant: {}", doc.url, e),
+            }
+        }
+
+        for image in &images {
+            if let Err(e) = qdrant
+                .index_image(
+                    &image.id,
+                    &image.image_url,
+                    &image.source_url,
+                    image.figcaption.as_deref(),
+                    image.alt_text.as_deref(),
+                    image.title.as_deref(),
+                    &image.page_title,
+                    &image.domain,
+                )
+                .await
+            {
+                warn!("Failed to index image {} to Qdrant: {}", image.image_url, e);
+            }
+        }
+    }
+
+    let image_count = images.len();
+    if let Err(e) = search_client.index_images(images).await {
+        warn!("Failed to index images: {}", e);
+    }
+
+    info!(
+        "Replay complete! Indexed {} documents ({} embedded) and {} images",
+        indexed, embedded, image_count
+    );
+
+    Ok(())
+}
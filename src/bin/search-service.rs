@@ -76,6 +76,17 @@ async fn main() -> Result<()> {
         requests_per_second: config.crawler_requests_per_second,
         min_delay_ms: config.crawler_min_delay_ms,
         max_retries: config.crawler_max_retries,
+        discover_sitemaps: true,
+        discover_feeds: true,
+        warc_dir: config.crawler_warc_dir.clone().map(std::path::PathBuf::from),
+        domain_budget: search::crawler::CrawlBudget {
+            max_pages: config.crawler_domain_max_pages,
+            max_bytes: config.crawler_domain_max_bytes,
+            max_duration_secs: config.crawler_domain_max_seconds,
+            ..Default::default()
+        },
+        scope: search::crawler::CrawlScope::SameHost,
+        extract_images: true,
     };
     let crawler = search::crawler::Crawler::with_headers(
         crawler_config,
@@ -83,10 +94,44 @@ async fn main() -> Result<()> {
         config.crawler_contact_email.clone(),
         config.crawler_bot_url.clone(),
         config.crawler_accept_language.clone(),
-    );
+    )
+    .with_redis(redis_conn.clone());
 
     info!("Crawler initialized with User-Agent: {}", crawler.user_agent());
 
+    // Restore near-duplicate fingerprints so detection survives restarts
+    match search::crawler::FingerprintRepository::new(db_pool.clone()).load_all().await {
+        Ok(entries) => {
+            let count = entries.len();
+            for entry in entries {
+                crawler.fingerprints().insert(entry);
+            }
+            info!("Loaded {} page fingerprints", count);
+        }
+        Err(e) => tracing::warn!("Failed to load page fingerprints: {}", e),
+    }
+
+    // Admin filter rules are loaded now and reloaded whenever they change
+    search::crawler::spawn_filter_rule_listener(db_pool.clone(), crawler.filter_rules().clone()).await?;
+
+    // Recrawl loop shares the workers' scheduler so sitemap discoveries get persisted
+    let crawl_scheduler = crawler.scheduler().clone();
+
+    // Feed poll loop shares the workers' crawler so discovered feeds get persisted
+    let feed_crawler = crawler.clone();
+
+    // Optional URL frontier: workers share single URLs instead of whole sites
+    let frontier = config.crawler_frontier.then(|| {
+        info!("Crawling jobs through the URL frontier");
+        search::crawler::UrlFrontier::new(
+            redis_conn.clone(),
+            search::crawler::FrontierConfig {
+                host_interval: std::time::Duration::from_millis(config.crawler_min_delay_ms),
+                ..Default::default()
+            },
+        )
+    });
+
     // Spawn background workers (Phase 5.3)
     search::worker::spawn_workers(
         2, // Number of workers
@@ -95,11 +140,35 @@ async fn main() -> Result<()> {
         qdrant_service.clone(),
         job_queue.clone(),
         db_pool.clone(),
+        frontier,
     )
     .await?;
 
     // Start job scheduler (Phase 5.4)
-    let _scheduler = search::scheduler::start_scheduler(db_pool.clone(), cache_manager.clone()).await?;
+    let _scheduler = search::scheduler::start_scheduler(
+        db_pool.clone(),
+        cache_manager.clone(),
+        search_client.clone(),
+    )
+    .await?;
+
+    // Start recrawl loop: enqueues due scheduled crawls as jobs
+    search::scheduler::spawn_recrawl_loop(
+        crawl_scheduler,
+        db_pool.clone(),
+        job_queue.clone(),
+        search::scheduler::RecrawlConfig::default(),
+    )
+    .await?;
+
+    // Start feed poll loop: enqueues new posts of discovered feeds
+    search::scheduler::spawn_feed_poll_loop(
+        feed_crawler,
+        db_pool.clone(),
+        job_queue.clone(),
+        search::scheduler::FeedPollConfig::default(),
+    )
+    .await?;
 
     // Start API server
     let addr = format!("{}:{}", config.server_host, config.server_port);
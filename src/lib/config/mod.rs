@@ -22,6 +22,14 @@ pub struct Config {
     pub crawler_contact_email: Option<String>,
     pub crawler_bot_url: Option<String>,
     pub crawler_accept_language: String,
+    // WARC archive output directory (archiving disabled when unset)
+    pub crawler_warc_dir: Option<String>,
+    // Default per-domain crawl budget (unlimited when unset)
+    pub crawler_domain_max_pages: Option<usize>,
+    pub crawler_domain_max_bytes: Option<u64>,
+    pub crawler_domain_max_seconds: Option<u64>,
+    // Crawl jobs URL by URL through the shared Redis frontier
+    pub crawler_frontier: bool,
     // Ory Kratos URLs (Phase 8.6)
     pub kratos_public_url: String,
     pub kratos_admin_url: String,
@@ -90,6 +98,22 @@ impl Config {
             crawler_bot_url: env::var("CRAWLER_BOT_URL").ok(),
             crawler_accept_language: env::var("CRAWLER_ACCEPT_LANGUAGE")
                 .unwrap_or_else(|_| "en-US,en;q=0.9".to_string()),
+            crawler_warc_dir: env::var("CRAWLER_WARC_DIR").ok().filter(|dir| !dir.is_empty()),
+            crawler_domain_max_pages: env::var("CRAWLER_DOMAIN_MAX_PAGES")
+                .ok()
+                .map(|value| value.parse())
+                .transpose()?,
+            crawler_domain_max_bytes: env::var("CRAWLER_DOMAIN_MAX_BYTES")
+                .ok()
+                .map(|value| value.parse())
+                .transpose()?,
+            crawler_domain_max_seconds: env::var("CRAWLER_DOMAIN_MAX_SECONDS")
+                .ok()
+                .map(|value| value.parse())
+                .transpose()?,
+            crawler_frontier: env::var("CRAWLER_FRONTIER")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()?,
             kratos_public_url: env::var("KRATOS_PUBLIC_URL")
                 .unwrap_or_else(|_| "http://127.0.0.1:4433".to_string()),
             kratos_admin_url: env::var("KRATOS_ADMIN_URL")
@@ -56,9 +56,11 @@ pub struct CreateCrawlHistory {
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct CrawlError {
     pub id: Uuid,
-    pub crawl_id: Uuid,
+    pub crawl_id: Option<Uuid>,
     pub url: String,
+    pub domain: Option<String>,
     pub error_type: String,
+    pub status_code: Option<i16>,
     pub error_message: Option<String>,
     pub occurred_at: DateTime<Utc>,
 }
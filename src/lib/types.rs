@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use crate::search::crawler::{CrawlBudget, CrawlOptions};
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct SearchQuery {
     pub q: String,
@@ -15,6 +17,13 @@ pub struct SearchQuery {
     pub to_date: Option<String>,
     // Phase 7.4: Domain filtering for faceted search
     pub domain: Option<String>,
+    // Language filter (ISO 639-1 code)
+    pub lang: Option<String>,
+    // Content type filter (MIME type, e.g. application/pdf)
+    pub content_type: Option<String>,
+    // Include pages detected as near-duplicates of another document
+    #[serde(default)]
+    pub include_duplicates: bool,
 }
 
 fn default_limit() -> usize {
@@ -26,6 +35,14 @@ pub struct CrawlRequest {
     pub urls: Vec<String>,
     #[serde(default = "default_depth")]
     pub max_depth: usize,
+    // Limits for the whole job
+    #[serde(default)]
+    pub budget: CrawlBudget,
+    // Limits per domain, replacing the configured default
+    pub domain_budget: Option<CrawlBudget>,
+    // Scope, filters, URL rules, rate and user agent for this job
+    #[serde(default)]
+    pub options: CrawlOptions,
 }
 
 fn default_depth() -> usize {